@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zk_schnorr_lib::Message;
+
+// untrusted JSON from the wire must parse to Ok or Err and never panic
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = serde_json::from_str::<Message>(text);
+    }
+});
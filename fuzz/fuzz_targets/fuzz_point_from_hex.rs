@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zk_schnorr_lib::point_from_hex;
+
+// any byte string, hex-encoded, must decode to Ok or Err and never panic
+fuzz_target!(|data: &[u8]| {
+    let _ = point_from_hex(&hex::encode(data));
+});
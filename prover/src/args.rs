@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use clap::Parser;
+use zk_schnorr_lib::{keypair_from_pem, Ed25519SigningKey, KeyPair, RetryPolicy, Scheme, WireFormat};
+
+/// Seed of the demo keypair, used when neither --secret-seed nor --keypair-pem is given
+pub const DEMO_SEED: &str = "demo-prover-secret";
+
+/// Command-line options for the prover
+#[derive(Parser, Debug)]
+#[command(about = "Schnorr zero-knowledge prover over TLS")]
+pub struct ProverArgs {
+    /// Host the verifier listens on
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+    /// Port the verifier listens on
+    #[arg(long, default_value_t = 4433)]
+    pub port: u16,
+    /// Connect to a WebSocket verifier at this URL, e.g. wss://localhost:4434, instead of --host and --port
+    #[arg(long, conflicts_with_all = ["host", "port"])]
+    pub ws: Option<String>,
+    /// Derive the keypair from this seed instead of the demo one
+    #[arg(long, conflicts_with = "keypair_pem")]
+    pub secret_seed: Option<String>,
+    /// Load the keypair from a PEM file written by `keypair_to_pem`
+    #[arg(long)]
+    pub keypair_pem: Option<PathBuf>,
+    /// ristretto runs the sigma protocol; ed25519 signs the verifier's nonce with a key derived from the seed
+    #[arg(long, default_value_t = Scheme::Ristretto)]
+    pub scheme: Scheme,
+    /// Number of proofs to send over the one TLS connection
+    #[arg(long, default_value_t = 1)]
+    pub rounds: usize,
+    /// Message encoding on the wire: json, cbor or binary; must match the verifier's --wire
+    #[arg(long, default_value_t = WireFormat::Json)]
+    pub wire: WireFormat,
+    /// Shorthand for --wire binary
+    #[arg(long, default_value_t = false, conflicts_with = "wire")]
+    pub binary: bool,
+    /// Derive each nonce from the secret key and session instead of the OS RNG
+    #[arg(long)]
+    pub deterministic_nonce: bool,
+    /// Ask the verifier to commit to each challenge before seeing the nonce commitment R
+    #[arg(long)]
+    pub committed_challenge: bool,
+    /// Seconds to wait for the connection and TLS handshake with the verifier, retries included
+    #[arg(long, default_value_t = 10)]
+    pub connect_timeout_secs: u64,
+    /// Times to retry reaching the verifier after the first attempt fails; a rejected certificate is never retried
+    #[arg(long, default_value_t = 4)]
+    pub retry: u32,
+    /// Milliseconds to wait before the first retry, doubling after each one after that (with jitter, at most 5s)
+    #[arg(long, default_value_t = 200, alias = "connect-retry-delay-ms")]
+    pub retry_backoff_ms: u64,
+    /// Seconds to wait for each message from the verifier
+    #[arg(long, default_value_t = 30)]
+    pub step_timeout_secs: u64,
+    /// Validate the verifier's certificate against the webpki roots for this
+    /// hostname instead of trusting verifier_cert.der
+    #[arg(long)]
+    pub server_name: Option<String>,
+    /// Name to prove as, for a verifier that holds keys for several provers
+    #[arg(long)]
+    pub identity: Option<String>,
+    /// Log filter such as `info` or `prover=debug` (overrides RUST_LOG)
+    #[arg(long)]
+    pub log_level: Option<String>,
+    /// Emit logs as JSON lines instead of human-readable text
+    #[arg(long)]
+    pub log_json: bool,
+}
+
+impl ProverArgs {
+    /// The wire format to speak, with --binary taken into account
+    pub fn wire(&self) -> WireFormat {
+        if self.binary { WireFormat::Binary } else { self.wire }
+    }
+
+    /// How to retry the connection from --retry and --retry-backoff-ms
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy { retries: self.retry, backoff: Duration::from_millis(self.retry_backoff_ms), ..RetryPolicy::default() }
+    }
+
+    /// `host:port` of the verifier, to connect to and for logs; an IPv6 host is bracketed
+    pub fn verifier_addr(&self) -> String {
+        if self.host.contains(':') && !self.host.starts_with('[') {
+            format!("[{}]:{}", self.host, self.port)
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+
+    /// The name the verifier's certificate must be valid for: --server-name, else --host without brackets
+    pub fn tls_server_name(&self) -> &str {
+        match &self.server_name {
+            Some(name) => name,
+            None => self.host.trim_start_matches('[').trim_end_matches(']'),
+        }
+    }
+
+    /// The keypair from --keypair-pem, --secret-seed or the demo seed, in that order
+    pub fn keypair(&self) -> Result<KeyPair> {
+        match (&self.keypair_pem, &self.secret_seed) {
+            (Some(path), _) => {
+                let pem = std::fs::read_to_string(path)?;
+                Ok(keypair_from_pem(&pem)?)
+            }
+            (None, Some(seed)) => Ok(KeyPair::from_seed(seed.as_bytes())),
+            (None, None) => Ok(KeyPair::from_seed(DEMO_SEED.as_bytes())),
+        }
+    }
+
+    /// The Ed25519 key for --scheme ed25519, from --secret-seed or the demo seed
+    ///
+    /// --keypair-pem holds a Ristretto key, which can't sign Ed25519.
+    pub fn ed25519_key(&self) -> Result<Ed25519SigningKey> {
+        if self.keypair_pem.is_some() {
+            bail!("--keypair-pem holds a ristretto key; use --secret-seed with --scheme ed25519");
+        }
+        Ok(Ed25519SigningKey::derive(self.secret_seed.as_deref().unwrap_or(DEMO_SEED).as_bytes()))
+    }
+}
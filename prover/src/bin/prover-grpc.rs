@@ -0,0 +1,65 @@
+use anyhow::Result; // propagate I/O, TLS, gRPC and protocol errors with `?`
+use std::time::Duration; // connect and per-message deadlines
+use clap::Parser; // command-line flags
+use tonic::transport::{ClientTlsConfig, Endpoint}; // the HTTP/2 channel to verifier-grpc, over tonic's TLS
+use tracing::{info, Instrument}; // structured logging, Instrument attaches a span to a future
+use prover::{init_logging, ProverArgs}; // the same flags as the TLS prover; --wire doesn't apply, gRPC has its own encoding
+
+//shared library
+use zk_schnorr_lib::{run_prover_as, run_prover_ed25519, run_prover_with, ProverOptions, Scheme}; // the whole prover session, exactly as over TLS, or one signed nonce with --scheme ed25519
+use zk_schnorr_lib::{GrpcTransport, TimeoutTransport}; // one Prove call as a Transport, bounded so a silent verifier can't leave us hanging
+use zk_schnorr_lib::grpc::{client_tls_config, SchnorrVerifierClient}; // the generated client, and TLS trusting the verifier's certificate
+
+const VERIFIER_CERT_PATH: &str = "verifier_cert.der"; // verifier-grpc writes its self-signed certificate here on startup
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = ProverArgs::parse();
+    init_logging(args.log_level.as_deref(), args.log_json);
+
+    let keypair = args.keypair()?;
+    info!(X = %keypair.public_key(), "Loaded keypair"); // log the public key in hex, never the secret
+    let ed25519_key = match args.scheme {
+        Scheme::Ed25519 => Some(args.ed25519_key()?), // refuses --keypair-pem
+        Scheme::Ristretto => None,
+    };
+    if let Some(key) = &ed25519_key {
+        info!(A = %key.public_key(), "Signing with Ed25519 instead of proving");
+    }
+
+    // TLS setup: by default trust exactly the certificate the verifier generated
+    let tls_config = match &args.server_name {
+        Some(name) => ClientTlsConfig::new().domain_name(name), // a CA-issued certificate, checked against the webpki roots
+        None => client_tls_config(&std::fs::read(VERIFIER_CERT_PATH)?, args.tls_server_name()), // fails if the verifier hasn't been started yet
+    };
+    let endpoint = Endpoint::from_shared(format!("https://{}", args.verifier_addr()))?
+        .tls_config(tls_config)?
+        .connect_timeout(Duration::from_secs(args.connect_timeout_secs));
+
+    let span = tracing::info_span!("session", remote_addr = %args.verifier_addr()); // same span name and field as the verifier's
+    async {
+        let mut client = SchnorrVerifierClient::new(endpoint.connect().await?);
+        info!(verifier = %args.verifier_addr(), "gRPC channel open");
+
+        // the whole session runs inside one Prove call
+        let grpc = GrpcTransport::connect(&mut client).await?;
+        let mut transport = TimeoutTransport::new(grpc, Duration::from_secs(args.step_timeout_secs));
+        let options = ProverOptions {
+            rounds: args.rounds,
+            deterministic_nonce: args.deterministic_nonce,
+            committed_challenge: args.committed_challenge,
+            ..ProverOptions::default()
+        };
+        let outcome = match (&ed25519_key, &args.identity) {
+            (Some(key), identity) => run_prover_ed25519(&mut transport, key, identity.as_deref()).await?,
+            (None, Some(identity)) => run_prover_as(&mut transport, &keypair, identity, &options).await?,
+            (None, None) => run_prover_with(&mut transport, &keypair, &options).await?,
+        };
+        info!(version = %outcome.version, verified = outcome.verified(), failed = outcome.failed(), "Proofs sent");
+        Ok::<_, anyhow::Error>(()) // dropping the transport ends the call, and the verifier's session with it
+    }
+    .instrument(span)
+    .await?;
+
+    Ok(())
+}
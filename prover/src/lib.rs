@@ -0,0 +1,26 @@
+//! The prover binaries' command-line options and session, kept out of
+//! `main.rs` so tests can parse argument lists without connecting to anything,
+//! or run the whole session against a verifier of their own.
+
+use tracing_subscriber::EnvFilter;
+
+pub mod args; // clap options and what they resolve to
+pub use args::{ProverArgs, DEMO_SEED};
+pub mod session; // connecting as the flags say and proving every round
+pub use session::{prove, run};
+
+/// Install the global tracing subscriber
+///
+/// `--log-level` wins over `RUST_LOG`; with neither set we log at `info`.
+pub fn init_logging(log_level: Option<&str>, json: bool) {
+    let filter = match log_level {
+        Some(level) => EnvFilter::new(level),
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+    };
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    if json {
+        builder.json().init();
+    } else {
+        builder.init();
+    }
+}
@@ -0,0 +1,93 @@
+//! One prover session as the binary runs it: reach the verifier the way the
+//! flags say, then send every round
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use rustls::{ClientConfig, ServerName};
+use tokio::io::AsyncWriteExt;
+use tokio_rustls::TlsConnector;
+use tracing::{info, Instrument};
+use zk_schnorr_lib::{
+    connect_with_retry, connect_ws, run_prover_as, run_prover_ed25519, run_prover_with, with_timeout, Ed25519SigningKey, KeyPair,
+    ProofOutcome, ProtocolConfig, ProverOptions, TimeoutTransport, TlsTransport, Transport,
+};
+
+use crate::ProverArgs;
+
+/// Connect to the verifier and prove, with `client_config` deciding which certificate to trust
+///
+/// Over TLS to --host and --port, retried as --retry says, or over a
+/// WebSocket with --ws; either way the connection is closed cleanly once the
+/// rounds are done, so the verifier sees the session end. `ed25519_key` signs
+/// the verifier's nonce instead of running the sigma protocol.
+pub async fn run(
+    args: &ProverArgs,
+    client_config: Arc<ClientConfig>,
+    keypair: &KeyPair,
+    ed25519_key: Option<&Ed25519SigningKey>,
+) -> Result<ProofOutcome> {
+    let config = ProtocolConfig {
+        connect_timeout: Duration::from_secs(args.connect_timeout_secs),
+        step_timeout: Duration::from_secs(args.step_timeout_secs),
+        ..ProtocolConfig::default()
+    };
+
+    if let Some(url) = &args.ws {
+        let span = tracing::info_span!("session", remote_addr = %url);
+        return async {
+            let ws = with_timeout(config.connect_timeout, "WebSocket connection to the verifier", connect_ws(url, client_config)).await??; // TLS and WebSocket handshakes, no retries
+            info!(url, "WebSocket handshake complete");
+            let mut transport = TimeoutTransport::new(ws, config.step_timeout); // always JSON, --wire doesn't apply
+            let outcome = prove(&mut transport, args, keypair, ed25519_key).await?;
+            transport.into_inner().close().await?; // close handshake, so the verifier sees the end of the session
+            Ok(outcome)
+        }
+        .instrument(span)
+        .await;
+    }
+    let connector = TlsConnector::from(client_config);
+
+    let verifier_addr = args.verifier_addr();
+    let span = tracing::info_span!("session", remote_addr = %verifier_addr); // same span name and field as the verifier's
+    async {
+        let policy = args.retry_policy(); // the verifier may still be starting
+        let server_name = ServerName::try_from(args.tls_server_name())?; // the self-signed certificate covers the verifier's --san names
+        let connect = connect_with_retry(&verifier_addr, &connector, server_name, &policy); // TCP, then TLS on top
+        let tls_stream = with_timeout(config.connect_timeout, "connection to the verifier", connect).await??; // but don't wait forever
+        info!(verifier = %verifier_addr, "TLS handshake complete");
+
+        // COMMITMENT, CHALLENGE and RESPONSE phases all happen inside each round
+        let mut transport = TimeoutTransport::new(TlsTransport::with_codec(tls_stream.into(), args.wire()), config.step_timeout);
+        let outcome = prove(&mut transport, args, keypair, ed25519_key).await?;
+
+        // close the TLS session cleanly so the verifier sees the end of the session
+        transport.into_inner().into_inner().shutdown().await?;
+        Ok(outcome)
+    }
+    .instrument(span)
+    .await
+}
+
+/// Every round over `transport`, whichever way it reached the verifier
+pub async fn prove<T: Transport>(
+    transport: &mut T,
+    args: &ProverArgs,
+    keypair: &KeyPair,
+    ed25519_key: Option<&Ed25519SigningKey>,
+) -> Result<ProofOutcome> {
+    let options = ProverOptions {
+        rounds: args.rounds,
+        deterministic_nonce: args.deterministic_nonce,
+        committed_challenge: args.committed_challenge,
+        ..ProverOptions::default()
+    };
+    let outcome = match (ed25519_key, &args.identity) { // agrees on a version before anything else
+        (Some(key), identity) => run_prover_ed25519(transport, key, identity.as_deref()).await?,
+        (None, Some(identity)) => run_prover_as(transport, keypair, identity, &options).await?,
+        (None, None) => run_prover_with(transport, keypair, &options).await?,
+    };
+    info!(version = %outcome.version, verified = outcome.verified(), failed = outcome.failed(), "Proofs sent");
+    Ok(outcome)
+}
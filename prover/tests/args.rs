@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Parser;
+use prover::{ProverArgs, DEMO_SEED};
+use zk_schnorr_lib::{keypair_to_pem, Ed25519SigningKey, KeyPair, Scheme, WireFormat};
+
+fn parse(args: &[&str]) -> Result<ProverArgs, clap::Error> {
+    ProverArgs::try_parse_from(std::iter::once("prover").chain(args.iter().copied()))
+}
+
+#[test]
+fn defaults_reach_the_local_demo_verifier() {
+    let args = parse(&[]).unwrap();
+    assert_eq!((args.host.as_str(), args.port), ("127.0.0.1", 4433));
+    assert_eq!(args.verifier_addr(), "127.0.0.1:4433");
+    assert_eq!(args.wire(), WireFormat::Json);
+    assert!(!args.committed_challenge);
+    assert_eq!(args.keypair().unwrap().public_key(), KeyPair::from_seed(DEMO_SEED.as_bytes()).public_key());
+}
+
+#[test]
+fn host_port_and_seed_are_taken_from_the_flags() {
+    let args = parse(&["--host", "verifier.example", "--port", "5000", "--secret-seed", "alice", "--rounds", "3"]).unwrap();
+    assert_eq!(args.verifier_addr(), "verifier.example:5000");
+    assert_eq!(args.secret_seed.as_deref(), Some("alice"));
+    assert_eq!(args.rounds, 3);
+    assert_eq!(args.keypair().unwrap().public_key(), KeyPair::from_seed(b"alice").public_key());
+}
+
+#[test]
+fn binary_selects_the_binary_wire_format() {
+    assert_eq!(parse(&["--binary"]).unwrap().wire(), WireFormat::Binary);
+    assert_eq!(parse(&["--wire", "cbor"]).unwrap().wire(), WireFormat::Cbor);
+    assert!(parse(&["--binary", "--wire", "cbor"]).is_err());
+}
+
+#[test]
+fn seed_and_pem_are_mutually_exclusive() {
+    let err = parse(&["--secret-seed", "alice", "--keypair-pem", "key.pem"]).unwrap_err();
+    assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+}
+
+#[test]
+fn keypair_pem_is_loaded() {
+    let keypair = KeyPair::from_seed(b"from-a-file");
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), keypair_to_pem(&keypair)).unwrap();
+
+    let args = parse(&["--keypair-pem", file.path().to_str().unwrap()]).unwrap();
+    assert_eq!(args.keypair_pem, Some(PathBuf::from(file.path())));
+    assert_eq!(args.keypair().unwrap().public_key(), keypair.public_key());
+}
+
+#[test]
+fn invalid_port_is_refused() {
+    assert!(parse(&["--port", "70000"]).is_err());
+}
+
+#[test]
+fn ed25519_scheme_derives_a_signing_key_from_the_seed() {
+    let args = parse(&["--scheme", "ed25519", "--secret-seed", "token"]).unwrap();
+    assert_eq!(args.scheme, Scheme::Ed25519);
+    assert_eq!(args.ed25519_key().unwrap().public_key(), Ed25519SigningKey::derive(b"token").public_key());
+    assert!(parse(&["--scheme", "ed25519", "--keypair-pem", "key.pem"]).unwrap().ed25519_key().is_err());
+    assert!(parse(&["--scheme", "rsa"]).is_err());
+    assert_eq!(parse(&[]).unwrap().scheme, Scheme::Ristretto);
+}
+
+#[test]
+fn connection_retries_default_to_four_from_200ms() {
+    let policy = parse(&[]).unwrap().retry_policy();
+    assert_eq!((policy.retries, policy.backoff), (4, Duration::from_millis(200)));
+
+    let policy = parse(&["--retry", "2", "--retry-backoff-ms", "50"]).unwrap().retry_policy();
+    assert_eq!((policy.retries, policy.backoff), (2, Duration::from_millis(50)));
+    assert_eq!(parse(&["--retry", "0"]).unwrap().retry_policy().retries, 0, "a single attempt");
+    assert_eq!(parse(&["--connect-retry-delay-ms", "75"]).unwrap().retry_backoff_ms, 75, "the old name still works");
+}
+
+#[test]
+fn ipv6_hosts_are_bracketed() {
+    assert_eq!(parse(&["--host", "::1", "--port", "4433"]).unwrap().verifier_addr(), "[::1]:4433");
+    assert_eq!(parse(&["--host", "[::1]"]).unwrap().verifier_addr(), "[::1]:4433");
+}
+
+#[test]
+fn certificate_name_is_the_host_unless_given() {
+    assert_eq!(parse(&[]).unwrap().tls_server_name(), "127.0.0.1");
+    assert_eq!(parse(&["--host", "[::1]"]).unwrap().tls_server_name(), "::1");
+    assert_eq!(parse(&["--host", "verifier.internal"]).unwrap().tls_server_name(), "verifier.internal");
+    assert_eq!(parse(&["--host", "10.0.0.5", "--server-name", "verifier.example"]).unwrap().tls_server_name(), "verifier.example");
+}
+
+#[test]
+fn ws_url_replaces_host_and_port() {
+    assert!(parse(&[]).unwrap().ws.is_none());
+    let args = parse(&["--ws", "wss://localhost:4434/prove"]).unwrap();
+    assert_eq!(args.ws.as_deref(), Some("wss://localhost:4434/prove"));
+    let err = parse(&["--ws", "wss://localhost:4434", "--port", "4433"]).unwrap_err();
+    assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+}
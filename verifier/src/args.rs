@@ -0,0 +1,137 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Parser;
+use zk_schnorr_lib::{AuditLogOptions, FsyncPolicy, Scheme, Timeouts, WireFormat, DEFAULT_MAX_MESSAGE_LEN};
+
+use crate::config::{VerifierConfig, DEFAULT_CONFIG_PATH};
+
+/// Port for length-prefixed messages over TLS, unless --port says otherwise
+pub const TLS_PORT: u16 = 4433;
+/// Port for JSON text frames over a TLS WebSocket, unless --port says otherwise
+pub const WS_PORT: u16 = 4434;
+
+/// Command-line options for the verifier
+#[derive(Parser, Debug)]
+#[command(about = "Schnorr zero-knowledge proof verifier over TLS")]
+pub struct VerifierArgs {
+    /// JSON file of expected keys and listener settings; used in place of the demo key and the matching flags when it exists
+    #[arg(long, default_value = DEFAULT_CONFIG_PATH)]
+    pub config: PathBuf,
+    /// Address to listen on
+    #[arg(long, default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    pub bind_addr: IpAddr,
+    /// Port to listen on [default: 4433, or 4434 with --ws]
+    #[arg(long)]
+    pub port: Option<u16>,
+    /// Serve this PEM certificate chain instead of generating a self-signed one
+    #[arg(long, requires = "key_pem")]
+    pub cert_pem: Option<PathBuf>,
+    /// PEM private key for --cert-pem
+    #[arg(long, requires = "cert_pem")]
+    pub key_pem: Option<PathBuf>,
+    /// A hostname or IP address the self-signed certificate is valid for; repeat for several
+    #[arg(long = "san", value_name = "NAME", default_values_t = [String::from("localhost"), String::from("127.0.0.1")])]
+    pub san: Vec<String>,
+    /// Days the self-signed certificate is valid for, from now
+    #[arg(long, default_value_t = 365)]
+    pub cert_validity_days: u32,
+    /// Seconds allowed for the TLS handshake
+    #[arg(long, default_value_t = 10)]
+    pub handshake_timeout_secs: u64,
+    /// Seconds to wait for each protocol message
+    #[arg(long, default_value_t = 30)]
+    pub message_timeout_secs: u64,
+    /// Seconds allowed for a whole proof session
+    #[arg(long, default_value_t = 120)]
+    pub session_timeout_secs: u64,
+    /// Seconds sessions in flight get to finish after ctrl-C or SIGTERM before they are cut off
+    #[arg(long, default_value_t = 30)]
+    pub shutdown_grace_secs: u64,
+    /// Maximum number of concurrent proof sessions
+    #[arg(long, default_value_t = 64)]
+    pub max_connections: usize,
+    /// New connections allowed per peer IP per minute
+    #[arg(long, default_value_t = 30)]
+    pub rate_limit: u32,
+    /// Message encoding on the wire: json, cbor or binary; must match the prover's --wire
+    #[arg(long, default_value_t = WireFormat::Json)]
+    pub wire: WireFormat,
+    /// Largest message a prover may send, in bytes; a longer length prefix ends the session before the body is read
+    #[arg(long, default_value_t = DEFAULT_MAX_MESSAGE_LEN)]
+    pub max_message_bytes: usize,
+    /// Shorthand for --wire binary
+    #[arg(long, default_value_t = false, conflicts_with = "wire")]
+    pub binary: bool,
+    /// How the demo prover proves itself: the ristretto sigma protocol, or an ed25519 signature over a nonce
+    #[arg(long, default_value_t = Scheme::Ristretto)]
+    pub scheme: Scheme,
+    /// Accept WebSocket (wss://) connections from browser provers instead of raw TLS
+    #[arg(long)]
+    pub ws: bool,
+    /// Also accept WebSocket (wss://) connections on this address, e.g. 127.0.0.1:4434, alongside raw TLS
+    #[arg(long, conflicts_with = "ws")]
+    pub listen_ws: Option<SocketAddr>,
+    /// Append a JSON record of every checked proof to this file
+    #[arg(long)]
+    pub audit_log: Option<PathBuf>,
+    /// Write the transcript of every checked proof to its own file in this directory, for verify-transcript
+    #[arg(long)]
+    pub save_transcripts: Option<PathBuf>,
+    /// Append a JSON record of every session (peer, identity, outcome, durations) to this file
+    #[arg(long)]
+    pub session_log: Option<PathBuf>,
+    /// When to sync the session log to disk: never, always or batched
+    #[arg(long, default_value_t = FsyncPolicy::Batched, requires = "session_log")]
+    pub session_log_fsync: FsyncPolicy,
+    /// Rotate the session log before it grows past this many bytes, keeping 5 old files
+    #[arg(long, requires = "session_log")]
+    pub session_log_max_bytes: Option<u64>,
+    /// Serve Prometheus metrics over plain HTTP on this address, e.g. 127.0.0.1:9464
+    #[arg(long)]
+    pub metrics_addr: Option<SocketAddr>,
+    /// Log filter such as `info` or `verifier=debug` (overrides RUST_LOG)
+    #[arg(long)]
+    pub log_level: Option<String>,
+    /// Emit logs as JSON lines instead of human-readable text
+    #[arg(long)]
+    pub log_json: bool,
+}
+
+impl VerifierArgs {
+    pub fn timeouts(&self) -> Timeouts {
+        Timeouts {
+            handshake: Duration::from_secs(self.handshake_timeout_secs),
+            message: Duration::from_secs(self.message_timeout_secs),
+            session: Duration::from_secs(self.session_timeout_secs),
+        }
+    }
+
+    /// The wire format to speak, with --binary taken into account
+    pub fn wire(&self) -> WireFormat {
+        if self.binary { WireFormat::Binary } else { self.wire }
+    }
+
+    /// How to write --session-log
+    pub fn session_log_options(&self) -> AuditLogOptions {
+        AuditLogOptions { fsync: self.session_log_fsync, max_bytes: self.session_log_max_bytes, ..AuditLogOptions::default() }
+    }
+
+    /// Take the listener address, certificate and session timeout from `config`
+    pub fn apply_config(&mut self, config: &VerifierConfig) {
+        self.bind_addr = config.bind_addr.ip();
+        self.port = Some(config.bind_addr.port());
+        if let (Some(cert), Some(key)) = (&config.tls_cert_path, &config.tls_key_path) {
+            self.cert_pem = Some(cert.clone());
+            self.key_pem = Some(key.clone());
+        }
+        self.session_timeout_secs = config.session_timeout_secs;
+    }
+
+    /// Where to listen: --bind-addr and --port, with the port defaulting by transport
+    pub fn listen_addr(&self) -> SocketAddr {
+        let default_port = if self.ws { WS_PORT } else { TLS_PORT };
+        SocketAddr::new(self.bind_addr, self.port.unwrap_or(default_port))
+    }
+}
@@ -0,0 +1,53 @@
+use anyhow::Result; // propagate I/O, TLS and transport errors with `?`
+use clap::Parser; // command-line flags
+use tonic::transport::{Identity, Server, ServerTlsConfig}; // tonic's HTTP/2 server, TLS included
+use tracing::info; // structured logging
+use verifier::{demo_registry, init_logging, proof_log, self_signed_cert, VerifierArgs}; // the same flags as the TLS verifier; --wire, --ws and the connection limits don't apply
+
+//shared library
+use zk_schnorr_lib::{
+    PemCertificate, // our certificate, self-signed unless --cert-pem is given
+    SchnorrService, grpc::server_tls_config, // every Prove call is one session, checked like the TLS verifier checks a connection
+};
+
+const CERT_PATH: &str = "verifier_cert.der"; // where provers pick up the certificate to trust
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = VerifierArgs::parse();
+    init_logging(args.log_level.as_deref(), args.log_json);
+    let audit_log = proof_log(&args)?; // --audit-log and --save-transcripts
+
+    // Step 1: TLS for tonic, from --cert-pem or a self-signed certificate for development
+    let tls_config = match (&args.cert_pem, &args.key_pem) {
+        (Some(cert_path), Some(key_path)) => {
+            let (cert_pem, key_pem) = (std::fs::read(cert_path)?, std::fs::read(key_path)?);
+            let pem_cert = PemCertificate::from_pem(&cert_pem, &key_pem)?; // fail here, with our error, on a bad file
+            std::fs::write(CERT_PATH, pem_cert.leaf_der())?;
+            info!(path = %cert_path.display(), "Loaded certificate");
+            ServerTlsConfig::new().identity(Identity::from_pem(cert_pem, key_pem))
+        }
+        _ => {
+            let tls_cert = self_signed_cert(&args)?; // for --san, localhost and 127.0.0.1 unless given
+            std::fs::write(CERT_PATH, &tls_cert.cert_der)?; // prover-grpc reads this to trust our self-signed cert
+            server_tls_config(&tls_cert)
+        }
+    };
+    info!(path = CERT_PATH, "Wrote certificate for provers to trust");
+
+    info!(scheme = %args.scheme, "Expecting the demo prover");
+    let service = SchnorrService::with_options(demo_registry(args.scheme), args.timeouts(), audit_log);
+
+    // Step 2: serve Prove calls until ctrl-C
+    let addr = args.listen_addr();
+    info!(%addr, "Verifier listening for gRPC");
+    Server::builder()
+        .tls_config(tls_config)?
+        .add_service(service.into_server())
+        .serve_with_shutdown(addr, async {
+            let _ = tokio::signal::ctrl_c().await;
+            info!("Shutting down");
+        })
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use std::path::PathBuf; // transcript files named on the command line
+use std::process::ExitCode; // non-zero when any transcript fails
+use clap::Parser; // command-line flags
+
+//shared library
+use zk_schnorr_lib::{
+    read_transcript, verify_transcript, // load a transcript and re-run its check
+    TranscriptValidity, // and say how much a pass is worth
+};
+
+/// Re-verify proof transcripts saved by `verifier --save-transcripts`, offline
+#[derive(Parser, Debug)]
+#[command(about = "Re-verify saved Schnorr proof transcripts")]
+struct Args {
+    /// Transcript files to check
+    #[arg(required = true)]
+    transcripts: Vec<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut all_valid = true;
+    for path in &args.transcripts {
+        let checked = read_transcript(path).map_err(|e| e.to_string())
+            .and_then(|transcript| verify_transcript(&transcript).map_err(|e| e.to_string()));
+        match checked {
+            Ok(TranscriptValidity::EquationHolds) => println!("{}: valid (interactive: the equation holds, convincing only to the verifier that chose c)", path.display()),
+            Ok(TranscriptValidity::NonInteractive) => println!("{}: valid (Fiat-Shamir: challenge re-derived, sound for anyone)", path.display()),
+            Err(e) => {
+                println!("{}: INVALID: {e}", path.display());
+                all_valid = false;
+            }
+        }
+    }
+    if all_valid { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
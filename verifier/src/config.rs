@@ -0,0 +1,101 @@
+//! The verifier's settings as a JSON file, `verifier.json` unless --config says otherwise
+//!
+//! ```json
+//! {
+//!   "expected_public_keys": ["2045d0b160f3bd0aa68a6ad1ba77987b73ae7b6bf5cd44919af093a01487e852"],
+//!   "tls_cert_path": "verifier.pem",
+//!   "tls_key_path": "verifier.key",
+//!   "bind_addr": "0.0.0.0:4433",
+//!   "session_timeout_secs": 120
+//! }
+//! ```
+//!
+//! Only `expected_public_keys` is required. A prover proving any of the keys
+//! is accepted, but as each challenge is bound to the key being proved, the
+//! prover has to say which one: it names the key's hex as its identity
+//! (`prover --identity <hex>`). With a single key it needn't name it at all.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use zk_schnorr_lib::{KeyPair, KeyRegistry, PointDecodeError, PublicKey, ZkError};
+
+use crate::args::TLS_PORT;
+use crate::DEMO_SEED;
+
+/// Where `VerifierConfig::from_file` looks unless told otherwise
+pub const DEFAULT_CONFIG_PATH: &str = "verifier.json";
+
+/// Keys to accept, and where and how to serve them
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VerifierConfig {
+    /// Accept a prover that proves any one of these
+    pub expected_public_keys: Vec<PublicKey>,
+    /// PEM certificate chain to serve, with `tls_key_path`; a self-signed one otherwise
+    #[serde(default)]
+    pub tls_cert_path: Option<PathBuf>,
+    #[serde(default)]
+    pub tls_key_path: Option<PathBuf>,
+    #[serde(default = "default_bind_addr")]
+    pub bind_addr: SocketAddr,
+    #[serde(default = "default_session_timeout_secs")]
+    pub session_timeout_secs: u64,
+}
+
+fn default_bind_addr() -> SocketAddr {
+    SocketAddr::from((Ipv4Addr::LOCALHOST, TLS_PORT))
+}
+
+fn default_session_timeout_secs() -> u64 {
+    120
+}
+
+/// The demo prover's key on the usual port, as with no file at all
+impl Default for VerifierConfig {
+    fn default() -> Self {
+        Self {
+            expected_public_keys: vec![KeyPair::from_seed(DEMO_SEED).public_key()],
+            tls_cert_path: None,
+            tls_key_path: None,
+            bind_addr: default_bind_addr(),
+            session_timeout_secs: default_session_timeout_secs(),
+        }
+    }
+}
+
+impl VerifierConfig {
+    /// Read and check a config file
+    ///
+    /// Besides JSON that doesn't fit, a file is refused if it lists no keys,
+    /// gives only one of the certificate and key paths, or lists the identity
+    /// or a [weak key](PublicKey::is_weak), whose secret is no secret.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ZkError> {
+        let file = File::open(path)?;
+        let config: Self = serde_json::from_reader(BufReader::new(file)).map_err(|e| ZkError::Config(e.to_string()))?;
+        if config.expected_public_keys.is_empty() {
+            return Err(ZkError::Config("expected_public_keys is empty".to_string()));
+        }
+        if config.tls_cert_path.is_some() != config.tls_key_path.is_some() {
+            return Err(ZkError::Config("tls_cert_path and tls_key_path go together".to_string()));
+        }
+        for key in &config.expected_public_keys {
+            if key.is_weak() {
+                return Err(ZkError::PointDecode(PointDecodeError::WeakKey));
+            }
+        }
+        Ok(config)
+    }
+
+    /// The keys as a registry, each under its own hex as the identity to claim
+    pub fn registry(&self) -> KeyRegistry {
+        let mut registry = KeyRegistry::new();
+        for key in &self.expected_public_keys {
+            registry.insert(key.to_string(), *key);
+        }
+        registry
+    }
+}
@@ -0,0 +1,153 @@
+//! The verifier's per-connection logic, kept out of `main.rs` so tests can
+//! drive it over any stream, e.g. one end of a `tokio::io::duplex` pipe.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustls::ServerConfig;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tracing::info;
+use tracing_subscriber::EnvFilter;
+use zk_schnorr_lib::{
+    create_server_config, create_server_config_from_pem, generate_self_signed_cert_for, run_verifier_logged, ConnectionLimiter,
+    Ed25519SigningKey, JsonFileLog, KeyPair, KeyRegistry, Metrics, PemCertificate, ProofLog, ProofOutcome, ProtocolError, PublicKey,
+    Scheme, StreamTransport, TimeoutTransport, Timeouts, TlsCertificate, TlsError, TranscriptDir, VerifierServer,
+    VerifierServerBuilder, WireFormat, ZkError,
+};
+
+pub use zk_schnorr_lib::server::{serve, ShutdownSummary}; // the accept loop the binary runs, via VerifierServer
+
+pub mod args; // the binary's clap options, parsed in tests without starting a server
+pub use args::VerifierArgs;
+pub mod config; // keys and listener settings from verifier.json
+pub use config::{VerifierConfig, DEFAULT_CONFIG_PATH};
+
+/// The one registered prover; unnamed provers are checked against it too
+pub const DEMO_IDENTITY: &str = "demo-prover";
+
+/// The seed the demo prover derives its key from by default
+pub const DEMO_SEED: &[u8] = b"demo-prover-secret";
+
+/// The binaries' registry: the key the demo prover derives from its default seed, for `scheme`
+///
+/// NB : a known public key X - in practice, keys would be looked up per prover
+pub fn demo_registry(scheme: Scheme) -> KeyRegistry {
+    match scheme {
+        Scheme::Ristretto => KeyRegistry::single(DEMO_IDENTITY, KeyPair::from_seed(DEMO_SEED).public_key()),
+        Scheme::Ed25519 => KeyRegistry::single(DEMO_IDENTITY, Ed25519SigningKey::derive(DEMO_SEED).public_key()),
+    }
+}
+
+/// The binaries' certificate when there's no --cert-pem: self-signed for every --san
+pub fn self_signed_cert(args: &VerifierArgs) -> Result<TlsCertificate, TlsError> {
+    generate_self_signed_cert_for(&args.san, args.cert_validity_days, "ZK Schnorr TLS Demo")
+}
+
+/// The TLS side of the verifier binary: --cert-pem and --key-pem, or else [`self_signed_cert`]
+///
+/// Also returns the DER of the leaf certificate, which provers that don't
+/// validate against a CA pin instead.
+pub fn server_tls(args: &VerifierArgs) -> Result<(ServerConfig, Vec<u8>), ZkError> {
+    match (&args.cert_pem, &args.key_pem) {
+        (Some(cert_path), Some(key_path)) => {
+            let pem_cert = PemCertificate::from_pem(&std::fs::read(cert_path)?, &std::fs::read(key_path)?)?;
+            info!(path = %cert_path.display(), "Loaded certificate");
+            Ok((create_server_config_from_pem(&pem_cert)?, pem_cert.leaf_der().to_vec()))
+        }
+        _ => {
+            let tls_cert = self_signed_cert(args)?;
+            Ok((create_server_config(&tls_cert, None)?, tls_cert.cert_der))
+        }
+    }
+}
+
+/// A `VerifierServer` set up from the flags, serving `tls` and checking provers against `registry`
+///
+/// Everything the verifier binary configures except the session log, which
+/// has to be opened first, and the metrics listener; add those, or an
+/// `on_result` callback, before building.
+pub fn server_builder(args: &VerifierArgs, tls: ServerConfig, registry: KeyRegistry) -> std::io::Result<VerifierServerBuilder> {
+    let mut builder = VerifierServer::builder()
+        .bind(args.listen_addr())
+        .tls(tls)
+        .expected_keys(registry)
+        .timeouts(args.timeouts())
+        .wire(args.wire()) // every connection speaks the same encoding
+        .max_message_len(args.max_message_bytes)
+        .websocket(args.ws)
+        .audit_log(proof_log(args)?) // --audit-log and --save-transcripts
+        .limiter(ConnectionLimiter::new(args.max_connections, args.rate_limit))
+        .shutdown_grace(Duration::from_secs(args.shutdown_grace_secs));
+    if let Some(ws_addr) = args.listen_ws {
+        builder = builder.bind_ws(ws_addr); // browser provers alongside the raw TLS ones, same keys and limits
+    }
+    Ok(builder)
+}
+
+/// Where the binaries record checked proofs: --audit-log, --save-transcripts, both or neither
+pub fn proof_log(args: &VerifierArgs) -> std::io::Result<Arc<dyn ProofLog>> {
+    let mut logs: Vec<Arc<dyn ProofLog>> = Vec::new();
+    if let Some(path) = &args.audit_log {
+        logs.push(Arc::new(JsonFileLog::new(path)));
+    }
+    if let Some(dir) = &args.save_transcripts {
+        logs.push(Arc::new(TranscriptDir::create(dir)?));
+    }
+    Ok(Arc::new(logs))
+}
+
+/// handle a single prover connection and run the Schnorr verification protocol
+/// 
+/// In the binary `stream` is a TLS-encrypted connection, but the Schnorr
+/// protocol logic doesn't care: TLS provides transparent encryption
+/// underneath our zero-knowledge proof, and tests pass an in-memory pipe.
+///
+/// The prover may send any number of proofs over the one connection; each
+/// round's outcome is returned in order once the prover hangs up, along with
+/// the agreed version. A malformed or out-of-order message ends the session,
+/// after the prover has been sent an `error` message saying why.
+///
+/// The prover must open with a `hello` whose version is compatible with ours;
+/// otherwise it gets an `error` listing our version and the session ends
+/// with `ProtocolError::Negotiation` before any crypto happens.
+///
+/// Messages are encoded as `wire`; nothing is negotiated, so a prover using
+/// another format fails on the first message with `ProtocolError::Codec`.
+///
+/// Each read is bounded by `timeouts.message`; when it runs out the returned
+/// error is `ProtocolError::Timeout` naming the message we were waiting for.
+///
+/// Every round that gets as far as a verdict is recorded in `audit_log`, and
+/// counted in `metrics` along with the reason a session ended early, if it did.
+pub async fn handle_prover<S>(
+    stream: S,
+    expected_key: &PublicKey,
+    timeouts: Timeouts,
+    wire: WireFormat,
+    audit_log: Arc<dyn ProofLog>,
+    metrics: &Metrics,
+) -> Result<ProofOutcome, ProtocolError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut transport = TimeoutTransport::new(StreamTransport::with_codec(stream, wire), timeouts.message);
+    let outcome = run_verifier_logged(&mut transport, expected_key, audit_log.as_ref()).await;
+    metrics.record_session(outcome.as_ref());
+    outcome
+}
+
+/// Install the global tracing subscriber
+///
+/// `--log-level` wins over `RUST_LOG`; with neither set we log at `info`.
+pub fn init_logging(log_level: Option<&str>, json: bool) {
+    let filter = match log_level {
+        Some(level) => EnvFilter::new(level),
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+    };
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    if json {
+        builder.json().init();
+    } else {
+        builder.init();
+    }
+}
@@ -0,0 +1,103 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+
+use clap::Parser;
+use verifier::{demo_registry, VerifierArgs, DEMO_IDENTITY};
+use zk_schnorr_lib::{AuditLogOptions, FsyncPolicy, RegisteredKey, Scheme, WireFormat, DEFAULT_MAX_MESSAGE_LEN};
+
+fn parse(args: &[&str]) -> Result<VerifierArgs, clap::Error> {
+    VerifierArgs::try_parse_from(std::iter::once("verifier").chain(args.iter().copied()))
+}
+
+#[test]
+fn defaults_listen_locally_on_the_demo_ports() {
+    let args = parse(&[]).unwrap();
+    assert_eq!(args.listen_addr(), SocketAddr::from(([127, 0, 0, 1], 4433)));
+    assert_eq!(args.max_connections, 64);
+    assert_eq!(args.wire(), WireFormat::Json);
+    assert!(args.cert_pem.is_none() && args.key_pem.is_none());
+
+    assert_eq!(parse(&["--ws"]).unwrap().listen_addr().port(), 4434);
+}
+
+#[test]
+fn bind_addr_port_and_limits_are_taken_from_the_flags() {
+    let args = parse(&["--bind-addr", "0.0.0.0", "--port", "9000", "--max-connections", "8", "--max-message-bytes", "1024", "--ws"]).unwrap();
+    assert_eq!(args.bind_addr, IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    assert_eq!(args.listen_addr(), SocketAddr::from(([0, 0, 0, 0], 9000)));
+    assert_eq!(args.max_connections, 8);
+    assert_eq!(args.max_message_bytes, 1024);
+    assert_eq!(parse(&[]).unwrap().max_message_bytes, DEFAULT_MAX_MESSAGE_LEN);
+}
+
+#[test]
+fn cert_and_key_pem_come_together() {
+    let args = parse(&["--cert-pem", "cert.pem", "--key-pem", "key.pem"]).unwrap();
+    assert_eq!(args.cert_pem, Some(PathBuf::from("cert.pem")));
+    assert_eq!(args.key_pem, Some(PathBuf::from("key.pem")));
+
+    let err = parse(&["--cert-pem", "cert.pem"]).unwrap_err();
+    assert_eq!(err.kind(), clap::error::ErrorKind::MissingRequiredArgument);
+}
+
+#[test]
+fn san_is_repeatable_and_defaults_to_localhost() {
+    let args = parse(&[]).unwrap();
+    assert_eq!(args.san, ["localhost", "127.0.0.1"]);
+    assert_eq!(args.cert_validity_days, 365);
+
+    let args = parse(&["--san", "verifier.internal", "--san", "10.0.0.5", "--cert-validity-days", "30"]).unwrap();
+    assert_eq!(args.san, ["verifier.internal", "10.0.0.5"]);
+    assert_eq!(args.cert_validity_days, 30);
+}
+
+#[test]
+fn binary_selects_the_binary_wire_format() {
+    assert_eq!(parse(&["--binary"]).unwrap().wire(), WireFormat::Binary);
+    assert!(parse(&["--binary", "--wire", "json"]).is_err());
+}
+
+#[test]
+fn malformed_bind_addr_is_refused() {
+    assert!(parse(&["--bind-addr", "localhost:4433"]).is_err());
+}
+
+#[test]
+fn scheme_picks_the_demo_key() {
+    assert_eq!(parse(&[]).unwrap().scheme, Scheme::Ristretto);
+    let args = parse(&["--scheme", "ed25519"]).unwrap();
+    let registry = demo_registry(args.scheme);
+    assert_eq!(registry.get(DEMO_IDENTITY).map(RegisteredKey::scheme), Some(Scheme::Ed25519));
+    assert!(parse(&["--scheme", "rsa"]).is_err());
+}
+
+#[test]
+fn listen_ws_adds_a_websocket_listener() {
+    assert!(parse(&[]).unwrap().listen_ws.is_none());
+    let args = parse(&["--listen-ws", "127.0.0.1:4434"]).unwrap();
+    assert_eq!(args.listen_ws, Some(SocketAddr::from(([127, 0, 0, 1], 4434))));
+    assert_eq!(args.listen_addr().port(), 4433, "raw TLS stays on its own port");
+    assert!(parse(&["--listen-ws", "127.0.0.1:4434", "--ws"]).is_err());
+}
+
+#[test]
+fn session_log_flags_configure_the_writer() {
+    let args = parse(&["--session-log", "sessions.jsonl"]).unwrap();
+    assert_eq!(args.session_log, Some(PathBuf::from("sessions.jsonl")));
+    assert_eq!(args.session_log_options(), AuditLogOptions::default());
+
+    let args = parse(&["--session-log", "s.jsonl", "--session-log-fsync", "always", "--session-log-max-bytes", "1048576"]).unwrap();
+    let options = args.session_log_options();
+    assert_eq!((options.fsync, options.max_bytes), (FsyncPolicy::Always, Some(1_048_576)));
+
+    assert!(parse(&["--session-log-fsync", "never"]).is_err(), "needs --session-log");
+    assert!(parse(&["--session-log", "s.jsonl", "--session-log-fsync", "sometimes"]).is_err());
+}
+
+#[test]
+fn saved_transcripts_go_to_the_given_directory() {
+    assert_eq!(parse(&[]).unwrap().save_transcripts, None);
+    let args = parse(&["--save-transcripts", "transcripts", "--audit-log", "audit.jsonl"]).unwrap();
+    assert_eq!(args.save_transcripts, Some(PathBuf::from("transcripts")));
+    assert_eq!(args.audit_log, Some(PathBuf::from("audit.jsonl")), "alongside the audit log");
+}
@@ -0,0 +1,79 @@
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use clap::Parser;
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use tempfile::NamedTempFile;
+use verifier::{VerifierArgs, VerifierConfig};
+use zk_schnorr_lib::{
+    run_prover_as, run_verifier_registry, ChannelTransport, KeyPair, NullLog, PointDecodeError, ProverOptions, ZkError,
+};
+
+fn config_file(json: &str) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(json.as_bytes()).unwrap();
+    file
+}
+
+#[test]
+fn keys_and_settings_are_read_from_the_file() {
+    let (alice, bob) = (KeyPair::from_seed(b"alice").public_key(), KeyPair::from_seed(b"bob").public_key());
+    let file = config_file(&format!(
+        r#"{{"expected_public_keys": ["{alice}", "{bob}"], "tls_cert_path": "v.pem", "tls_key_path": "v.key",
+            "bind_addr": "0.0.0.0:9000", "session_timeout_secs": 15}}"#
+    ));
+    let config = VerifierConfig::from_file(file.path()).unwrap();
+    assert_eq!(config.expected_public_keys, [alice, bob]);
+    assert_eq!(config.tls_cert_path, Some(PathBuf::from("v.pem")));
+    assert_eq!(config.bind_addr, SocketAddr::from(([0, 0, 0, 0], 9000)));
+    assert_eq!(config.session_timeout_secs, 15);
+
+    let mut args = VerifierArgs::try_parse_from(["verifier"]).unwrap();
+    args.apply_config(&config);
+    assert_eq!(args.listen_addr(), config.bind_addr);
+    assert_eq!(args.key_pem, Some(PathBuf::from("v.key")));
+    assert_eq!(args.session_timeout_secs, 15);
+}
+
+#[test]
+fn only_the_keys_are_required() {
+    let key = KeyPair::from_seed(b"alice").public_key();
+    let file = config_file(&format!(r#"{{"expected_public_keys": ["{key}"]}}"#));
+    let config = VerifierConfig::from_file(file.path()).unwrap();
+    let defaults = VerifierConfig::default();
+    assert_eq!(config, VerifierConfig { expected_public_keys: vec![key], ..defaults });
+}
+
+#[test]
+fn unusable_files_are_refused() {
+    let key = KeyPair::from_seed(b"alice").public_key();
+    let weak = zk_schnorr_lib::PublicKey(RistrettoPoint::mul_base(&Scalar::from(5u64)));
+    for json in [
+        r#"{"expected_public_keys": []}"#.to_string(),
+        format!(r#"{{"expected_public_keys": ["{key}"], "tls_cert_path": "v.pem"}}"#),
+        format!(r#"{{"expected_public_keys": ["{key}"], "port": 9000}}"#),
+        "not json".to_string(),
+    ] {
+        assert!(matches!(VerifierConfig::from_file(config_file(&json).path()), Err(ZkError::Config(_))), "{json}");
+    }
+    let file = config_file(&format!(r#"{{"expected_public_keys": ["{key}", "{weak}"]}}"#));
+    assert!(matches!(VerifierConfig::from_file(file.path()), Err(ZkError::PointDecode(PointDecodeError::WeakKey))));
+    assert!(matches!(VerifierConfig::from_file("no-such-verifier.json"), Err(ZkError::Io(_))));
+}
+
+#[tokio::test]
+async fn a_prover_of_any_listed_key_is_accepted() {
+    let keypairs = [KeyPair::from_seed(b"alice"), KeyPair::from_seed(b"bob")];
+    let config = VerifierConfig { expected_public_keys: keypairs.iter().map(KeyPair::public_key).collect(), ..Default::default() };
+    let (registry, options) = (config.registry(), ProverOptions::default());
+
+    for keypair in &keypairs {
+        let identity = keypair.public_key().to_string();
+        let (mut prover, mut verifier) = ChannelTransport::pair();
+        let prove = async move { run_prover_as(&mut prover, keypair, &identity, &options).await }; // hangs up when done
+        let (proved, verified) = tokio::join!(prove, run_verifier_registry(&mut verifier, &registry, &NullLog));
+        assert_eq!(proved.unwrap().rounds, [true]);
+        assert_eq!(verified.unwrap().0, keypair.public_key().to_string());
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use tokio::io::DuplexStream;
+use verifier::handle_prover;
+use zk_schnorr_lib::{
+    run_prover, DecodeError, KeyPair, Message, Metrics, MetricsSnapshot, NullLog, PointDecodeError, ProofOutcome,
+    ProtocolError, RejectReason, ScalarDecodeError, StreamTransport, Timeouts, Transport, VersionInfo, WireFormat,
+};
+
+const TIMEOUTS: Timeouts = Timeouts {
+    handshake: Duration::from_secs(5),
+    message: Duration::from_secs(5),
+    session: Duration::from_secs(5),
+};
+
+fn expected_keypair() -> KeyPair {
+    KeyPair::from_seed(b"demo-prover-secret")
+}
+
+// run `handle_prover` on one end of a duplex pipe while `prover` drives the other
+async fn with_verifier<F, Fut>(prover: F) -> Result<ProofOutcome, ProtocolError>
+where
+    F: FnOnce(StreamTransport<DuplexStream>) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    with_metered_verifier(prover).await.0
+}
+
+// `with_verifier`, also returning what the session added to the metrics
+async fn with_metered_verifier<F, Fut>(prover: F) -> (Result<ProofOutcome, ProtocolError>, MetricsSnapshot)
+where
+    F: FnOnce(StreamTransport<DuplexStream>) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let (prover_end, verifier_end) = tokio::io::duplex(4096);
+    let expected = expected_keypair().public_key();
+    let metrics = Metrics::new();
+    let verify = handle_prover(verifier_end, &expected, TIMEOUTS, WireFormat::Json, Arc::new(NullLog), &metrics);
+    let ((), verified) = tokio::join!(prover(StreamTransport::new(prover_end)), verify);
+    (verified, metrics.snapshot())
+}
+
+// the opening exchange every session starts with: hello, then the verifier's context
+async fn open(transport: &mut StreamTransport<DuplexStream>) {
+    transport.send(Message::hello(&VersionInfo::current())).await.unwrap();
+    assert_eq!(transport.recv().await.unwrap().kind, "hello");
+    assert_eq!(transport.recv().await.unwrap().kind, "context");
+}
+
+fn message(kind: &str, payload: String) -> Message {
+    Message { kind: kind.to_string(), payload, session: None }
+}
+
+#[tokio::test]
+async fn honest_prover_is_accepted() {
+    let outcome = with_verifier(|mut transport| async move {
+        run_prover(&mut transport, &expected_keypair()).await.unwrap();
+    })
+    .await
+    .unwrap();
+    assert_eq!(outcome.rounds, vec![true]);
+}
+
+#[tokio::test]
+async fn wrong_secret_is_not_accepted() {
+    let verified = with_verifier(|mut transport| async move {
+        let err = run_prover(&mut transport, &KeyPair::from_seed(b"someone else")).await.unwrap_err();
+        assert!(matches!(err, ProtocolError::InvalidChallenge));
+    })
+    .await;
+    assert!(matches!(verified, Err(ProtocolError::Rejected { .. })));
+}
+
+#[tokio::test]
+async fn tampered_commitment_is_a_decode_error() {
+    let verified = with_verifier(|mut transport| async move {
+        open(&mut transport).await;
+        let mut commitment = Message::commit(&RistrettoPoint::mul_base(&Scalar::from(5u64)));
+        commitment.payload.replace_range(..2, "ff"); // no longer a canonical field element, so not a point
+        transport.send(commitment).await.unwrap();
+        assert_eq!(transport.recv().await.unwrap().kind, "error");
+    })
+    .await;
+    assert!(matches!(verified, Err(ProtocolError::Decode(DecodeError::Point(PointDecodeError::InvalidPoint)))));
+}
+
+#[tokio::test]
+async fn non_canonical_response_is_a_decode_error() {
+    let verified = with_verifier(|mut transport| async move {
+        open(&mut transport).await;
+        transport.send(Message::commit(&RistrettoPoint::mul_base(&Scalar::from(5u64)))).await.unwrap();
+        assert_eq!(transport.recv().await.unwrap().kind, "challenge");
+        transport.send(message("response", "ff".repeat(32))).await.unwrap(); // 2^256 - 1, above the group order
+        assert_eq!(transport.recv().await.unwrap().kind, "error");
+    })
+    .await;
+    assert!(matches!(verified, Err(ProtocolError::Decode(DecodeError::Scalar(ScalarDecodeError::NonCanonical)))));
+}
+
+#[tokio::test]
+async fn response_before_commit_is_out_of_order() {
+    let verified = with_verifier(|mut transport| async move {
+        open(&mut transport).await;
+        transport.send(Message::response(&Scalar::ONE)).await.unwrap();
+        assert_eq!(transport.recv().await.unwrap().kind, "error");
+    })
+    .await;
+    assert!(matches!(verified, Err(ProtocolError::UnexpectedMessage { expected: "commit", .. })));
+}
+
+#[tokio::test]
+async fn dropping_the_connection_mid_round_is_reported() {
+    let verified = with_verifier(|mut transport| async move {
+        open(&mut transport).await;
+        transport.send(Message::commit(&RistrettoPoint::mul_base(&Scalar::from(5u64)))).await.unwrap();
+        assert_eq!(transport.recv().await.unwrap().kind, "challenge");
+        // dropping the transport here closes the pipe before any response
+    })
+    .await;
+    assert!(matches!(verified, Err(ProtocolError::ConnectionClosed)));
+}
+
+#[tokio::test]
+async fn metrics_count_verified_proofs_and_rejections_by_reason() {
+    let (_, honest) = with_metered_verifier(|mut transport| async move {
+        run_prover(&mut transport, &expected_keypair()).await.unwrap();
+    })
+    .await;
+    assert_eq!((honest.proofs_verified, honest.total_rejected()), (1, 0));
+
+    let (_, wrong_key) = with_metered_verifier(|mut transport| async move {
+        let _ = run_prover(&mut transport, &KeyPair::from_seed(b"someone else")).await;
+    })
+    .await;
+    assert_eq!((wrong_key.proofs_verified, wrong_key.rejected(RejectReason::WrongKey)), (0, 1));
+
+    let (_, out_of_order) = with_metered_verifier(|mut transport| async move {
+        open(&mut transport).await;
+        transport.send(Message::response(&Scalar::ONE)).await.unwrap();
+        let _ = transport.recv().await;
+    })
+    .await;
+    assert_eq!((out_of_order.rejected(RejectReason::BadCommit), out_of_order.total_rejected()), (1, 1));
+}
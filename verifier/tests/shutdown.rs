@@ -0,0 +1,74 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot};
+use verifier::{handle_prover, serve, ShutdownSummary};
+use zk_schnorr_lib::{
+    KeyPair, Metrics, NullLog, ProofOutcome, ProtocolError, SchnorrProtocol, StreamTransport, Timeouts, VersionInfo, WireFormat,
+};
+
+const TIMEOUTS: Timeouts = Timeouts {
+    handshake: Duration::from_secs(5),
+    message: Duration::from_secs(5),
+    session: Duration::from_secs(5),
+};
+
+type Outcomes = mpsc::UnboundedReceiver<Result<ProofOutcome, ProtocolError>>;
+
+// a plain-TCP verifier on a free port that stops when the returned sender fires
+async fn start(grace: Duration) -> (std::net::SocketAddr, oneshot::Sender<()>, Outcomes, tokio::task::JoinHandle<ShutdownSummary>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stop, stopped) = oneshot::channel::<()>();
+    let (outcome_tx, outcomes) = mpsc::unbounded_channel();
+    let expected = KeyPair::from_seed(b"demo-prover-secret").public_key();
+    let server = tokio::spawn(async move {
+        let shutdown = async {
+            let _ = stopped.await;
+        };
+        serve(listener, shutdown, grace, move |stream, _| {
+            let outcome_tx = outcome_tx.clone();
+            Some(async move {
+                let outcome = handle_prover(stream, &expected, TIMEOUTS, WireFormat::Json, Arc::new(NullLog), &Metrics::new()).await;
+                let _ = outcome_tx.send(outcome);
+            })
+        })
+        .await
+        .unwrap()
+    });
+    (addr, stop, outcomes, server)
+}
+
+#[tokio::test]
+async fn session_in_flight_finishes_and_new_connections_are_refused() {
+    let (addr, stop, mut outcomes, server) = start(Duration::from_secs(5)).await;
+    let keypair = KeyPair::from_seed(b"demo-prover-secret");
+
+    let mut transport = StreamTransport::new(TcpStream::connect(addr).await.unwrap());
+    SchnorrProtocol::send_hello(&mut transport, &VersionInfo::current()).await.unwrap();
+    let context = SchnorrProtocol::open_prover_session(&mut transport).await.unwrap();
+
+    stop.send(()).unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await; // let the accept loop see it and close the listener
+    let refused = TcpStream::connect(addr).await.unwrap_err();
+    assert_eq!(refused.kind(), std::io::ErrorKind::ConnectionRefused);
+
+    SchnorrProtocol::prove_round(&mut transport, &context, &keypair).await.unwrap();
+    drop(transport);
+
+    assert_eq!(outcomes.recv().await.unwrap().unwrap().rounds, vec![true]);
+    assert_eq!(server.await.unwrap(), ShutdownSummary { completed: 1, aborted: 0 });
+}
+
+#[tokio::test]
+async fn session_outlasting_the_grace_period_is_aborted() {
+    let (addr, stop, mut outcomes, server) = start(Duration::from_millis(100)).await;
+
+    let _silent = TcpStream::connect(addr).await.unwrap(); // never says hello
+    tokio::time::sleep(Duration::from_millis(50)).await; // let it be accepted
+    stop.send(()).unwrap();
+
+    assert_eq!(server.await.unwrap(), ShutdownSummary { completed: 0, aborted: 1 });
+    assert!(outcomes.recv().await.is_none());
+}
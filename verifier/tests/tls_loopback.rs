@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use clap::Parser;
+use prover::ProverArgs;
+use tokio::sync::mpsc;
+use verifier::{demo_registry, server_builder, server_tls, VerifierArgs};
+use zk_schnorr_lib::{create_client_config_insecure_from_der, ProofOutcome, ProtocolError, Scheme, ServerHandle, VerificationEvent};
+
+// the verifier binary's server on a free port, the verdicts it reports, and the certificate for provers to trust
+async fn start_verifier() -> (ServerHandle, mpsc::UnboundedReceiver<VerificationEvent>, Vec<u8>) {
+    let args = VerifierArgs::try_parse_from(["verifier", "--port", "0"]).unwrap();
+    let (tls, leaf_der) = server_tls(&args).unwrap();
+    let (events_tx, events) = mpsc::unbounded_channel();
+    let server = server_builder(&args, tls, demo_registry(Scheme::Ristretto))
+        .unwrap()
+        .on_result(move |event| {
+            let _ = events_tx.send(event.clone());
+        })
+        .build()
+        .unwrap();
+    (server.spawn().await.unwrap(), events, leaf_der)
+}
+
+// the prover binary's session against `handle`, trusting exactly `cert_der`
+async fn prove(handle: &ServerHandle, cert_der: &[u8], flags: &[&str]) -> anyhow::Result<ProofOutcome> {
+    let port = handle.local_addr().port().to_string();
+    let args = ProverArgs::try_parse_from(["prover", "--port", &port].into_iter().chain(flags.iter().copied())).unwrap();
+    let client_config = Arc::new(create_client_config_insecure_from_der(cert_der).unwrap());
+    prover::run(&args, client_config, &args.keypair().unwrap(), None).await
+}
+
+#[tokio::test]
+async fn demo_prover_is_accepted_over_tls() {
+    let (handle, mut events, cert_der) = start_verifier().await;
+    assert_ne!(handle.local_addr().port(), 0);
+
+    let outcome = prove(&handle, &cert_der, &["--rounds", "2"]).await.unwrap();
+    assert_eq!(outcome.rounds, [true, true]);
+
+    let event = events.recv().await.unwrap();
+    assert!(event.accepted);
+    assert_eq!(event.identity.as_deref(), Some(verifier::DEMO_IDENTITY));
+    assert_eq!(handle.metrics().proofs_verified, 2);
+    assert_eq!(handle.shutdown().await.unwrap().aborted, 0);
+}
+
+#[tokio::test]
+async fn prover_with_the_wrong_secret_is_rejected_over_tls() {
+    let (handle, mut events, cert_der) = start_verifier().await;
+
+    // the challenge is bound to the demo key, so the prover refuses to answer it and says so
+    let err = prove(&handle, &cert_der, &["--secret-seed", "not-the-demo-secret"]).await.unwrap_err();
+    assert!(matches!(err.downcast_ref(), Some(ProtocolError::InvalidChallenge)), "{err}");
+
+    let event = events.recv().await.unwrap();
+    assert!(!event.accepted);
+    assert_eq!(handle.metrics().total_rejected(), 1);
+    handle.shutdown().await.unwrap();
+}
@@ -0,0 +1,131 @@
+//! Per-operation timings for the core primitives: `cargo bench --bench zk`
+//!
+//! Every key, nonce and batch weight comes from an RNG seeded with [`SEED`],
+//! so two runs time exactly the same inputs.
+
+#![allow(non_snake_case)] // follow the usual maths notation
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use zk_schnorr_lib::signature::{sign, verify};
+use zk_schnorr_lib::{
+    batch_verify, commit, point_from_hex, point_to_hex, respond, scalar_from_hex, scalar_to_hex, BinaryCodec,
+    ChallengeContext, Codec, JsonCodec, KeyPair, Message, Proof, PublicKey, SchnorrProof,
+};
+
+const SEED: u64 = 0x5eed_2c4a_77a1_0001;
+
+fn rng() -> StdRng {
+    StdRng::seed_from_u64(SEED)
+}
+
+// A valid interactive proof for `keypair`, as the verifier would hold it after a round
+fn valid_proof(keypair: &KeyPair, rng: &mut StdRng) -> Proof {
+    let k = Scalar::random(rng);
+    let R = commit(&k);
+    let c = ChallengeContext::random(rng).challenge(&R, &keypair.public_key());
+    Proof::new(R, c, respond(&k, &c, keypair.secret().expose_secret()))
+}
+
+fn scalar_mul(c: &mut Criterion) {
+    let x = Scalar::random(&mut rng());
+    c.bench_function("scalar_mul G*x", |b| b.iter(|| RISTRETTO_BASEPOINT_POINT * black_box(x)));
+}
+
+fn hex_round_trips(c: &mut Criterion) {
+    let x = Scalar::random(&mut rng());
+    let X = RISTRETTO_BASEPOINT_POINT * x;
+    c.bench_function("point hex round-trip", |b| {
+        b.iter(|| point_from_hex(&point_to_hex(black_box(&X))).unwrap())
+    });
+    c.bench_function("scalar hex round-trip", |b| {
+        b.iter(|| scalar_from_hex(&scalar_to_hex(black_box(&x))).unwrap())
+    });
+}
+
+fn message_encoding(c: &mut Criterion) {
+    let msg = Message::commit(&(RISTRETTO_BASEPOINT_POINT * Scalar::random(&mut rng())));
+    let mut group = c.benchmark_group("message");
+    for (name, codec) in [("json", &JsonCodec as &dyn Codec), ("binary", &BinaryCodec)] {
+        let bytes = codec.encode(&msg);
+        group.bench_function(BenchmarkId::new("encode", name), |b| b.iter(|| codec.encode(black_box(&msg))));
+        group.bench_function(BenchmarkId::new("decode", name), |b| b.iter(|| codec.decode(black_box(&bytes)).unwrap()));
+    }
+    group.finish();
+}
+
+fn signatures(c: &mut Criterion) {
+    let mut rng = rng();
+    let x = Scalar::random(&mut rng);
+    let keypair = KeyPair::from_secret(x);
+    let public = keypair.public_key();
+    let sig = sign(&x, b"bench", &mut rng);
+    c.bench_function("sign", |b| b.iter(|| sign(black_box(&x), black_box(b"bench"), &mut rng)));
+    c.bench_function("verify signature", |b| {
+        b.iter(|| verify(black_box(&public), black_box(b"bench"), black_box(&sig)))
+    });
+}
+
+fn interactive_proof(c: &mut Criterion) {
+    let mut rng = rng();
+    let keypair = KeyPair::generate(&mut rng);
+    let secret = *keypair.secret().expose_secret();
+    let context = ChallengeContext::random(&mut rng);
+    let public = keypair.public_key();
+    let proof = valid_proof(&keypair, &mut rng);
+    c.bench_function("proof generate", |b| {
+        b.iter(|| {
+            let k = Scalar::random(&mut rng);
+            let R = commit(&k);
+            let challenge = context.challenge(&R, &public);
+            respond(&k, &challenge, black_box(&secret))
+        })
+    });
+    c.bench_function("proof verify", |b| b.iter(|| black_box(&proof).verify(black_box(&public))));
+}
+
+fn schnorr_proof(c: &mut Criterion) {
+    let mut rng = rng();
+    let keypair = KeyPair::generate(&mut rng);
+    let secret = *keypair.secret().expose_secret();
+    let public = keypair.public_key();
+    let proof = SchnorrProof::prove(&secret, &mut rng);
+    c.bench_function("SchnorrProof::prove", |b| b.iter(|| SchnorrProof::prove(black_box(&secret), &mut rng)));
+    c.bench_function("SchnorrProof::verify", |b| b.iter(|| black_box(&proof).verify(black_box(&public))));
+}
+
+// batch_verify's one multiscalar multiplication against checking each proof in turn
+fn batch_verification(c: &mut Criterion) {
+    let mut rng = rng();
+    let mut group = c.benchmark_group("batch_verify");
+    for size in [1, 10, 100, 1000] {
+        let proofs: Vec<(Proof, PublicKey)> = (0..size)
+            .map(|_| {
+                let keypair = KeyPair::generate(&mut rng);
+                (valid_proof(&keypair, &mut rng), keypair.public_key())
+            })
+            .collect();
+        group.bench_with_input(BenchmarkId::new("batch", size), &proofs, |b, proofs| {
+            b.iter(|| assert!(batch_verify(black_box(proofs), &mut rng)))
+        });
+        group.bench_with_input(BenchmarkId::new("loop", size), &proofs, |b, proofs| {
+            b.iter(|| assert!(black_box(proofs).iter().all(|(proof, public)| proof.verify(public))))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    scalar_mul,
+    hex_round_trips,
+    message_encoding,
+    signatures,
+    interactive_proof,
+    schnorr_proof,
+    batch_verification
+);
+criterion_main!(benches);
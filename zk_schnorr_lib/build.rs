@@ -0,0 +1,12 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // the grpc feature's message and service types, from proto/schnorr.proto
+    #[cfg(feature = "grpc")]
+    {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("no vendored protoc for this platform");
+        // SAFETY: the build script is single-threaded and nothing else reads the environment yet
+        unsafe { std::env::set_var("PROTOC", protoc) };
+        tonic_build::compile_protos("proto/schnorr.proto").expect("failed to compile proto/schnorr.proto");
+    }
+}
@@ -0,0 +1,49 @@
+//! Schnorr adaptor signatures
+//!
+//! A pre-signature is a signature that is "encrypted" under an adaptor point
+//! `T = t*G`: it commits to the nonce `R' = R + T` but leaves `t` out of the
+//! response. Whoever learns `t` can complete it into an ordinary `Signature`,
+//! and once that signature is published, anyone holding the pre-signature
+//! can read `t` back out of it. That link is what atomic swaps and payment
+//! channels are built on.
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::RngCore;
+
+use crate::schnorr::{random_scalar, KeyPair, PublicKey};
+use crate::signature::{challenge, Signature};
+
+/// A pre-signature, valid only once the adaptor secret `t` is added
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptorSignature {
+    /// The final signature's nonce point R' = k*G + T
+    pub R: RistrettoPoint,
+    /// s_hat = k + c*x, with c computed over R'
+    pub s_hat: Scalar,
+}
+
+/// Pre-sign `message` under the adaptor point `T`
+pub fn pre_sign(keypair: &KeyPair, message: &[u8], T: &RistrettoPoint, rng: &mut impl RngCore) -> AdaptorSignature {
+    let k = random_scalar(rng);
+    let R = RISTRETTO_BASEPOINT_POINT * k + T; // R' = R + T
+    let c = challenge(&R, &keypair.public_key(), message);
+    AdaptorSignature { R, s_hat: k + c * keypair.secret().expose_secret() }
+}
+
+/// Check a pre-signature before relying on it: `s_hat*G = R' - T + c*X`
+pub fn pre_verify(public: &PublicKey, message: &[u8], T: &RistrettoPoint, pre_sig: &AdaptorSignature) -> bool {
+    let c = challenge(&pre_sig.R, public, message);
+    RISTRETTO_BASEPOINT_POINT * pre_sig.s_hat == pre_sig.R - T + public.0 * c
+}
+
+/// Complete a pre-signature with the adaptor secret: `s = s_hat + t`
+pub fn adapt(pre_sig: &AdaptorSignature, t: &Scalar) -> Signature {
+    Signature { R: pre_sig.R, s: pre_sig.s_hat + t }
+}
+
+/// Recover the adaptor secret from a pre-signature and its completed signature: `t = s - s_hat`
+pub fn extract(pre_sig: &AdaptorSignature, sig: &Signature) -> Scalar {
+    sig.s - pre_sig.s_hat
+}
@@ -0,0 +1,199 @@
+//! AND-composition: prove knowledge of two secrets at once
+//!
+//! Proves knowledge of `x` with `A = x*G` AND `y` with `B = y*H`. Both Schnorr
+//! proofs share a single challenge `c`, squeezed from a transcript that binds
+//! the generators, both public keys and both commitments, so neither half can
+//! be swapped out or replayed on its own.
+//!
+//! [`MultiAndProof`] is the same idea for any number of keys over the
+//! basepoint: one nonce and one response per statement, one challenge for all.
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::error::DecodeError;
+use crate::schnorr::{commit, random_scalar, respond};
+use crate::transcript::{append_point, challenge_scalar, TranscriptBuilder};
+use crate::{PointDecodeError, ScalarDecodeError};
+
+/// Proof of knowledge of both `log_G(A)` and `log_H(B)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AndProof {
+    #[serde(with = "crate::serde_hex::point")]
+    pub R1: RistrettoPoint,
+    #[serde(with = "crate::serde_hex::point")]
+    pub R2: RistrettoPoint,
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub c: Scalar,
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub s1: Scalar,
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub s2: Scalar,
+}
+
+/// Prove knowledge of `x` and `y` for `A = x*G` and `B = y*H`
+///
+/// Anything already appended to `transcript` (a session id, a message, ...)
+/// is bound into the challenge; the verifier must start from an identical
+/// transcript.
+pub fn prove(
+    x: &Scalar,
+    y: &Scalar,
+    G: &RistrettoPoint,
+    H: &RistrettoPoint,
+    transcript: &mut Transcript,
+    rng: &mut impl RngCore,
+) -> AndProof {
+    let (A, B) = (G * x, H * y);
+    let (k1, k2) = (random_scalar(rng), random_scalar(rng));
+    let (R1, R2) = (G * k1, H * k2);
+
+    let c = challenge(transcript, G, H, &A, &B, &R1, &R2);
+    AndProof { R1, R2, c, s1: k1 + c * x, s2: k2 + c * y }
+}
+
+/// Check both equations against the shared transcript challenge
+pub fn verify(
+    A: &RistrettoPoint,
+    B: &RistrettoPoint,
+    G: &RistrettoPoint,
+    H: &RistrettoPoint,
+    proof: &AndProof,
+    transcript: &mut Transcript,
+) -> bool {
+    let c = challenge(transcript, G, H, A, B, &proof.R1, &proof.R2);
+    let challenge_ok = c == proof.c;
+    let first_ok = G * proof.s1 == proof.R1 + A * c; // s1*G = R1 + c*A
+    let second_ok = H * proof.s2 == proof.R2 + B * c; // s2*H = R2 + c*B
+    challenge_ok & first_ok & second_ok
+}
+
+fn challenge(
+    transcript: &mut Transcript,
+    G: &RistrettoPoint,
+    H: &RistrettoPoint,
+    A: &RistrettoPoint,
+    B: &RistrettoPoint,
+    R1: &RistrettoPoint,
+    R2: &RistrettoPoint,
+) -> Scalar {
+    transcript.append_message(b"dom-sep", b"zk-schnorr-tls/and-proof/v1");
+    for (label, point) in [(b"G", G), (b"H", H), (b"A", A), (b"B", B)] {
+        append_point(transcript, label, point);
+    }
+    append_point(transcript, b"R1", R1);
+    append_point(transcript, b"R2", R2);
+    challenge_scalar(transcript, b"c")
+}
+
+/// Most statements a verifier accepts in one interactive AND round
+///
+/// Every statement costs the verifier a scalar multiplication, so a prover
+/// that commits to more is turned away before any of them is decoded.
+pub const MAX_AND_STATEMENTS: usize = 64;
+
+/// Why a `MultiAndProof` couldn't be made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum AndProofError {
+    #[error("{secrets} secrets for {publics} public keys")]
+    LengthMismatch { secrets: usize, publics: usize },
+    #[error("an AND-proof needs at least one statement")]
+    Empty,
+}
+
+/// Proof of knowledge of `log_G(X_i)` for every key `X_1..X_n` at once
+///
+/// The challenge is Fiat-Shamir, squeezed from every key and every
+/// commitment, so only the `(R_i, s_i)` pairs are carried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiAndProof {
+    /// One `(R_i, s_i)` per statement, in the order of the public keys
+    pub pairs: Vec<(RistrettoPoint, Scalar)>,
+}
+
+impl MultiAndProof {
+    /// Prove knowledge of `secrets[i]` for `publics[i] = secrets[i] * G`, for every `i`
+    pub fn prove(secrets: &[Scalar], publics: &[RistrettoPoint], rng: &mut impl RngCore) -> Result<Self, AndProofError> {
+        if secrets.len() != publics.len() {
+            return Err(AndProofError::LengthMismatch { secrets: secrets.len(), publics: publics.len() });
+        }
+        if secrets.is_empty() {
+            return Err(AndProofError::Empty);
+        }
+        let nonces: Vec<Scalar> = secrets.iter().map(|_| random_scalar(rng)).collect();
+        let commitments: Vec<RistrettoPoint> = nonces.iter().map(commit).collect();
+        let c = multi_challenge(publics, &commitments);
+        let pairs = commitments
+            .into_iter()
+            .zip(nonces.iter().zip(secrets))
+            .map(|(R, (k, x))| (R, respond(k, &c, x)))
+            .collect();
+        Ok(Self { pairs })
+    }
+
+    /// Check `s_i*G = R_i + c*X_i` for every statement against the one shared challenge
+    ///
+    /// `false` if `publics` doesn't have one key per pair.
+    pub fn verify(&self, publics: &[RistrettoPoint]) -> bool {
+        if self.pairs.is_empty() || self.pairs.len() != publics.len() {
+            return false;
+        }
+        let commitments: Vec<RistrettoPoint> = self.pairs.iter().map(|(R, _)| *R).collect();
+        let c = multi_challenge(publics, &commitments);
+        self.pairs.iter().zip(publics).all(|((R, s), X)| commit(s) == R + X * c)
+    }
+
+    /// The number of statements, as a big-endian `u32`, then `R_i || s_i` for each, 64 bytes per pair
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let count = u32::try_from(self.pairs.len()).expect("fewer than 2^32 statements");
+        let mut bytes = Vec::with_capacity(4 + 64 * self.pairs.len());
+        bytes.extend_from_slice(&count.to_be_bytes());
+        for (R, s) in &self.pairs {
+            bytes.extend_from_slice(R.compress().as_bytes());
+            bytes.extend_from_slice(s.as_bytes());
+        }
+        bytes
+    }
+
+    /// Decode `to_bytes` output, rejecting invalid points and non-canonical scalars
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (count, body) = bytes
+            .split_first_chunk::<4>()
+            .ok_or(DecodeError::Length { expected: 4, got: bytes.len() })?;
+        let count = u32::from_be_bytes(*count) as usize;
+        let expected = count.checked_mul(64).and_then(|n| n.checked_add(4)).unwrap_or(usize::MAX);
+        if bytes.len() != expected {
+            return Err(DecodeError::Length { expected, got: bytes.len() });
+        }
+        let pairs = body
+            .chunks_exact(64)
+            .map(|pair| {
+                let (r_bytes, s_bytes) = pair.split_at(32);
+                let R = CompressedRistretto::from_slice(r_bytes)
+                    .expect("32 bytes")
+                    .decompress()
+                    .ok_or(PointDecodeError::InvalidPoint)?;
+                let s = Option::from(Scalar::from_canonical_bytes(s_bytes.try_into().expect("32 bytes")))
+                    .ok_or(ScalarDecodeError::NonCanonical)?;
+                Ok((R, s))
+            })
+            .collect::<Result<_, DecodeError>>()?;
+        Ok(Self { pairs })
+    }
+}
+
+// c squeezed from a transcript over n, every X_i and every R_i
+fn multi_challenge(publics: &[RistrettoPoint], commitments: &[RistrettoPoint]) -> Scalar {
+    let mut transcript = TranscriptBuilder::new(b"zk-schnorr-tls/and-proof/multi/v1");
+    transcript.append_bytes(b"n", &(publics.len() as u64).to_le_bytes());
+    for X in publics {
+        transcript.append_point(b"X", X);
+    }
+    for R in commitments {
+        transcript.append_point(b"R", R);
+    }
+    transcript.challenge()
+}
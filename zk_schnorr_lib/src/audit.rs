@@ -0,0 +1,169 @@
+//! Recording completed proofs for later audit
+//!
+//! The verifier hands every finished round to a [`ProofLog`]: the session it
+//! belonged to, the key it was checked against, the full `(R, c, s)`
+//! transcript and the verdict. [`NullLog`] drops them; [`JsonFileLog`] keeps
+//! one JSON object per line in a file. For lookups by key or session, see
+//! [`crate::proof_store`].
+//!
+//! A [`SessionAuditRecord`] sums up a whole session instead: who connected,
+//! the key they were checked against, how it ended and how long it took. The
+//! `server` feature's `AuditLog` writes one per line.
+
+use std::fs;
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::ZkError;
+use crate::proof_store::ProofEntry;
+use crate::registry::RegisteredKey;
+use crate::schnorr::{Proof, PublicKey};
+use crate::session::SessionId;
+use crate::{point_to_hex, scalar_to_hex};
+
+/// Somewhere to record every proof the verifier has checked
+pub trait ProofLog: Send + Sync {
+    fn record(&self, session_id: &SessionId, public_key: &PublicKey, proof: &Proof, verified: bool, timestamp: SystemTime);
+
+    /// `record`, failing the session if the entry couldn't be kept
+    ///
+    /// This is what the verifier calls. The default records and always
+    /// succeeds, as a log's failures shouldn't end sessions; a
+    /// [`ProofStoreBackend`](crate::ProofStoreBackend) reports its errors instead.
+    fn try_record(&self, entry: ProofEntry) -> Result<(), ZkError> {
+        self.record(&entry.session_id, &entry.public_key, &entry.proof, entry.verified, entry.timestamp);
+        Ok(())
+    }
+}
+
+/// Records nothing
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullLog;
+
+impl ProofLog for NullLog {
+    fn record(&self, _: &SessionId, _: &PublicKey, _: &Proof, _: bool, _: SystemTime) {}
+}
+
+/// Records to every log in turn; the first error from `try_record` fails the session
+impl ProofLog for Vec<Arc<dyn ProofLog>> {
+    fn record(&self, session_id: &SessionId, public_key: &PublicKey, proof: &Proof, verified: bool, timestamp: SystemTime) {
+        for log in self {
+            log.record(session_id, public_key, proof, verified, timestamp);
+        }
+    }
+
+    fn try_record(&self, entry: ProofEntry) -> Result<(), ZkError> {
+        self.iter().try_for_each(|log| log.try_record(entry))
+    }
+}
+
+/// One line of a `JsonFileLog`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub session_id: String,
+    pub public_key: String,
+    pub commitment: String,
+    pub challenge: String,
+    pub response: String,
+    pub verified: bool,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// How a session ended, in a [`SessionAuditRecord`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionOutcome {
+    /// At least one round, and every round verified
+    Accepted,
+    /// The protocol ran to the end but a proof failed, or none was sent
+    Rejected,
+    /// The session broke off: a malformed message, unknown identity, timeout or lost connection
+    Error,
+}
+
+/// One line of a session audit log: how one prover's session went
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionAuditRecord {
+    /// When the session ended, in seconds since the Unix epoch
+    pub timestamp: u64,
+    pub peer_addr: SocketAddr,
+    /// The identity the prover was checked as, or claimed if it isn't registered
+    pub identity: Option<String>,
+    /// The registered key the prover was checked against
+    pub public_key: Option<RegisteredKey>,
+    /// The protocol session, once a proof was checked in it; matches the proof audit records
+    pub session_id: Option<SessionId>,
+    pub outcome: SessionOutcome,
+    /// Why the session wasn't accepted, `None` if it was
+    pub failure_reason: Option<String>,
+    pub rounds: usize,
+    pub verified: usize,
+    /// The TLS or WebSocket handshake, in milliseconds
+    pub handshake_ms: u64,
+    /// From the accepted connection to the end of the session, in milliseconds
+    pub duration_ms: u64,
+}
+
+/// Appends one JSON record per line to `path`
+///
+/// Each append rewrites the whole log into `<path>.tmp` and renames it over
+/// `path`, so a reader never sees a half-written line. A failed write is
+/// logged and otherwise ignored: auditing must not take the verifier down.
+#[derive(Debug)]
+pub struct JsonFileLog {
+    pub path: PathBuf,
+    lock: Mutex<()>, // one rewrite at a time, or concurrent sessions would lose records
+}
+
+impl JsonFileLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), lock: Mutex::new(()) }
+    }
+
+    fn append(&self, line: &str) -> io::Result<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        contents.push_str(line);
+        contents.push('\n');
+
+        let tmp = tmp_path(&self.path);
+        fs::write(&tmp, contents)?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+impl ProofLog for JsonFileLog {
+    fn record(&self, session_id: &SessionId, public_key: &PublicKey, proof: &Proof, verified: bool, timestamp: SystemTime) {
+        let record = AuditRecord {
+            session_id: session_id.to_hex(),
+            public_key: public_key.to_string(),
+            commitment: point_to_hex(&proof.commitment),
+            challenge: scalar_to_hex(&proof.challenge),
+            response: scalar_to_hex(&proof.response),
+            verified,
+            timestamp: timestamp.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default(),
+        };
+        let line = serde_json::to_string(&record).expect("an AuditRecord always serializes");
+        if let Err(e) = self.append(&line) {
+            warn!(error = %e, path = %self.path.display(), "Failed to write audit record");
+        }
+    }
+}
+
+// `audit.jsonl` -> `audit.jsonl.tmp`, next to the log so the rename stays on one filesystem
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    PathBuf::from(tmp)
+}
@@ -0,0 +1,134 @@
+//! Swapping the server certificate of a running verifier
+//!
+//! A `ServerConfig` built with a [`RotatingCertResolver`] (see
+//! `create_server_config`) asks the resolver for the certificate on every
+//! handshake, so [`RotatingCertResolver::reload`] takes effect for the next
+//! connection without restarting anything. Sessions already past their
+//! handshake carry on under the old certificate.
+//!
+//! [`watch_cert_file`] reloads from a PEM certificate and key on disk whenever
+//! either file's modification time changes, for certificates renewed by
+//! another process before they expire.
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+#[cfg(feature = "server")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "server")]
+use std::time::{Duration, SystemTime};
+
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::{any_supported_type, CertifiedKey};
+use rustls::{Certificate as RustlsCertificate, PrivateKey};
+#[cfg(feature = "server")]
+use tracing::{info, warn};
+
+use crate::error::ZkError;
+use crate::tls::{PemCertificate, TlsCertificate, TlsError};
+
+/// A server certificate that can be replaced while the server runs
+pub struct RotatingCertResolver {
+    current: Arc<RwLock<Arc<CertifiedKey>>>,
+}
+
+impl RotatingCertResolver {
+    /// A resolver serving `tls_cert` until the first reload
+    pub fn new(tls_cert: &TlsCertificate) -> Result<Self, ZkError> {
+        Ok(Self { current: Arc::new(RwLock::new(certified_key(vec![tls_cert.cert_der.clone()], &tls_cert.private_key_der)?)) })
+    }
+
+    /// A resolver serving a certificate chain loaded from PEM until the first reload
+    pub fn from_pem(pem_cert: &PemCertificate) -> Result<Self, ZkError> {
+        Ok(Self { current: Arc::new(RwLock::new(certified_key(pem_cert.chain_der.clone(), &pem_cert.private_key_der)?)) })
+    }
+
+    /// Serve `new_cert` from the next handshake on
+    ///
+    /// The key is checked before anything is swapped, so a failed reload
+    /// leaves the current certificate in place.
+    pub fn reload(&self, new_cert: &TlsCertificate) -> Result<(), ZkError> {
+        Ok(self.replace(vec![new_cert.cert_der.clone()], &new_cert.private_key_der)?)
+    }
+
+    /// `reload` with a certificate chain loaded from PEM
+    pub fn reload_pem(&self, new_cert: &PemCertificate) -> Result<(), ZkError> {
+        Ok(self.replace(new_cert.chain_der.clone(), &new_cert.private_key_der)?)
+    }
+
+    /// DER of the leaf certificate currently served
+    pub fn current_leaf_der(&self) -> Vec<u8> {
+        self.current().cert[0].0.clone()
+    }
+
+    fn current(&self) -> Arc<CertifiedKey> {
+        Arc::clone(&self.current.read().unwrap_or_else(|poisoned| poisoned.into_inner()))
+    }
+
+    pub(crate) fn replace(&self, chain_der: Vec<Vec<u8>>, private_key_der: &[u8]) -> Result<(), TlsError> {
+        let key = certified_key(chain_der, private_key_der)?;
+        *self.current.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = key;
+        Ok(())
+    }
+}
+
+impl ResolvesServerCert for RotatingCertResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.current())
+    }
+}
+
+// the private key never appears in the output
+impl fmt::Debug for RotatingCertResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RotatingCertResolver").field("chain", &self.current().cert.len()).finish_non_exhaustive()
+    }
+}
+
+fn certified_key(chain_der: Vec<Vec<u8>>, private_key_der: &[u8]) -> Result<Arc<CertifiedKey>, TlsError> {
+    let key = any_supported_type(&PrivateKey(private_key_der.to_vec()))
+        .map_err(|_| rustls::Error::General("unsupported private key type".to_string()))?;
+    Ok(Arc::new(CertifiedKey::new(chain_der.into_iter().map(RustlsCertificate).collect(), key)))
+}
+
+/// Reload `resolver` from `cert_path` and `key_path` whenever either changes
+///
+/// Every `check_interval` the files' modification times are compared with
+/// those of the previous check. A pair that doesn't load (say, the certificate
+/// was replaced but not yet its key) is logged and the current certificate
+/// stays in place until either file changes again. The task runs until aborted.
+#[cfg(feature = "server")]
+pub fn watch_cert_file(
+    resolver: Arc<RotatingCertResolver>,
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    check_interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut previous = modified(&cert_path, &key_path);
+        let mut ticks = tokio::time::interval(check_interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticks.tick().await;
+            let seen = modified(&cert_path, &key_path);
+            if std::mem::replace(&mut previous, seen) == seen {
+                continue;
+            }
+            match load_pem(&cert_path, &key_path).and_then(|pem_cert| resolver.reload_pem(&pem_cert)) {
+                Ok(()) => info!(path = %cert_path.display(), "Reloaded TLS certificate"),
+                Err(e) => warn!(path = %cert_path.display(), error = %e, "Couldn't reload TLS certificate, still serving the old one"),
+            }
+        }
+    })
+}
+
+// modification times of the certificate and key, None for a file that can't be read
+#[cfg(feature = "server")]
+fn modified(cert_path: &Path, key_path: &Path) -> (Option<SystemTime>, Option<SystemTime>) {
+    let mtime = |path: &Path| std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    (mtime(cert_path), mtime(key_path))
+}
+
+#[cfg(feature = "server")]
+fn load_pem(cert_path: &Path, key_path: &Path) -> Result<PemCertificate, ZkError> {
+    Ok(PemCertificate::from_pem(&std::fs::read(cert_path)?, &std::fs::read(key_path)?)?)
+}
@@ -0,0 +1,103 @@
+//! Session-bound, domain-separated challenges
+//!
+//! The verifier draws a fresh random [`SessionId`] and 16 more random bytes for
+//! every connection and sends both to the prover before the first round. Each
+//! challenge is then squeezed from a transcript over
+//! `("zk-schnorr-tls/v2", session_id, R, X, random)`: tied to this protocol and
+//! version, to this session, to the commitment just received and to the key it
+//! proves knowledge of. A prover can't precompute responses for a session it
+//! hasn't seen yet, a challenge can't be replayed into another session or
+//! protocol, and since the prover can recompute `c` itself, it can refuse
+//! challenges that weren't derived this way.
+
+use alloc::string::String;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::RngCore;
+
+use crate::session::SessionId;
+use crate::transcript::TranscriptBuilder;
+use crate::DecodeError;
+
+/// Domain tag every challenge transcript starts from
+pub const CHALLENGE_DOMAIN: &[u8] = b"zk-schnorr-tls/v2";
+
+/// Per-connection input to challenge generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChallengeContext {
+    session_id: SessionId,
+    random: [u8; 16],
+}
+
+impl ChallengeContext {
+    /// Split 32 bytes into the session id (first half) and the verifier's randomness
+    pub fn new(bytes: [u8; 32]) -> Self {
+        let (session_id, random) = bytes.split_at(16);
+        Self {
+            session_id: SessionId::new(session_id.try_into().expect("16 bytes")),
+            random: random.try_into().expect("16 bytes"),
+        }
+    }
+
+    /// A context with a fresh random session id, one per connection
+    pub fn random(rng: &mut impl RngCore) -> Self {
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes);
+        Self::new(bytes)
+    }
+
+    pub fn session_id(&self) -> &SessionId {
+        &self.session_id
+    }
+
+    /// The session id followed by the randomness, as carried on the wire
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[..16].copy_from_slice(self.session_id.as_bytes());
+        bytes[16..].copy_from_slice(&self.random);
+        bytes
+    }
+
+    /// The challenge for commitment `R` to public key `X` in this session
+    pub fn challenge(&self, R: &RistrettoPoint, X: &RistrettoPoint) -> Scalar {
+        self.challenge_for(&[*R], &[*X])
+    }
+
+    /// The challenge for a round that commits to several points, e.g. both branches of an OR-proof
+    pub fn challenge_for(&self, commitments: &[RistrettoPoint], public_keys: &[RistrettoPoint]) -> Scalar {
+        let mut transcript = TranscriptBuilder::new(CHALLENGE_DOMAIN);
+        transcript.append_bytes(b"session_id", self.session_id.as_bytes());
+        for R in commitments {
+            transcript.append_point(b"R", R);
+        }
+        for X in public_keys {
+            transcript.append_point(b"X", X);
+        }
+        transcript.append_bytes(b"random", &self.random);
+        transcript.challenge()
+    }
+
+    /// The challenge for sub-proof `proof_id` of a multiplexed session, so no
+    /// two sub-proofs share a challenge even if their commitments collide
+    pub fn challenge_for_proof(&self, proof_id: u64, R: &RistrettoPoint, X: &RistrettoPoint) -> Scalar {
+        TranscriptBuilder::new(CHALLENGE_DOMAIN)
+            .append_bytes(b"session_id", self.session_id.as_bytes())
+            .append_bytes(b"proof_id", &proof_id.to_le_bytes())
+            .append_point(b"R", R)
+            .append_point(b"X", X)
+            .append_bytes(b"random", &self.random)
+            .challenge()
+    }
+
+    /// Hex encoding of the whole context, as carried in a `context` message
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+
+    /// Parse a context from exactly 32 hex-encoded bytes
+    pub fn from_hex(s: &str) -> Result<Self, DecodeError> {
+        let bytes = hex::decode(s).map_err(|_| DecodeError::Context)?;
+        Ok(Self::new(bytes.try_into().map_err(|_| DecodeError::Context)?))
+    }
+}
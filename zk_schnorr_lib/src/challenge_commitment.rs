@@ -0,0 +1,134 @@
+//! What a prover accepts as a challenge, and the committed-challenge extension
+//!
+//! Whatever the [`ChallengePolicy`], a challenge must be a canonical, nonzero
+//! scalar: `c = 0` makes the response `s = k` and proves nothing. By default
+//! (`Derived`) it must also be the one derived from the session context, `R`
+//! and `X` (see [`crate::challenge`]), which the prover recomputes.
+//!
+//! With the `committed-challenge` feature in both `hello`s (`Committed`), the
+//! verifier instead draws `c` and a 32-byte salt at random before each round
+//! and sends `SHA-256(domain || c || salt)` in a `challenge-commit` before the
+//! prover commits to `R`. Once `R` arrives it reveals `(c, salt)` in a
+//! `challenge-reveal`, and the prover answers only if they open the
+//! commitment. A verifier that has already fixed `c` can't pick it after
+//! seeing `R`, however its implementation is written.
+
+use curve25519_dalek::scalar::Scalar;
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha256};
+
+use crate::error::DecodeError;
+use crate::version::VersionInfo;
+use crate::ScalarDecodeError;
+
+/// The `hello` feature that turns on committed challenges
+pub const COMMITTED_CHALLENGE: &str = "committed-challenge";
+
+/// Domain tag every challenge commitment hashes first
+pub const CHALLENGE_COMMITMENT_DOMAIN: &[u8] = b"zk-schnorr-tls/challenge-commitment/v1";
+
+/// How the prover checks the challenges of a session, besides them being canonical and nonzero
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChallengePolicy {
+    /// Recomputed from the session context, `R` and `X`
+    #[default]
+    Derived,
+    /// Revealed after `R`, opening a commitment sent before it
+    Committed,
+}
+
+impl ChallengePolicy {
+    /// `Committed` if both sides list [`COMMITTED_CHALLENGE`] in their `hello`
+    pub fn negotiated(ours: &VersionInfo, theirs: &VersionInfo) -> Self {
+        if ours.supports(COMMITTED_CHALLENGE) && theirs.supports(COMMITTED_CHALLENGE) {
+            ChallengePolicy::Committed
+        } else {
+            ChallengePolicy::Derived
+        }
+    }
+}
+
+/// `SHA-256(domain || c || salt)`, the payload of a `challenge-commit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChallengeCommitment([u8; 32]);
+
+impl ChallengeCommitment {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// Parse exactly 32 hex-encoded bytes
+    pub fn from_hex(s: &str) -> Result<Self, DecodeError> {
+        let bytes = hex::decode(s)?;
+        let got = bytes.len();
+        Ok(Self(bytes.try_into().map_err(|_| DecodeError::Length { expected: 32, got })?))
+    }
+}
+
+/// A challenge and the salt that hides it until it is revealed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChallengeOpening {
+    challenge: Scalar,
+    salt: [u8; 32],
+}
+
+impl ChallengeOpening {
+    /// A fresh nonzero challenge under a fresh salt, for the next round
+    pub fn random(rng: &mut impl CryptoRngCore) -> Self {
+        let mut challenge = Scalar::random(rng);
+        while challenge == Scalar::ZERO {
+            challenge = Scalar::random(rng);
+        }
+        let mut salt = [0u8; 32];
+        rng.fill_bytes(&mut salt);
+        Self { challenge, salt }
+    }
+
+    pub fn new(challenge: Scalar, salt: [u8; 32]) -> Self {
+        Self { challenge, salt }
+    }
+
+    pub fn challenge(&self) -> Scalar {
+        self.challenge
+    }
+
+    /// What the verifier sends before it sees `R`
+    pub fn commitment(&self) -> ChallengeCommitment {
+        let digest = Sha256::new()
+            .chain_update(CHALLENGE_COMMITMENT_DOMAIN)
+            .chain_update(self.challenge.as_bytes())
+            .chain_update(self.salt)
+            .finalize();
+        ChallengeCommitment(digest.into())
+    }
+
+    /// Whether this is the challenge and salt `commitment` was made from
+    pub fn opens(&self, commitment: &ChallengeCommitment) -> bool {
+        self.commitment() == *commitment
+    }
+
+    /// `c || salt` in hex, the payload of a `challenge-reveal`
+    pub fn to_hex(&self) -> String {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(self.challenge.as_bytes());
+        bytes[32..].copy_from_slice(&self.salt);
+        hex::encode(bytes)
+    }
+
+    /// Parse `c || salt` from 64 hex-encoded bytes; `c` must be canonical and nonzero
+    pub fn from_hex(s: &str) -> Result<Self, DecodeError> {
+        let bytes = hex::decode(s)?;
+        let bytes: [u8; 64] = bytes.as_slice().try_into().map_err(|_| DecodeError::Length { expected: 64, got: bytes.len() })?;
+        let (challenge, salt) = bytes.split_at(32);
+        let challenge = Option::<Scalar>::from(Scalar::from_canonical_bytes(challenge.try_into().expect("32 bytes")))
+            .ok_or(ScalarDecodeError::NonCanonical)?;
+        if challenge == Scalar::ZERO {
+            return Err(ScalarDecodeError::Zero.into());
+        }
+        Ok(Self { challenge, salt: salt.try_into().expect("32 bytes") })
+    }
+}
@@ -0,0 +1,277 @@
+//! Wire encodings for a `Message` inside a length-prefixed frame
+//!
+//! A [`Codec`] turns one message into frame bytes and back. The framing
+//! itself (a 4-byte length prefix) is the same whatever the codec.
+//! - `JsonCodec`: the default, readable in a packet capture.
+//! - `CborCodec` (with the `cbor` feature): CBOR, with hex payloads such as
+//!   points and scalars carried as raw bytes, about half the size of JSON.
+//! - `BinaryCodec`: a two-byte kind length, the kind, the 16-byte session id
+//!   if there is one (flagged by the length's top bit), then the payload bytes.
+//!
+//! A message also converts to and from `bytes::Bytes` as JSON, serialized
+//! straight into the buffer that becomes the `Bytes`.
+//!
+//! [`WireFormat`] names one of them, for command-line flags and for the
+//! optional `codec` negotiation (`SchnorrProtocol::request_codec` and
+//! `SchnorrProtocol::accept_codec`). Without negotiation both sides must be
+//! configured alike; a mismatch shows up as a `CodecError` on the first
+//! message rather than a hang.
+
+use std::fmt;
+use std::str::FromStr;
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::error::ZkError;
+use crate::session::SessionId;
+use crate::Message;
+
+/// Errors from decoding a frame body
+#[derive(Debug, thiserror::Error)]
+pub enum CodecError {
+    #[error("frame is not a JSON message: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("frame is not a CBOR message: {0}")]
+    Cbor(String),
+    #[error("frame is not a binary message: {0}")]
+    Binary(&'static str),
+    #[error("the {0} wire format is not supported by this build")]
+    Unsupported(WireFormat),
+}
+
+/// Encodes messages into frame bodies and decodes them again
+pub trait Codec {
+    fn encode(&self, msg: &Message) -> Vec<u8>;
+    fn decode(&self, bytes: &[u8]) -> Result<Message, CodecError>;
+}
+
+/// Messages as JSON objects `{"kind": ..., "payload": ...}`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn encode(&self, msg: &Message) -> Vec<u8> {
+        serde_json::to_vec(msg).expect("a Message always serializes")
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Message, CodecError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+/// The message as JSON, written into a `BytesMut` and frozen without copying
+impl From<&Message> for Bytes {
+    fn from(msg: &Message) -> Self {
+        let mut writer = BytesMut::new().writer();
+        serde_json::to_writer(&mut writer, msg).expect("a Message always serializes");
+        writer.into_inner().freeze()
+    }
+}
+
+impl From<Message> for Bytes {
+    fn from(msg: Message) -> Self {
+        Bytes::from(&msg)
+    }
+}
+
+/// Parse JSON as written by `From<&Message> for Bytes`
+impl TryFrom<Bytes> for Message {
+    type Error = ZkError;
+
+    fn try_from(bytes: Bytes) -> Result<Self, ZkError> {
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+}
+
+/// Messages as a CBOR map, with canonical lowercase-hex payloads sent as raw bytes
+#[cfg(feature = "cbor")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CborCodec;
+
+#[cfg(feature = "cbor")]
+impl Codec for CborCodec {
+    fn encode(&self, msg: &Message) -> Vec<u8> {
+        use ciborium::Value;
+
+        // only when re-encoding gives back the exact string, so decoding is lossless
+        let payload = match hex::decode(&msg.payload) {
+            Ok(raw) if !raw.is_empty() && hex::encode(&raw) == msg.payload => Value::Bytes(raw),
+            _ => Value::Text(msg.payload.clone()),
+        };
+        let mut entries = vec![
+            (Value::Text("kind".into()), Value::Text(msg.kind.clone())),
+            (Value::Text("payload".into()), payload),
+        ];
+        if let Some(session) = &msg.session {
+            entries.push((Value::Text("session".into()), Value::Bytes(session.as_bytes().to_vec())));
+        }
+        let value = Value::Map(entries);
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&value, &mut bytes).expect("writing CBOR to a Vec can't fail");
+        bytes
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Message, CodecError> {
+        use ciborium::Value;
+
+        let value: Value = ciborium::from_reader(bytes).map_err(|e| CodecError::Cbor(e.to_string()))?;
+        let Value::Map(entries) = value else {
+            return Err(CodecError::Cbor("expected a map".into()));
+        };
+        let (mut kind, mut payload, mut session) = (None, None, None);
+        for (key, value) in entries {
+            match (key.as_text(), value) {
+                (Some("kind"), Value::Text(text)) => kind = Some(text),
+                (Some("payload"), Value::Text(text)) => payload = Some(text),
+                (Some("payload"), Value::Bytes(raw)) => payload = Some(hex::encode(raw)),
+                (Some("session"), Value::Bytes(raw)) => {
+                    let raw: [u8; 16] = raw.try_into().map_err(|_| CodecError::Cbor("session id is not 16 bytes".into()))?;
+                    session = Some(SessionId::new(raw));
+                }
+                _ => return Err(CodecError::Cbor("unexpected map entry".into())),
+            }
+        }
+        match (kind, payload) {
+            (Some(kind), Some(payload)) => Ok(Message { kind, payload, session }),
+            _ => Err(CodecError::Cbor("missing kind or payload".into())),
+        }
+    }
+}
+
+/// Messages as `kind length (u16, big-endian) || kind || [session id] || payload`
+///
+/// The top bit of the kind length says a 16-byte session id follows the kind;
+/// messages without one encode exactly as they did before sessions were added.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BinaryCodec;
+
+const HAS_SESSION: u16 = 0x8000;
+
+impl Codec for BinaryCodec {
+    fn encode(&self, msg: &Message) -> Vec<u8> {
+        let kind_len = u16::try_from(msg.kind.len())
+            .ok()
+            .filter(|len| len & HAS_SESSION == 0)
+            .expect("message kinds are short identifiers");
+        let flag = if msg.session.is_some() { HAS_SESSION } else { 0 };
+        let mut bytes = Vec::with_capacity(2 + msg.kind.len() + 16 + msg.payload.len());
+        bytes.extend_from_slice(&(kind_len | flag).to_be_bytes());
+        bytes.extend_from_slice(msg.kind.as_bytes());
+        if let Some(session) = &msg.session {
+            bytes.extend_from_slice(session.as_bytes());
+        }
+        bytes.extend_from_slice(msg.payload.as_bytes());
+        bytes
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Message, CodecError> {
+        let (len, rest) = bytes.split_first_chunk::<2>().ok_or(CodecError::Binary("shorter than its header"))?;
+        let header = u16::from_be_bytes(*len);
+        let kind_len = (header & !HAS_SESSION) as usize;
+        if rest.len() < kind_len {
+            return Err(CodecError::Binary("kind runs past the end of the frame"));
+        }
+        let (kind, mut payload) = rest.split_at(kind_len);
+        let kind = String::from_utf8(kind.to_vec()).map_err(|_| CodecError::Binary("kind is not UTF-8"))?;
+        let mut session = None;
+        if header & HAS_SESSION != 0 {
+            let (id, rest) = payload.split_first_chunk::<16>().ok_or(CodecError::Binary("session id runs past the end of the frame"))?;
+            session = Some(SessionId::new(*id));
+            payload = rest;
+        }
+        let payload = String::from_utf8(payload.to_vec()).map_err(|_| CodecError::Binary("payload is not UTF-8"))?;
+        Ok(Message { kind, payload, session })
+    }
+}
+
+/// One of the codecs above, by name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WireFormat {
+    #[default]
+    Json,
+    Cbor,
+    Binary,
+}
+
+impl WireFormat {
+    /// Every wire format this build can speak
+    pub fn supported() -> &'static [WireFormat] {
+        if cfg!(feature = "cbor") {
+            &[WireFormat::Json, WireFormat::Cbor, WireFormat::Binary]
+        } else {
+            &[WireFormat::Json, WireFormat::Binary]
+        }
+    }
+
+    pub fn is_supported(self) -> bool {
+        Self::supported().contains(&self)
+    }
+}
+
+impl Codec for WireFormat {
+    /// # Panics
+    /// For `Cbor` in a build without the `cbor` feature; check `is_supported` first.
+    fn encode(&self, msg: &Message) -> Vec<u8> {
+        match self {
+            WireFormat::Json => JsonCodec.encode(msg),
+            #[cfg(feature = "cbor")]
+            WireFormat::Cbor => CborCodec.encode(msg),
+            #[cfg(not(feature = "cbor"))]
+            WireFormat::Cbor => panic!("the cbor wire format needs the `cbor` feature"),
+            WireFormat::Binary => BinaryCodec.encode(msg),
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Message, CodecError> {
+        match self {
+            WireFormat::Json => JsonCodec.decode(bytes),
+            #[cfg(feature = "cbor")]
+            WireFormat::Cbor => CborCodec.decode(bytes),
+            #[cfg(not(feature = "cbor"))]
+            WireFormat::Cbor => Err(CodecError::Unsupported(*self)),
+            WireFormat::Binary => BinaryCodec.decode(bytes),
+        }
+    }
+}
+
+impl fmt::Display for WireFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WireFormat::Json => "json",
+            WireFormat::Cbor => "cbor",
+            WireFormat::Binary => "binary",
+        })
+    }
+}
+
+impl FromStr for WireFormat {
+    type Err = UnknownWireFormat;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(WireFormat::Json),
+            "cbor" => Ok(WireFormat::Cbor),
+            "binary" => Ok(WireFormat::Binary),
+            other => Err(UnknownWireFormat(other.to_string())),
+        }
+    }
+}
+
+/// A wire format name that isn't one of `json`, `cbor` or `binary`
+#[derive(Debug, thiserror::Error)]
+#[error("unknown wire format {0:?}, expected json, cbor or binary")]
+pub struct UnknownWireFormat(pub String);
+
+/// Encode a message as plain CBOR, payloads as text
+#[cfg(feature = "cbor")]
+pub fn message_to_cbor(msg: &Message) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(msg, &mut bytes).expect("writing CBOR to a Vec can't fail");
+    bytes
+}
+
+/// Decode a message from plain CBOR
+#[cfg(feature = "cbor")]
+pub fn message_from_cbor(bytes: &[u8]) -> Result<Message, ciborium::de::Error<std::io::Error>> {
+    ciborium::from_reader(bytes)
+}
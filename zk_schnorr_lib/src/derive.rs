@@ -0,0 +1,28 @@
+//! HKDF-based derivation of child keys from a master secret
+//!
+//! One root secret can serve several protocol roles or sessions: each child
+//! is `HKDF-SHA256(master, info)` expanded to 64 bytes and reduced to a
+//! scalar, so children with different `info` strings are independent and
+//! none of them reveals the master or a sibling.
+
+use curve25519_dalek::scalar::Scalar;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::schnorr::KeyPair;
+
+/// Derive the child scalar for `info` from `master`
+pub fn derive_key(master: &Scalar, info: &[u8]) -> Scalar {
+    let ikm = Zeroizing::new(master.to_bytes());
+    let hkdf = Hkdf::<Sha256>::new(None, ikm.as_ref()); // extract
+    let mut okm = Zeroizing::new([0u8; 64]);
+    hkdf.expand(info, okm.as_mut())
+        .expect("64 bytes is well within HKDF-SHA256's output limit"); // expand
+    Scalar::from_bytes_mod_order_wide(&okm)
+}
+
+/// Derive the child keypair for `info` from `master`'s secret
+pub fn derive_keypair(master: &KeyPair, info: &[u8]) -> KeyPair {
+    KeyPair::from_secret(derive_key(master.secret().expose_secret(), info))
+}
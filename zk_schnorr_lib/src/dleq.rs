@@ -0,0 +1,83 @@
+//! Discrete-log equality (DLEQ) proofs: `X1 = x*G` and `X2 = x*H` share `x`
+//!
+//! Chaum–Pedersen with a Fiat–Shamir challenge that binds both generators and
+//! both public points, so a proof for one pair of statements can't be replayed
+//! for another. Proofs travel as a `dleq` message whose payload is the proof
+//! as JSON.
+//!
+//! `prove_with_params` / `verify_with_params` take the first generator from
+//! [`Params`], so `X1` is a key under whatever generator the session uses.
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ProtocolError;
+use crate::params::Params;
+use crate::schnorr::random_scalar;
+use crate::transcript::transcript_challenge;
+use crate::Message;
+
+/// Proof that `log_G(X1) = log_H(X2)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DleqProof {
+    /// R1 = k*G
+    #[serde(with = "crate::serde_hex::point")]
+    pub R1: RistrettoPoint,
+    /// R2 = k*H
+    #[serde(with = "crate::serde_hex::point")]
+    pub R2: RistrettoPoint,
+    /// c = H(G || H || X1 || X2 || R1 || R2)
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub c: Scalar,
+    /// s = k + c*x
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub s: Scalar,
+}
+
+impl DleqProof {
+    /// Prove that `x*G` and `x*H` have the same discrete log `x`
+    pub fn prove(x: &Scalar, G: &RistrettoPoint, H: &RistrettoPoint, rng: &mut impl RngCore) -> Self {
+        let k = random_scalar(rng);
+        let (R1, R2) = (G * k, H * k);
+        let c = challenge(G, H, &(G * x), &(H * x), &R1, &R2);
+        Self { R1, R2, c, s: k + c * x }
+    }
+
+    /// Check `s*G = R1 + c*X1` and `s*H = R2 + c*X2` for the recomputed `c`
+    pub fn verify(&self, X1: &RistrettoPoint, X2: &RistrettoPoint, G: &RistrettoPoint, H: &RistrettoPoint) -> bool {
+        let c = challenge(G, H, X1, X2, &self.R1, &self.R2);
+        c == self.c && G * self.s == self.R1 + X1 * c && H * self.s == self.R2 + X2 * c
+    }
+
+    /// `prove` with `params`' generator as `G`
+    pub fn prove_with_params(x: &Scalar, params: &Params, H: &RistrettoPoint, rng: &mut impl RngCore) -> Self {
+        Self::prove(x, params.generator(), H, rng)
+    }
+
+    /// `verify` with `params`' generator as `G`
+    pub fn verify_with_params(&self, X1: &RistrettoPoint, X2: &RistrettoPoint, params: &Params, H: &RistrettoPoint) -> bool {
+        self.verify(X1, X2, params.generator(), H)
+    }
+
+    /// Decode a proof from a `dleq` message
+    pub fn from_message(msg: &Message) -> Result<Self, ProtocolError> {
+        if msg.kind != "dleq" {
+            return Err(ProtocolError::UnexpectedMessage { expected: "dleq", got: msg.kind.clone() });
+        }
+        Ok(serde_json::from_str(&msg.payload)?)
+    }
+}
+
+// c squeezed from a transcript over (G, H, X1, X2, R1, R2)
+fn challenge(
+    G: &RistrettoPoint,
+    H: &RistrettoPoint,
+    X1: &RistrettoPoint,
+    X2: &RistrettoPoint,
+    R1: &RistrettoPoint,
+    R2: &RistrettoPoint,
+) -> Scalar {
+    transcript_challenge(b"zk-schnorr-tls/dleq/v2", &[(b"G", G), (b"H", H), (b"X1", X1), (b"X2", X2), (b"R1", R1), (b"R2", R2)])
+}
@@ -0,0 +1,132 @@
+//! Standard Ed25519 signatures, for provers that can't run the sigma protocol
+//!
+//! Some provers, such as hardware tokens, can only sign. For them the verifier
+//! sends an `ed25519-challenge` carrying 32 fresh random bytes, and the prover
+//! answers with an `ed25519-signature` over [`ED25519_DOMAIN`] followed by
+//! those bytes (see `SchnorrProtocol::verify_ed25519_round`). The nonce is
+//! never reused, so an old signature can't be replayed.
+//!
+//! Signing and verification are ed25519-dalek's RFC 8032 implementation, so
+//! signatures interoperate with any other Ed25519 signer, hardware tokens
+//! included. Verification is `verify_strict`: `s` must be canonical and keys
+//! of small order are refused, so a key nobody holds can't "sign" anything.
+//! Ed25519 keys live on the Edwards curve, not in the Ristretto group, so they
+//! can't be used with the rest of the crate or vice versa.
+
+use std::fmt;
+use std::str::FromStr;
+
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::scalar::Scalar;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::DecodeError;
+use crate::schnorr::scalar_from_seed;
+
+/// Prefix of every signed challenge, so a signature for this protocol is never valid for anything else
+pub const ED25519_DOMAIN: &[u8] = b"zk-schnorr-tls/ed25519-challenge/v1";
+
+/// The domain [`Ed25519SigningKey::derive`] hashes its seed under
+pub const ED25519_SEED_DOMAIN: &[u8] = b"zk-schnorr-tls/ed25519-seed/v1";
+
+/// Why an Ed25519 signature was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum Ed25519Error {
+    #[error("Ed25519 public key is not a valid point, or has small order")]
+    InvalidPublicKey,
+    #[error("Ed25519 signature is malformed: R is not a point or s is not canonical")]
+    MalformedSignature,
+    #[error("Ed25519 signature does not verify")]
+    VerificationFailed,
+}
+
+/// A 32-byte Ed25519 public key, hex-encoded when serialized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ed25519PublicKey(pub [u8; 32]);
+
+impl fmt::Display for Ed25519PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl FromStr for Ed25519PublicKey {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s)?;
+        let got = bytes.len();
+        Ok(Self(bytes.try_into().map_err(|_| DecodeError::Length { expected: 32, got })?))
+    }
+}
+
+impl Serialize for Ed25519PublicKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Ed25519PublicKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+/// An Ed25519 signing key: the RFC 8032 32-byte seed, zeroized on drop
+pub struct Ed25519SigningKey(SigningKey);
+
+impl Ed25519SigningKey {
+    /// The key for the 32-byte secret `seed`, as RFC 8032 defines it
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        Self(SigningKey::from_bytes(seed))
+    }
+
+    /// Hash any seed to the 32-byte secret with [`scalar_from_seed`] under [`ED25519_SEED_DOMAIN`]
+    ///
+    /// The same seed gives unrelated keys here and in `KeyPair::from_seed`,
+    /// which hashes under its own domain.
+    pub fn derive(seed: &[u8]) -> Self {
+        Self::from_seed(scalar_from_seed(ED25519_SEED_DOMAIN, seed).as_bytes())
+    }
+
+    pub fn public_key(&self) -> Ed25519PublicKey {
+        Ed25519PublicKey(self.0.verifying_key().to_bytes())
+    }
+
+    /// The deterministic RFC 8032 signature `R || s` on `msg`
+    pub fn sign(&self, msg: &[u8]) -> [u8; 64] {
+        self.0.sign(msg).to_bytes()
+    }
+}
+
+// only the public half is printed so the secret can't end up in logs
+impl fmt::Debug for Ed25519SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ed25519SigningKey").field("public", &self.public_key()).finish_non_exhaustive()
+    }
+}
+
+/// Check an RFC 8032 signature `R || s` on `msg` under the compressed key `pubkey_bytes`
+pub fn verify_ed25519(pubkey_bytes: &[u8; 32], msg: &[u8], sig: &[u8; 64]) -> Result<(), Ed25519Error> {
+    let key = VerifyingKey::from_bytes(pubkey_bytes).map_err(|_| Ed25519Error::InvalidPublicKey)?;
+    if key.is_weak() {
+        return Err(Ed25519Error::InvalidPublicKey); // verify_strict refuses these too, without saying why
+    }
+    let sig = Signature::from_bytes(sig);
+    key.verify_strict(msg, &sig).map_err(|_| match is_malformed(&sig) {
+        true => Ed25519Error::MalformedSignature,
+        false => Ed25519Error::VerificationFailed,
+    })
+}
+
+// whether verify_strict refused `sig` for its encoding rather than for the equation
+fn is_malformed(sig: &Signature) -> bool {
+    let s_canonical = bool::from(Scalar::from_canonical_bytes(*sig.s_bytes()).is_some());
+    !s_canonical || CompressedEdwardsY(*sig.r_bytes()).decompress().is_none()
+}
+
+/// The bytes a prover signs to answer the challenge `nonce`
+pub fn challenge_message(nonce: &[u8; 32]) -> Vec<u8> {
+    [ED25519_DOMAIN, nonce].concat()
+}
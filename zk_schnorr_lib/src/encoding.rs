@@ -0,0 +1,165 @@
+//! Raw-byte and base64url encodings of points and scalars, alongside hex
+//!
+//! Hex stays the protocol's wire encoding. Raw 32-byte arrays suit binary
+//! protocols and base64url (no padding) suits tokens and URLs; all three carry
+//! the same bytes: compressed Ristretto for points, canonical little-endian for
+//! scalars.
+//!
+//! Every decoder accepts exactly one encoding. To take either hex or base64url
+//! from the same field, ask for it with [`PayloadEncoding::detect`].
+
+use std::fmt;
+use std::str::FromStr;
+
+use base64::prelude::{Engine, BASE64_URL_SAFE_NO_PAD};
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+
+use crate::{point_from_hex, point_to_hex, scalar_from_hex_canonical, scalar_to_hex, PointDecodeError, ScalarDecodeError};
+
+/// Compressed point, 32 bytes
+pub fn point_to_bytes(p: &RistrettoPoint) -> [u8; 32] {
+    p.compress().to_bytes()
+}
+
+/// Decompress 32 bytes, failing if they aren't a valid Ristretto encoding
+pub fn point_from_bytes(bytes: [u8; 32]) -> Result<RistrettoPoint, PointDecodeError> {
+    CompressedRistretto(bytes).decompress().ok_or(PointDecodeError::InvalidPoint)
+}
+
+/// Canonical little-endian scalar, 32 bytes
+pub fn scalar_to_bytes(s: &Scalar) -> [u8; 32] {
+    s.to_bytes()
+}
+
+/// Parse 32 bytes, rejecting values at or above the group order
+pub fn scalar_from_bytes(bytes: [u8; 32]) -> Result<Scalar, ScalarDecodeError> {
+    Option::from(Scalar::from_canonical_bytes(bytes)).ok_or(ScalarDecodeError::NonCanonical)
+}
+
+/// Errors that can occur when decoding points or scalars from base64url
+#[derive(Debug, thiserror::Error)]
+pub enum B64DecodeError {
+    #[error("Base64url decoding failed: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("Invalid length: expected 32 bytes, got {0}")]
+    InvalidLength(usize),
+    #[error("Invalid point: failed to decompress")]
+    InvalidPoint,
+    #[error("Non-canonical scalar encoding")]
+    NonCanonical,
+}
+
+pub fn point_to_b64(p: &RistrettoPoint) -> String {
+    BASE64_URL_SAFE_NO_PAD.encode(point_to_bytes(p))
+}
+
+pub fn point_from_b64(s: &str) -> Result<RistrettoPoint, B64DecodeError> {
+    point_from_bytes(b64_decode_32(s)?).map_err(|_| B64DecodeError::InvalidPoint)
+}
+
+pub fn scalar_to_b64(s: &Scalar) -> String {
+    BASE64_URL_SAFE_NO_PAD.encode(scalar_to_bytes(s))
+}
+
+pub fn scalar_from_b64(s: &str) -> Result<Scalar, B64DecodeError> {
+    scalar_from_bytes(b64_decode_32(s)?).map_err(|_| B64DecodeError::NonCanonical)
+}
+
+// unpadded base64url of exactly 32 bytes
+fn b64_decode_32(s: &str) -> Result<[u8; 32], B64DecodeError> {
+    let bytes = BASE64_URL_SAFE_NO_PAD.decode(s)?;
+    let got = bytes.len();
+    bytes.try_into().map_err(|_| B64DecodeError::InvalidLength(got))
+}
+
+/// How a point or scalar is written into a `Message` payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PayloadEncoding {
+    /// 64 lowercase hex digits, what the protocol sends
+    #[default]
+    Hex,
+    /// 43 characters of unpadded base64url
+    Base64Url,
+}
+
+impl PayloadEncoding {
+    /// Guess the encoding of a 32-byte payload from its length, for fields
+    /// that explicitly accept either; `None` if it fits neither
+    pub fn detect(payload: &str) -> Option<Self> {
+        match payload.len() {
+            64 => Some(Self::Hex),
+            43 => Some(Self::Base64Url),
+            _ => None,
+        }
+    }
+
+    pub fn encode_point(self, p: &RistrettoPoint) -> String {
+        match self {
+            Self::Hex => point_to_hex(p),
+            Self::Base64Url => point_to_b64(p),
+        }
+    }
+
+    pub fn encode_scalar(self, s: &Scalar) -> String {
+        match self {
+            Self::Hex => scalar_to_hex(s),
+            Self::Base64Url => scalar_to_b64(s),
+        }
+    }
+
+    /// Decode a point in exactly this encoding
+    pub fn decode_point(self, payload: &str) -> Result<RistrettoPoint, PayloadDecodeError> {
+        match self {
+            Self::Hex => point_from_hex(payload).map_err(PayloadDecodeError::Point),
+            Self::Base64Url => point_from_b64(payload).map_err(PayloadDecodeError::Base64),
+        }
+    }
+
+    /// Decode a canonical scalar in exactly this encoding
+    pub fn decode_scalar(self, payload: &str) -> Result<Scalar, PayloadDecodeError> {
+        match self {
+            Self::Hex => scalar_from_hex_canonical(payload).map_err(PayloadDecodeError::Scalar),
+            Self::Base64Url => scalar_from_b64(payload).map_err(PayloadDecodeError::Base64),
+        }
+    }
+}
+
+impl fmt::Display for PayloadEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Hex => "hex",
+            Self::Base64Url => "base64url",
+        })
+    }
+}
+
+impl FromStr for PayloadEncoding {
+    type Err = UnknownPayloadEncoding;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(Self::Hex),
+            "base64url" | "b64" => Ok(Self::Base64Url),
+            other => Err(UnknownPayloadEncoding(other.to_string())),
+        }
+    }
+}
+
+/// A payload encoding name other than `hex` or `base64url`
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown payload encoding {0:?}, expected hex or base64url")]
+pub struct UnknownPayloadEncoding(pub String);
+
+/// A point or scalar payload that didn't decode in the encoding asked for
+#[derive(Debug, thiserror::Error)]
+pub enum PayloadDecodeError {
+    #[error("invalid point: {0}")]
+    Point(PointDecodeError),
+    #[error("invalid scalar: {0}")]
+    Scalar(ScalarDecodeError),
+    #[error("{0}")]
+    Base64(B64DecodeError),
+    #[error("payload is neither 64 hex digits nor 43 base64url characters")]
+    UnknownEncoding,
+}
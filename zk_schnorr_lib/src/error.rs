@@ -0,0 +1,200 @@
+//! Error types: `ProtocolError` for running the protocol, `ZkError` for everything else
+//!
+//! `ProtocolError` keeps the three things a caller usually has to tell apart
+//! in separate variants: the peer sent something malformed or out of order
+//! (`UnexpectedMessage`, `Serde`, `Codec`, `Decode`, `SessionMismatch`, `TooManyStatements`,
+//! `StatementCountMismatch`, `NonceReuse`, `MessageTooLarge`), the proof itself didn't check out (`VerificationFailed`,
+//! `StatementFailed`), and the connection broke (`ConnectionClosed`, `Io`,
+//! `Timeout`).
+//!
+//! Both need `std`; [`DecodeError`] and [`ProofDecodeError`] are all the
+//! `no_std` core has.
+
+#[cfg(feature = "std")]
+use crate::codec::CodecError;
+#[cfg(feature = "std")]
+use crate::ed25519::Ed25519Error;
+#[cfg(feature = "std")]
+use crate::ring::RingError;
+#[cfg(feature = "std")]
+use crate::session::SessionId;
+#[cfg(feature = "std")]
+use crate::timeouts::TimedOut;
+#[cfg(feature = "std")]
+use crate::version::NegotiationError;
+use crate::{PointDecodeError, ScalarDecodeError};
+
+/// Errors that can occur while running either side of the Schnorr protocol
+#[cfg(feature = "std")]
+#[derive(Debug, thiserror::Error)]
+pub enum ProtocolError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Connection closed by peer")]
+    ConnectionClosed,
+    #[error("Expected {expected} message, got: {got}")]
+    UnexpectedMessage { expected: &'static str, got: String },
+    #[error("Message is not valid JSON: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("Undecodable message, is the peer using the same wire format? {0}")]
+    Codec(CodecError),
+    #[error("Malformed message: {0}")]
+    Decode(#[from] DecodeError),
+    #[error("Proof rejected: s*G != R + c*X")]
+    VerificationFailed,
+    #[error("Challenge was not derived from this session's context")]
+    InvalidChallenge,
+    #[error("Revealed challenge doesn't open the verifier's commitment to it")]
+    ChallengeCommitmentMismatch,
+    #[error("Session rejected by peer: {reason}")]
+    Rejected { reason: String },
+    #[error("{0}")]
+    Timeout(#[from] TimedOut),
+    /// The same commitment `R` met a second challenge: `s1 - s2 = (c1 - c2) * x`,
+    /// so answering both would hand the secret to whoever saw the two responses
+    #[error("Nonce reuse: commitment R was already used with another challenge, and a second response would reveal the secret")]
+    NonceReuse,
+    #[error("Peer asked for codec {0:?}, which this side doesn't support")]
+    UnsupportedCodec(String),
+    #[error("Version negotiation failed: {0}")]
+    Negotiation(#[from] NegotiationError),
+    #[error("Session {0} was already used")]
+    ReplayedSession(SessionId),
+    #[error("No registered key for identity {}", .0.as_deref().unwrap_or("(none claimed)"))]
+    UnknownIdentity(Option<String>),
+    #[error("Message for session {} arrived in session {expected}", .got.map_or_else(|| "(none)".to_string(), |id| id.to_string()))]
+    SessionMismatch { expected: SessionId, got: Option<SessionId> },
+    #[error("{got} statements in one AND-proof, at most {max} allowed")]
+    TooManyStatements { got: usize, max: usize },
+    #[error("Expected {expected} statements in the AND-proof, got {got}")]
+    StatementCountMismatch { expected: usize, got: usize },
+    #[error("AND-proof rejected: statement {index} fails s_i*G = R_i + c*X_i")]
+    StatementFailed { index: usize },
+    /// A length prefix announced more than the transport accepts; the body is never read
+    #[error("Message exceeds the maximum length of {max} bytes")]
+    MessageTooLarge { max: usize },
+    #[error("Couldn't store the checked proof: {0}")]
+    Store(Box<ZkError>), // boxed: a ZkError can hold a ProtocolError
+}
+
+#[cfg(feature = "std")]
+impl ProtocolError {
+    /// Whether the peer broke the protocol (as opposed to a failed proof or a broken connection)
+    pub fn is_malformed(&self) -> bool {
+        matches!(
+            self,
+            Self::UnexpectedMessage { .. }
+                | Self::Serde(_)
+                | Self::Codec(_)
+                | Self::Decode(_)
+                | Self::SessionMismatch { .. }
+                | Self::TooManyStatements { .. }
+                | Self::StatementCountMismatch { .. }
+                | Self::NonceReuse
+                | Self::MessageTooLarge { .. }
+        )
+    }
+}
+
+/// A message payload that couldn't be decoded
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    #[error("invalid scalar: {0}")]
+    Scalar(#[from] ScalarDecodeError),
+    #[error("invalid point: {0}")]
+    Point(#[from] PointDecodeError),
+    #[error("invalid challenge context: expected 32 hex-encoded bytes")]
+    Context,
+    #[error("invalid hex: {0}")]
+    Hex(#[cfg_attr(feature = "std", source)] hex::FromHexError), // hex only implements Error with std
+    #[error("expected {expected} bytes, got {got}")]
+    Length { expected: usize, got: usize },
+}
+
+/// A `SchnorrProof` blob that couldn't be decoded
+#[derive(Debug, thiserror::Error)]
+pub enum ProofDecodeError {
+    #[error("invalid proof length: expected 64 bytes, got {0}")]
+    InvalidLength(usize),
+    #[cfg(feature = "std")]
+    #[error("invalid base64: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("invalid commitment: {0}")]
+    Point(#[from] PointDecodeError),
+    #[error("invalid response: {0}")]
+    Scalar(#[from] ScalarDecodeError),
+}
+
+impl From<hex::FromHexError> for DecodeError {
+    fn from(e: hex::FromHexError) -> Self {
+        Self::Hex(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<ScalarDecodeError> for ProtocolError {
+    fn from(e: ScalarDecodeError) -> Self {
+        Self::Decode(e.into())
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<PointDecodeError> for ProtocolError {
+    fn from(e: PointDecodeError) -> Self {
+        Self::Decode(e.into())
+    }
+}
+
+/// General library error for operations outside a protocol run
+#[cfg(feature = "std")]
+#[derive(Debug, thiserror::Error)]
+pub enum ZkError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+    #[error("{0}")]
+    Timeout(#[from] TimedOut),
+    #[error("Invalid scalar encoding: {0}")]
+    ScalarDecode(#[from] ScalarDecodeError),
+    #[error("Invalid point encoding: {0}")]
+    PointDecode(#[from] PointDecodeError),
+    #[error("Not a ZK Schnorr PEM key: {0}")]
+    PemFormat(String),
+    #[error("Invalid base64 in PEM body: {0}")]
+    PemBase64(#[from] base64::DecodeError),
+    #[error("Can't make ring signature: {0}")]
+    Ring(#[from] RingError),
+    #[error("The public key isn't in the set")]
+    KeyNotInSet,
+    #[error("Ed25519 signature rejected: {0}")]
+    Ed25519(#[from] Ed25519Error),
+    #[error("{value} doesn't fit in {n_bits} bits")]
+    ValueOutOfRange { value: u64, n_bits: u8 },
+    #[error("Range proofs cover 1 to 64 bits, not {0}")]
+    InvalidRangeBits(u8),
+    #[error("A keypair named {0:?} is already in the keyring")]
+    DuplicateKey(String),
+    #[error("No keypair named {0:?} in the keyring")]
+    UnknownKey(String),
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+    #[error("Not a JSON message: {0}")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "tls")]
+    #[error("TLS error: {0}")]
+    Tls(#[from] crate::tls::TlsError),
+    #[cfg(feature = "server")]
+    #[error("Incomplete server configuration: {0}")]
+    ServerConfig(&'static str),
+    #[cfg(feature = "ws")]
+    #[error("WebSocket error: {0}")]
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>), // boxed: it would triple the size of every ZkError
+}
+
+#[cfg(feature = "ws")]
+impl From<tokio_tungstenite::tungstenite::Error> for ZkError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocket(Box::new(e))
+    }
+}
@@ -0,0 +1,292 @@
+//! Bounded reading and writing of protocol messages
+//!
+//! Two framings are available:
+//! - newline-delimited JSON (`read_message`/`write_message`). Reading a line with
+//!   `BufReader::lines()` has no upper bound, so a peer could send an endless
+//!   line and exhaust our memory; these helpers stop reading as soon as a frame
+//!   grows past `max_len`.
+//! - length-prefixed frames (`send_message`/`recv_message`), which is what the
+//!   prover and verifier speak on the wire. The body is JSON unless the two
+//!   sides use another [`Codec`] (`send_message_with`/`recv_message_with`).
+//!   [`MessageCodec`] speaks the same frames as a `tokio_util` codec, for
+//!   `Framed`, `FramedRead` and `FramedWrite`; `StreamTransport` is built on it.
+
+use std::io;
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::codec::{Codec, JsonCodec, WireFormat};
+use crate::Message;
+
+/// Default upper bound for a single frame - legit messages are ~100 bytes
+pub const DEFAULT_MAX_FRAME_LEN: usize = 4096;
+
+/// Errors that can occur while reading or writing a framed message
+#[derive(Debug, thiserror::Error)]
+pub enum FrameError {
+    #[error("Frame exceeds the maximum length of {max} bytes")]
+    TooLarge { max: usize },
+    #[error("Frame contains an embedded NUL byte")]
+    EmbeddedNul,
+    #[error("Frame is not valid UTF-8")]
+    InvalidUtf8(#[from] std::str::Utf8Error),
+    #[error("Frame is not a valid message: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Connection closed")]
+    ConnectionClosed,
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Read one newline-terminated message, refusing frames longer than `max_len`
+///
+/// Bytes are pulled from the reader's buffer chunk by chunk, so an oversized
+/// frame is rejected once `max_len` is crossed rather than after it has been
+/// read into memory in full.
+pub async fn read_message<R>(reader: &mut R, max_len: usize) -> Result<Message, FrameError>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut frame = Vec::new();
+    loop {
+        let available = reader.fill_buf().await?; // borrow whatever is already buffered
+        if available.is_empty() {
+            // EOF: a clean close between frames, or a truncated final frame
+            if frame.is_empty() {
+                return Err(FrameError::ConnectionClosed);
+            }
+            break;
+        }
+
+        let (chunk, done) = match available.iter().position(|&b| b == b'\n') {
+            Some(i) => (&available[..i], i + 1),
+            None => (available, available.len()),
+        };
+        if frame.len() + chunk.len() > max_len {
+            return Err(FrameError::TooLarge { max: max_len });
+        }
+        frame.extend_from_slice(chunk);
+        let found_newline = done > chunk.len();
+        reader.consume(done); // mark the bytes we used as read
+        if found_newline {
+            break;
+        }
+    }
+
+    if frame.last() == Some(&b'\r') {
+        frame.pop(); // tolerate CRLF line endings
+    }
+    if frame.contains(&0) {
+        return Err(FrameError::EmbeddedNul);
+    }
+    let text = std::str::from_utf8(&frame)?;
+    Ok(serde_json::from_str(text)?)
+}
+
+/// Serialize a message to JSON and write it as a single newline-terminated frame
+pub async fn write_message<W>(writer: &mut W, msg: &Message) -> Result<(), FrameError>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut frame = serde_json::to_vec(msg)?;
+    frame.push(b'\n');
+    writer.write_all(&frame).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+// Length-prefixed framing
+// =======================
+
+/// Largest payload a length-prefixed frame may carry
+pub const MAX_FRAME_LEN: usize = 65_535;
+
+/// Default cap on a length-prefixed frame a [`StreamTransport`](crate::StreamTransport) accepts
+///
+/// Twice [`DEFAULT_MAX_FRAME_LEN`]: a single-statement message is ~100 bytes,
+/// but an AND-proof batch of [`MAX_AND_STATEMENTS`](crate::MAX_AND_STATEMENTS)
+/// hex points comes to about 4.5 KiB of JSON.
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = 8192;
+
+/// Send a message as a 4-byte big-endian length followed by its JSON encoding
+///
+/// Unlike newline framing this cannot be confused by a newline inside the
+/// payload. The prefix and body are written with a single `write_all`.
+pub async fn send_message<W>(writer: &mut W, msg: &Message) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    send_message_with(writer, msg, &JsonCodec).await
+}
+
+/// Receive one length-prefixed JSON message
+///
+/// The length is checked against `MAX_FRAME_LEN` before anything is allocated,
+/// and an oversized frame is reported as `io::ErrorKind::InvalidData`.
+pub async fn recv_message<R>(reader: &mut R) -> io::Result<Message>
+where
+    R: AsyncRead + Unpin,
+{
+    recv_message_with(reader, &JsonCodec).await
+}
+
+/// `send_message` with the body encoded by `codec`
+pub async fn send_message_with<W>(writer: &mut W, msg: &Message, codec: &impl Codec) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let body = codec.encode(msg);
+    if body.len() > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("frame of {} bytes exceeds the maximum of {MAX_FRAME_LEN}", body.len()),
+        ));
+    }
+    let mut frame = Vec::with_capacity(4 + body.len());
+    frame.extend_from_slice(&(body.len() as u32).to_be_bytes()); // length prefix
+    frame.extend_from_slice(&body);
+    writer.write_all(&frame).await?;
+    writer.flush().await
+}
+
+/// `recv_message` with the body decoded by `codec`
+///
+/// A body the codec can't decode is `io::ErrorKind::InvalidData` wrapping the `CodecError`.
+pub async fn recv_message_with<R>(reader: &mut R, codec: &impl Codec) -> io::Result<Message>
+where
+    R: AsyncRead + Unpin,
+{
+    recv_message_limited(reader, codec, MAX_FRAME_LEN).await
+}
+
+/// `recv_message_with`, refusing frames longer than `max_len` (itself capped at `MAX_FRAME_LEN`)
+///
+/// The length prefix is checked before the body is allocated or read; one
+/// over the limit is `io::ErrorKind::InvalidData` wrapping
+/// `FrameError::TooLarge`, which the protocol reports as
+/// `ProtocolError::MessageTooLarge`.
+pub async fn recv_message_limited<R>(reader: &mut R, codec: &impl Codec, max_len: usize) -> io::Result<Message>
+where
+    R: AsyncRead + Unpin,
+{
+    let max = max_len.min(MAX_FRAME_LEN);
+    let mut prefix = [0u8; 4];
+    reader.read_exact(&mut prefix).await?; // UnexpectedEof if the peer hung up
+    let len = u32::from_be_bytes(prefix) as usize;
+    if len > max {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, FrameError::TooLarge { max }));
+    }
+    let mut body = vec![0u8; len]; // exactly the announced size
+    reader.read_exact(&mut body).await?;
+    codec.decode(&body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Length-prefixed frames as a `tokio_util` codec
+///
+/// Frames are exactly those of `send_message_with` and `recv_message_limited`:
+/// a 4-byte big-endian length, then the body in `format`. JSON bodies are
+/// serialized straight into the write buffer, and a decoded body is split off
+/// the read buffer rather than copied out of it. Errors are the ones the
+/// functions above return: an incoming length over `max_len` is
+/// `io::ErrorKind::InvalidData` wrapping `FrameError::TooLarge`, a body the
+/// format can't decode is `InvalidData` wrapping the `CodecError`, and a
+/// stream that ends partway through a frame is `UnexpectedEof`.
+#[derive(Debug, Clone, Copy)]
+pub struct MessageCodec {
+    format: WireFormat,
+    max_len: usize,
+}
+
+impl MessageCodec {
+    /// Frames in `format`, refusing incoming ones over [`DEFAULT_MAX_MESSAGE_LEN`]
+    pub fn new(format: WireFormat) -> Self {
+        Self { format, max_len: DEFAULT_MAX_MESSAGE_LEN }
+    }
+
+    /// Refuse incoming frames longer than `max` bytes; anything over `MAX_FRAME_LEN` is refused regardless
+    pub fn with_max_len(self, max: usize) -> Self {
+        Self { max_len: max, ..self }
+    }
+
+    pub fn format(&self) -> WireFormat {
+        self.format
+    }
+
+    /// Encode and decode every later frame in `format`
+    pub fn set_format(&mut self, format: WireFormat) {
+        self.format = format;
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+}
+
+impl Default for MessageCodec {
+    fn default() -> Self {
+        Self::new(WireFormat::Json)
+    }
+}
+
+impl Encoder<&Message> for MessageCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, msg: &Message, dst: &mut BytesMut) -> io::Result<()> {
+        let start = dst.len();
+        dst.put_u32(0); // the length prefix, filled in once the body is written
+        match self.format {
+            WireFormat::Json => serde_json::to_writer(dst.writer(), msg).expect("a Message always serializes"),
+            format => dst.extend_from_slice(&format.encode(msg)),
+        }
+        let len = dst.len() - start - 4;
+        if len > MAX_FRAME_LEN {
+            dst.truncate(start);
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("frame of {len} bytes exceeds the maximum of {MAX_FRAME_LEN}"),
+            ));
+        }
+        dst[start..start + 4].copy_from_slice(&(len as u32).to_be_bytes());
+        Ok(())
+    }
+}
+
+impl Encoder<Message> for MessageCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, msg: Message, dst: &mut BytesMut) -> io::Result<()> {
+        self.encode(&msg, dst)
+    }
+}
+
+impl Decoder for MessageCodec {
+    type Item = Message;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Message>> {
+        let Some(prefix) = src.first_chunk::<4>() else {
+            return Ok(None);
+        };
+        let len = u32::from_be_bytes(*prefix) as usize;
+        let max = self.max_len.min(MAX_FRAME_LEN);
+        if len > max {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, FrameError::TooLarge { max }));
+        }
+        if src.len() < 4 + len {
+            src.reserve(4 + len - src.len()); // room for the rest of this frame, and no more
+            return Ok(None);
+        }
+        src.advance(4);
+        let body = src.split_to(len);
+        self.format.decode(&body).map(Some).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> io::Result<Option<Message>> {
+        match self.decode(src)? {
+            None if !src.is_empty() => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "stream ended partway through a frame")),
+            decoded => Ok(decoded),
+        }
+    }
+}
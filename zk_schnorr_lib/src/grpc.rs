@@ -0,0 +1,188 @@
+//! The protocol over gRPC (`grpc` feature)
+//!
+//! `proto/schnorr.proto` defines one bidirectional streaming call,
+//! `SchnorrVerifier.Prove`: a whole session runs inside it, with every
+//! [`Message`] carried as a `SchnorrMessage` (kind, payload bytes and the
+//! session tag). [`GrpcTransport`] turns either end of the call into a
+//! [`Transport`], so the prover and verifier run the same code as over TLS.
+//!
+//! [`SchnorrService`] is the verifier's side, checking provers against a
+//! [`KeyRegistry`] like [`VerifierServer`](crate::server::VerifierServer)
+//! does. Both ends secure the call with tonic's TLS, configured from a
+//! [`TlsCertificate`] by [`server_tls_config`] and [`client_tls_config`].
+
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
+
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity, ServerTlsConfig};
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{info, info_span, Instrument};
+
+use crate::audit::{NullLog, ProofLog};
+use crate::metrics::Metrics;
+use crate::registry::KeyRegistry;
+use crate::runner::run_verifier_registry;
+use crate::server::{report, SessionResult};
+use crate::session::SessionId;
+use crate::timeouts::{with_timeout, Timeouts};
+use crate::tls::{der_to_pem, TlsCertificate};
+use crate::transport::{TimeoutTransport, Transport};
+use crate::Message;
+
+/// Types generated from `proto/schnorr.proto`
+pub mod pb {
+    tonic::include_proto!("schnorr");
+}
+
+pub use pb::schnorr_verifier_client::SchnorrVerifierClient;
+pub use pb::schnorr_verifier_server::{SchnorrVerifier, SchnorrVerifierServer};
+pub use pb::SchnorrMessage;
+
+// messages either end may send before the other has read them
+const MESSAGE_BUFFER: usize = 16;
+
+impl From<Message> for SchnorrMessage {
+    fn from(msg: Message) -> Self {
+        Self {
+            kind: msg.kind,
+            payload: msg.payload.into_bytes(),
+            session: msg.session.map(|id| id.as_bytes().to_vec()).unwrap_or_default(),
+        }
+    }
+}
+
+impl TryFrom<SchnorrMessage> for Message {
+    type Error = io::Error;
+
+    /// Fails with `InvalidData` unless the payload is UTF-8 and the session tag empty or 16 bytes
+    fn try_from(msg: SchnorrMessage) -> Result<Self, Self::Error> {
+        let payload = String::from_utf8(msg.payload).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let session = match msg.session.len() {
+            0 => None,
+            _ => Some(SessionId::new(msg.session.try_into().map_err(|tag: Vec<u8>| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("session tag of {} bytes, expected 16", tag.len()))
+            })?)),
+        };
+        Ok(Self { kind: msg.kind, payload, session })
+    }
+}
+
+/// One end of a `Prove` call: messages out through a channel, in from the peer's stream
+pub struct GrpcTransport {
+    outgoing: mpsc::Sender<SchnorrMessage>,
+    incoming: Streaming<SchnorrMessage>,
+}
+
+impl GrpcTransport {
+    /// The prover's end: start a `Prove` call on `client`
+    pub async fn connect(client: &mut SchnorrVerifierClient<Channel>) -> Result<Self, Status> {
+        let (outgoing, requests) = mpsc::channel(MESSAGE_BUFFER);
+        let incoming = client.prove(ReceiverStream::new(requests)).await?.into_inner();
+        Ok(Self { outgoing, incoming })
+    }
+}
+
+impl Transport for GrpcTransport {
+    async fn send(&mut self, msg: Message) -> io::Result<()> {
+        self.outgoing
+            .send(msg.into())
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "gRPC stream closed"))
+    }
+
+    async fn recv(&mut self) -> io::Result<Message> {
+        match self.incoming.message().await {
+            Ok(Some(msg)) => msg.try_into(),
+            Ok(None) => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "gRPC stream ended")),
+            Err(status) => Err(io::Error::other(status)),
+        }
+    }
+}
+
+/// The verifier as a gRPC service: every `Prove` call is one session
+pub struct SchnorrService {
+    state: Arc<ServiceState>,
+}
+
+struct ServiceState {
+    registry: KeyRegistry,
+    timeouts: Timeouts,
+    audit_log: Arc<dyn ProofLog>,
+    metrics: Arc<Metrics>,
+}
+
+impl SchnorrService {
+    /// Check provers against `registry`, with default timeouts and no audit log
+    pub fn new(registry: KeyRegistry) -> Self {
+        Self::with_options(registry, Timeouts::default(), Arc::new(NullLog))
+    }
+
+    pub fn with_options(registry: KeyRegistry, timeouts: Timeouts, audit_log: Arc<dyn ProofLog>) -> Self {
+        let state = ServiceState { registry, timeouts, audit_log, metrics: Arc::new(Metrics::new()) };
+        Self { state: Arc::new(state) }
+    }
+
+    /// Counters for every session this service has run
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.state.metrics.clone()
+    }
+
+    /// Wrap in the generated server, ready for `tonic::transport::Server::add_service`
+    pub fn into_server(self) -> SchnorrVerifierServer<Self> {
+        SchnorrVerifierServer::new(self)
+    }
+}
+
+type ProveStream = Pin<Box<dyn Stream<Item = Result<SchnorrMessage, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl SchnorrVerifier for SchnorrService {
+    type ProveStream = ProveStream;
+
+    async fn prove(&self, request: Request<Streaming<SchnorrMessage>>) -> Result<Response<ProveStream>, Status> {
+        let span = match request.remote_addr() {
+            Some(addr) => info_span!("session", remote_addr = %addr),
+            None => info_span!("session"),
+        };
+        let (outgoing, responses) = mpsc::channel(MESSAGE_BUFFER);
+        let transport = GrpcTransport { outgoing, incoming: request.into_inner() };
+        let state = self.state.clone();
+        // the session outlives this call: it ends, and closes the response stream, when the prover is done
+        tokio::spawn(state.session(transport).instrument(span));
+        Ok(Response::new(Box::pin(ReceiverStream::new(responses).map(Ok))))
+    }
+}
+
+impl ServiceState {
+    async fn session(self: Arc<Self>, transport: GrpcTransport) {
+        info!("Accepted gRPC call");
+        self.metrics.connection_accepted();
+        let started = Instant::now();
+        let result = self.verify(TimeoutTransport::new(transport, self.timeouts.message)).await;
+        report(&result);
+        self.metrics.record_session(result.as_ref().map(|(_, outcome)| outcome));
+        self.metrics.observe_latency(started.elapsed());
+    }
+
+    async fn verify<T: Transport>(&self, mut transport: T) -> SessionResult {
+        let session = run_verifier_registry(&mut transport, &self.registry, self.audit_log.as_ref());
+        with_timeout(self.timeouts.session, "session to complete", session).await?
+    }
+}
+
+/// Server-side TLS for [`SchnorrService`] from a certificate made by `generate_self_signed_cert`
+pub fn server_tls_config(tls_cert: &TlsCertificate) -> ServerTlsConfig {
+    ServerTlsConfig::new().identity(Identity::from_pem(tls_cert.cert_pem(), tls_cert.private_key_pem().as_bytes()))
+}
+
+/// Client-side TLS that trusts exactly `server_cert_der` and checks it is valid for `domain`
+pub fn client_tls_config(server_cert_der: &[u8], domain: &str) -> ClientTlsConfig {
+    ClientTlsConfig::new()
+        .ca_certificate(Certificate::from_pem(der_to_pem("CERTIFICATE", server_cert_der)))
+        .domain_name(domain)
+}
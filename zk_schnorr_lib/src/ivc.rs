@@ -0,0 +1,87 @@
+//! Proof chains over a sequence of states, a simplified form of incrementally
+//! verifiable computation
+//!
+//! Step `n` commits to its state as `C_n = h*G` with `h = H(C_{n-1}, state_n)`,
+//! and carries a Schnorr proof of the chain key whose challenge is
+//! `c = H(C_{n-1}, C_n, X, R)`. A step therefore only verifies on top of the
+//! exact commitment it was made after, and changing any step breaks every
+//! later one. The first step folds in the identity point as `C_0`.
+//!
+//! This is not recursive SNARK composition: a step's proof shows it extends
+//! the previous commitment, not that the previous step was itself valid, so
+//! [`ivc_verify_chain`] still checks every step, one Schnorr verification each.
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use rand_core::RngCore;
+
+use crate::schnorr::{random_scalar, KeyPair, Proof, PublicKey};
+use crate::transcript::TranscriptBuilder;
+
+const STATE_DOMAIN: &[u8] = b"zk-schnorr-tls/ivc/state/v1";
+const STEP_DOMAIN: &[u8] = b"zk-schnorr-tls/ivc/step/v1";
+
+/// One link of a proof chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IVCStep {
+    /// C_n, folding the previous commitment and this step's state
+    pub state_commitment: RistrettoPoint,
+    /// Knowledge of the chain key, bound to C_{n-1} and C_n
+    pub proof: Proof,
+}
+
+/// Extend the chain ending at `prev` (or start one, for `None`) with `new_state`
+pub fn ivc_prove(prev: Option<&IVCStep>, new_state: &[u8], keypair: &KeyPair, rng: &mut impl RngCore) -> IVCStep {
+    let prev_commitment = prev.map_or_else(RistrettoPoint::identity, |step| step.state_commitment);
+    let state_commitment = fold_state(&prev_commitment, new_state);
+
+    let k = random_scalar(rng);
+    let R = RISTRETTO_BASEPOINT_POINT * k;
+    let c = step_challenge(&prev_commitment, &state_commitment, &keypair.public_key(), &R);
+    let s = k + c * keypair.secret().expose_secret();
+    IVCStep { state_commitment, proof: Proof::new(R, c, s) }
+}
+
+/// Check every step of `chain`, first to last, against the key that made it
+///
+/// An empty chain proves nothing and is rejected.
+pub fn ivc_verify_chain(chain: &[IVCStep], genesis_key: &PublicKey) -> bool {
+    if chain.is_empty() {
+        return false;
+    }
+    let mut prev_commitment = RistrettoPoint::identity();
+    for step in chain {
+        let c = step_challenge(&prev_commitment, &step.state_commitment, genesis_key, &step.proof.commitment);
+        if step.proof.challenge != c || !step.proof.verify(genesis_key) {
+            return false;
+        }
+        prev_commitment = step.state_commitment;
+    }
+    true
+}
+
+// C_n = H(C_{n-1}, state)*G
+fn fold_state(prev_commitment: &RistrettoPoint, state: &[u8]) -> RistrettoPoint {
+    let h = TranscriptBuilder::new(STATE_DOMAIN)
+        .append_point(b"prev", prev_commitment)
+        .append_bytes(b"state", state)
+        .challenge();
+    RISTRETTO_BASEPOINT_POINT * h
+}
+
+// c = H(C_{n-1}, C_n, X, R)
+fn step_challenge(
+    prev_commitment: &RistrettoPoint,
+    state_commitment: &RistrettoPoint,
+    public_key: &PublicKey,
+    R: &RistrettoPoint,
+) -> Scalar {
+    TranscriptBuilder::new(STEP_DOMAIN)
+        .append_point(b"prev", prev_commitment)
+        .append_point(b"C", state_commitment)
+        .append_point(b"X", public_key)
+        .append_point(b"R", R)
+        .challenge()
+}
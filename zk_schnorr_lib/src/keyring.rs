@@ -0,0 +1,68 @@
+//! Several named keypairs behind one shared handle
+//!
+//! A [`SchnorrKeyring`] is for applications that sign as more than one
+//! identity. Callers refer to keys by name and get back public keys and
+//! signatures; the secret scalars stay inside, and [`SchnorrKeyring::sign`] is
+//! the only place they are used. Clones share the same keys, so one keyring
+//! can be handed to every task that needs to sign.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use rand_core::RngCore;
+
+use crate::error::ZkError;
+use crate::schnorr::{KeyPair, PublicKey};
+use crate::signature::{self, Signature};
+
+/// Named keypairs, safe to share between threads
+#[derive(Clone, Default)]
+pub struct SchnorrKeyring(Arc<RwLock<HashMap<String, KeyPair>>>);
+
+impl SchnorrKeyring {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `kp` under `name`; a name already in use is refused and its key left as it was
+    pub fn add_keypair(&self, name: &str, kp: KeyPair) -> Result<(), ZkError> {
+        let mut keys = self.0.write();
+        if keys.contains_key(name) {
+            return Err(ZkError::DuplicateKey(name.to_string()));
+        }
+        keys.insert(name.to_string(), kp);
+        Ok(())
+    }
+
+    /// Take the keypair named `name` out of the keyring
+    pub fn remove_keypair(&self, name: &str) -> Option<KeyPair> {
+        self.0.write().remove(name)
+    }
+
+    pub fn get_public_key(&self, name: &str) -> Option<PublicKey> {
+        self.0.read().get(name).map(KeyPair::public_key)
+    }
+
+    /// Sign `message` with the key named `name`, drawing the nonce from `rng`
+    pub fn sign(&self, name: &str, message: &[u8], rng: &mut impl RngCore) -> Result<Signature, ZkError> {
+        let keys = self.0.read();
+        let kp = keys.get(name).ok_or_else(|| ZkError::UnknownKey(name.to_string()))?;
+        Ok(signature::sign(kp.secret().expose_secret(), message, rng))
+    }
+
+    /// The names in the keyring, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.0.read().keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+// names only; neither secrets nor public keys end up in logs
+impl fmt::Debug for SchnorrKeyring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SchnorrKeyring").field("names", &self.names()).finish()
+    }
+}
@@ -1,13 +1,228 @@
 //! This library provides the core types and utilities for implementing
+//!
+//! Without the default `std` feature the crate is `no_std` and needs only
+//! `alloc`: the curve and proof types in [`schnorr`], [`Message`] and the hex
+//! helpers. [`verify`] checks a [`SchnorrProof`] from fixed-size buffers
+//! without allocating at all, for firmware that has no heap to spare.
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(non_snake_case)] // R, X, G etc. follow the usual maths notation
+extern crate alloc; // String and Vec for the core, with or without std
+
+use alloc::string::{String, ToString};
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
 // ristretto because it provides a clean safe abstraction overthe curve 25519
 use curve25519_dalek::ristretto::RistrettoPoint; // reperesents a point on the Curve25519 elliptic curve
 use curve25519_dalek::scalar::Scalar; // reperesents a scalar value on the curves field
 use hex::{encode as hex_encode, decode as hex_decode}; // to transmit binary data as readabe text
 use serde::{Deserialize, Serialize}; // trait for converting structs to and from JSON
+pub use subtle::Choice; // constant-time boolean returned by ct_scalar_eq
+use curve25519_dalek::traits::IsIdentity; // the identity check for strictly decoded points
+use subtle::ConstantTimeEq; // constant-time equality for scalars, points and proofs
 
-// TLS certificate generation
-use rcgen::{Certificate, CertificateParams, DistinguishedName};
-use rustls::{Certificate as RustlsCertificate, PrivateKey, ServerConfig, ClientConfig, RootCertStore};
+pub mod schnorr; // proof transcripts and (batch) verification
+pub use schnorr::{KeyPair, Proof, PublicKey, SchnorrProof, VerifyOutcome, batch_verify, commit, hash_to_point, respond, scalar_from_seed, verify, KEYPAIR_SEED_DOMAIN, WEAK_SECRET_BOUND};
+pub mod params; // the generator proofs are made over, G unless configured otherwise
+pub use params::Params;
+#[cfg(feature = "tls")]
+pub mod framing; // length-limited reading and writing of messages
+#[cfg(feature = "tls")]
+pub use framing::{read_message, write_message, FrameError, DEFAULT_MAX_FRAME_LEN};
+#[cfg(feature = "tls")]
+pub use framing::{send_message, recv_message, send_message_with, recv_message_with, recv_message_limited, MessageCodec, DEFAULT_MAX_MESSAGE_LEN, MAX_FRAME_LEN};
+#[cfg(feature = "std")]
+pub mod codec; // JSON, CBOR or compact binary message bodies
+#[cfg(feature = "std")]
+pub use codec::{BinaryCodec, Codec, CodecError, JsonCodec, UnknownWireFormat, WireFormat};
+#[cfg(feature = "cbor")]
+pub use codec::{message_from_cbor, message_to_cbor, CborCodec};
+#[cfg(feature = "tls")]
+pub mod protocol; // both sides of the interactive protocol
+#[cfg(feature = "tls")]
+pub use protocol::{prove_interactive, prove_interactive_deterministic, verify_stream, SchnorrProtocol};
+#[cfg(feature = "tls")]
+pub mod runner; // whole prover and verifier sessions: hello, then the rounds
+#[cfg(feature = "tls")]
+pub use runner::{
+    run_prover, run_prover_as, run_prover_ed25519, run_prover_with, run_verifier, run_verifier_logged, run_verifier_registry, run_verifier_with_params,
+    ProofOutcome, ProverOptions,
+};
+#[cfg(feature = "std")]
+pub mod registry; // public keys by prover identity
+#[cfg(feature = "std")]
+pub use registry::{KeyRegistry, RegisteredKey, Scheme, UnknownScheme};
+#[cfg(feature = "std")]
+pub mod audit; // recording every verified proof for later audit
+#[cfg(feature = "std")]
+pub use audit::{AuditRecord, JsonFileLog, NullLog, ProofLog, SessionAuditRecord, SessionOutcome};
+#[cfg(feature = "std")]
+pub mod proof_store; // checked proofs, looked up by public key or session
+#[cfg(feature = "std")]
+pub use proof_store::{InMemoryProofStore, ProofEntry, ProofStoreBackend};
+#[cfg(feature = "std")]
+pub mod proof_transcript; // single checks saved to disk and re-verified offline
+#[cfg(feature = "std")]
+pub use proof_transcript::{read_transcript, verify_transcript, ChallengeSource, Transcript, TranscriptDir, TranscriptValidity};
+#[cfg(feature = "tls")]
+pub mod multiplex; // numbered sub-proofs over one connection, each with its own verdict
+#[cfg(feature = "tls")]
+pub use multiplex::{MultiplexedProver, MultiplexedVerifier, ProofRequest, ProofResult};
+#[cfg(feature = "tls")]
+pub mod transport; // message transports: TLS, generic streams, in-memory channels
+#[cfg(feature = "tls")]
+pub use transport::{ChannelTransport, StreamTransport, TimeoutTransport, TlsTransport, Transport};
+#[cfg(feature = "ws")]
+pub mod ws; // WebSocket transport, one JSON text frame per message
+#[cfg(feature = "ws")]
+pub use ws::{accept_ws, connect_ws, ws_config, WsTransport};
+pub mod error; // protocol error type
+pub use error::{DecodeError, ProofDecodeError};
+#[cfg(feature = "std")]
+pub use error::{ProtocolError, ZkError};
+#[cfg(feature = "tls")]
+pub mod limits; // concurrent connection cap and per-IP rate limiting
+#[cfg(feature = "tls")]
+pub use limits::{ConnectionLimiter, ConnectionPermit, LimiterStats, Rejection};
+#[cfg(feature = "tls")]
+pub mod metrics; // verification counters and latency histogram, Prometheus text output
+#[cfg(feature = "tls")]
+pub use metrics::{Metrics, MetricsSnapshot, RejectReason};
+#[cfg(feature = "std")]
+pub mod or_proof; // prove knowledge of one of two secrets without revealing which
+#[cfg(feature = "std")]
+pub use or_proof::{OrCommitment, OrProof, OrProver};
+#[cfg(feature = "std")]
+pub mod and_proof; // prove knowledge of several secrets with one shared challenge
+#[cfg(feature = "std")]
+pub use and_proof::{AndProof, AndProofError, MultiAndProof, MAX_AND_STATEMENTS};
+pub mod secret; // zeroize-on-drop wrapper for secret scalars and nonces
+pub use secret::SecretScalar;
+#[cfg(feature = "std")]
+pub mod pem; // PEM export and import of keypairs
+#[cfg(feature = "std")]
+pub use pem::{keypair_from_pem, keypair_to_pem};
+#[cfg(feature = "derive")]
+pub mod derive; // HKDF child keys from a master secret
+#[cfg(feature = "derive")]
+pub use derive::{derive_key, derive_keypair};
+#[cfg(feature = "wasm")]
+pub mod wasm; // wasm-bindgen keypair, sign and verify for browser provers
+#[cfg(feature = "wasm")]
+pub use wasm::{wasm_generate_keypair, wasm_schnorr_sign, wasm_schnorr_verify};
+#[cfg(feature = "std")]
+pub mod version; // protocol versions and the opening hello exchange
+#[cfg(feature = "std")]
+pub use version::{negotiate, NegotiationError, Version, VersionInfo, VersionRejection, PROTOCOL_VERSION};
+pub mod transcript; // merlin-based Fiat-Shamir challenges
+pub use transcript::{transcript_challenge, TranscriptBuilder};
+pub mod session; // session ids and replay detection
+pub use session::SessionId;
+#[cfg(feature = "std")]
+pub use session::SessionCache;
+pub mod challenge; // session-bound, domain-separated challenge derivation
+pub use challenge::ChallengeContext;
+#[cfg(feature = "std")]
+pub mod challenge_commitment; // what the prover accepts as a challenge, and verifier-committed challenges
+#[cfg(feature = "std")]
+pub use challenge_commitment::{ChallengeCommitment, ChallengeOpening, ChallengePolicy, CHALLENGE_COMMITMENT_DOMAIN, COMMITTED_CHALLENGE};
+#[cfg(feature = "std")]
+pub mod signature; // Schnorr signatures on arbitrary messages
+#[cfg(feature = "std")]
+pub use signature::{challenge_from_public_input, schnorr_sign_with_context, schnorr_verify_with_context, verify_signature, Signature};
+#[cfg(feature = "std")]
+pub mod keyring; // named keypairs that sign without handing out their secrets
+#[cfg(feature = "std")]
+pub use keyring::SchnorrKeyring;
+#[cfg(feature = "std")]
+pub mod ring; // sign as one of N keys without revealing which
+#[cfg(feature = "std")]
+pub use ring::{ring_sign, ring_verify, RingError, RingSignature};
+#[cfg(feature = "std")]
+pub mod membership; // prove a key is in a set without revealing which
+#[cfg(feature = "std")]
+pub use membership::{prove_membership, verify_membership, MembershipProof, MEMBERSHIP_DOMAIN};
+#[cfg(feature = "std")]
+pub mod ed25519; // standard Ed25519 signatures, for provers that can only sign
+#[cfg(feature = "std")]
+pub use ed25519::{verify_ed25519, Ed25519Error, Ed25519PublicKey, Ed25519SigningKey};
+#[cfg(feature = "std")]
+pub mod musig2; // two-round multi-signatures under one aggregate key
+#[cfg(feature = "std")]
+pub use musig2::{MuSig2Error, MuSig2Session};
+#[cfg(feature = "std")]
+pub mod musig; // cosigners answering one Schnorr round under their aggregate key
+#[cfg(feature = "std")]
+pub use musig::{aggregate_keys, Cosigner, CosignerNonce};
+#[cfg(feature = "std")]
+pub mod adaptor; // adaptor (pre-)signatures for atomic swaps
+#[cfg(feature = "std")]
+pub use adaptor::AdaptorSignature;
+#[cfg(feature = "std")]
+pub mod pedersen; // Pedersen commitments and proofs of opening
+#[cfg(feature = "std")]
+pub use pedersen::{Commitment, OpeningProof, OpeningProver, PedersenParams};
+#[cfg(feature = "std")]
+pub mod range_proof; // a committed value lies in [0, 2^n), by bit decomposition
+#[cfg(feature = "std")]
+pub use range_proof::{BitProof, RangeProof};
+#[cfg(feature = "std")]
+pub mod dleq; // prove two points share one discrete log
+#[cfg(feature = "std")]
+pub use dleq::DleqProof;
+#[cfg(feature = "std")]
+pub mod ivc; // chains of proofs over successive states, each bound to the one before
+#[cfg(feature = "std")]
+pub use ivc::{ivc_prove, ivc_verify_chain, IVCStep};
+pub mod serde_hex; // serde helpers encoding points and scalars as hex
+pub use serde_hex::{SerdePoint, SerdeScalar};
+#[cfg(feature = "std")]
+pub mod encoding; // raw-byte and base64url encodings alongside hex
+#[cfg(feature = "std")]
+pub use encoding::{
+    point_from_b64, point_from_bytes, point_to_b64, point_to_bytes, scalar_from_b64, scalar_from_bytes, scalar_to_b64,
+    scalar_to_bytes, B64DecodeError, PayloadDecodeError, PayloadEncoding, UnknownPayloadEncoding,
+};
+mod uncompressed; // 64-byte affine coordinates for point_{to,from}_hex_uncompressed
+#[cfg(feature = "tls")]
+pub mod tls; // self-signed certificates and rustls client/server configs
+#[cfg(feature = "tls")]
+pub use tls::{
+    cert_fingerprint, create_client_config_insecure, create_client_config_insecure_from_der, create_client_config_insecure_tls13,
+    create_client_config_pinned, create_client_config_verified, create_client_config_with_identity, create_server_config,
+    create_server_config_from_pem, create_server_config_mtls, create_server_config_tls13, der_to_pem, generate_self_signed_cert,
+    generate_self_signed_cert_for, generate_self_signed_cert_with, CertKeyAlgorithm, CertOptions, PemCertificate, PinnedCert,
+    TlsCertificate, TlsError,
+};
+#[cfg(feature = "tls")]
+pub mod retry; // TCP connect and TLS handshake, retried with backoff while the verifier starts
+#[cfg(feature = "tls")]
+pub use retry::{connect_with_retry, ConnectError, ConnectStage, RetryPolicy};
+#[cfg(feature = "tls")]
+pub mod cert_rotation; // a server certificate that can be swapped, or reloaded from disk, while the server runs
+#[cfg(feature = "tls")]
+pub use cert_rotation::RotatingCertResolver;
+#[cfg(feature = "server")]
+pub use cert_rotation::watch_cert_file;
+#[cfg(feature = "server")]
+pub mod server; // the verifier binary as a library: accept, verify, report
+#[cfg(feature = "server")]
+pub use server::{serve_metrics, ServerHandle, ShutdownSummary, VerificationEvent, VerifierServer, VerifierServerBuilder};
+#[cfg(feature = "server")]
+pub mod session_log; // one JSON line per session, from a single writer task
+#[cfg(feature = "server")]
+pub use session_log::{AuditLog, AuditLogOptions, FsyncPolicy, UnknownFsyncPolicy};
+#[cfg(feature = "grpc")]
+pub mod grpc; // the protocol as one bidirectional streaming gRPC call
+#[cfg(feature = "grpc")]
+pub use grpc::{GrpcTransport, SchnorrService};
+#[cfg(feature = "testutil")]
+pub mod testutil; // run both sides over an in-memory duplex pipe
+#[cfg(feature = "std")]
+pub mod timeouts; // deadlines for handshakes, messages and whole sessions
+#[cfg(feature = "std")]
+pub use timeouts::{ProtocolConfig, TimedOut, Timeouts};
+#[cfg(feature = "tls")]
+pub use timeouts::with_timeout;
 
 
 
@@ -18,6 +233,10 @@ pub struct Message {
     pub kind: String,
     // The payload data as a hex-encoded string
     pub payload: String,
+    /// The session this message belongs to, echoed by both sides once the
+    /// verifier has sent its context; left out of the encoding when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<SessionId>,
 }
 
 impl Message {
@@ -26,6 +245,7 @@ impl Message {
         Self {
             kind: "commit".to_string(), // string literal to owned string
             payload: point_to_hex(point), // converts the elliptic curve point to a hex string
+            session: None, // not tied to a session until `in_session`
         }
     }
 
@@ -34,14 +254,230 @@ impl Message {
         Self {
             kind: "challenge".to_string(),
             payload: scalar_to_hex(scalar),
+            session: None,
         }
     }
 
+    /// A `challenge-commit`: the verifier's commitment to the next round's challenge, sent before `R`
+    #[cfg(feature = "std")]
+    pub fn challenge_commit(commitment: &ChallengeCommitment) -> Self {
+        Self { kind: "challenge-commit".to_string(), payload: commitment.to_hex(), session: None }
+    }
+
+    /// A `challenge-reveal`: the committed challenge and its salt, in place of a `challenge`
+    #[cfg(feature = "std")]
+    pub fn challenge_reveal(opening: &ChallengeOpening) -> Self {
+        Self { kind: "challenge-reveal".to_string(), payload: opening.to_hex(), session: None }
+    }
+
     /// Create a new response message with a scalar
     pub fn response(scalar: &Scalar) -> Self {
         Self {
             kind: "response".to_string(),
             payload: scalar_to_hex(scalar),
+            session: None,
+        }
+    }
+
+    /// Tag the message with the session it belongs to
+    pub fn in_session(mut self, session: &SessionId) -> Self {
+        self.session = Some(*session);
+        self
+    }
+
+    /// `commit`, with the point written in `encoding` instead of hex
+    #[cfg(feature = "std")]
+    pub fn commit_with(point: &RistrettoPoint, encoding: PayloadEncoding) -> Self {
+        Self { kind: "commit".to_string(), payload: encoding.encode_point(point), session: None }
+    }
+
+    /// `challenge`, with the scalar written in `encoding` instead of hex
+    #[cfg(feature = "std")]
+    pub fn challenge_with(scalar: &Scalar, encoding: PayloadEncoding) -> Self {
+        Self { kind: "challenge".to_string(), payload: encoding.encode_scalar(scalar), session: None }
+    }
+
+    /// `response`, with the scalar written in `encoding` instead of hex
+    #[cfg(feature = "std")]
+    pub fn response_with(scalar: &Scalar, encoding: PayloadEncoding) -> Self {
+        Self { kind: "response".to_string(), payload: encoding.encode_scalar(scalar), session: None }
+    }
+
+    /// The payload as a point written in exactly `encoding`
+    #[cfg(feature = "std")]
+    pub fn payload_point(&self, encoding: PayloadEncoding) -> Result<RistrettoPoint, PayloadDecodeError> {
+        encoding.decode_point(&self.payload)
+    }
+
+    /// The payload as a canonical scalar written in exactly `encoding`
+    #[cfg(feature = "std")]
+    pub fn payload_scalar(&self, encoding: PayloadEncoding) -> Result<Scalar, PayloadDecodeError> {
+        encoding.decode_scalar(&self.payload)
+    }
+
+    /// `payload_point` for a payload that may be hex or base64url, told apart by length
+    #[cfg(feature = "std")]
+    pub fn payload_point_detect(&self) -> Result<RistrettoPoint, PayloadDecodeError> {
+        PayloadEncoding::detect(&self.payload).ok_or(PayloadDecodeError::UnknownEncoding)?.decode_point(&self.payload)
+    }
+
+    /// `payload_scalar` for a payload that may be hex or base64url, told apart by length
+    #[cfg(feature = "std")]
+    pub fn payload_scalar_detect(&self) -> Result<Scalar, PayloadDecodeError> {
+        PayloadEncoding::detect(&self.payload).ok_or(PayloadDecodeError::UnknownEncoding)?.decode_scalar(&self.payload)
+    }
+
+    /// The verifier's per-connection challenge context, sent before the first round
+    pub fn context(context: &ChallengeContext) -> Self {
+        Self {
+            kind: "context".to_string(),
+            payload: context.to_hex(),
+            session: None,
+        }
+    }
+
+    /// The verifier's verdict on a round: "accepted" or "rejected"
+    pub fn result(verified: bool) -> Self {
+        Self {
+            kind: "result".to_string(),
+            payload: if verified { "accepted" } else { "rejected" }.to_string(),
+            session: None,
+        }
+    }
+
+    /// Tell the peer why the session is being ended
+    pub fn error(reason: &str) -> Self {
+        Self {
+            kind: "error".to_string(),
+            payload: reason.to_string(),
+            session: None,
+        }
+    }
+
+    /// An `opening-commit` message carrying `R = k_v*G + k_r*H` of a Pedersen opening proof
+    pub fn opening_commit(R: &RistrettoPoint) -> Self {
+        Self {
+            kind: "opening-commit".to_string(),
+            payload: point_to_hex(R),
+            session: None,
+        }
+    }
+
+    /// An `opening-response` message carrying the finished opening proof as JSON
+    #[cfg(feature = "std")]
+    pub fn opening_response(proof: &OpeningProof) -> Self {
+        Self {
+            kind: "opening-response".to_string(),
+            payload: serde_json::to_string(proof).expect("an OpeningProof always serializes"),
+            session: None,
+        }
+    }
+
+    /// An `or-commit` message carrying both branch commitments of an OR-proof as JSON
+    #[cfg(feature = "std")]
+    pub fn or_commit(commitment: &OrCommitment) -> Self {
+        Self {
+            kind: "or-commit".to_string(),
+            payload: serde_json::to_string(commitment).expect("an OrCommitment always serializes"),
+            session: None,
+        }
+    }
+
+    /// An `or-response` message carrying the finished OR-proof as JSON
+    #[cfg(feature = "std")]
+    pub fn or_response(proof: &OrProof) -> Self {
+        Self {
+            kind: "or-response".to_string(),
+            payload: serde_json::to_string(proof).expect("an OrProof always serializes"),
+            session: None,
+        }
+    }
+
+    /// A `commit-batch` message: one commitment per statement of an AND-proof,
+    /// as a JSON array of hex points
+    #[cfg(feature = "std")]
+    pub fn commit_batch(points: &[RistrettoPoint]) -> Self {
+        let hex: Vec<String> = points.iter().map(point_to_hex).collect();
+        Self {
+            kind: "commit-batch".to_string(),
+            payload: serde_json::to_string(&hex).expect("a list of strings always serializes"),
+            session: None,
+        }
+    }
+
+    /// A `response-batch` message: one response per statement, as a JSON array of hex scalars
+    #[cfg(feature = "std")]
+    pub fn response_batch(scalars: &[Scalar]) -> Self {
+        let hex: Vec<String> = scalars.iter().map(scalar_to_hex).collect();
+        Self {
+            kind: "response-batch".to_string(),
+            payload: serde_json::to_string(&hex).expect("a list of strings always serializes"),
+            session: None,
+        }
+    }
+
+    /// A `proof-request` opening sub-proof `request.proof_id` of a multiplexed session
+    #[cfg(feature = "tls")]
+    pub fn proof_request(request: &ProofRequest) -> Self {
+        Self {
+            kind: "proof-request".to_string(),
+            payload: serde_json::to_string(request).expect("a ProofRequest always serializes"),
+            session: None,
+        }
+    }
+
+    /// A `proof-result`: the verifier's verdict on one sub-proof, with its id
+    #[cfg(feature = "tls")]
+    pub fn proof_result(result: &ProofResult) -> Self {
+        Self {
+            kind: "proof-result".to_string(),
+            payload: serde_json::to_string(result).expect("a ProofResult always serializes"),
+            session: None,
+        }
+    }
+
+    /// `end-session`: the prover is done and the verifier can close the connection
+    pub fn end_session() -> Self {
+        Self { kind: "end-session".to_string(), payload: String::new(), session: None }
+    }
+
+    /// The opening `hello`: the prover's offer, or the verifier's selection
+    #[cfg(feature = "std")]
+    pub fn hello(info: &VersionInfo) -> Self {
+        Self {
+            kind: "hello".to_string(),
+            payload: serde_json::to_string(info).expect("a VersionInfo always serializes"),
+            session: None,
+        }
+    }
+
+    /// The prover's codec request, or the verifier's confirmation of it
+    #[cfg(feature = "std")]
+    pub fn codec(codec: WireFormat) -> Self {
+        Self {
+            kind: "codec".to_string(),
+            payload: codec.to_string(),
+            session: None,
+        }
+    }
+
+    /// An `ed25519-challenge`: the fresh nonce an Ed25519 prover must sign, in hex
+    pub fn ed25519_challenge(nonce: &[u8; 32]) -> Self {
+        Self { kind: "ed25519-challenge".to_string(), payload: hex_encode(nonce), session: None }
+    }
+
+    /// An `ed25519-signature`: the 64-byte signature `R || s` over the challenge, in hex
+    pub fn ed25519_signature(signature: &[u8; 64]) -> Self {
+        Self { kind: "ed25519-signature".to_string(), payload: hex_encode(signature), session: None }
+    }
+
+    /// A `dleq` message carrying a DLEQ proof as JSON
+    #[cfg(feature = "std")]
+    pub fn dleq(proof: &DleqProof) -> Self {
+        Self {
+            kind: "dleq".to_string(),
+            payload: serde_json::to_string(proof).expect("a DleqProof always serializes"),
+            session: None,
         }
     }
 }
@@ -50,6 +486,10 @@ impl Message {
 // 
 // function takes a hex-encoded string and converts it to a scalar.
 // The `from_bytes_mod_order` ensures the result is valid in our field.
+//
+// Permissive: any 32 bytes are accepted and reduced, so several different
+// strings decode to the same scalar. Use `scalar_from_hex_canonical` for
+// anything received from a peer.
 
 pub fn scalar_from_hex(s: &str) -> Result<Scalar, hex::FromHexError> { // s is a reference to a string
     let bytes = hex_decode(s)?; // decode the hex string into bytes
@@ -61,11 +501,75 @@ pub fn scalar_from_hex(s: &str) -> Result<Scalar, hex::FromHexError> { // s is a
     Ok(Scalar::from_bytes_mod_order(arr)) // convert the array to a scalar reducing modulo the curve order
 }
 
+/// Convert a hex string to a Scalar, rejecting non-canonical encodings
+///
+/// Only the unique encoding of each scalar (its value below the group order)
+/// is accepted, so a response `s` can't be re-encoded into a different but
+/// equally valid wire string.
+pub fn scalar_from_hex_canonical(s: &str) -> Result<Scalar, ScalarDecodeError> {
+    let bytes = hex_decode(s)?;
+    let arr: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| ScalarDecodeError::InvalidLength(bytes.len()))?;
+    Option::from(Scalar::from_canonical_bytes(arr)).ok_or(ScalarDecodeError::NonCanonical)
+}
+
+/// `scalar_from_hex_canonical`, refusing zero as well
+///
+/// What a verifier decodes a response `s` with: `s = 0` would mean `R = -c*X`,
+/// a commitment made from the key alone rather than from a secret nonce.
+pub fn scalar_from_hex_nonzero(s: &str) -> Result<Scalar, ScalarDecodeError> {
+    let scalar = scalar_from_hex_canonical(s)?;
+    if scalar == Scalar::ZERO {
+        return Err(ScalarDecodeError::Zero);
+    }
+    Ok(scalar)
+}
+
+/// Errors that can occur when strictly decoding scalars from hex
+#[derive(Debug, thiserror::Error)]
+pub enum ScalarDecodeError {
+    #[error("Hex decoding failed: {0}")]
+    HexDecode(#[cfg_attr(feature = "std", source)] hex::FromHexError), // hex only implements Error with std
+    #[error("Invalid scalar length: expected 32 bytes, got {0}")]
+    InvalidLength(usize),
+    #[error("Non-canonical scalar encoding")]
+    NonCanonical,
+    #[error("Scalar is zero")]
+    Zero,
+}
+
+impl From<hex::FromHexError> for ScalarDecodeError {
+    fn from(e: hex::FromHexError) -> Self {
+        Self::HexDecode(e)
+    }
+}
+
 //  Convert a Scalar to a hex string
 pub fn scalar_to_hex(s: &Scalar) -> String { // s is a reference to a scalar
     hex_encode(s.to_bytes()) // convert the scalar to bytes and then encode the bytes to a hex string
 }
 
+/// Compare two scalars in constant time
+///
+/// Use this instead of `==` whenever either side is derived from a secret
+/// (responses, nonces, keys): the time taken does not depend on where the
+/// scalars first differ. Convert the result with `bool::from(choice)` only
+/// once the decision no longer depends on secret data.
+pub fn ct_scalar_eq(a: &Scalar, b: &Scalar) -> Choice {
+    a.ct_eq(b)
+}
+
+/// Compare two proofs field by field in constant time
+///
+/// The scalars go through `ct_scalar_eq` and the commitments are compared as
+/// compressed bytes, so how long it takes says nothing about which field
+/// differs or where. `==` on `Proof` is this.
+pub fn proof_eq_ct(a: &Proof, b: &Proof) -> bool {
+    a.ct_eq(b).into()
+}
+
 // Convert a RistrettoPoint to a hex string
 // 
 // compress the point to 32 bytes before encoding to hex.
@@ -77,8 +581,11 @@ pub fn point_to_hex(p: &RistrettoPoint) -> String {
 /// Convert a hex string to a RistrettoPoint
 pub fn point_from_hex(s: &str) -> Result<RistrettoPoint, PointDecodeError> { // s is a reference to a string
     let bytes = hex_decode(s).map_err(PointDecodeError::HexDecode)?; // decode the hex string into bytes
+    if bytes.len() == 64 { // the uncompressed x || y form
+        return decode_uncompressed(&bytes);
+    }
     if bytes.len() != 32 { // check if the length of the bytes is 32
-        return Err(PointDecodeError::InvalidLength(bytes.len())); // return an error if the length is not 32
+        return Err(PointDecodeError::InvalidLength { got: bytes.len(), expected: &[32, 64] }); // neither form
     }
     let mut arr = [0u8; 32]; // create an array of 32 bytes where each element is 0
     arr.copy_from_slice(&bytes); // copy the bytes into the array from the vec
@@ -90,129 +597,66 @@ pub fn point_from_hex(s: &str) -> Result<RistrettoPoint, PointDecodeError> { //
         .ok_or(PointDecodeError::InvalidPoint) // return an error if the point is invalid converts option to result 
 }
 
+/// `point_from_hex`, refusing the identity point as well
+///
+/// What a verifier decodes a commitment `R` with: `R = 0` carries no nonce,
+/// and the all-zero compressed encoding is its canonical form.
+pub fn point_from_hex_nonidentity(s: &str) -> Result<RistrettoPoint, PointDecodeError> {
+    let point = point_from_hex(s)?;
+    if point.is_identity() {
+        return Err(PointDecodeError::IdentityPoint);
+    }
+    Ok(point)
+}
+
+/// Decode a public key that came from outside, refusing the identity and any [weak key](PublicKey::is_weak)
+pub fn public_key_from_hex(s: &str) -> Result<PublicKey, PointDecodeError> {
+    let key = PublicKey(point_from_hex_nonidentity(s)?);
+    if key.is_weak() {
+        return Err(PointDecodeError::WeakKey);
+    }
+    Ok(key)
+}
+
+/// Convert a RistrettoPoint to the hex of its 64-byte uncompressed form, affine `x || y`
+///
+/// Only for peers that need coordinates; `point_to_hex` is half the size.
+pub fn point_to_hex_uncompressed(p: &RistrettoPoint) -> String {
+    hex_encode(uncompressed::to_uncompressed(p))
+}
+
+/// Convert the hex of a 64-byte uncompressed point back to a RistrettoPoint
+pub fn point_from_hex_uncompressed(s: &str) -> Result<RistrettoPoint, PointDecodeError> {
+    let bytes = hex_decode(s)?;
+    if bytes.len() != 64 {
+        return Err(PointDecodeError::InvalidLength { got: bytes.len(), expected: &[64] });
+    }
+    decode_uncompressed(&bytes)
+}
+
+fn decode_uncompressed(bytes: &[u8]) -> Result<RistrettoPoint, PointDecodeError> {
+    let mut arr = [0u8; 64];
+    arr.copy_from_slice(bytes);
+    uncompressed::from_uncompressed(&arr).ok_or(PointDecodeError::InvalidPoint)
+}
+
 /// Errors that can occur when decoding points from hex
 #[derive(Debug, thiserror::Error)]
 pub enum PointDecodeError {
     #[error("Hex decoding failed: {0}")] //defines error message format
-    HexDecode(#[from] hex::FromHexError), // automatically convert the hex::FromHexError to PointDecodeError
-    #[error("Invalid point length: expected 32 bytes, got {0}")] // defines error message format 0 is the placeholder for the first field variable
-    InvalidLength(usize),
+    HexDecode(#[cfg_attr(feature = "std", source)] hex::FromHexError), // hex only implements Error with std
+    #[error("Invalid point length: expected one of {expected:?} bytes, got {got}")] // lists every length we could have decoded
+    InvalidLength { got: usize, expected: &'static [usize] },
     #[error("Invalid point: failed to decompress")] // defines error message format
     InvalidPoint,
+    #[error("Point is the identity")]
+    IdentityPoint,
+    #[error("Public key is a small multiple of the basepoint, so its secret is known")]
+    WeakKey,
 }
 
-// TLS Certificate Management
-// =========================
-
-/// Errors that can occur during TLS certificate operations
-#[derive(Debug, thiserror::Error)]
-pub enum TlsError {
-    #[error("Certificate generation failed: {0}")]
-    CertificateGeneration(#[from] rcgen::RcgenError),
-    #[error("TLS configuration failed: {0}")]
-    TlsConfig(#[from] rustls::Error),
-    #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
-}
-
-/// Generated TLS certificate and private key pair
-pub struct TlsCertificate {
-    pub certificate: Certificate,
-    pub cert_der: Vec<u8>,
-    pub private_key_der: Vec<u8>,
-}
-
-/// Generate a self-signed certificate for development use
-/// 
-/// This creates a certificate valid for 'localhost' and '127.0.0.1'
-/// which is perfect for our local development and testing.
-/// 
-/// # Returns
-/// A `TlsCertificate` containing both the certificate and private key
-/// in DER format, ready to be used with rustls.
-pub fn generate_self_signed_cert() -> Result<TlsCertificate, TlsError> {
-    // Set up certificate parameters
-    let mut params = CertificateParams::new(vec![
-        "localhost".to_string(),
-        "127.0.0.1".to_string(),
-    ]);
-    
-    // Set certificate details
-    params.distinguished_name = DistinguishedName::new();
-    params.distinguished_name.push(
-        rcgen::DnType::CommonName,
-        "ZK Schnorr TLS Demo"
-    );
-    params.distinguished_name.push(
-        rcgen::DnType::OrganizationName,
-        "Zero Knowledge Demo"
-    );
-    
-    // Generate the certificate
-    let certificate = Certificate::from_params(params)?;
-    
-    // Get DER-encoded certificate and private key
-    let cert_der = certificate.serialize_der()?;
-    let private_key_der = certificate.serialize_private_key_der();
-    
-    println!("📜 Generated self-signed TLS certificate for localhost");
-    println!("   Valid for: localhost, 127.0.0.1");
-    println!("   Issuer: ZK Schnorr TLS Demo");
-    
-    Ok(TlsCertificate {
-        certificate,
-        cert_der,
-        private_key_der,
-    })
-}
-
-/// Create a TLS server configuration from a certificate
-/// 
-/// This sets up the server-side TLS configuration that will:
-/// - Use the provided certificate for authentication
-/// - Support modern TLS versions (1.2 and 1.3)
-/// - Use secure cipher suites
-/// - Not require client certificates (server-only authentication)
-pub fn create_server_config(tls_cert: &TlsCertificate) -> Result<ServerConfig, TlsError> {
-    let cert = RustlsCertificate(tls_cert.cert_der.clone());
-    let private_key = PrivateKey(tls_cert.private_key_der.clone());
-    
-    let config = ServerConfig::builder()
-        .with_safe_defaults()
-        .with_no_client_auth()
-        .with_single_cert(vec![cert], private_key)?;
-    
-    println!("🔒 Created TLS server configuration");
-    println!("   Mode: Server-only authentication (no client certs required)");
-    
-    Ok(config)
-}
-
-/// Create a TLS client configuration that accepts our self-signed certificate
-/// 
-/// For development, we need to explicitly trust our self-signed certificate
-/// since it won't be signed by a standard Certificate Authority.
-/// 
-/// # Security Note
-/// This configuration accepts ANY certificate without validation!
-/// This is ONLY safe for development/demo purposes on localhost.
-/// Production code should use proper certificate validation.
-pub fn create_client_config(server_cert: &TlsCertificate) -> Result<ClientConfig, TlsError> {
-    let mut root_store = RootCertStore::empty();
-    
-    // Add our self-signed certificate as a trusted root
-    // This is needed because our cert isn't signed by a standard CA
-    let cert = RustlsCertificate(server_cert.cert_der.clone());
-    root_store.add(&cert)?;
-    
-    let config = ClientConfig::builder()
-        .with_safe_defaults()
-        .with_root_certificates(root_store)
-        .with_no_client_auth();
-    
-    println!("🔒 Created TLS client configuration");
-    println!("   Mode: Accept self-signed certificate for localhost");
-    println!("   ⚠️  Development only - not for production!");
-    
-    Ok(config)
-}
\ No newline at end of file
+impl From<hex::FromHexError> for PointDecodeError {
+    fn from(e: hex::FromHexError) -> Self {
+        Self::HexDecode(e)
+    }
+}
@@ -0,0 +1,141 @@
+//! Admission control for the verifier: concurrent session cap and per-IP rate limit
+//!
+//! Spawning a task for every accepted connection is trivially DoS-able, so the
+//! verifier asks a `ConnectionLimiter` before doing any work for a new peer.
+//! Nothing here touches sockets, which keeps the logic easy to test.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+// drop idle buckets once we track this many addresses
+const MAX_TRACKED_ADDRS: usize = 10_000;
+
+/// Why a connection was turned away
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum Rejection {
+    #[error("too many concurrent connections")]
+    TooManyConnections,
+    #[error("rate limited")]
+    RateLimited,
+}
+
+/// Held for the lifetime of an admitted session; dropping it frees the slot
+#[derive(Debug)]
+pub struct ConnectionPermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Snapshot of the limiter's counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LimiterStats {
+    pub accepted: u64,
+    pub rejected_too_many: u64,
+    pub rejected_rate_limited: u64,
+}
+
+impl fmt::Display for LimiterStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "accepted: {}, rejected: {} (too many connections: {}, rate limited: {})",
+            self.accepted,
+            self.rejected_too_many + self.rejected_rate_limited,
+            self.rejected_too_many,
+            self.rejected_rate_limited,
+        )
+    }
+}
+
+// classic token bucket: holds up to `capacity` tokens, refilled continuously
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Caps concurrent sessions and rate-limits new connections per peer IP
+#[derive(Debug)]
+pub struct ConnectionLimiter {
+    slots: Arc<Semaphore>,
+    per_minute: u32,
+    buckets: Mutex<HashMap<IpAddr, TokenBucket>>,
+    accepted: AtomicU64,
+    rejected_too_many: AtomicU64,
+    rejected_rate_limited: AtomicU64,
+}
+
+impl ConnectionLimiter {
+    /// Allow at most `max_connections` concurrent sessions and
+    /// `per_minute` new connections per IP address per minute
+    pub fn new(max_connections: usize, per_minute: u32) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(max_connections)),
+            per_minute,
+            buckets: Mutex::new(HashMap::new()),
+            accepted: AtomicU64::new(0),
+            rejected_too_many: AtomicU64::new(0),
+            rejected_rate_limited: AtomicU64::new(0),
+        }
+    }
+
+    /// Decide whether a new connection from `ip` may proceed
+    pub fn try_admit(&self, ip: IpAddr) -> Result<ConnectionPermit, Rejection> {
+        self.try_admit_at(ip, Instant::now())
+    }
+
+    /// Same as `try_admit` with an explicit clock, so tests can move time forward
+    pub fn try_admit_at(&self, ip: IpAddr, now: Instant) -> Result<ConnectionPermit, Rejection> {
+        if !self.take_token(ip, now) {
+            self.rejected_rate_limited.fetch_add(1, Ordering::Relaxed);
+            return Err(Rejection::RateLimited);
+        }
+        match self.slots.clone().try_acquire_owned() {
+            Ok(permit) => {
+                self.accepted.fetch_add(1, Ordering::Relaxed);
+                Ok(ConnectionPermit { _permit: permit })
+            }
+            Err(_) => {
+                self.rejected_too_many.fetch_add(1, Ordering::Relaxed);
+                Err(Rejection::TooManyConnections)
+            }
+        }
+    }
+
+    /// Current counter values
+    pub fn stats(&self) -> LimiterStats {
+        LimiterStats {
+            accepted: self.accepted.load(Ordering::Relaxed),
+            rejected_too_many: self.rejected_too_many.load(Ordering::Relaxed),
+            rejected_rate_limited: self.rejected_rate_limited.load(Ordering::Relaxed),
+        }
+    }
+
+    fn take_token(&self, ip: IpAddr, now: Instant) -> bool {
+        let capacity = f64::from(self.per_minute);
+        let refill_per_sec = capacity / 60.0;
+        let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if buckets.len() >= MAX_TRACKED_ADDRS {
+            // forget addresses whose bucket would be full again by now
+            buckets.retain(|_, b| b.tokens + now.saturating_duration_since(b.last_refill).as_secs_f64() * refill_per_sec < capacity);
+        }
+
+        let bucket = buckets.entry(ip).or_insert(TokenBucket { tokens: capacity, last_refill: now });
+        let elapsed = now.saturating_duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
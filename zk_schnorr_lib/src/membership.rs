@@ -0,0 +1,58 @@
+//! Set membership: prove your key is one of a set without saying which
+//!
+//! A membership proof is a ring signature (see [`crate::ring`]) over the set,
+//! made with the prover's own key wherever it sits in the list. The verifier
+//! learns only that the holder of some key in the set made it. The message is
+//! tagged with [`MEMBERSHIP_DOMAIN`] first, so a membership proof never passes
+//! as a plain ring signature over the same bytes, or the other way round.
+
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZkError;
+use crate::ring::{ring_sign, ring_verify, RingSignature};
+use crate::schnorr::{KeyPair, PublicKey};
+
+/// Domain tag prefixed to every message a membership proof signs
+pub const MEMBERSHIP_DOMAIN: &[u8] = b"zk-schnorr-tls/membership/v1";
+
+/// Proof that the holder of one of a set of public keys signed a message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MembershipProof(RingSignature);
+
+impl MembershipProof {
+    /// The ring signature underneath, one `(R_i, c_i, s_i)` per key in the set
+    pub fn ring_signature(&self) -> &RingSignature {
+        &self.0
+    }
+}
+
+/// Prove `keypair` is one of `public_keys`, bound to `message`
+///
+/// Fails with [`ZkError::KeyNotInSet`] if its public key isn't in the set.
+pub fn prove_membership(
+    keypair: &KeyPair,
+    public_keys: &[PublicKey],
+    message: &[u8],
+    rng: &mut impl RngCore,
+) -> Result<MembershipProof, ZkError> {
+    let ours = keypair.public_key();
+    let index = public_keys.iter().position(|key| *key == ours).ok_or(ZkError::KeyNotInSet)?;
+    let sig = ring_sign(&tagged(message), public_keys, index, keypair.secret().expose_secret(), rng)?;
+    Ok(MembershipProof(sig))
+}
+
+/// Check `proof` was made for `message` by the holder of one of exactly these `public_keys`, in this order
+pub fn verify_membership(public_keys: &[PublicKey], message: &[u8], proof: &MembershipProof) -> bool {
+    ring_verify(&tagged(message), public_keys, &proof.0)
+}
+
+// length-prefixed domain, then the message
+fn tagged(message: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(8 + MEMBERSHIP_DOMAIN.len() + message.len());
+    bytes.extend_from_slice(&(MEMBERSHIP_DOMAIN.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(MEMBERSHIP_DOMAIN);
+    bytes.extend_from_slice(message);
+    bytes
+}
@@ -0,0 +1,186 @@
+//! Counters and a latency histogram for a verifier running as a service
+//!
+//! A [`Metrics`] is shared by every session and only ever touched through
+//! relaxed atomics, so recording costs next to nothing. Read it back as a
+//! [`MetricsSnapshot`], which renders itself in the Prometheus text format.
+
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::error::{DecodeError, ProtocolError};
+use crate::runner::ProofOutcome;
+
+/// Upper bounds of the latency histogram buckets, in seconds
+pub const LATENCY_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Why a proof didn't verify
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    /// The commitment was missing, out of order or not a valid point
+    BadCommit,
+    /// The response was malformed or failed `s*G = R + c*X`
+    BadResponse,
+    /// The prover doesn't hold the key we checked against: it refused our
+    /// challenge, or claimed an identity we have no key for
+    WrongKey,
+    /// The prover went quiet past a deadline
+    Timeout,
+    /// Anything else that ended the session early
+    Other,
+}
+
+impl RejectReason {
+    const ALL: [RejectReason; 5] = [Self::BadCommit, Self::BadResponse, Self::WrongKey, Self::Timeout, Self::Other];
+
+    /// Classify the error a verifier session ended with
+    pub fn of(error: &ProtocolError) -> Self {
+        match error {
+            ProtocolError::Decode(DecodeError::Point(_)) | ProtocolError::NonceReuse => Self::BadCommit,
+            ProtocolError::Decode(DecodeError::Scalar(_))
+            | ProtocolError::VerificationFailed
+            | ProtocolError::StatementFailed { .. } => Self::BadResponse,
+            ProtocolError::UnexpectedMessage { expected: "commit", .. } => Self::BadCommit,
+            ProtocolError::UnexpectedMessage { expected: "response", .. } => Self::BadResponse,
+            ProtocolError::Rejected { .. } | ProtocolError::UnknownIdentity(_) => Self::WrongKey,
+            ProtocolError::Timeout(_) => Self::Timeout,
+            _ => Self::Other,
+        }
+    }
+
+    /// The `reason` label value in the Prometheus output
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::BadCommit => "bad_commit",
+            Self::BadResponse => "bad_response",
+            Self::WrongKey => "wrong_key",
+            Self::Timeout => "timeout",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Live counters, shared by every session of a verifier
+#[derive(Debug, Default)]
+pub struct Metrics {
+    connections_accepted: AtomicU64,
+    tls_handshake_failures: AtomicU64,
+    proofs_verified: AtomicU64,
+    proofs_rejected: [AtomicU64; 5],
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    latency_count: AtomicU64,
+    latency_sum_micros: AtomicU64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn connection_accepted(&self) {
+        self.connections_accepted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn tls_handshake_failed(&self) {
+        self.tls_handshake_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn proof_rejected(&self, reason: RejectReason) {
+        self.proofs_rejected[reason as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count the rounds of a finished session, or the one proof its error cut short
+    pub fn record_session(&self, result: Result<&ProofOutcome, &ProtocolError>) {
+        match result {
+            Ok(outcome) => {
+                self.proofs_verified.fetch_add(outcome.verified() as u64, Ordering::Relaxed);
+                self.proofs_rejected[RejectReason::BadResponse as usize].fetch_add(outcome.failed() as u64, Ordering::Relaxed);
+            }
+            Err(e) => self.proof_rejected(RejectReason::of(e)),
+        }
+    }
+
+    /// Time from accepting the connection to the session's verdict
+    pub fn observe_latency(&self, latency: Duration) {
+        let seconds = latency.as_secs_f64();
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            self.latency_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+        self.latency_count.fetch_add(1, Ordering::Relaxed);
+        self.latency_sum_micros.fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Current counter values
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        let mut cumulative = 0;
+        MetricsSnapshot {
+            connections_accepted: load(&self.connections_accepted),
+            tls_handshake_failures: load(&self.tls_handshake_failures),
+            proofs_verified: load(&self.proofs_verified),
+            proofs_rejected: RejectReason::ALL.map(|reason| (reason, load(&self.proofs_rejected[reason as usize]))),
+            latency_buckets: self.latency_buckets.each_ref().map(|bucket| {
+                cumulative += load(bucket);
+                cumulative
+            }),
+            latency_count: load(&self.latency_count),
+            latency_sum: Duration::from_micros(load(&self.latency_sum_micros)),
+        }
+    }
+}
+
+/// Counter values at one point in time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    pub connections_accepted: u64,
+    pub tls_handshake_failures: u64,
+    pub proofs_verified: u64,
+    /// Rejections by reason, every reason listed
+    pub proofs_rejected: [(RejectReason, u64); 5],
+    /// Cumulative count of sessions at or under each of [`LATENCY_BUCKETS`]
+    pub latency_buckets: [u64; LATENCY_BUCKETS.len()],
+    pub latency_count: u64,
+    pub latency_sum: Duration,
+}
+
+impl MetricsSnapshot {
+    pub fn rejected(&self, reason: RejectReason) -> u64 {
+        self.proofs_rejected[reason as usize].1
+    }
+
+    pub fn total_rejected(&self) -> u64 {
+        self.proofs_rejected.iter().map(|(_, count)| count).sum()
+    }
+
+    /// The Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        self.write_prometheus(&mut out).expect("writing to a String can't fail");
+        out
+    }
+
+    fn write_prometheus(&self, out: &mut String) -> fmt::Result {
+        let counters = [
+            ("zk_connections_accepted_total", "TCP connections accepted", self.connections_accepted),
+            ("zk_tls_handshake_failures_total", "TLS or WebSocket handshakes that failed or timed out", self.tls_handshake_failures),
+            ("zk_proofs_verified_total", "Proofs that verified", self.proofs_verified),
+        ];
+        for (name, help, value) in counters {
+            writeln!(out, "# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}")?;
+        }
+
+        writeln!(out, "# HELP zk_proofs_rejected_total Proofs that didn't verify, by reason\n# TYPE zk_proofs_rejected_total counter")?;
+        for (reason, count) in self.proofs_rejected {
+            writeln!(out, "zk_proofs_rejected_total{{reason=\"{}\"}} {count}", reason.as_str())?;
+        }
+
+        let name = "zk_verification_latency_seconds";
+        writeln!(out, "# HELP {name} Time from accepting a connection to the session's verdict\n# TYPE {name} histogram")?;
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(self.latency_buckets) {
+            writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {count}")?;
+        }
+        writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {}", self.latency_count)?;
+        writeln!(out, "{name}_sum {}", self.latency_sum.as_secs_f64())?;
+        writeln!(out, "{name}_count {}", self.latency_count)
+    }
+}
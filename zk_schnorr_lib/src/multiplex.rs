@@ -0,0 +1,185 @@
+//! Numbered proofs over one connection, each answered with its own verdict
+//!
+//! After the verifier's `context`, the prover opens every sub-proof with a
+//! `proof-request` naming a `proof_id`, then runs the usual commit, challenge
+//! and response. The challenge also covers the id (see
+//! [`ChallengeContext::challenge_for_proof`]) and the verdict comes back as a
+//! `proof-result` carrying the same id, so a verdict can't be taken for
+//! another sub-proof's. The prover ends the session with `end-session`.
+//!
+//! Sub-proofs run strictly one after another. `verify_interactive` also
+//! accepts them, interleaved with ordinary rounds, so the `verifier` binary
+//! serves multiplexing provers without any extra setup.
+
+use std::time::SystemTime;
+
+use curve25519_dalek::scalar::Scalar;
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::audit::{NullLog, ProofLog};
+use crate::proof_store::ProofEntry;
+use crate::challenge::ChallengeContext;
+use crate::error::ProtocolError;
+use crate::protocol::{
+    check_session, expect_message, recv_error, refuse_challenge, reject_if_malformed, send_context, session_matches,
+    SchnorrProtocol, SeenCommitments,
+};
+use crate::schnorr::{respond, KeyPair, Proof, PublicKey};
+use crate::secret::SecretScalar;
+use crate::transport::Transport;
+use crate::{point_from_hex_nonidentity, scalar_from_hex_canonical, scalar_from_hex_nonzero, Message};
+
+/// Opens a sub-proof, carried in a `proof-request` message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofRequest {
+    pub proof_id: u64,
+}
+
+/// The verifier's verdict on one sub-proof, carried in a `proof-result` message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofResult {
+    pub proof_id: u64,
+    pub verified: bool,
+}
+
+/// Verifies numbered sub-proofs against one key until the prover ends the session
+pub struct MultiplexedVerifier<'a> {
+    expected_key: PublicKey,
+    log: &'a dyn ProofLog,
+}
+
+impl MultiplexedVerifier<'static> {
+    pub fn new(expected_key: PublicKey) -> Self {
+        Self { expected_key, log: &NullLog }
+    }
+}
+
+impl<'a> MultiplexedVerifier<'a> {
+    /// Hand every checked sub-proof to `log`
+    pub fn with_log(self, log: &'a dyn ProofLog) -> MultiplexedVerifier<'a> {
+        MultiplexedVerifier { expected_key: self.expected_key, log }
+    }
+
+    /// Send the context, then verify sub-proofs until `end-session`
+    ///
+    /// Returns every verdict in the order the requests came. A failed
+    /// sub-proof doesn't end the session; a prover that hangs up without
+    /// `end-session` does, with `ProtocolError::ConnectionClosed`, and so does
+    /// a commitment repeated from an earlier sub-proof, with `ProtocolError::NonceReuse`.
+    pub async fn run<T: Transport>(&self, transport: &mut T) -> Result<Vec<ProofResult>, ProtocolError> {
+        let context = send_context(transport).await?;
+        let mut results = Vec::new();
+        let mut seen = SeenCommitments::default();
+        loop {
+            let msg = match transport.recv().await {
+                Ok(msg) => msg,
+                Err(e) => return reject_if_malformed(transport, Err(recv_error(e, "proof-request"))).await,
+            };
+            let result = match msg.kind.as_str() {
+                "proof-request" => verify_request(transport, &context, &self.expected_key, &msg, &mut seen, self.log).await,
+                "end-session" => break,
+                _ => Err(ProtocolError::UnexpectedMessage { expected: "proof-request", got: msg.kind }),
+            };
+            results.push(reject_if_malformed(transport, result).await?);
+        }
+        debug!(proofs = results.len(), "Prover ended the session");
+        Ok(results)
+    }
+}
+
+/// Proves knowledge of one key for each of a list of `proof_id`s over one connection
+pub struct MultiplexedProver<'a> {
+    keypair: &'a KeyPair,
+}
+
+impl<'a> MultiplexedProver<'a> {
+    pub fn new(keypair: &'a KeyPair) -> Self {
+        Self { keypair }
+    }
+
+    /// Receive the context, prove once per id in `proof_ids`, then send `end-session`
+    ///
+    /// Returns the verifier's verdict on each, in order; a rejected
+    /// sub-proof is `verified: false` and the session goes on.
+    pub async fn run<T: Transport>(&self, transport: &mut T, proof_ids: &[u64]) -> Result<Vec<ProofResult>, ProtocolError> {
+        let context = SchnorrProtocol::open_prover_session(transport).await?;
+        let mut results = Vec::with_capacity(proof_ids.len());
+        for &proof_id in proof_ids {
+            results.push(self.prove_request(transport, &context, proof_id).await?);
+        }
+        transport.send(Message::end_session().in_session(context.session_id())).await?;
+        Ok(results)
+    }
+
+    async fn prove_request<T: Transport>(
+        &self,
+        transport: &mut T,
+        context: &ChallengeContext,
+        proof_id: u64,
+    ) -> Result<ProofResult, ProtocolError> {
+        let session = context.session_id();
+        transport.send(Message::proof_request(&ProofRequest { proof_id }).in_session(session)).await?;
+
+        let k = SecretScalar::random(&mut OsRng);
+        let R = k.mul_base();
+        transport.send(Message::commit(&R).in_session(session)).await?;
+
+        let ch_msg = expect_message(transport, "challenge").await?;
+        check_session(transport, &ch_msg, session, None).await?;
+        let c = scalar_from_hex_canonical(&ch_msg.payload)?;
+        if c == Scalar::ZERO || c != context.challenge_for_proof(proof_id, &R, &self.keypair.public_key()) {
+            return refuse_challenge(transport).await;
+        }
+
+        let s = respond(k.expose_secret(), &c, self.keypair.secret().expose_secret());
+        transport.send(Message::response(&s).in_session(session)).await?;
+
+        let result_msg = expect_message(transport, "proof-result").await?;
+        let result: ProofResult = serde_json::from_str(&result_msg.payload)?;
+        if result.proof_id != proof_id {
+            let got = format!("proof-result for {}", result.proof_id);
+            return reject_if_malformed(transport, Err(ProtocolError::UnexpectedMessage { expected: "proof-result", got })).await;
+        }
+        if !result.verified {
+            warn!(proof_id, "Verifier rejected the sub-proof");
+        }
+        Ok(result)
+    }
+}
+
+// one sub-proof, from the commitment that follows `request_msg` to the `proof-result`
+pub(crate) async fn verify_request<T: Transport>(
+    transport: &mut T,
+    context: &ChallengeContext,
+    expected_public_key: &PublicKey,
+    request_msg: &Message,
+    seen: &mut SeenCommitments,
+    log: &dyn ProofLog,
+) -> Result<ProofResult, ProtocolError> {
+    let session = context.session_id();
+    session_matches(request_msg, session, None)?;
+    let ProofRequest { proof_id } = serde_json::from_str(&request_msg.payload)?;
+
+    let commit_msg = expect_message(transport, "commit").await?;
+    session_matches(&commit_msg, session, Some(request_msg))?;
+    let R = point_from_hex_nonidentity(&commit_msg.payload)?;
+    seen.insert(&R)?; // sub-proofs share the session, so a nonce reused across them leaks the key too
+
+    let c = context.challenge_for_proof(proof_id, &R, expected_public_key);
+    transport.send(Message::challenge(&c).in_session(session)).await?;
+
+    let response_msg = expect_message(transport, "response").await?;
+    session_matches(&response_msg, session, Some(&commit_msg))?;
+    let s = scalar_from_hex_nonzero(&response_msg.payload)?;
+
+    let proof = Proof::new(R, c, s);
+    let verified = proof.verify(expected_public_key);
+    debug!(proof_id, verified, "Sub-proof checked");
+    let entry = ProofEntry { session_id: *session, public_key: *expected_public_key, proof, verified, timestamp: SystemTime::now() };
+    log.try_record(entry).map_err(|e| ProtocolError::Store(Box::new(e)))?;
+    let result = ProofResult { proof_id, verified };
+    transport.send(Message::proof_result(&result).in_session(session)).await?;
+    Ok(result)
+}
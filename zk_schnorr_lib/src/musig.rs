@@ -0,0 +1,74 @@
+//! Several cosigners answer one Schnorr round as a single prover
+//!
+//! The keys are combined as in [`crate::musig2`]: each `X_i` is weighted by
+//! `a_i = H_agg(L, X_i)` and `X_agg = sum(a_i*X_i)`, so [`aggregate_keys`]
+//! gives the same key as [`musig2::aggregate_key`](crate::musig2::aggregate_key).
+//! Without the coefficients a rogue cosigner could pick
+//! `X_rogue = x'*G - sum(X_honest)` and prove for the plain sum alone.
+//!
+//! For one round, every cosigner commits to `R_i = k_i*G`, the commitments
+//! are summed into `R` and sent as an ordinary `commit`. The verifier's
+//! challenge `c` goes back to every cosigner, each answers
+//! `s_i = k_i + c*a_i*x_i`, and `s = sum(s_i)` satisfies `s*G = R + c*X_agg`.
+//! The verifier only ever sees a normal [`Proof`] under `X_agg`.
+//!
+//! A single nonce per cosigner is only safe one round at a time: cosigners
+//! that answer many rounds concurrently should use MuSig2's two nonces.
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::RngCore;
+
+use crate::musig2::{self, key_coefficient};
+use crate::schnorr::{random_scalar, respond, KeyPair, Proof, PublicKey};
+use crate::secret::SecretScalar;
+
+/// `X_agg = sum(a_i*X_i)` with `a_i = H_agg(L, X_i)`, the key cosigners prove for together
+pub fn aggregate_keys(keys: &[RistrettoPoint]) -> RistrettoPoint {
+    musig2::aggregate_key(&public_keys(keys)).0
+}
+
+/// One cosigner's share of proofs under the aggregate key
+pub struct Cosigner {
+    secret: SecretScalar,
+    coefficient: Scalar,
+}
+
+/// A cosigner's secret nonce for one round, used up by [`Cosigner::respond`]
+pub struct CosignerNonce(SecretScalar);
+
+impl Cosigner {
+    /// Cosign as `my_keypair`, one of `all_keys` in the order every cosigner uses
+    pub fn new(my_keypair: &KeyPair, all_keys: &[RistrettoPoint]) -> Self {
+        let coefficient = key_coefficient(&public_keys(all_keys), &my_keypair.public_key());
+        Self { secret: my_keypair.secret().clone(), coefficient }
+    }
+
+    /// Draw a fresh nonce `k_i` and return it with its commitment `R_i = k_i*G`
+    pub fn commit(&self, rng: &mut impl RngCore) -> (CosignerNonce, RistrettoPoint) {
+        let k = SecretScalar::new(random_scalar(rng));
+        let R = k.mul_base();
+        (CosignerNonce(k), R)
+    }
+
+    /// This cosigner's share `s_i = k_i + c*a_i*x_i` of the response to `challenge`
+    pub fn respond(&self, nonce: CosignerNonce, challenge: &Scalar) -> Scalar {
+        respond(nonce.0.expose_secret(), &(challenge * self.coefficient), self.secret.expose_secret())
+    }
+}
+
+/// `R = sum(R_i)`, the commitment sent to the verifier
+pub fn combine_commitments(commitments: &[RistrettoPoint]) -> RistrettoPoint {
+    commitments.iter().sum()
+}
+
+/// The proof `(R, c, sum(s_i))`, which verifies under [`aggregate_keys`] of every cosigner
+///
+/// A missing or altered share still yields a `Proof`, but one that doesn't verify.
+pub fn combine_responses(commitment: RistrettoPoint, challenge: Scalar, responses: &[Scalar]) -> Proof {
+    Proof::new(commitment, challenge, responses.iter().sum())
+}
+
+fn public_keys(keys: &[RistrettoPoint]) -> Vec<PublicKey> {
+    keys.iter().copied().map(PublicKey).collect()
+}
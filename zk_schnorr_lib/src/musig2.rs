@@ -0,0 +1,148 @@
+//! MuSig2: n signers, two rounds, one ordinary Schnorr signature
+//!
+//! Following the MuSig2 draft, over Ristretto:
+//!
+//! - **Key aggregation.** Each key gets a coefficient `a_i = H_agg(L, X_i)`,
+//!   where `L` commits to the whole list of keys, and `X_agg = sum(a_i*X_i)`.
+//!   The coefficients stop a signer from choosing its key to cancel the others'.
+//! - **Round 1.** Every signer commits to two nonces, `R_i1 = k_i1*G` and
+//!   `R_i2 = k_i2*G`, and sends both to the others.
+//! - **Round 2.** With everyone's commitments, `b = H_non(X_agg, R_1, R_2, m)`
+//!   over the sums `R_1`, `R_2`, the aggregate nonce is `R = R_1 + b*R_2` and
+//!   each signer returns `s_i = k_i1 + b*k_i2 + c*a_i*x_i` with the usual
+//!   signature challenge `c = H(R || X_agg || m)`.
+//!
+//! `(R, sum(s_i))` is a [`Signature`] that [`verify`](crate::signature::verify)
+//! accepts under `X_agg`; nothing shows it was made by several keys. Nonce
+//! commitments and partial signatures are ordered like the list of public
+//! keys, and every signer must use the same list in the same order.
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use rand_core::RngCore;
+
+use crate::schnorr::{random_scalar, KeyPair, PublicKey};
+use crate::secret::SecretScalar;
+use crate::signature::{self, Signature};
+use crate::transcript::TranscriptBuilder;
+
+const KEY_AGG_DOMAIN: &[u8] = b"zk-schnorr-tls/musig2/key-agg/v1";
+const NONCE_DOMAIN: &[u8] = b"zk-schnorr-tls/musig2/nonce/v1";
+
+/// Why a session couldn't be started or a partial signature made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum MuSig2Error {
+    #[error("our public key is not in the list of signers")]
+    NotASigner,
+    #[error("no unused nonces: call commit before every partial_sign")]
+    NoNonces,
+    #[error("{nonces} nonce commitments for {signers} signers")]
+    NonceCountMismatch { nonces: usize, signers: usize },
+    #[error("our own nonce commitments are not at our key's position in the list")]
+    MissingOwnNonces,
+}
+
+/// One signer's state for one signature
+pub struct MuSig2Session {
+    secret: SecretScalar,
+    coefficient: Scalar,
+    index: usize,
+    signers: usize,
+    aggregate_key: PublicKey,
+    nonces: Option<(SecretScalar, SecretScalar, (RistrettoPoint, RistrettoPoint))>,
+}
+
+impl MuSig2Session {
+    /// Start signing as `my_keypair`, one of `all_public_keys`
+    ///
+    /// Fails with [`MuSig2Error::NotASigner`] if `my_keypair`'s public key
+    /// isn't in the list; its position there is where `partial_sign` expects
+    /// our nonce commitments.
+    pub fn new(my_keypair: &KeyPair, all_public_keys: &[PublicKey]) -> Result<Self, MuSig2Error> {
+        let public = my_keypair.public_key();
+        let index = all_public_keys.iter().position(|X| *X == public).ok_or(MuSig2Error::NotASigner)?;
+        Ok(Self {
+            secret: my_keypair.secret().clone(),
+            coefficient: key_coefficient(all_public_keys, &public),
+            index,
+            signers: all_public_keys.len(),
+            aggregate_key: aggregate_key(all_public_keys),
+            nonces: None,
+        })
+    }
+
+    /// The key the finished signature verifies under
+    pub fn aggregate_key(&self) -> PublicKey {
+        self.aggregate_key
+    }
+
+    /// Round 1: draw two fresh nonces and return their commitments for the other signers
+    ///
+    /// Replaces any nonces from an earlier call that weren't signed with.
+    pub fn commit(&mut self, rng: &mut impl RngCore) -> (RistrettoPoint, RistrettoPoint) {
+        let (k1, k2) = (SecretScalar::new(random_scalar(rng)), SecretScalar::new(random_scalar(rng)));
+        let commitments = (k1.mul_base(), k2.mul_base());
+        self.nonces = Some((k1, k2, commitments));
+        commitments
+    }
+
+    /// Round 2: this signer's share of the signature on `message`
+    ///
+    /// `nonces` holds every signer's commitments, ours included, in key order.
+    /// The secret nonces are used up: signing again needs another `commit`,
+    /// since answering two challenges with the same nonces reveals the key.
+    pub fn partial_sign(&mut self, nonces: &[(RistrettoPoint, RistrettoPoint)], message: &[u8]) -> Result<Scalar, MuSig2Error> {
+        if nonces.len() != self.signers {
+            return Err(MuSig2Error::NonceCountMismatch { nonces: nonces.len(), signers: self.signers });
+        }
+        let (k1, k2, ours) = self.nonces.take().ok_or(MuSig2Error::NoNonces)?;
+        if nonces[self.index] != ours {
+            return Err(MuSig2Error::MissingOwnNonces);
+        }
+        let (R, b) = aggregate_nonce(&self.aggregate_key, nonces, message);
+        let c = signature::challenge(&R, &self.aggregate_key, message);
+        Ok(k1.expose_secret() + b * k2.expose_secret() + c * self.coefficient * self.secret.expose_secret())
+    }
+}
+
+/// `X_agg = sum(a_i*X_i)`, the key a MuSig2 signature by all of `public_keys` verifies under
+pub fn aggregate_key(public_keys: &[PublicKey]) -> PublicKey {
+    PublicKey(public_keys.iter().map(|X| X.0 * key_coefficient(public_keys, X)).sum())
+}
+
+/// Combine every signer's partial signature into the final `(R, s)`
+///
+/// `nonces` must be the commitments the partial signatures were made with,
+/// and `aggregate_key` the key of all signers. A missing or altered partial
+/// signature still yields a `Signature`, but one that doesn't verify.
+pub fn aggregate(partial_sigs: &[Scalar], nonces: &[(RistrettoPoint, RistrettoPoint)], aggregate_key: &PublicKey, message: &[u8]) -> Signature {
+    let (R, _) = aggregate_nonce(aggregate_key, nonces, message);
+    Signature { R, s: partial_sigs.iter().sum() }
+}
+
+// a_i = H_agg(L, X_i), with L every key in order
+pub(crate) fn key_coefficient(public_keys: &[PublicKey], key: &PublicKey) -> Scalar {
+    let mut transcript = TranscriptBuilder::new(KEY_AGG_DOMAIN);
+    transcript.append_bytes(b"n", &(public_keys.len() as u64).to_le_bytes());
+    for X in public_keys {
+        transcript.append_point(b"L", X);
+    }
+    transcript.append_point(b"X", key);
+    transcript.challenge()
+}
+
+// R = R_1 + b*R_2 with b = H_non(X_agg, R_1, R_2, m), returned with b
+fn aggregate_nonce(aggregate_key: &PublicKey, nonces: &[(RistrettoPoint, RistrettoPoint)], message: &[u8]) -> (RistrettoPoint, Scalar) {
+    let (R1, R2) = nonces
+        .iter()
+        .fold((RistrettoPoint::identity(), RistrettoPoint::identity()), |(R1, R2), (D, E)| (R1 + D, R2 + E));
+    let b = TranscriptBuilder::new(NONCE_DOMAIN)
+        .append_point(b"X", aggregate_key)
+        .append_point(b"R1", &R1)
+        .append_point(b"R2", &R2)
+        .append_bytes(b"message", message)
+        .challenge();
+    (R1 + R2 * b, b)
+}
+
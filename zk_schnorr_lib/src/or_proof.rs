@@ -0,0 +1,209 @@
+//! OR-composition of two Schnorr proofs
+//!
+//! Proves knowledge of `x` with `A = x*G` OR of `y` with `B = y*H` without
+//! revealing which. The prover runs the branch it knows honestly and simulates
+//! the other one: it picks that branch's challenge and response first and
+//! solves for a matching commitment. The two challenges must add up to the
+//! Fiat-Shamir challenge squeezed from a transcript over `(G, H, A, B, R1, R2)`,
+//! so at most one of them can have been chosen freely.
+//!
+//! `OrProver` runs the same composition interactively: it commits to
+//! `(R1, R2)` first and splits whatever challenge the verifier sends back,
+//! which is how `SchnorrProtocol::prove_or_round` uses it over a connection.
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::schnorr::random_scalar;
+use crate::secret::SecretScalar;
+use crate::transcript::transcript_challenge;
+
+const DOMAIN: &[u8] = b"zk-schnorr-tls/or-proof/v2";
+
+/// Non-interactive proof of knowing the discrete log of `A` (base `G`) or of `B` (base `H`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrProof {
+    #[serde(with = "crate::serde_hex::point")]
+    pub R1: RistrettoPoint,
+    #[serde(with = "crate::serde_hex::point")]
+    pub R2: RistrettoPoint,
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub c1: Scalar,
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub c2: Scalar,
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub s1: Scalar,
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub s2: Scalar,
+}
+
+impl OrProof {
+    /// Prove knowledge of the discrete log of `x1` or `x2`, both over the basepoint
+    ///
+    /// `known_index` is 0 if `secret` belongs to `x1` and 1 if it belongs to
+    /// `x2`. The resulting proof looks the same either way.
+    ///
+    /// # Panics
+    /// If `known_index` is neither 0 nor 1.
+    pub fn prove(known_index: usize, secret: &Scalar, x1: &RistrettoPoint, x2: &RistrettoPoint) -> Self {
+        let G = &RISTRETTO_BASEPOINT_POINT;
+        match known_index {
+            0 => prove_first(secret, x1, x2, G, G, &mut OsRng),
+            1 => prove_second(secret, x1, x2, G, G, &mut OsRng),
+            _ => panic!("OrProof::prove: known_index must be 0 or 1, got {known_index}"),
+        }
+    }
+
+    /// Verify a proof made by `prove` against the two basepoint public keys
+    pub fn verify(&self, x1: &RistrettoPoint, x2: &RistrettoPoint) -> bool {
+        let G = &RISTRETTO_BASEPOINT_POINT;
+        verify(x1, x2, G, G, self)
+    }
+}
+
+impl OrProof {
+    /// Check a proof against an interactively chosen `challenge` over the basepoint
+    ///
+    /// Both branch equations must hold and `c1 + c2` must equal `challenge`;
+    /// otherwise the prover could have simulated both branches.
+    pub fn verify_with_challenge(&self, pubkeys: &[RistrettoPoint; 2], challenge: &Scalar) -> bool {
+        let G = &RISTRETTO_BASEPOINT_POINT;
+        let challenge_ok = self.c1 + self.c2 == *challenge;
+        let first_ok = G * self.s1 == self.R1 + pubkeys[0] * self.c1;
+        let second_ok = G * self.s2 == self.R2 + pubkeys[1] * self.c2;
+        challenge_ok & first_ok & second_ok
+    }
+}
+
+/// The prover's first message in an interactive OR-proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrCommitment {
+    #[serde(with = "crate::serde_hex::point")]
+    pub R1: RistrettoPoint,
+    #[serde(with = "crate::serde_hex::point")]
+    pub R2: RistrettoPoint,
+}
+
+/// Prover state between sending the commitment and receiving the challenge
+pub struct OrProver {
+    which: usize,
+    secret: SecretScalar,
+    k: SecretScalar,
+    simulated_c: Scalar,
+    simulated_s: Scalar,
+    commitment: OrCommitment,
+}
+
+impl OrProver {
+    /// Commit, knowing the secret for `pubkeys[which]` (over the basepoint)
+    ///
+    /// # Panics
+    /// If `which` is neither 0 nor 1.
+    pub fn commit(secret: &Scalar, which: usize, pubkeys: &[RistrettoPoint; 2], rng: &mut impl RngCore) -> Self {
+        assert!(which < 2, "OrProver::commit: which must be 0 or 1, got {which}");
+        let G = &RISTRETTO_BASEPOINT_POINT;
+        // simulate the other branch: pick its challenge and response, solve for its commitment
+        let simulated_c = random_scalar(rng);
+        let simulated_s = random_scalar(rng);
+        let simulated_R = G * simulated_s - pubkeys[1 - which] * simulated_c;
+
+        let k = SecretScalar::new(random_scalar(rng));
+        let honest_R = k.mul_base();
+        let (R1, R2) = if which == 0 { (honest_R, simulated_R) } else { (simulated_R, honest_R) };
+        Self { which, secret: SecretScalar::new(*secret), k, simulated_c, simulated_s, commitment: OrCommitment { R1, R2 } }
+    }
+
+    pub fn commitment(&self) -> OrCommitment {
+        self.commitment
+    }
+
+    /// Split `challenge` between the branches and answer the honest one
+    pub fn respond(self, challenge: &Scalar) -> OrProof {
+        let honest_c = challenge - self.simulated_c;
+        let honest_s = self.k.expose_secret() + honest_c * self.secret.expose_secret();
+        let OrCommitment { R1, R2 } = self.commitment;
+        if self.which == 0 {
+            OrProof { R1, R2, c1: honest_c, c2: self.simulated_c, s1: honest_s, s2: self.simulated_s }
+        } else {
+            OrProof { R1, R2, c1: self.simulated_c, c2: honest_c, s1: self.simulated_s, s2: honest_s }
+        }
+    }
+}
+
+/// Prove the first statement, knowing `x` such that `A = x*G`
+pub fn prove_first(
+    x: &Scalar,
+    A: &RistrettoPoint,
+    B: &RistrettoPoint,
+    G: &RistrettoPoint,
+    H: &RistrettoPoint,
+    rng: &mut impl RngCore,
+) -> OrProof {
+    // simulate branch 2: pick c2, s2 and solve R2 = s2*H - c2*B
+    let c2 = random_scalar(rng);
+    let s2 = random_scalar(rng);
+    let R2 = H * s2 - B * c2;
+
+    // honest branch 1
+    let k = random_scalar(rng);
+    let R1 = G * k;
+    let c1 = challenge(G, H, A, B, &R1, &R2) - c2;
+    let s1 = k + c1 * x;
+
+    OrProof { R1, R2, c1, c2, s1, s2 }
+}
+
+/// Prove the second statement, knowing `y` such that `B = y*H`
+pub fn prove_second(
+    y: &Scalar,
+    A: &RistrettoPoint,
+    B: &RistrettoPoint,
+    G: &RistrettoPoint,
+    H: &RistrettoPoint,
+    rng: &mut impl RngCore,
+) -> OrProof {
+    // simulate branch 1: pick c1, s1 and solve R1 = s1*G - c1*A
+    let c1 = random_scalar(rng);
+    let s1 = random_scalar(rng);
+    let R1 = G * s1 - A * c1;
+
+    // honest branch 2
+    let k = random_scalar(rng);
+    let R2 = H * k;
+    let c2 = challenge(G, H, A, B, &R1, &R2) - c1;
+    let s2 = k + c2 * y;
+
+    OrProof { R1, R2, c1, c2, s1, s2 }
+}
+
+/// Check both branch equations and that the challenges split the Fiat-Shamir challenge
+///
+/// Both branches are always checked in full, so the work done does not
+/// depend on which one was simulated.
+pub fn verify(
+    A: &RistrettoPoint,
+    B: &RistrettoPoint,
+    G: &RistrettoPoint,
+    H: &RistrettoPoint,
+    proof: &OrProof,
+) -> bool {
+    let challenge_ok = proof.c1 + proof.c2 == challenge(G, H, A, B, &proof.R1, &proof.R2);
+    let first_ok = G * proof.s1 == proof.R1 + A * proof.c1; // s1*G = R1 + c1*A
+    let second_ok = H * proof.s2 == proof.R2 + B * proof.c2; // s2*H = R2 + c2*B
+    challenge_ok & first_ok & second_ok
+}
+
+// c squeezed from a transcript over G, H, A, B, R1 and R2, binding both statements and both commitments
+fn challenge(
+    G: &RistrettoPoint,
+    H: &RistrettoPoint,
+    A: &RistrettoPoint,
+    B: &RistrettoPoint,
+    R1: &RistrettoPoint,
+    R2: &RistrettoPoint,
+) -> Scalar {
+    transcript_challenge(DOMAIN, &[(b"G", G), (b"H", H), (b"A", A), (b"B", B), (b"R1", R1), (b"R2", R2)])
+}
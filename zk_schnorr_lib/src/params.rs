@@ -0,0 +1,69 @@
+//! The group parameters a proof is made over
+//!
+//! Everything defaults to the Ristretto basepoint `G`. [`Params`] names another
+//! generator `H` instead, given directly or derived from a domain tag with
+//! [`hash_to_point`], so nobody knows `log_G(H)`. A key for `H` is `X = x*H`,
+//! and a proof for it checks `s*H = R + c*X`.
+//!
+//! Both sides of a session must use the same generator. The prover's `hello`
+//! carries it (see [`VersionInfo::with_params`](crate::VersionInfo::with_params))
+//! and a verifier using another one refuses the session with
+//! `NegotiationError::GeneratorMismatch` before any proof is exchanged.
+//! The default generator is left out of the `hello` and of every challenge,
+//! so sessions and signatures over `G` are unchanged.
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+
+use crate::schnorr::{hash_to_point, KeyPair, PublicKey};
+
+/// The generator proofs, keys and signatures are made over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    generator: RistrettoPoint,
+}
+
+impl Default for Params {
+    /// The Ristretto basepoint `G`
+    fn default() -> Self {
+        Self { generator: RISTRETTO_BASEPOINT_POINT }
+    }
+}
+
+impl Params {
+    /// Use `generator` in place of `G`
+    pub fn with_generator(generator: RistrettoPoint) -> Self {
+        Self { generator }
+    }
+
+    /// A nothing-up-my-sleeve generator: `tag` hashed to a point
+    pub fn from_domain_tag(tag: &[u8]) -> Self {
+        Self::with_generator(hash_to_point(tag))
+    }
+
+    pub fn generator(&self) -> &RistrettoPoint {
+        &self.generator
+    }
+
+    /// Whether this is the Ristretto basepoint
+    pub fn is_default(&self) -> bool {
+        self.generator == RISTRETTO_BASEPOINT_POINT
+    }
+
+    /// The public key `X = x*H` of `secret` under this generator
+    pub fn public_key(&self, secret: &Scalar) -> PublicKey {
+        PublicKey(self.generator * secret)
+    }
+
+    /// `keypair`'s secret as a key under this generator; the same as `keypair.public_key()` for `G`
+    pub fn public_key_of(&self, keypair: &KeyPair) -> PublicKey {
+        self.public_key(keypair.secret().expose_secret())
+    }
+
+    // the generator as challenges should bind it: nothing for `G`, so those challenges don't change
+    #[cfg(feature = "std")]
+    pub(crate) fn bound_generator(&self) -> Option<&RistrettoPoint> {
+        (!self.is_default()).then_some(&self.generator)
+    }
+}
@@ -0,0 +1,146 @@
+//! Pedersen commitments `C = v*G + r*H` and proofs of opening
+//!
+//! `G` is the Ristretto basepoint and `H` an independent generator obtained
+//! with `hash_to_point`, so nobody knows `log_G(H)` and the commitment is
+//! binding. `OpeningProof` is a two-generator Schnorr proof that the prover
+//! knows `(v, r)` without revealing either.
+//!
+//! Commitments add: `commit(v1, r1) + commit(v2, r2) = commit(v1 + v2, r1 + r2)`.
+//! `OpeningProver` runs the opening proof interactively, for
+//! `SchnorrProtocol::prove_opening_round`; `prove_opening` is its Fiat-Shamir form.
+
+use std::ops::Add;
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT; // G
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::schnorr::{hash_to_point, random_scalar};
+use crate::secret::SecretScalar;
+use crate::transcript::transcript_challenge;
+
+const H_DOMAIN: &[u8] = b"zk-schnorr-tls/pedersen/H";
+
+/// The second generator `H`, derived the same way by every party
+pub fn generator_h() -> RistrettoPoint {
+    hash_to_point(H_DOMAIN)
+}
+
+/// The generators `G` and `H` a commitment is made over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PedersenParams {
+    pub G: RistrettoPoint,
+    pub H: RistrettoPoint,
+}
+
+impl Default for PedersenParams {
+    /// The basepoint and [`generator_h`], the generators `commit` uses
+    fn default() -> Self {
+        Self { G: RISTRETTO_BASEPOINT_POINT, H: generator_h() }
+    }
+}
+
+impl PedersenParams {
+    /// `C = value*G + blinding*H`
+    pub fn commit(&self, value: &Scalar, blinding: &Scalar) -> Commitment {
+        Commitment(self.G * value + self.H * blinding)
+    }
+}
+
+/// A commitment `C = v*G + r*H` to a value `v` with blinding factor `r`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Commitment(#[serde(with = "crate::serde_hex::point")] pub RistrettoPoint);
+
+/// Commit to `value` with `blinding` over the generators `G` and `h`
+pub fn commit(value: &Scalar, blinding: &Scalar, h: &RistrettoPoint) -> Commitment {
+    Commitment(RISTRETTO_BASEPOINT_POINT * value + h * blinding)
+}
+
+impl Commitment {
+    /// Prove knowledge of the `(value, blinding)` that open this commitment
+    pub fn prove_opening(&self, value: &Scalar, blinding: &Scalar, h: &RistrettoPoint, rng: &mut impl RngCore) -> OpeningProof {
+        let prover = OpeningProver::commit(value, blinding, h, rng);
+        let c = opening_challenge(h, &self.0, &prover.commitment());
+        prover.respond(&c)
+    }
+}
+
+/// `C1 + C2` commits to `v1 + v2` with blinding `r1 + r2`
+impl Add for Commitment {
+    type Output = Commitment;
+
+    fn add(self, other: Commitment) -> Commitment {
+        Commitment(self.0 + other.0)
+    }
+}
+
+/// Prover state between sending `R` and receiving the challenge
+pub struct OpeningProver {
+    value: SecretScalar,
+    blinding: SecretScalar,
+    k_v: SecretScalar,
+    k_r: SecretScalar,
+    commitment: RistrettoPoint,
+}
+
+impl OpeningProver {
+    /// Draw the nonces `k_v`, `k_r` and commit to `R = k_v*G + k_r*H`
+    pub fn commit(value: &Scalar, blinding: &Scalar, h: &RistrettoPoint, rng: &mut impl RngCore) -> Self {
+        let (k_v, k_r) = (SecretScalar::new(random_scalar(rng)), SecretScalar::new(random_scalar(rng)));
+        let commitment = k_v.mul_base() + h * k_r.expose_secret();
+        Self { value: SecretScalar::new(*value), blinding: SecretScalar::new(*blinding), k_v, k_r, commitment }
+    }
+
+    pub fn commitment(&self) -> RistrettoPoint {
+        self.commitment
+    }
+
+    /// Answer `challenge` with `s_v = k_v + c*v` and `s_r = k_r + c*r`
+    pub fn respond(self, challenge: &Scalar) -> OpeningProof {
+        OpeningProof {
+            commitment: self.commitment,
+            response_value: self.k_v.expose_secret() + challenge * self.value.expose_secret(),
+            response_blinding: self.k_r.expose_secret() + challenge * self.blinding.expose_secret(),
+        }
+    }
+}
+
+/// Non-interactive proof of knowing an opening `(v, r)` of a `Commitment`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpeningProof {
+    /// R = k_v*G + k_r*H
+    #[serde(with = "crate::serde_hex::point")]
+    pub commitment: RistrettoPoint,
+    /// s_v = k_v + c*v
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub response_value: Scalar,
+    /// s_r = k_r + c*r
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub response_blinding: Scalar,
+}
+
+impl OpeningProof {
+    /// Check `s_v*G + s_r*H = R + c*C` with the Fiat-Shamir challenge
+    pub fn verify(&self, commitment: &Commitment, h: &RistrettoPoint) -> bool {
+        let c = opening_challenge(h, &commitment.0, &self.commitment);
+        self.verify_with_challenge(commitment, h, &c)
+    }
+
+    /// Check `s_v*G + s_r*H = R + c*C` for a challenge `c` the verifier chose
+    pub fn verify_with_challenge(&self, commitment: &Commitment, h: &RistrettoPoint, c: &Scalar) -> bool {
+        let left_side = RISTRETTO_BASEPOINT_POINT * self.response_value + h * self.response_blinding;
+        let right_side = self.commitment + commitment.0 * c;
+        left_side == right_side
+    }
+}
+
+// c squeezed from a transcript over (G, H, C, R)
+fn opening_challenge(h: &RistrettoPoint, commitment: &RistrettoPoint, R: &RistrettoPoint) -> Scalar {
+    transcript_challenge(
+        b"zk-schnorr-tls/pedersen-opening/v2",
+        &[(b"G", &RISTRETTO_BASEPOINT_POINT), (b"H", h), (b"C", commitment), (b"R", R)],
+    )
+}
@@ -0,0 +1,108 @@
+//! Indexed storage of checked proofs
+//!
+//! A [`ProofLog`] only appends. A [`ProofStoreBackend`] also answers queries:
+//! every proof checked against a public key, or the one checked in a given
+//! session. Every store is a `ProofLog` too, so it plugs in wherever an audit
+//! log does (`run_verifier_logged`, `VerifierServerBuilder::audit_log`, ...);
+//! unlike a plain log, a store that fails to keep an entry fails the session
+//! with `ProtocolError::Store`.
+//!
+//! [`InMemoryProofStore`] keeps everything in memory, for tests and
+//! development; it is lost when the process exits.
+
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+use parking_lot::RwLock;
+use tracing::warn;
+
+use crate::audit::ProofLog;
+use crate::error::ZkError;
+use crate::schnorr::{Proof, PublicKey};
+use crate::session::SessionId;
+
+/// One checked proof, as kept by a [`ProofStoreBackend`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofEntry {
+    pub session_id: SessionId,
+    /// The key the proof was checked against
+    pub public_key: PublicKey,
+    /// The full `(R, c, s)` transcript
+    pub proof: Proof,
+    pub verified: bool,
+    pub timestamp: SystemTime,
+}
+
+/// Somewhere to keep checked proofs and look them up again
+pub trait ProofStoreBackend: Send + Sync {
+    fn store(&self, entry: ProofEntry) -> Result<(), ZkError>;
+
+    /// Every entry for `key`, oldest first
+    fn lookup_by_public_key(&self, key: &PublicKey) -> Result<Vec<ProofEntry>, ZkError>;
+
+    /// The entry stored for session `id`, the latest one if the session had several rounds
+    fn lookup_by_session(&self, id: &SessionId) -> Result<Option<ProofEntry>, ZkError>;
+}
+
+impl<S: ProofStoreBackend> ProofLog for S {
+    fn record(&self, session_id: &SessionId, public_key: &PublicKey, proof: &Proof, verified: bool, timestamp: SystemTime) {
+        let entry = ProofEntry { session_id: *session_id, public_key: *public_key, proof: *proof, verified, timestamp };
+        if let Err(e) = self.store(entry) {
+            warn!(error = %e, session_id = %session_id, "Failed to store proof");
+        }
+    }
+
+    fn try_record(&self, entry: ProofEntry) -> Result<(), ZkError> {
+        self.store(entry)
+    }
+}
+
+/// A [`ProofStoreBackend`] in memory, indexed by public key and by session
+#[derive(Debug, Default)]
+pub struct InMemoryProofStore {
+    entries: RwLock<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    all: Vec<ProofEntry>,
+    by_key: HashMap<PublicKey, Vec<usize>>, // positions in `all`, oldest first
+    by_session: HashMap<SessionId, usize>,  // the latest entry of each session
+}
+
+impl InMemoryProofStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of entries stored
+    pub fn len(&self) -> usize {
+        self.entries.read().all.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl ProofStoreBackend for InMemoryProofStore {
+    fn store(&self, entry: ProofEntry) -> Result<(), ZkError> {
+        let mut entries = self.entries.write();
+        let position = entries.all.len();
+        entries.by_key.entry(entry.public_key).or_default().push(position);
+        entries.by_session.insert(entry.session_id, position);
+        entries.all.push(entry);
+        Ok(())
+    }
+
+    fn lookup_by_public_key(&self, key: &PublicKey) -> Result<Vec<ProofEntry>, ZkError> {
+        let entries = self.entries.read();
+        let positions = entries.by_key.get(key).map(Vec::as_slice).unwrap_or_default();
+        Ok(positions.iter().map(|&position| entries.all[position]).collect())
+    }
+
+    fn lookup_by_session(&self, id: &SessionId) -> Result<Option<ProofEntry>, ZkError> {
+        let entries = self.entries.read();
+        Ok(entries.by_session.get(id).map(|&position| entries.all[position]))
+    }
+}
@@ -0,0 +1,218 @@
+//! Proof transcripts that can be saved and checked again offline
+//!
+//! A [`Transcript`] is everything needed to re-run one Schnorr check: the
+//! generator, `X`, `R`, `c` and `s`, the session it came from and when it was
+//! checked. [`verify_transcript`] re-runs the check.
+//!
+//! What a passing check proves depends on where `c` came from. In an
+//! interactive session the verifier picked it, and only the verifier knows it
+//! was picked after `R` arrived: anyone can make up `(R, c, s)` that satisfy
+//! `s*G = R + c*X` by choosing `c` and `s` first. So an interactive transcript
+//! only shows that the equation holds, which convinces the party who chose
+//! `c` and nobody else. A Fiat-Shamir transcript's challenge is a hash of
+//! `(G, X, R)` (and the session id, for a session-bound proof), which
+//! [`verify_transcript`] recomputes, so it stands on its own.
+//!
+//! [`TranscriptDir`] is a [`ProofLog`] that writes one transcript file per
+//! checked round.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::audit::ProofLog;
+use crate::error::ProtocolError;
+use crate::proof_store::ProofEntry;
+use crate::schnorr::{fiat_shamir_challenge, session_challenge, Proof, PublicKey, SchnorrProof};
+use crate::serde_hex;
+use crate::session::SessionId;
+
+/// Who chose the challenge in a [`Transcript`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChallengeSource {
+    /// The verifier, during a live session
+    Interactive,
+    /// A hash of the statement and commitment, as for a [`SchnorrProof`]
+    FiatShamir,
+}
+
+/// One Schnorr check, written down so it can be re-run later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub source: ChallengeSource,
+    /// The interactive session, or the session a Fiat-Shamir proof is bound to
+    pub session_id: Option<SessionId>,
+    /// `G`, or whichever generator the proof was made over
+    #[serde(with = "serde_hex::point")]
+    pub generator: RistrettoPoint,
+    pub public_key: PublicKey,
+    #[serde(with = "serde_hex::point")]
+    pub commitment: RistrettoPoint,
+    #[serde(with = "serde_hex::scalar")]
+    pub challenge: Scalar,
+    #[serde(with = "serde_hex::scalar")]
+    pub response: Scalar,
+    /// Whether the verifier accepted it at the time
+    pub verified: bool,
+    /// When it was checked (or, for a Fiat-Shamir proof, exported), in seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// What a passing [`verify_transcript`] establishes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptValidity {
+    /// `s*G = R + c*X`, but `c` was the verifier's choice; this convinces nobody else
+    EquationHolds,
+    /// The challenge was re-derived from the transcript and the equation holds: a sound proof for anyone
+    NonInteractive,
+}
+
+impl Transcript {
+    /// The transcript of an interactive round the verifier checked over `generator`
+    pub fn interactive(entry: &ProofEntry, generator: &RistrettoPoint) -> Self {
+        Self {
+            source: ChallengeSource::Interactive,
+            session_id: Some(entry.session_id),
+            generator: *generator,
+            public_key: entry.public_key,
+            commitment: entry.proof.commitment,
+            challenge: entry.proof.challenge,
+            response: entry.proof.response,
+            verified: entry.verified,
+            timestamp: unix_secs(entry.timestamp),
+        }
+    }
+
+    /// The transcript of a [`SchnorrProof`] for `public_key = x * generator`
+    pub fn fiat_shamir(proof: &SchnorrProof, public_key: &PublicKey, generator: &RistrettoPoint) -> Self {
+        let outcome = proof.verify_detailed_with_generator(public_key, generator);
+        Self {
+            source: ChallengeSource::FiatShamir,
+            session_id: None,
+            generator: *generator,
+            public_key: *public_key,
+            commitment: proof.commitment,
+            challenge: outcome.challenge,
+            response: proof.response,
+            verified: outcome.valid,
+            timestamp: unix_secs(SystemTime::now()),
+        }
+    }
+
+    /// The transcript of a proof made with [`SchnorrProof::prove_in_session`]
+    pub fn fiat_shamir_in_session(proof: &SchnorrProof, public_key: &PublicKey, session: &SessionId) -> Self {
+        Self {
+            session_id: Some(*session),
+            challenge: session_challenge(session, public_key, &proof.commitment),
+            verified: proof.verify_in_session(public_key, session),
+            ..Self::fiat_shamir(proof, public_key, &RISTRETTO_BASEPOINT_POINT)
+        }
+    }
+
+    pub fn proof(&self) -> Proof {
+        Proof::new(self.commitment, self.challenge, self.response)
+    }
+}
+
+/// Re-run the check a transcript records
+///
+/// For a Fiat-Shamir transcript the challenge is derived again first, and one
+/// that doesn't match is `ProtocolError::InvalidChallenge`. Either way a
+/// response that doesn't satisfy `s*G = R + c*X` is
+/// `ProtocolError::VerificationFailed`. Which assurance a pass gives is in
+/// the [`TranscriptValidity`]: see the [module docs](self) for why an
+/// interactive transcript only ever gets `EquationHolds`.
+pub fn verify_transcript(transcript: &Transcript) -> Result<TranscriptValidity, ProtocolError> {
+    let validity = match transcript.source {
+        ChallengeSource::Interactive => TranscriptValidity::EquationHolds,
+        ChallengeSource::FiatShamir => {
+            let (X, R) = (&transcript.public_key.0, &transcript.commitment);
+            let c = match &transcript.session_id {
+                Some(session) => session_challenge(session, X, R), // bound to G; other generators fail below
+                None => fiat_shamir_challenge(&transcript.generator, X, R),
+            };
+            if c != transcript.challenge {
+                return Err(ProtocolError::InvalidChallenge);
+            }
+            TranscriptValidity::NonInteractive
+        }
+    };
+    if !transcript.proof().verify_with_generator(&transcript.public_key, &transcript.generator) {
+        return Err(ProtocolError::VerificationFailed);
+    }
+    Ok(validity)
+}
+
+/// Writes every checked round as a transcript file in a directory
+///
+/// Files are named `<session id>-<round>.json`, rounds counting from 1. As
+/// with [`JsonFileLog`](crate::JsonFileLog), a failed write is logged and
+/// otherwise ignored.
+#[derive(Debug, Clone)]
+pub struct TranscriptDir {
+    dir: PathBuf,
+    generator: RistrettoPoint,
+}
+
+impl TranscriptDir {
+    /// Create `dir` if need be, for rounds checked over the basepoint
+    pub fn create(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, generator: RISTRETTO_BASEPOINT_POINT })
+    }
+
+    /// For a verifier whose `Params` use another generator
+    pub fn with_generator(self, generator: RistrettoPoint) -> Self {
+        Self { generator, ..self }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // the first `<session>-<n>.json` not taken yet; rounds of one session are checked one after another
+    fn write(&self, transcript: &Transcript) -> io::Result<PathBuf> {
+        let session = transcript.session_id.map_or_else(|| "none".to_string(), |id| id.to_hex());
+        let contents = serde_json::to_string_pretty(transcript).expect("a Transcript always serializes");
+        for round in 1.. {
+            let path = self.dir.join(format!("{session}-{round}.json"));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(contents.as_bytes())?;
+                    return Ok(path);
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        unreachable!("some round number is free")
+    }
+}
+
+impl ProofLog for TranscriptDir {
+    fn record(&self, session_id: &SessionId, public_key: &PublicKey, proof: &Proof, verified: bool, timestamp: SystemTime) {
+        let entry = ProofEntry { session_id: *session_id, public_key: *public_key, proof: *proof, verified, timestamp };
+        if let Err(e) = self.write(&Transcript::interactive(&entry, &self.generator)) {
+            warn!(error = %e, dir = %self.dir.display(), "Failed to write proof transcript");
+        }
+    }
+}
+
+/// Read a transcript written by [`TranscriptDir`], or any JSON `Transcript`
+pub fn read_transcript(path: &Path) -> io::Result<Transcript> {
+    let contents = fs::read_to_string(path)?;
+    serde_json::from_str(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
@@ -0,0 +1,1127 @@
+//! Both sides of the interactive protocol, independent of how messages travel
+//!
+//! `SchnorrProtocol` runs the prover or the verifier over any `Transport`.
+//! `prove_interactive` draws a fresh random nonce for every run.
+//! `prove_interactive_deterministic` instead derives the nonce from the secret
+//! and a caller-supplied context, for machines without a trustworthy RNG and
+//! for reproducible test vectors. `run_prover_rounds_deterministic` does the
+//! same for whole sessions with [`derive_nonce`], keyed by the session id.
+//!
+//! Each connection opens with the verifier's `context` message carrying a
+//! fresh session id; every challenge in the session is derived from it (see
+//! [`ChallengeContext`]) and the prover checks that it was. The verifier
+//! remembers the ids it recently issued and refuses to reuse one.
+//! Every round ends with the verifier's verdict in a `result` message, so the
+//! prover learns whether it was accepted. A verifier that gives up because the
+//! prover sent something malformed says why in an `error` message first.
+//!
+//! A connection may open with a `hello` exchange that settles the protocol
+//! version (see [`crate::version`]) before anything else is sent.
+//!
+//! When both `hello`s list the `committed-challenge` feature (see
+//! [`crate::challenge_commitment`]), each round instead opens with the
+//! verifier's `challenge-commit`, and the challenge comes as a
+//! `challenge-reveal` that the prover checks against it.
+//!
+//! Before any of that the two sides may agree on a [`WireFormat`]: the prover
+//! sends a `codec` message, the verifier echoes it back if it can speak it
+//! (or ends the connection with an `error`), and both switch from JSON.
+//!
+//! An OR-proof round (`prove_or_round` / `verify_or_round`) has the same
+//! shape with `or-commit` and `or-response` in place of `commit` and `response`.
+//! An AND round (`prove_and_round` / `verify_and_round`) proves several keys
+//! at once: `commit-batch` carries one `R_i` per key, a single challenge covers
+//! them all, and `response-batch` carries each `s_i = k_i + c*x_i`.
+//! An opening round (`prove_opening_round` / `verify_opening_round`) proves
+//! knowledge of a Pedersen commitment's value and blinding, with
+//! `opening-commit` and `opening-response`.
+//! Numbered sub-proofs (see [`crate::multiplex`]) open with a `proof-request`
+//! and are accepted by `verify_interactive` between ordinary rounds.
+//! An Ed25519 round (`prove_ed25519_round` / `verify_ed25519_round`) takes the
+//! place of all of these for provers that can only sign: the verifier sends an
+//! `ed25519-challenge` nonce and gets back an `ed25519-signature` over it.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha512};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tracing::{debug, info, warn};
+
+use crate::and_proof::MAX_AND_STATEMENTS;
+use crate::audit::{NullLog, ProofLog};
+use crate::proof_store::ProofEntry;
+use crate::challenge::ChallengeContext;
+use crate::challenge_commitment::{ChallengeCommitment, ChallengeOpening, ChallengePolicy};
+use crate::codec::{CodecError, WireFormat};
+use crate::ed25519::{challenge_message, verify_ed25519, Ed25519PublicKey, Ed25519SigningKey};
+use crate::error::DecodeError;
+use crate::error::{ProtocolError, ZkError};
+use crate::framing::FrameError;
+use crate::multiplex::verify_request;
+use crate::or_proof::{OrCommitment, OrProof, OrProver};
+use crate::params::Params;
+use crate::pedersen::{Commitment, OpeningProof, OpeningProver};
+use crate::schnorr::{respond, KeyPair, Proof, PublicKey};
+use crate::secret::SecretScalar;
+use crate::serde_hex::SerdePoint;
+use crate::session::{SessionCache, SessionId};
+use crate::timeouts::{ProtocolConfig, TimedOut};
+use crate::transport::{StreamTransport, TimeoutTransport, Transport};
+use crate::version::{check_generator, check_selected, negotiate, NegotiationError, Version, VersionInfo, VersionRejection};
+use crate::{
+    ct_scalar_eq, point_from_hex_nonidentity, point_to_hex, scalar_from_hex_canonical, scalar_from_hex_nonzero, scalar_to_hex, Message,
+};
+
+/// Entry points for running either side of the Schnorr identification protocol
+pub struct SchnorrProtocol;
+
+impl SchnorrProtocol {
+    /// Open the connection with our `hello` and learn which version was selected
+    ///
+    /// A verifier that can't speak our version, or proves over another
+    /// generator, answers with a structured `error`, reported as
+    /// `ProtocolError::Negotiation`.
+    pub async fn send_hello<T: Transport>(transport: &mut T, ours: &VersionInfo) -> Result<Version, ProtocolError> {
+        Self::send_hello_reply(transport, ours).await.map(|(selected, _)| selected)
+    }
+
+    /// `send_hello`, also returning the verifier's `hello`, which lists the features both sides support
+    pub async fn send_hello_reply<T: Transport>(
+        transport: &mut T,
+        ours: &VersionInfo,
+    ) -> Result<(Version, VersionInfo), ProtocolError> {
+        transport.send(Message::hello(ours)).await?;
+        let reply = match expect_message(transport, "hello").await {
+            Err(ProtocolError::Rejected { reason }) => {
+                return Err(match serde_json::from_str::<VersionRejection>(&reason) {
+                    Ok(VersionRejection { generator: Some(SerdePoint(verifier)), .. }) => {
+                        NegotiationError::GeneratorMismatch { verifier: verifier.compress(), prover: ours.params().generator().compress() }.into()
+                    }
+                    Ok(rejection) => NegotiationError::Incompatible { peer: ours.version, supported: rejection.supported }.into(),
+                    Err(_) => ProtocolError::Rejected { reason },
+                });
+            }
+            reply => reply?,
+        };
+        let theirs: VersionInfo = serde_json::from_str(&reply.payload)?;
+        let selected = check_selected(ours, theirs.version)?;
+        check_generator(ours, &theirs)?; // a verifier that predates generators names none, which is G
+        debug!(version = %selected, "Protocol version agreed");
+        Ok((selected, theirs))
+    }
+
+    /// Wait for the prover's `hello` and answer with the selected version
+    ///
+    /// An incompatible version gets an `error` listing ours and ends the
+    /// connection with `ProtocolError::Negotiation` before any crypto happens.
+    /// So does a prover using another generator than `ours` names, with ours
+    /// in the `error`.
+    pub async fn accept_hello<T: Transport>(transport: &mut T, ours: &VersionInfo) -> Result<Version, ProtocolError> {
+        Self::accept_hello_offer(transport, ours).await.map(|(selected, _)| selected)
+    }
+
+    /// `accept_hello`, also returning the prover's `hello` as it was offered, e.g. for its claimed identity
+    pub async fn accept_hello_offer<T: Transport>(
+        transport: &mut T,
+        ours: &VersionInfo,
+    ) -> Result<(Version, VersionInfo), ProtocolError> {
+        let result = async {
+            let msg = expect_message(transport, "hello").await?;
+            Ok::<VersionInfo, ProtocolError>(serde_json::from_str(&msg.payload)?)
+        }
+        .await;
+        let theirs = reject_if_malformed(transport, result).await?;
+
+        match negotiate(&theirs, ours) {
+            Ok(selected) => {
+                let features = theirs.supported_features.iter().filter(|f| ours.supported_features.contains(f)).cloned().collect();
+                let reply = VersionInfo { version: selected, supported_features: features, identity: None, generator: ours.generator };
+                transport.send(Message::hello(&reply)).await?;
+                debug!(version = %selected, "Protocol version agreed");
+                Ok((selected, theirs))
+            }
+            Err(e) => {
+                let generator = matches!(e, NegotiationError::GeneratorMismatch { .. }).then_some(SerdePoint(*ours.params().generator()));
+                let rejection = VersionRejection { reason: e.to_string(), supported: vec![ours.version], generator };
+                let payload = serde_json::to_string(&rejection).expect("a VersionRejection always serializes");
+                let _ = transport.send(Message::error(&payload)).await;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Ask the verifier to use `codec` for the rest of the connection
+    ///
+    /// Must be the first exchange on the connection. A verifier that doesn't
+    /// support `codec` answers with an `error`, reported as `ProtocolError::Rejected`.
+    pub async fn request_codec<T: Transport>(transport: &mut T, codec: WireFormat) -> Result<(), ProtocolError> {
+        transport.send(Message::codec(codec)).await?;
+        let reply = expect_message(transport, "codec").await?;
+        if reply.payload != codec.to_string() {
+            return Err(ProtocolError::UnexpectedMessage { expected: "codec", got: format!("codec {}", reply.payload) });
+        }
+        transport.set_codec(codec);
+        debug!(%codec, "Codec agreed");
+        Ok(())
+    }
+
+    /// Wait for the prover's codec request and switch to it if it is in `supported`
+    ///
+    /// An unsupported codec is answered with an `error` message and returned as
+    /// `ProtocolError::UnsupportedCodec`; the connection should then be dropped.
+    pub async fn accept_codec<T: Transport>(transport: &mut T, supported: &[WireFormat]) -> Result<WireFormat, ProtocolError> {
+        let msg = match expect_message(transport, "codec").await {
+            Ok(msg) => msg,
+            Err(e) => return reject_if_malformed(transport, Err(e)).await,
+        };
+        let codec = match msg.payload.parse::<WireFormat>() {
+            Ok(codec) if supported.contains(&codec) => codec,
+            _ => {
+                let err = ProtocolError::UnsupportedCodec(msg.payload);
+                let _ = transport.send(Message::error(&err.to_string())).await;
+                return Err(err);
+            }
+        };
+        transport.send(Message::codec(codec)).await?; // the confirmation still goes out as JSON
+        transport.set_codec(codec);
+        debug!(%codec, "Codec agreed");
+        Ok(codec)
+    }
+
+    /// Prove knowledge of `keypair`'s secret: commit, answer the challenge, done
+    ///
+    /// Fails with `ProtocolError::VerificationFailed` if the verifier rejects the proof.
+    pub async fn run_prover<T: Transport>(transport: &mut T, keypair: &KeyPair) -> Result<(), ProtocolError> {
+        Self::run_prover_rounds(transport, keypair, 1).await
+    }
+
+    /// Prove `rounds` times in a row over the same transport
+    ///
+    /// Every round uses a fresh nonce. Pairs with `verify_interactive` on the
+    /// other end, which keeps verifying until the transport is closed.
+    pub async fn run_prover_rounds<T: Transport>(transport: &mut T, keypair: &KeyPair, rounds: usize) -> Result<(), ProtocolError> {
+        let context = Self::open_prover_session(transport).await?;
+        for round in 1..=rounds {
+            Self::prove_round(transport, &context, keypair).await?;
+            debug!(round, "Round complete");
+        }
+        Ok(())
+    }
+
+    /// Start a prover session by receiving the verifier's challenge context
+    ///
+    /// Use together with `prove_round` to drive the rounds of a session yourself.
+    pub async fn open_prover_session<T: Transport>(transport: &mut T) -> Result<ChallengeContext, ProtocolError> {
+        let msg = expect_message(transport, "context").await?;
+        let context = ChallengeContext::from_hex(&msg.payload)?;
+        info!(session_id = %context.session_id(), "Received challenge context");
+        Ok(context)
+    }
+
+    /// Run one round of an open session with a fresh random nonce
+    pub async fn prove_round<T: Transport>(
+        transport: &mut T,
+        context: &ChallengeContext,
+        keypair: &KeyPair,
+    ) -> Result<Proof, ProtocolError> {
+        Self::prove_round_with_params(transport, context, keypair, &Params::default()).await
+    }
+
+    /// `prove_round` over `params`' generator `H`: commit `k*H` and answer for `X = x*H`
+    pub async fn prove_round_with_params<T: Transport>(
+        transport: &mut T,
+        context: &ChallengeContext,
+        keypair: &KeyPair,
+        params: &Params,
+    ) -> Result<Proof, ProtocolError> {
+        Self::prove_round_with_policy(transport, context, keypair, params, ChallengePolicy::Derived).await
+    }
+
+    /// `prove_round_with_params`, holding the challenge to `policy`
+    ///
+    /// Under `ChallengePolicy::Committed` the round waits for the verifier's
+    /// `challenge-commit` before sending `R`, and a revealed challenge that
+    /// doesn't open it is refused with `ProtocolError::ChallengeCommitmentMismatch`.
+    pub async fn prove_round_with_policy<T: Transport>(
+        transport: &mut T,
+        context: &ChallengeContext,
+        keypair: &KeyPair,
+        params: &Params,
+        policy: ChallengePolicy,
+    ) -> Result<Proof, ProtocolError> {
+        let k = SecretScalar::random(&mut OsRng); // fresh nonce from the OS RNG, wiped after the round
+        run_round(transport, context, params, policy, keypair.secret().expose_secret(), k, false).await
+    }
+
+    /// Like `run_prover_rounds`, but every nonce comes from [`derive_nonce`]
+    ///
+    /// The transcript for a round is the session id followed by the round
+    /// number, so no two rounds share a nonce and the OS RNG is never consulted.
+    pub async fn run_prover_rounds_deterministic<T: Transport>(
+        transport: &mut T,
+        keypair: &KeyPair,
+        rounds: usize,
+    ) -> Result<(), ProtocolError> {
+        let context = Self::open_prover_session(transport).await?;
+        for round in 1..=rounds {
+            Self::prove_round_deterministic(transport, &context, keypair, round as u64).await?;
+            debug!(round, "Round complete");
+        }
+        Ok(())
+    }
+
+    /// Run round number `round` of an open session with a derived nonce
+    ///
+    /// Each `(keypair, session, round)` must be proven at most once; a repeat
+    /// that meets a different challenge is refused with `ProtocolError::NonceReuse`.
+    pub async fn prove_round_deterministic<T: Transport>(
+        transport: &mut T,
+        context: &ChallengeContext,
+        keypair: &KeyPair,
+        round: u64,
+    ) -> Result<Proof, ProtocolError> {
+        Self::prove_round_deterministic_with_params(transport, context, keypair, round, &Params::default()).await
+    }
+
+    /// `prove_round_deterministic` over `params`' generator
+    pub async fn prove_round_deterministic_with_params<T: Transport>(
+        transport: &mut T,
+        context: &ChallengeContext,
+        keypair: &KeyPair,
+        round: u64,
+        params: &Params,
+    ) -> Result<Proof, ProtocolError> {
+        Self::prove_round_deterministic_with_policy(transport, context, keypair, round, params, ChallengePolicy::Derived).await
+    }
+
+    /// `prove_round_deterministic_with_params`, holding the challenge to `policy` as `prove_round_with_policy` does
+    pub async fn prove_round_deterministic_with_policy<T: Transport>(
+        transport: &mut T,
+        context: &ChallengeContext,
+        keypair: &KeyPair,
+        round: u64,
+        params: &Params,
+        policy: ChallengePolicy,
+    ) -> Result<Proof, ProtocolError> {
+        let mut transcript = context.to_bytes().to_vec();
+        transcript.extend_from_slice(&round.to_le_bytes());
+        let k = SecretScalar::new(derive_nonce(keypair.secret(), &transcript));
+        run_round(transport, context, params, policy, keypair.secret().expose_secret(), k, true).await
+    }
+
+    /// Prove knowledge of the secret for one of `pubkeys` without revealing which
+    ///
+    /// `which` is the index of the key `secret` belongs to. The challenge is
+    /// derived from the session context and both branch commitments.
+    pub async fn prove_or_round<T: Transport>(
+        transport: &mut T,
+        context: &ChallengeContext,
+        secret: &Scalar,
+        which: usize,
+        pubkeys: &[RistrettoPoint; 2],
+    ) -> Result<OrProof, ProtocolError> {
+        let prover = OrProver::commit(secret, which, pubkeys, &mut OsRng);
+        let commitment = prover.commitment();
+        transport.send(Message::or_commit(&commitment).in_session(context.session_id())).await?;
+
+        let ch_msg = expect_message(transport, "challenge").await?;
+        check_session(transport, &ch_msg, context.session_id(), None).await?;
+        let c = scalar_from_hex_canonical(&ch_msg.payload)?;
+        if c == Scalar::ZERO || c != context.challenge_for(&[commitment.R1, commitment.R2], pubkeys) {
+            return refuse_challenge(transport).await;
+        }
+
+        let proof = prover.respond(&c);
+        transport.send(Message::or_response(&proof).in_session(context.session_id())).await?;
+        match expect_message(transport, "result").await?.payload.as_str() {
+            "accepted" => Ok(proof),
+            "rejected" => Err(ProtocolError::VerificationFailed),
+            other => Err(ProtocolError::UnexpectedMessage { expected: "result", got: format!("result {other}") }),
+        }
+    }
+
+    /// Verify one OR-proof round against `pubkeys` and tell the prover the verdict
+    pub async fn verify_or_round<T: Transport>(
+        transport: &mut T,
+        context: &ChallengeContext,
+        pubkeys: &[RistrettoPoint; 2],
+    ) -> Result<bool, ProtocolError> {
+        let result = verify_or(transport, context, pubkeys).await;
+        reject_if_malformed(transport, result).await
+    }
+
+    /// Prove knowledge of the secret of every one of `keypairs` in one round
+    ///
+    /// The challenge is derived from the session context, every commitment
+    /// and every public key. Fails with `ProtocolError::TooManyStatements`
+    /// beyond [`MAX_AND_STATEMENTS`] keys, before anything is sent.
+    pub async fn prove_and_round<T: Transport>(
+        transport: &mut T,
+        context: &ChallengeContext,
+        keypairs: &[KeyPair],
+    ) -> Result<Vec<Proof>, ProtocolError> {
+        check_statement_count(keypairs.len())?;
+        let pubkeys: Vec<RistrettoPoint> = keypairs.iter().map(|keypair| keypair.public_key().0).collect();
+        let nonces: Vec<SecretScalar> = keypairs.iter().map(|_| SecretScalar::random(&mut OsRng)).collect();
+        let commitments: Vec<RistrettoPoint> = nonces.iter().map(SecretScalar::mul_base).collect();
+        transport.send(Message::commit_batch(&commitments).in_session(context.session_id())).await?;
+
+        let ch_msg = expect_message(transport, "challenge").await?;
+        check_session(transport, &ch_msg, context.session_id(), None).await?;
+        let c = scalar_from_hex_canonical(&ch_msg.payload)?;
+        if c == Scalar::ZERO || c != context.challenge_for(&commitments, &pubkeys) {
+            return refuse_challenge(transport).await;
+        }
+
+        let responses: Vec<Scalar> = nonces
+            .iter()
+            .zip(keypairs)
+            .map(|(k, keypair)| respond(k.expose_secret(), &c, keypair.secret().expose_secret()))
+            .collect();
+        transport.send(Message::response_batch(&responses).in_session(context.session_id())).await?;
+        match expect_message(transport, "result").await?.payload.as_str() {
+            "accepted" => Ok(commitments.into_iter().zip(responses).map(|(R, s)| Proof::new(R, c, s)).collect()),
+            "rejected" => Err(ProtocolError::VerificationFailed),
+            other => Err(ProtocolError::UnexpectedMessage { expected: "result", got: format!("result {other}") }),
+        }
+    }
+
+    /// Verify one AND round against `pubkeys` and tell the prover the verdict
+    ///
+    /// A proof where some `s_i*G != R_i + c*X_i` is rejected as a whole, with
+    /// `ProtocolError::StatementFailed` naming the first such `i`.
+    pub async fn verify_and_round<T: Transport>(
+        transport: &mut T,
+        context: &ChallengeContext,
+        pubkeys: &[PublicKey],
+    ) -> Result<(), ProtocolError> {
+        let result = verify_and(transport, context, pubkeys).await;
+        reject_if_malformed(transport, result).await
+    }
+
+    /// Prove knowledge of the opening `(value, blinding)` of `commitment` in one round
+    ///
+    /// The challenge is derived from the session context, `R`, the
+    /// commitment and `h`; neither value nor blinding is revealed.
+    pub async fn prove_opening_round<T: Transport>(
+        transport: &mut T,
+        context: &ChallengeContext,
+        commitment: &Commitment,
+        value: &Scalar,
+        blinding: &Scalar,
+        h: &RistrettoPoint,
+    ) -> Result<OpeningProof, ProtocolError> {
+        let prover = OpeningProver::commit(value, blinding, h, &mut OsRng);
+        let R = prover.commitment();
+        transport.send(Message::opening_commit(&R).in_session(context.session_id())).await?;
+
+        let ch_msg = expect_message(transport, "challenge").await?;
+        check_session(transport, &ch_msg, context.session_id(), None).await?;
+        let c = scalar_from_hex_canonical(&ch_msg.payload)?;
+        if c == Scalar::ZERO || c != context.challenge_for(&[R], &[commitment.0, *h]) {
+            return refuse_challenge(transport).await;
+        }
+
+        let proof = prover.respond(&c);
+        transport.send(Message::opening_response(&proof).in_session(context.session_id())).await?;
+        match expect_message(transport, "result").await?.payload.as_str() {
+            "accepted" => Ok(proof),
+            "rejected" => Err(ProtocolError::VerificationFailed),
+            other => Err(ProtocolError::UnexpectedMessage { expected: "result", got: format!("result {other}") }),
+        }
+    }
+
+    /// Verify one opening round for `commitment` and tell the prover the verdict
+    pub async fn verify_opening_round<T: Transport>(
+        transport: &mut T,
+        context: &ChallengeContext,
+        commitment: &Commitment,
+        h: &RistrettoPoint,
+    ) -> Result<bool, ProtocolError> {
+        let result = verify_opening(transport, context, commitment, h).await;
+        reject_if_malformed(transport, result).await
+    }
+
+    /// Sign the verifier's `ed25519-challenge` nonce with `key`, in place of a sigma round
+    pub async fn prove_ed25519_round<T: Transport>(
+        transport: &mut T,
+        context: &ChallengeContext,
+        key: &Ed25519SigningKey,
+    ) -> Result<[u8; 64], ProtocolError> {
+        let ch_msg = expect_message(transport, "ed25519-challenge").await?;
+        check_session(transport, &ch_msg, context.session_id(), None).await?;
+        let nonce: [u8; 32] = decode_bytes(&ch_msg.payload)?;
+
+        let signature = key.sign(&challenge_message(&nonce));
+        transport.send(Message::ed25519_signature(&signature).in_session(context.session_id())).await?;
+        match expect_message(transport, "result").await?.payload.as_str() {
+            "accepted" => Ok(signature),
+            "rejected" => Err(ProtocolError::VerificationFailed),
+            other => Err(ProtocolError::UnexpectedMessage { expected: "result", got: format!("result {other}") }),
+        }
+    }
+
+    /// Send a fresh nonce, check the prover's Ed25519 signature over it under `public_key`, and tell it the verdict
+    ///
+    /// A signature that doesn't verify is `Ok(false)`, as for a failed sigma round.
+    pub async fn verify_ed25519_round<T: Transport>(
+        transport: &mut T,
+        context: &ChallengeContext,
+        public_key: &Ed25519PublicKey,
+    ) -> Result<bool, ProtocolError> {
+        let result = verify_ed25519_answer(transport, context, public_key).await;
+        reject_if_malformed(transport, result).await
+    }
+
+    /// Run the verifier's side and report whether the proof checked out
+    ///
+    /// A proof that simply fails the equation is `Ok(false)`; errors are
+    /// reserved for transport problems and malformed or out-of-order messages.
+    pub async fn run_verifier<T: Transport>(
+        transport: &mut T,
+        expected_public_key: &PublicKey,
+    ) -> Result<bool, ProtocolError> {
+        debug!(X = %expected_public_key, "Expecting proof for public key");
+        let context = send_context(transport).await?;
+
+        // 1) receive the commitment R
+        let result = match expect_message(transport, "commit").await {
+            Ok(commit_msg) => {
+                let mut seen = SeenCommitments::default();
+                verify_round(transport, &context, &Params::default(), expected_public_key, &commit_msg, None, &mut seen, &NullLog).await
+            }
+            Err(e) => Err(e),
+        };
+        reject_if_malformed(transport, result).await
+    }
+
+    /// Verify proofs from the same peer until it closes the connection
+    ///
+    /// Every `commit` message starts a new round. A round whose equation doesn't
+    /// hold is recorded as `false` and the loop carries on with the next one;
+    /// only transport errors and malformed or out-of-order messages end the
+    /// session early. Returns one entry per completed round, in order.
+    ///
+    /// A commitment `R` seen earlier in the session, in a round or a numbered
+    /// sub-proof, ends it with `ProtocolError::NonceReuse` before a second
+    /// challenge goes out: two responses for one nonce reveal the prover's secret.
+    pub async fn verify_interactive<T: Transport>(
+        transport: &mut T,
+        expected_public_key: &PublicKey,
+    ) -> Result<Vec<bool>, ProtocolError> {
+        Self::verify_interactive_logged(transport, expected_public_key, &NullLog).await
+    }
+
+    /// `verify_interactive`, handing every completed round to `log`
+    ///
+    /// A round is recorded, with its verdict, once the response has been
+    /// checked; rounds cut short by an error never reach the log.
+    pub async fn verify_interactive_logged<T: Transport>(
+        transport: &mut T,
+        expected_public_key: &PublicKey,
+        log: &dyn ProofLog,
+    ) -> Result<Vec<bool>, ProtocolError> {
+        Self::verify_interactive_with_params(transport, expected_public_key, &Params::default(), log).await
+    }
+
+    /// `verify_interactive_logged` over `params`' generator: each round must satisfy `s*H = R + c*X`
+    ///
+    /// `expected_public_key` is `x*H`. Numbered sub-proofs are still checked over `G`.
+    pub async fn verify_interactive_with_params<T: Transport>(
+        transport: &mut T,
+        expected_public_key: &PublicKey,
+        params: &Params,
+        log: &dyn ProofLog,
+    ) -> Result<Vec<bool>, ProtocolError> {
+        Self::verify_interactive_with_policy(transport, expected_public_key, params, log, ChallengePolicy::Derived).await
+    }
+
+    /// `verify_interactive_with_params`, issuing challenges the way `policy` says
+    ///
+    /// Under `ChallengePolicy::Committed` a fresh random challenge is committed
+    /// to in a `challenge-commit` after the context and after every verdict,
+    /// ready for the next `commit`, and revealed in a `challenge-reveal` once
+    /// it arrives. Numbered sub-proofs don't take part, so a `proof-request`
+    /// is refused as unexpected.
+    pub async fn verify_interactive_with_policy<T: Transport>(
+        transport: &mut T,
+        expected_public_key: &PublicKey,
+        params: &Params,
+        log: &dyn ProofLog,
+        policy: ChallengePolicy,
+    ) -> Result<Vec<bool>, ProtocolError> {
+        let context = match send_context(transport).await {
+            Err(ProtocolError::ConnectionClosed) => return Ok(Vec::new()), // gone before the first round
+            context => context?,
+        };
+        let mut results = Vec::new();
+        let mut seen = SeenCommitments::default();
+        let mut pending = None; // the committed challenge for the next round
+        loop {
+            if policy == ChallengePolicy::Committed && pending.is_none() {
+                let opening = ChallengeOpening::random(&mut OsRng);
+                let commit = Message::challenge_commit(&opening.commitment()).in_session(context.session_id());
+                match transport.send(commit).await {
+                    Ok(()) => pending = Some(opening),
+                    // a prover that is done may already have hung up
+                    Err(e) if matches!(e.kind(), io::ErrorKind::BrokenPipe | io::ErrorKind::ConnectionReset) => break,
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            let commit_msg = match transport.recv().await {
+                Ok(msg) => msg,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break, // clean end of the session
+                Err(e) => return reject_if_malformed(transport, Err(recv_error(e, "commit"))).await,
+            };
+            let round = match commit_msg.kind.as_str() {
+                "commit" => verify_round(transport, &context, params, expected_public_key, &commit_msg, pending.take(), &mut seen, log).await,
+                "proof-request" if policy == ChallengePolicy::Derived => verify_request(transport, &context, expected_public_key, &commit_msg, &mut seen, log)
+                    .await
+                    .map(|result| result.verified),
+                "end-session" => break,
+                _ => {
+                    let unexpected = ProtocolError::UnexpectedMessage { expected: "commit", got: commit_msg.kind };
+                    return reject_if_malformed(transport, Err(unexpected)).await;
+                }
+            };
+            let verified = reject_if_malformed(transport, round).await?;
+            if !verified {
+                warn!(round = results.len() + 1, "Proof failed, waiting for the next round");
+            }
+            results.push(verified);
+        }
+        Ok(results)
+    }
+}
+
+// open a session: pick a fresh context, check its id was never handed out, and send it to the prover
+pub(crate) async fn send_context<T: Transport>(transport: &mut T) -> Result<ChallengeContext, ProtocolError> {
+    let context = ChallengeContext::random(&mut OsRng);
+    record_session(context.session_id())?;
+    transport.send(Message::context(&context).in_session(context.session_id())).await.map_err(|e| match e.kind() {
+        io::ErrorKind::BrokenPipe | io::ErrorKind::ConnectionReset => ProtocolError::ConnectionClosed,
+        _ => ProtocolError::Io(e),
+    })?;
+    info!(session_id = %context.session_id(), "Sent challenge context"); // ties this connection to its audit records
+    Ok(context)
+}
+
+/// Commitments already challenged in one verifier session
+///
+/// A prover that sends the same `R = k*G` twice has reused its nonce `k`. Two
+/// responses to different challenges, `s1 = k + c1*x` and `s2 = k + c2*x`, give
+/// away the secret as `x = (s1 - s2) / (c1 - c2)`, so a repeated commitment
+/// ends the session with [`ProtocolError::NonceReuse`] before it is challenged
+/// again. That protects a buggy prover from leaking its key to us, and tells
+/// its operator something is badly wrong.
+#[derive(Debug, Default)]
+pub(crate) struct SeenCommitments(HashSet<[u8; 32]>);
+
+impl SeenCommitments {
+    pub(crate) fn insert(&mut self, R: &RistrettoPoint) -> Result<(), ProtocolError> {
+        if !self.0.insert(R.compress().to_bytes()) {
+            warn!(R = %point_to_hex(R), "Prover reused a commitment, refusing to challenge it again");
+            return Err(ProtocolError::NonceReuse);
+        }
+        Ok(())
+    }
+}
+
+// rest of a verifier round once the commitment has arrived; `committed` is
+// the challenge already committed to, if the session uses committed challenges
+#[allow(clippy::too_many_arguments)]
+async fn verify_round<T: Transport>(
+    transport: &mut T,
+    context: &ChallengeContext,
+    params: &Params,
+    expected_public_key: &PublicKey,
+    commit_msg: &Message,
+    committed: Option<ChallengeOpening>,
+    seen: &mut SeenCommitments,
+    log: &dyn ProofLog,
+) -> Result<bool, ProtocolError> {
+    session_matches(commit_msg, context.session_id(), None)?;
+    let R = point_from_hex_nonidentity(&commit_msg.payload)?;
+    debug!(R = %point_to_hex(&R), "Received commitment");
+    seen.insert(&R)?; // before any challenge goes out for it
+
+    // 2) send the challenge c = H(session_id || R || X || random), or reveal the committed one
+    let (c, challenge_msg) = match committed {
+        None => {
+            let c = round_challenge(context, params, &R, &expected_public_key.0);
+            (c, Message::challenge(&c))
+        }
+        Some(opening) => (opening.challenge(), Message::challenge_reveal(&opening)),
+    };
+    transport.send(challenge_msg.in_session(context.session_id())).await?;
+    debug!(c = %scalar_to_hex(&c), "Sent challenge");
+
+    // 3) receive the response s, from the same session as its commitment
+    let response_msg = expect_message(transport, "response").await?;
+    session_matches(&response_msg, context.session_id(), Some(commit_msg))?;
+    let s = scalar_from_hex_nonzero(&response_msg.payload)?;
+    debug!(s = %response_msg.payload, "Received response");
+
+    // 4) check s*G = R + c*X and tell the prover
+    let proof = Proof::new(R, c, s);
+    let verified = proof.verify_with_generator(expected_public_key, params.generator());
+    info!(verified = %verified, "Schnorr verification complete");
+    let entry = ProofEntry { session_id: *context.session_id(), public_key: *expected_public_key, proof, verified, timestamp: SystemTime::now() };
+    log.try_record(entry).map_err(|e| ProtocolError::Store(Box::new(e)))?;
+    transport.send(Message::result(verified).in_session(context.session_id())).await?;
+    Ok(verified)
+}
+
+async fn verify_or<T: Transport>(
+    transport: &mut T,
+    context: &ChallengeContext,
+    pubkeys: &[RistrettoPoint; 2],
+) -> Result<bool, ProtocolError> {
+    let commit_msg = expect_message(transport, "or-commit").await?;
+    session_matches(&commit_msg, context.session_id(), None)?;
+    let commitment: OrCommitment = serde_json::from_str(&commit_msg.payload)?;
+
+    let c = context.challenge_for(&[commitment.R1, commitment.R2], pubkeys);
+    transport.send(Message::challenge(&c).in_session(context.session_id())).await?;
+
+    let response_msg = expect_message(transport, "or-response").await?;
+    session_matches(&response_msg, context.session_id(), Some(&commit_msg))?;
+    let proof: OrProof = serde_json::from_str(&response_msg.payload)?;
+    // the response must answer the commitments we challenged, not fresh ones
+    let verified = proof.R1 == commitment.R1 && proof.R2 == commitment.R2 && proof.verify_with_challenge(pubkeys, &c);
+    transport.send(Message::result(verified).in_session(context.session_id())).await?;
+    Ok(verified)
+}
+
+async fn verify_opening<T: Transport>(
+    transport: &mut T,
+    context: &ChallengeContext,
+    commitment: &Commitment,
+    h: &RistrettoPoint,
+) -> Result<bool, ProtocolError> {
+    let commit_msg = expect_message(transport, "opening-commit").await?;
+    session_matches(&commit_msg, context.session_id(), None)?;
+    let R = point_from_hex_nonidentity(&commit_msg.payload)?;
+
+    let c = context.challenge_for(&[R], &[commitment.0, *h]);
+    transport.send(Message::challenge(&c).in_session(context.session_id())).await?;
+
+    let response_msg = expect_message(transport, "opening-response").await?;
+    session_matches(&response_msg, context.session_id(), Some(&commit_msg))?;
+    let proof: OpeningProof = serde_json::from_str(&response_msg.payload)?;
+    // the response must answer the commitment we challenged, not a fresh one
+    let verified = proof.commitment == R && proof.verify_with_challenge(commitment, h, &c);
+    transport.send(Message::result(verified).in_session(context.session_id())).await?;
+    Ok(verified)
+}
+
+async fn verify_and<T: Transport>(
+    transport: &mut T,
+    context: &ChallengeContext,
+    pubkeys: &[PublicKey],
+) -> Result<(), ProtocolError> {
+    let commit_msg = expect_message(transport, "commit-batch").await?;
+    session_matches(&commit_msg, context.session_id(), None)?;
+    let commitments = decode_batch(&commit_msg, pubkeys.len(), point_from_hex_nonidentity)?;
+
+    let pubkeys: Vec<RistrettoPoint> = pubkeys.iter().map(|X| X.0).collect();
+    let c = context.challenge_for(&commitments, &pubkeys);
+    transport.send(Message::challenge(&c).in_session(context.session_id())).await?;
+
+    let response_msg = expect_message(transport, "response-batch").await?;
+    session_matches(&response_msg, context.session_id(), Some(&commit_msg))?;
+    let responses = decode_batch(&response_msg, pubkeys.len(), scalar_from_hex_nonzero)?;
+
+    let failed = (0..pubkeys.len()).find(|&i| !Proof::new(commitments[i], c, responses[i]).verify(&PublicKey(pubkeys[i])));
+    debug!(statements = pubkeys.len(), failed = ?failed, "AND-proof checked");
+    transport.send(Message::result(failed.is_none()).in_session(context.session_id())).await?;
+    match failed {
+        None => Ok(()),
+        Some(index) => Err(ProtocolError::StatementFailed { index }),
+    }
+}
+
+async fn verify_ed25519_answer<T: Transport>(
+    transport: &mut T,
+    context: &ChallengeContext,
+    public_key: &Ed25519PublicKey,
+) -> Result<bool, ProtocolError> {
+    let mut nonce = [0u8; 32];
+    OsRng.fill_bytes(&mut nonce);
+    transport.send(Message::ed25519_challenge(&nonce).in_session(context.session_id())).await?;
+
+    let sig_msg = expect_message(transport, "ed25519-signature").await?;
+    session_matches(&sig_msg, context.session_id(), None)?;
+    let signature: [u8; 64] = decode_bytes(&sig_msg.payload)?;
+
+    let verified = match verify_ed25519(&public_key.0, &challenge_message(&nonce), &signature) {
+        Ok(()) => true,
+        Err(e) => {
+            debug!(error = %e, "Ed25519 signature refused");
+            false
+        }
+    };
+    info!(verified = %verified, "Ed25519 verification complete");
+    transport.send(Message::result(verified).in_session(context.session_id())).await?;
+    Ok(verified)
+}
+
+// exactly N hex-encoded bytes
+fn decode_bytes<const N: usize>(payload: &str) -> Result<[u8; N], DecodeError> {
+    let bytes = hex::decode(payload)?;
+    let got = bytes.len();
+    bytes.try_into().map_err(|_| DecodeError::Length { expected: N, got })
+}
+
+// a JSON array of exactly `expected` hex values; the count is checked against
+// the cap before any of them is decoded
+fn decode_batch<V, E>(msg: &Message, expected: usize, decode: fn(&str) -> Result<V, E>) -> Result<Vec<V>, ProtocolError>
+where
+    ProtocolError: From<E>,
+{
+    let items: Vec<String> = serde_json::from_str(&msg.payload)?;
+    check_statement_count(items.len())?;
+    if items.len() != expected {
+        return Err(ProtocolError::StatementCountMismatch { expected, got: items.len() });
+    }
+    items.iter().map(|item| decode(item).map_err(ProtocolError::from)).collect()
+}
+
+fn check_statement_count(n: usize) -> Result<(), ProtocolError> {
+    if n > MAX_AND_STATEMENTS {
+        return Err(ProtocolError::TooManyStatements { got: n, max: MAX_AND_STATEMENTS });
+    }
+    Ok(())
+}
+
+// on a malformed or out-of-order message, tell the peer why before giving up;
+// if that send fails too the original error is still the one worth reporting
+pub(crate) async fn reject_if_malformed<T: Transport, V>(transport: &mut T, result: Result<V, ProtocolError>) -> Result<V, ProtocolError> {
+    if let Err(e) = &result
+        && e.is_malformed()
+    {
+        let _ = transport.send(Message::error(&e.to_string())).await;
+    }
+    result
+}
+
+/// Verify proofs from a prover on `stream` until it hangs up, bounding every step
+///
+/// Each message must arrive within `config.step_timeout`; a prover that stalls
+/// ends the session with `ZkError::Timeout` instead of holding the task forever.
+pub async fn verify_stream<S>(stream: S, expected_public_key: &PublicKey, config: &ProtocolConfig) -> Result<Vec<bool>, ZkError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut transport = TimeoutTransport::new(StreamTransport::new(stream), config.step_timeout);
+    SchnorrProtocol::verify_interactive(&mut transport, expected_public_key)
+        .await
+        .map_err(|e| match e {
+            ProtocolError::Timeout(timed_out) => ZkError::Timeout(timed_out),
+            other => ZkError::Protocol(other),
+        })
+}
+
+/// Run one commit/challenge/response round over a stream with a fresh random nonce
+///
+/// Returns the completed transcript `(R, c, s)` once the verifier has accepted it.
+pub async fn prove_interactive<S>(stream: S, secret: &Scalar) -> Result<Proof, ProtocolError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let k = SecretScalar::random(&mut OsRng);
+    prove_once(stream, secret, k, false).await
+}
+
+/// Run one round with a nonce derived as `k = SHA-512(secret || context)`
+///
+/// # Nonce reuse
+/// The nonce is fixed before the verifier's challenge arrives, so the same
+/// `(secret, context)` pair always commits to the same `R`. Answering two
+/// *different* challenges `c1 != c2` for the same `k` hands out
+/// `s1 - s2 = (c1 - c2)*x`, which reveals the secret `x` immediately.
+///
+/// The context must therefore be unique per run (a session id, a counter, ...).
+/// As a safety net, the last [`NonceLedger::DEFAULT_CAPACITY`] derived
+/// commitments are remembered together with the challenge each was answered
+/// for, and a different challenge for a known commitment is refused with
+/// [`ProtocolError::NonceReuse`] before any response is computed. Re-answering
+/// the *same* challenge is harmless (it reproduces the same `s`) and allowed.
+///
+/// The net only catches reuse inside this process and within that window: a
+/// restarted prover, a second process sharing the key, or a context last used
+/// more than that many runs ago is not checked. Unique contexts are what
+/// actually keeps the secret safe.
+pub async fn prove_interactive_deterministic<S>(
+    stream: S,
+    secret: &Scalar,
+    context: &[u8],
+) -> Result<Proof, ProtocolError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let k = SecretScalar::new(deterministic_nonce(secret, context));
+    prove_once(stream, secret, k, true).await
+}
+
+// a whole single-round session over a raw stream
+async fn prove_once<S>(stream: S, secret: &Scalar, k: SecretScalar, guard_nonce: bool) -> Result<Proof, ProtocolError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut transport = StreamTransport::new(stream);
+    let context = SchnorrProtocol::open_prover_session(&mut transport).await?;
+    run_round(&mut transport, &context, &Params::default(), ChallengePolicy::Derived, secret, k, guard_nonce).await
+}
+
+/// Derive a nonce `k = H(secret || context)` reduced to a scalar
+pub fn deterministic_nonce(secret: &Scalar, context: &[u8]) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(secret.as_bytes());
+    hasher.update(context);
+    Scalar::from_hash(hasher)
+}
+
+/// Derive a nonce `k = H(x || X || transcript)` from the secret key, RFC 6979 style
+///
+/// The transcript must only hold values known before the commitment is sent
+/// (the session id, a round number), never the challenge. Equal inputs give
+/// equal nonces, so a transcript must not be reused with a different challenge.
+pub fn derive_nonce(secret: &SecretScalar, transcript: &[u8]) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(b"zk-schnorr-tls/nonce/v1");
+    hasher.update(secret.expose_secret().as_bytes());
+    hasher.update(secret.mul_base().compress().as_bytes());
+    hasher.update(transcript);
+    Scalar::from_hash(hasher)
+}
+
+async fn run_round<T: Transport>(
+    transport: &mut T,
+    context: &ChallengeContext,
+    params: &Params,
+    policy: ChallengePolicy,
+    secret: &Scalar,
+    k: SecretScalar,
+    guard_nonce: bool,
+) -> Result<Proof, ProtocolError> {
+    // 0) with committed challenges, the verifier fixes c before seeing R
+    let committed = match policy {
+        ChallengePolicy::Derived => None,
+        ChallengePolicy::Committed => {
+            let msg = expect_message(transport, "challenge-commit").await?;
+            check_session(transport, &msg, context.session_id(), None).await?;
+            let commitment = reject_if_malformed(transport, ChallengeCommitment::from_hex(&msg.payload).map_err(ProtocolError::from)).await?;
+            debug!(commitment = %msg.payload, "Received challenge commitment");
+            Some(commitment)
+        }
+    };
+
+    // 1) commit R = k*G
+    let R = params.generator() * k.expose_secret();
+    transport.send(Message::commit(&R).in_session(context.session_id())).await?;
+    debug!(R = %point_to_hex(&R), "Sent commitment"); // R is public, the nonce k is never logged
+
+    // 2) read the challenge, canonical and nonzero whichever way it came
+    let c = match &committed {
+        None => {
+            let ch_msg = expect_message(transport, "challenge").await?;
+            check_session(transport, &ch_msg, context.session_id(), None).await?;
+            let c = scalar_from_hex_canonical(&ch_msg.payload)?;
+            debug!(c = %ch_msg.payload, "Received challenge");
+            if c == Scalar::ZERO {
+                return refuse_challenge(transport).await; // s = k would prove nothing, whatever the context says
+            }
+            if c != round_challenge(context, params, &R, &params.public_key(secret).0) {
+                return refuse_challenge(transport).await; // not bound to this session and key, don't answer it
+            }
+            c
+        }
+        Some(commitment) => {
+            let reveal_msg = expect_message(transport, "challenge-reveal").await?;
+            check_session(transport, &reveal_msg, context.session_id(), None).await?;
+            let opening = reject_if_malformed(transport, ChallengeOpening::from_hex(&reveal_msg.payload).map_err(ProtocolError::from)).await?;
+            debug!(c = %scalar_to_hex(&opening.challenge()), "Received revealed challenge");
+            if !opening.opens(commitment) {
+                let _ = transport.send(Message::error(&ProtocolError::ChallengeCommitmentMismatch.to_string())).await;
+                return Err(ProtocolError::ChallengeCommitmentMismatch); // picked after seeing R, or just wrong: don't answer it
+            }
+            opening.challenge()
+        }
+    };
+
+    if guard_nonce {
+        record_nonce_use(&R, &c)?; // must happen before s is computed
+    }
+
+    // 3) respond with s = k + c*x
+    let s = respond(k.expose_secret(), &c, secret);
+    transport.send(Message::response(&s).in_session(context.session_id())).await?;
+    debug!(s = %scalar_to_hex(&s), "Sent response");
+
+    // 4) hear the verdict
+    let result_msg = expect_message(transport, "result").await?;
+    match result_msg.payload.as_str() {
+        "accepted" => Ok(Proof::new(R, c, s)),
+        "rejected" => Err(ProtocolError::VerificationFailed),
+        other => Err(ProtocolError::UnexpectedMessage { expected: "result", got: format!("result {other}") }),
+    }
+}
+
+// c for commitment R to key X; a generator other than G is bound into it too
+fn round_challenge(context: &ChallengeContext, params: &Params, R: &RistrettoPoint, X: &RistrettoPoint) -> Scalar {
+    match params.bound_generator() {
+        None => context.challenge(R, X),
+        Some(H) => context.challenge_for(&[*R], &[*X, *H]),
+    }
+}
+
+// a message tagged for another session is refused; an untagged one is accepted
+// from peers that predate session tags, unless `earlier` (the commitment a
+// response answers) was tagged, in which case the tags must agree
+pub(crate) fn session_matches(msg: &Message, session: &SessionId, earlier: Option<&Message>) -> Result<(), ProtocolError> {
+    let ok = match (msg.session, earlier) {
+        (got, Some(earlier)) => got == earlier.session && got.is_none_or(|id| id == *session),
+        (Some(id), None) => id == *session,
+        (None, None) => true,
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(ProtocolError::SessionMismatch { expected: *session, got: msg.session })
+    }
+}
+
+// `session_matches` on the prover's side, telling the verifier why before giving up
+pub(crate) async fn check_session<T: Transport>(transport: &mut T, msg: &Message, session: &SessionId, earlier: Option<&Message>) -> Result<(), ProtocolError> {
+    reject_if_malformed(transport, session_matches(msg, session, earlier)).await
+}
+
+// tell the verifier we won't answer its challenge, so it isn't left waiting for a response;
+// a prover whose secret doesn't match the verifier's expected key ends up here too
+pub(crate) async fn refuse_challenge<T: Transport, V>(transport: &mut T) -> Result<V, ProtocolError> {
+    let _ = transport.send(Message::error(&ProtocolError::InvalidChallenge.to_string())).await;
+    Err(ProtocolError::InvalidChallenge)
+}
+
+// receive the next message and insist it is of the `expected` kind
+pub(crate) async fn expect_message<T: Transport>(transport: &mut T, expected: &'static str) -> Result<Message, ProtocolError> {
+    let msg = transport.recv().await.map_err(|e| recv_error(e, expected))?;
+    if msg.kind == "error" && expected != "error" {
+        return Err(ProtocolError::Rejected { reason: msg.payload });
+    }
+    if msg.kind != expected {
+        return Err(ProtocolError::UnexpectedMessage { expected, got: msg.kind });
+    }
+    Ok(msg)
+}
+
+pub(crate) fn recv_error(e: io::Error, expected: &'static str) -> ProtocolError {
+    match e.kind() {
+        io::ErrorKind::UnexpectedEof => ProtocolError::ConnectionClosed,
+        io::ErrorKind::TimedOut => ProtocolError::Timeout(TimedOut { waiting_for: expected }),
+        // framing hands an oversized frame or a codec error back wrapped in io::Error
+        io::ErrorKind::InvalidData if e.get_ref().is_some_and(|inner| inner.is::<FrameError>()) => {
+            match *e.into_inner().expect("checked above").downcast::<FrameError>().expect("checked above") {
+                FrameError::TooLarge { max } => ProtocolError::MessageTooLarge { max },
+                other => ProtocolError::Io(io::Error::new(io::ErrorKind::InvalidData, other)),
+            }
+        }
+        io::ErrorKind::InvalidData if e.get_ref().is_some_and(|inner| inner.is::<CodecError>()) => {
+            let inner = e.into_inner().expect("checked above");
+            match *inner.downcast::<CodecError>().expect("checked above") {
+                CodecError::Json(e) => ProtocolError::Serde(e),
+                other => ProtocolError::Codec(other),
+            }
+        }
+        _ => ProtocolError::Io(e),
+    }
+}
+
+// session ids this process has issued as a verifier, most recent last
+static SEEN_SESSIONS: OnceLock<Mutex<SessionCache>> = OnceLock::new();
+
+fn record_session(id: &SessionId) -> Result<(), ProtocolError> {
+    let mut seen = SEEN_SESSIONS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if !seen.insert(*id) {
+        return Err(ProtocolError::ReplayedSession(*id));
+    }
+    Ok(())
+}
+
+/// Commitments of deterministic nonces answered so far, each with the challenge it was answered for
+///
+/// Holds at most `capacity` commitments and forgets the oldest first, so a
+/// commitment older than that is no longer checked.
+#[derive(Debug, Clone)]
+pub struct NonceLedger {
+    capacity: usize,
+    order: VecDeque<[u8; 32]>, // oldest at the front
+    answered: HashMap<[u8; 32], Scalar>,
+}
+
+impl NonceLedger {
+    /// Default number of commitments remembered by `prove_interactive_deterministic`
+    pub const DEFAULT_CAPACITY: usize = 10_000;
+
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "a nonce ledger must hold at least one commitment");
+        Self { capacity, order: VecDeque::with_capacity(capacity), answered: HashMap::with_capacity(capacity) }
+    }
+
+    /// Record that `R` is being answered with the challenge `c`
+    ///
+    /// Fails with [`ProtocolError::NonceReuse`] if `R` was already answered
+    /// for a different challenge; the same challenge again is fine.
+    pub fn record(&mut self, R: &RistrettoPoint, c: &Scalar) -> Result<(), ProtocolError> {
+        let key = R.compress().to_bytes();
+        if let Some(answered) = self.answered.get(&key) {
+            return match bool::from(ct_scalar_eq(answered, c)) {
+                true => Ok(()),
+                false => Err(ProtocolError::NonceReuse),
+            };
+        }
+        if self.order.len() == self.capacity
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.answered.remove(&oldest);
+        }
+        self.order.push_back(key);
+        self.answered.insert(key, *c);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+impl Default for NonceLedger {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+// the ledger behind every deterministic round in this process
+static USED_NONCES: OnceLock<Mutex<NonceLedger>> = OnceLock::new();
+
+fn record_nonce_use(R: &RistrettoPoint, c: &Scalar) -> Result<(), ProtocolError> {
+    USED_NONCES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .record(R, c)
+}
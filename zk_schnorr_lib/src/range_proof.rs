@@ -0,0 +1,167 @@
+//! Range proofs: a Pedersen commitment opens to a value in `[0, 2^n)`
+//!
+//! Bit decomposition. The value `v` is split into bits `b_i`, each committed
+//! as `C_i = b_i*G + r_i*H` with blindings chosen so that
+//! `sum(2^i * r_i) = r`, which makes `sum(2^i * C_i) = v*G + r*H = C`. The
+//! verifier checks that sum, and for every bit a one-of-two Schnorr proof that
+//! the prover knows `log_H` of either `C_i` (the bit is 0) or `C_i - G` (the
+//! bit is 1), without learning which.
+//!
+//! Each bit proof is a Cramer-Damgard-Schoenmakers OR: the branch the prover
+//! can't answer is simulated with a challenge it picks itself, and the two
+//! branch challenges must add up to the Fiat-Shamir one. A proof is
+//! `5 * 32` bytes per bit plus the bit commitment, so this suits small `n`;
+//! Bulletproofs would be logarithmic.
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZkError;
+use crate::pedersen::PedersenParams;
+use crate::schnorr::random_scalar;
+use crate::transcript::TranscriptBuilder;
+
+const DOMAIN: &[u8] = b"zk-schnorr-tls/range-proof/v1";
+
+/// The largest supported bit length
+pub const MAX_BITS: u8 = 64;
+
+/// Proof that each bit commitment holds 0 or 1, and that they add up to the commitment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RangeProof {
+    /// One per bit, least significant first
+    pub bits: Vec<BitProof>,
+}
+
+/// `C_i` and the OR-proof that it commits to 0 or to 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BitProof {
+    /// C_i = b_i*G + r_i*H
+    #[serde(with = "crate::serde_hex::point")]
+    pub commitment: RistrettoPoint,
+    /// Nonce commitment of the "bit is 0" branch
+    #[serde(with = "crate::serde_hex::point")]
+    pub A0: RistrettoPoint,
+    /// Nonce commitment of the "bit is 1" branch
+    #[serde(with = "crate::serde_hex::point")]
+    pub A1: RistrettoPoint,
+    /// Challenge of the "bit is 0" branch; the other is `c - c0`
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub c0: Scalar,
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub s0: Scalar,
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub s1: Scalar,
+}
+
+/// Prove that `params.commit(value, blinding)` holds a value below `2^n_bits`
+///
+/// Fails with `ZkError::InvalidRangeBits` unless `1 <= n_bits <= 64`, and
+/// with `ZkError::ValueOutOfRange` if `value` doesn't fit in `n_bits`.
+pub fn prove(
+    value: u64,
+    blinding: &Scalar,
+    params: &PedersenParams,
+    n_bits: u8,
+    rng: &mut impl RngCore,
+) -> Result<RangeProof, ZkError> {
+    check_bits(n_bits)?;
+    if n_bits < MAX_BITS && value >> n_bits != 0 {
+        return Err(ZkError::ValueOutOfRange { value, n_bits });
+    }
+    let commitment = params.commit(&Scalar::from(value), blinding).0;
+
+    // r_1.. are random; r_0 takes up the difference so sum(2^i * r_i) = r
+    let mut blindings: Vec<Scalar> = (0..n_bits).map(|_| random_scalar(rng)).collect();
+    let rest: Scalar = blindings.iter().enumerate().skip(1).map(|(i, r_i)| power_of_two(i) * r_i).sum();
+    blindings[0] = blinding - rest;
+
+    let bits = blindings
+        .iter()
+        .enumerate()
+        .map(|(i, r_i)| prove_bit(params, &commitment, n_bits, i, (value >> i) & 1 == 1, r_i, rng))
+        .collect();
+    Ok(RangeProof { bits })
+}
+
+/// Check that `commitment` holds a value in `[0, 2^n_bits)`
+pub fn verify(commitment: &RistrettoPoint, proof: &RangeProof, params: &PedersenParams, n_bits: u8) -> bool {
+    if check_bits(n_bits).is_err() || proof.bits.len() != usize::from(n_bits) {
+        return false;
+    }
+    let sum: RistrettoPoint = proof.bits.iter().enumerate().map(|(i, bit)| bit.commitment * power_of_two(i)).sum();
+    sum == *commitment
+        && proof.bits.iter().enumerate().all(|(i, bit)| verify_bit(params, commitment, n_bits, i, bit))
+}
+
+fn check_bits(n_bits: u8) -> Result<(), ZkError> {
+    if n_bits == 0 || n_bits > MAX_BITS {
+        return Err(ZkError::InvalidRangeBits(n_bits));
+    }
+    Ok(())
+}
+
+fn power_of_two(i: usize) -> Scalar {
+    Scalar::from(1u64 << i)
+}
+
+// OR-proof for C_i = r*H (bit 0) or C_i - G = r*H (bit 1), knowing r for the branch `bit`
+fn prove_bit(
+    params: &PedersenParams,
+    commitment: &RistrettoPoint,
+    n_bits: u8,
+    index: usize,
+    bit: bool,
+    r: &Scalar,
+    rng: &mut impl RngCore,
+) -> BitProof {
+    let C_i = params.G * Scalar::from(u64::from(bit)) + params.H * r;
+    let targets = [C_i, C_i - params.G];
+    let (real, fake) = if bit { (1, 0) } else { (0, 1) };
+
+    // simulate the branch we can't answer: pick its challenge and response, solve for A
+    let (c_fake, s_fake) = (random_scalar(rng), random_scalar(rng));
+    let mut A = [RistrettoPoint::default(); 2];
+    A[fake] = params.H * s_fake - targets[fake] * c_fake;
+    let k = random_scalar(rng);
+    A[real] = params.H * k;
+
+    let c = bit_challenge(params, commitment, n_bits, index, &C_i, &A);
+    let c_real = c - c_fake;
+    let s_real = k + c_real * r;
+
+    let (c0, s0, s1) = if bit { (c_fake, s_fake, s_real) } else { (c_real, s_real, s_fake) };
+    BitProof { commitment: C_i, A0: A[0], A1: A[1], c0, s0, s1 }
+}
+
+fn verify_bit(params: &PedersenParams, commitment: &RistrettoPoint, n_bits: u8, index: usize, proof: &BitProof) -> bool {
+    let C_i = proof.commitment;
+    let c = bit_challenge(params, commitment, n_bits, index, &C_i, &[proof.A0, proof.A1]);
+    let c1 = c - proof.c0;
+    let zero_ok = params.H * proof.s0 == proof.A0 + C_i * proof.c0; // s0*H = A0 + c0*C_i
+    let one_ok = params.H * proof.s1 == proof.A1 + (C_i - params.G) * c1; // s1*H = A1 + c1*(C_i - G)
+    zero_ok && one_ok
+}
+
+// c = H(G, H, C, n, i, C_i, A0, A1), so a bit proof can't move to another position or commitment
+fn bit_challenge(
+    params: &PedersenParams,
+    commitment: &RistrettoPoint,
+    n_bits: u8,
+    index: usize,
+    C_i: &RistrettoPoint,
+    A: &[RistrettoPoint; 2],
+) -> Scalar {
+    TranscriptBuilder::new(DOMAIN)
+        .append_point(b"G", &params.G)
+        .append_point(b"H", &params.H)
+        .append_point(b"C", commitment)
+        .append_bytes(b"n", &[n_bits])
+        .append_bytes(b"i", &(index as u64).to_le_bytes())
+        .append_point(b"C_i", C_i)
+        .append_point(b"A0", &A[0])
+        .append_point(b"A1", &A[1])
+        .challenge()
+}
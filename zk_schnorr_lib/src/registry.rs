@@ -0,0 +1,166 @@
+//! The public keys a verifier accepts proofs for, by identity
+//!
+//! A prover names itself in its `hello` (see [`VersionInfo::with_identity`]);
+//! the verifier looks that name up here and challenges against the key it
+//! finds. A registry holding a single key also serves provers that don't
+//! name themselves, as every prover did before identities existed.
+//!
+//! Each key carries the [`Scheme`] its prover speaks: the Ristretto sigma
+//! protocol, or a plain Ed25519 signature over a nonce (see [`crate::ed25519`]).
+//! Serialized, a registry is a JSON object of identities to
+//! `{"scheme": "ed25519", "key": "<hex>"}`; a key without a `scheme` is Ristretto.
+//!
+//! [`VersionInfo::with_identity`]: crate::VersionInfo::with_identity
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ed25519::Ed25519PublicKey;
+use crate::error::DecodeError;
+use crate::schnorr::PublicKey;
+
+/// How a registered prover proves itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scheme {
+    /// The interactive Schnorr protocol over Ristretto
+    #[default]
+    Ristretto,
+    /// An Ed25519 signature over a fresh nonce from the verifier
+    Ed25519,
+}
+
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Scheme::Ristretto => "ristretto",
+            Scheme::Ed25519 => "ed25519",
+        })
+    }
+}
+
+impl FromStr for Scheme {
+    type Err = UnknownScheme;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ristretto" => Ok(Scheme::Ristretto),
+            "ed25519" => Ok(Scheme::Ed25519),
+            other => Err(UnknownScheme(other.to_string())),
+        }
+    }
+}
+
+/// A scheme name that isn't `ristretto` or `ed25519`
+#[derive(Debug, thiserror::Error)]
+#[error("unknown scheme {0:?}, expected ristretto or ed25519")]
+pub struct UnknownScheme(pub String);
+
+/// A registered public key, for the scheme it belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RegistryEntry", into = "RegistryEntry")]
+pub enum RegisteredKey {
+    Ristretto(PublicKey),
+    Ed25519(Ed25519PublicKey),
+}
+
+impl RegisteredKey {
+    pub fn scheme(&self) -> Scheme {
+        match self {
+            RegisteredKey::Ristretto(_) => Scheme::Ristretto,
+            RegisteredKey::Ed25519(_) => Scheme::Ed25519,
+        }
+    }
+}
+
+impl From<PublicKey> for RegisteredKey {
+    fn from(key: PublicKey) -> Self {
+        RegisteredKey::Ristretto(key)
+    }
+}
+
+impl From<Ed25519PublicKey> for RegisteredKey {
+    fn from(key: Ed25519PublicKey) -> Self {
+        RegisteredKey::Ed25519(key)
+    }
+}
+
+// the serialized form: the scheme next to the hex key, Ristretto when left out
+#[derive(Serialize, Deserialize)]
+struct RegistryEntry {
+    #[serde(default)]
+    scheme: Scheme,
+    key: String,
+}
+
+impl TryFrom<RegistryEntry> for RegisteredKey {
+    type Error = DecodeError;
+
+    fn try_from(entry: RegistryEntry) -> Result<Self, Self::Error> {
+        Ok(match entry.scheme {
+            Scheme::Ristretto => RegisteredKey::Ristretto(crate::public_key_from_hex(&entry.key)?),
+            Scheme::Ed25519 => RegisteredKey::Ed25519(entry.key.parse()?),
+        })
+    }
+}
+
+impl From<RegisteredKey> for RegistryEntry {
+    fn from(key: RegisteredKey) -> Self {
+        let hex = match key {
+            RegisteredKey::Ristretto(key) => key.to_string(),
+            RegisteredKey::Ed25519(key) => key.to_string(),
+        };
+        RegistryEntry { scheme: key.scheme(), key: hex }
+    }
+}
+
+/// Identity names mapped to the public keys they must prove
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KeyRegistry {
+    keys: HashMap<String, RegisteredKey>,
+}
+
+impl KeyRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry of one key, used whatever name the prover gives (or none)
+    pub fn single(identity: impl Into<String>, key: impl Into<RegisteredKey>) -> Self {
+        let mut registry = Self::new();
+        registry.insert(identity, key);
+        registry
+    }
+
+    /// Register `key` under `identity`, returning the key it replaces
+    pub fn insert(&mut self, identity: impl Into<String>, key: impl Into<RegisteredKey>) -> Option<RegisteredKey> {
+        self.keys.insert(identity.into(), key.into())
+    }
+
+    pub fn get(&self, identity: &str) -> Option<&RegisteredKey> {
+        self.keys.get(identity)
+    }
+
+    /// The identity and key to verify a prover against, given the identity it claimed
+    ///
+    /// An unnamed prover is only matched when there is exactly one key to pick.
+    pub fn resolve(&self, claimed: Option<&str>) -> Option<(&str, &RegisteredKey)> {
+        match claimed {
+            Some(identity) => self.keys.get_key_value(identity).map(|(name, key)| (name.as_str(), key)),
+            None if self.keys.len() == 1 => self.keys.iter().next().map(|(name, key)| (name.as_str(), key)),
+            None => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
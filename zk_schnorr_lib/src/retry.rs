@@ -0,0 +1,124 @@
+//! Reaching a verifier that may not be listening yet
+//!
+//! [`connect_with_retry`] makes the TCP connection and the TLS handshake,
+//! trying again with exponential backoff while the failure is one that can go
+//! away by itself: nothing listening yet, the connection reset, a handshake
+//! cut short. A handshake that rustls itself rejects (an untrusted
+//! certificate, a wrong server name, no common protocol version) would fail
+//! the same way every time, so it ends the attempts at once.
+
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+use rand_core::{OsRng, RngCore};
+use rustls::ServerName;
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::TlsConnector;
+use tracing::{debug, warn};
+
+/// How many times to retry a connection, and how long to wait in between
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Tries after the first one; 0 gives up after a single failure
+    pub retries: u32,
+    /// Wait before the first retry, doubling for each one after that
+    pub backoff: Duration,
+    /// Longest wait between two tries, however many have failed
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { retries: 4, backoff: Duration::from_millis(200), max_backoff: Duration::from_secs(5) }
+    }
+}
+
+impl RetryPolicy {
+    /// The wait before retry number `retry` (from 1): `backoff * 2^(retry - 1)`, capped
+    /// at `max_backoff`, then drawn from its upper half so provers that failed
+    /// together don't all come back at the same moment
+    pub fn delay(&self, retry: u32) -> Duration {
+        let full = self.backoff.saturating_mul(2u32.saturating_pow(retry.saturating_sub(1))).min(self.max_backoff);
+        let half = full / 2;
+        let jitter = Duration::from_nanos(OsRng.next_u64() % (half.as_nanos() as u64).max(1));
+        half + jitter
+    }
+}
+
+/// Which step of a connection attempt failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectStage {
+    Tcp,
+    Handshake,
+}
+
+impl fmt::Display for ConnectStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConnectStage::Tcp => "TCP connect",
+            ConnectStage::Handshake => "TLS handshake",
+        })
+    }
+}
+
+/// Why [`connect_with_retry`] gave up
+#[derive(Debug, thiserror::Error)]
+pub enum ConnectError {
+    /// Every try failed in a way that might have cleared up
+    #[error("couldn't connect to {addr} after {attempts} attempts ({refused} refused); last failure was the {stage}: {last}")]
+    Exhausted { addr: String, attempts: u32, refused: u32, stage: ConnectStage, last: io::Error },
+    /// The TLS handshake was rejected for a reason that won't change, e.g. a bad certificate
+    #[error("TLS handshake with {addr} failed on attempt {attempt}, not retrying: {error}")]
+    Handshake { addr: String, attempt: u32, error: io::Error },
+}
+
+/// Connect to `addr` and complete a TLS handshake for `server_name`, retrying as `policy` says
+///
+/// Every retry is logged at `warn`. A handshake error that rustls raised
+/// itself (see the [module docs](self)) is returned at once as
+/// [`ConnectError::Handshake`]; otherwise, once the retries are used up,
+/// [`ConnectError::Exhausted`] says how many tries were made, how many were
+/// refused outright, and what went wrong last.
+pub async fn connect_with_retry(
+    addr: &str,
+    tls_connector: &TlsConnector,
+    server_name: ServerName,
+    policy: &RetryPolicy,
+) -> Result<TlsStream<TcpStream>, ConnectError> {
+    let attempts = policy.retries.saturating_add(1);
+    let mut refused = 0;
+    for attempt in 1..=attempts {
+        let (stage, error) = match TcpStream::connect(addr).await {
+            Ok(tcp) => match tls_connector.connect(server_name.clone(), tcp).await {
+                Ok(tls) => {
+                    debug!(addr, attempt, "Connected to the verifier");
+                    return Ok(tls);
+                }
+                Err(error) if is_permanent(&error) => {
+                    return Err(ConnectError::Handshake { addr: addr.to_string(), attempt, error });
+                }
+                Err(error) => (ConnectStage::Handshake, error),
+            },
+            Err(error) => {
+                if error.kind() == io::ErrorKind::ConnectionRefused {
+                    refused += 1;
+                }
+                (ConnectStage::Tcp, error)
+            }
+        };
+        if attempt == attempts {
+            return Err(ConnectError::Exhausted { addr: addr.to_string(), attempts, refused, stage, last: error });
+        }
+        let delay = policy.delay(attempt);
+        warn!(attempt, attempts, %stage, error = %error, retry_in = ?delay, "Couldn't reach the verifier, retrying");
+        tokio::time::sleep(delay).await;
+    }
+    unreachable!("the last attempt always returns")
+}
+
+// rustls's own verdict on the handshake, rather than the connection under it failing
+fn is_permanent(error: &io::Error) -> bool {
+    error.get_ref().is_some_and(|inner| inner.is::<rustls::Error>())
+}
@@ -0,0 +1,113 @@
+//! Ring signatures: sign as one of N keys without revealing which
+//!
+//! This is the Abe-Ohkubo-Suzuki construction. Each challenge is chained
+//! from the previous commitment, `c_{i+1} = H(ring, message, R_i)`, indices
+//! taken mod `n`. The signer at index `j` starts the chain from a real nonce
+//! `R_j = k*G`, simulates every other member `i` by picking `s_i` and solving
+//! `R_i = s_i*G - c_i*X_i`, and closes the ring with `s_j = k + c_j*x`. A
+//! verifier can check the chain closes but not where it was started.
+//!
+//! A ring of one key is an ordinary Schnorr signature: `s*G = R + c*X` with
+//! `c = H(ring, message, R)`.
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ZkError;
+use crate::schnorr::{commit, random_scalar, respond, PublicKey};
+use crate::transcript::TranscriptBuilder;
+
+const DOMAIN: &[u8] = b"zk-schnorr-tls/ring-signature/v1";
+
+/// Why a ring signature couldn't be made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum RingError {
+    #[error("a ring needs at least one public key")]
+    EmptyRing,
+    #[error("signer index {index} is outside a ring of {size} keys")]
+    SignerOutOfRange { index: usize, size: usize },
+    #[error("the signing key doesn't match the ring's key at index {0}")]
+    WrongSignerKey(usize),
+}
+
+/// One `(R_i, c_i, s_i)` per ring member, in the order of the ring's keys
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RingSignature {
+    #[serde(with = "crate::serde_hex::points")]
+    pub R_list: Vec<RistrettoPoint>,
+    #[serde(with = "crate::serde_hex::scalars")]
+    pub c_list: Vec<Scalar>,
+    #[serde(with = "crate::serde_hex::scalars")]
+    pub s_list: Vec<Scalar>,
+}
+
+/// Sign `message` as `public_keys[signer_index]`, whose secret is `signer_key`
+pub fn ring_sign(
+    message: &[u8],
+    public_keys: &[PublicKey],
+    signer_index: usize,
+    signer_key: &Scalar,
+    rng: &mut impl RngCore,
+) -> Result<RingSignature, ZkError> {
+    let n = public_keys.len();
+    if n == 0 {
+        return Err(RingError::EmptyRing.into());
+    }
+    if signer_index >= n {
+        return Err(RingError::SignerOutOfRange { index: signer_index, size: n }.into());
+    }
+    if commit(signer_key) != public_keys[signer_index].0 {
+        return Err(RingError::WrongSignerKey(signer_index).into());
+    }
+
+    let mut R_list = vec![RistrettoPoint::default(); n];
+    let mut c_list = vec![Scalar::ZERO; n];
+    let mut s_list = vec![Scalar::ZERO; n];
+
+    // start the chain at the signer with a real nonce
+    let k = random_scalar(rng);
+    R_list[signer_index] = commit(&k);
+    let mut c = ring_challenge(public_keys, message, &R_list[signer_index]);
+
+    // simulate everyone else, walking round the ring back to the signer
+    for offset in 1..n {
+        let i = (signer_index + offset) % n;
+        c_list[i] = c;
+        s_list[i] = random_scalar(rng);
+        R_list[i] = commit(&s_list[i]) - public_keys[i].0 * c;
+        c = ring_challenge(public_keys, message, &R_list[i]);
+    }
+
+    // close the ring: the challenge that came round is answered with the real secret
+    c_list[signer_index] = c;
+    s_list[signer_index] = respond(&k, &c, signer_key);
+    Ok(RingSignature { R_list, c_list, s_list })
+}
+
+/// Check that `sig` was made by the holder of one of `public_keys` for `message`
+pub fn ring_verify(message: &[u8], public_keys: &[PublicKey], sig: &RingSignature) -> bool {
+    let n = public_keys.len();
+    if n == 0 || sig.R_list.len() != n || sig.c_list.len() != n || sig.s_list.len() != n {
+        return false;
+    }
+    (0..n).all(|i| {
+        let (R, c, s) = (&sig.R_list[i], &sig.c_list[i], &sig.s_list[i]);
+        let equation_ok = commit(s) == R + public_keys[i].0 * c; // s_i*G = R_i + c_i*X_i
+        let chain_ok = ring_challenge(public_keys, message, R) == sig.c_list[(i + 1) % n];
+        equation_ok && chain_ok
+    })
+}
+
+// c = H(ring, message, R), over every key so a signature can't move to another ring
+fn ring_challenge(public_keys: &[PublicKey], message: &[u8], R: &RistrettoPoint) -> Scalar {
+    let mut transcript = TranscriptBuilder::new(DOMAIN);
+    transcript.append_bytes(b"n", &(public_keys.len() as u64).to_le_bytes());
+    for X in public_keys {
+        transcript.append_point(b"X", X);
+    }
+    transcript.append_bytes(b"message", message);
+    transcript.append_point(b"R", R);
+    transcript.challenge()
+}
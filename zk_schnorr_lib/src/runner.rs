@@ -0,0 +1,234 @@
+//! High-level entry points for a whole prover or verifier session
+//!
+//! `run_prover` and `run_verifier` do everything the binaries do once a
+//! connection is up: the `hello` version exchange, then the proof rounds.
+//! They work over any [`Transport`], so the same calls drive a TLS stream, a
+//! plain TCP socket or an in-memory `tokio::io::duplex` pipe wrapped in a
+//! [`StreamTransport`](crate::StreamTransport).
+//!
+//! A prover can ask for committed challenges with
+//! [`ProverOptions::committed_challenge`]; `run_verifier` and its variants
+//! always agree to them.
+//!
+//! A prover registered under [`Scheme::Ed25519`](crate::Scheme::Ed25519)
+//! runs `run_prover_ed25519` instead, answering a single nonce with a signature.
+
+use tracing::{debug, warn};
+
+use crate::audit::{NullLog, ProofLog};
+use crate::challenge_commitment::{ChallengePolicy, COMMITTED_CHALLENGE};
+use crate::ed25519::{Ed25519PublicKey, Ed25519SigningKey};
+use crate::error::ProtocolError;
+use crate::params::Params;
+use crate::protocol::SchnorrProtocol;
+use crate::registry::{KeyRegistry, RegisteredKey};
+use crate::schnorr::{KeyPair, PublicKey};
+use crate::transport::Transport;
+use crate::version::{Version, VersionInfo};
+use crate::Message;
+
+/// How a session went, seen from either end
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofOutcome {
+    /// The protocol version both sides agreed on
+    pub version: Version,
+    /// Whether each round was accepted, in order
+    pub rounds: Vec<bool>,
+}
+
+impl ProofOutcome {
+    /// At least one round, and every round accepted
+    pub fn accepted(&self) -> bool {
+        !self.rounds.is_empty() && self.rounds.iter().all(|ok| *ok)
+    }
+
+    pub fn verified(&self) -> usize {
+        self.rounds.iter().filter(|ok| **ok).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.rounds.len() - self.verified()
+    }
+}
+
+/// How the prover runs its session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProverOptions {
+    /// Number of proofs to send over the one connection
+    pub rounds: usize,
+    /// Derive each nonce from the secret key and session instead of the OS RNG
+    pub deterministic_nonce: bool,
+    /// The generator to prove over, named in the `hello`; the verifier must use the same one
+    pub params: Params,
+    /// Offer the committed-challenge extension, used if the verifier supports it too
+    pub committed_challenge: bool,
+}
+
+impl Default for ProverOptions {
+    fn default() -> Self {
+        Self { rounds: 1, deterministic_nonce: false, params: Params::default(), committed_challenge: false }
+    }
+}
+
+/// Prove knowledge of `keypair`'s secret in a single round
+pub async fn run_prover<T: Transport>(transport: &mut T, keypair: &KeyPair) -> Result<ProofOutcome, ProtocolError> {
+    run_prover_with(transport, keypair, &ProverOptions::default()).await
+}
+
+/// Open with a `hello`, then prove `options.rounds` times
+///
+/// A round the verifier rejects is recorded as `false` and the session goes
+/// on, just as `verify_interactive` does on the other end. Any other error
+/// ends the session.
+pub async fn run_prover_with<T: Transport>(
+    transport: &mut T,
+    keypair: &KeyPair,
+    options: &ProverOptions,
+) -> Result<ProofOutcome, ProtocolError> {
+    prove_session(transport, keypair, options, &VersionInfo::current().with_params(&options.params)).await
+}
+
+/// `run_prover_with`, naming `identity` in the `hello` so a verifier holding
+/// several keys knows which one to check against
+pub async fn run_prover_as<T: Transport>(
+    transport: &mut T,
+    keypair: &KeyPair,
+    identity: &str,
+    options: &ProverOptions,
+) -> Result<ProofOutcome, ProtocolError> {
+    let hello = VersionInfo::current().with_identity(identity).with_params(&options.params);
+    prove_session(transport, keypair, options, &hello).await
+}
+
+async fn prove_session<T: Transport>(
+    transport: &mut T,
+    keypair: &KeyPair,
+    options: &ProverOptions,
+    hello: &VersionInfo,
+) -> Result<ProofOutcome, ProtocolError> {
+    let hello = match options.committed_challenge {
+        true => hello.clone().with_feature(COMMITTED_CHALLENGE),
+        false => hello.clone(),
+    };
+    let (version, reply) = SchnorrProtocol::send_hello_reply(transport, &hello).await?;
+    let policy = ChallengePolicy::negotiated(&hello, &reply);
+    debug!(?policy, "Challenge policy agreed");
+    let context = SchnorrProtocol::open_prover_session(transport).await?;
+
+    let mut rounds = Vec::with_capacity(options.rounds);
+    for round in 1..=options.rounds {
+        let params = &options.params;
+        let proved = if options.deterministic_nonce {
+            SchnorrProtocol::prove_round_deterministic_with_policy(transport, &context, keypair, round as u64, params, policy).await
+        } else {
+            SchnorrProtocol::prove_round_with_policy(transport, &context, keypair, params, policy).await
+        };
+        match proved {
+            Ok(_) => rounds.push(true),
+            Err(ProtocolError::VerificationFailed) => {
+                warn!(round, "Verifier rejected the proof");
+                rounds.push(false);
+            }
+            Err(e) => return Err(e),
+        }
+        debug!(round, "Round complete");
+    }
+    Ok(ProofOutcome { version, rounds })
+}
+
+/// Open with a `hello`, naming `identity` if given, and sign the verifier's nonce with `key`
+///
+/// The Ed25519 counterpart of `run_prover_as`: the session is one round, `false`
+/// if the verifier rejected the signature.
+pub async fn run_prover_ed25519<T: Transport>(
+    transport: &mut T,
+    key: &Ed25519SigningKey,
+    identity: Option<&str>,
+) -> Result<ProofOutcome, ProtocolError> {
+    let hello = match identity {
+        Some(identity) => VersionInfo::current().with_identity(identity),
+        None => VersionInfo::current(),
+    };
+    let version = SchnorrProtocol::send_hello(transport, &hello).await?;
+    let context = SchnorrProtocol::open_prover_session(transport).await?;
+    let verified = match SchnorrProtocol::prove_ed25519_round(transport, &context, key).await {
+        Ok(_) => true,
+        Err(ProtocolError::VerificationFailed) => {
+            warn!("Verifier rejected the signature");
+            false
+        }
+        Err(e) => return Err(e),
+    };
+    Ok(ProofOutcome { version, rounds: vec![verified] })
+}
+
+/// Accept the prover's `hello`, then verify proofs until it hangs up
+pub async fn run_verifier<T: Transport>(transport: &mut T, expected_key: &PublicKey) -> Result<ProofOutcome, ProtocolError> {
+    run_verifier_logged(transport, expected_key, &NullLog).await
+}
+
+/// `run_verifier`, recording every completed round in `log`
+pub async fn run_verifier_logged<T: Transport>(
+    transport: &mut T,
+    expected_key: &PublicKey,
+    log: &dyn ProofLog,
+) -> Result<ProofOutcome, ProtocolError> {
+    run_verifier_with_params(transport, expected_key, &Params::default(), log).await
+}
+
+/// `run_verifier_logged` over `params`' generator, with `expected_key` a key for it
+///
+/// A prover whose `hello` names another generator is sent an `error` naming
+/// ours and the session ends with `NegotiationError::GeneratorMismatch`
+/// before any proof is exchanged.
+pub async fn run_verifier_with_params<T: Transport>(
+    transport: &mut T,
+    expected_key: &PublicKey,
+    params: &Params,
+    log: &dyn ProofLog,
+) -> Result<ProofOutcome, ProtocolError> {
+    let ours = VersionInfo::current().with_params(params).with_feature(COMMITTED_CHALLENGE);
+    let (version, offer) = SchnorrProtocol::accept_hello_offer(transport, &ours).await?;
+    let policy = ChallengePolicy::negotiated(&ours, &offer);
+    let rounds = SchnorrProtocol::verify_interactive_with_policy(transport, expected_key, params, log, policy).await?;
+    Ok(ProofOutcome { version, rounds })
+}
+
+/// `run_verifier_logged` against whichever key in `registry` the prover names in its `hello`
+///
+/// Returns the identity the prover was verified as along with the outcome. A
+/// prover naming no registered identity (or none at all, when the registry
+/// holds several keys) is sent an `error` and the session ends with
+/// `ProtocolError::UnknownIdentity` before any proof is exchanged.
+///
+/// An identity registered with an Ed25519 key is sent one nonce to sign
+/// instead of being asked for proofs; that session has a single round and
+/// nothing is recorded in `log`, which only holds Schnorr transcripts.
+pub async fn run_verifier_registry<T: Transport>(
+    transport: &mut T,
+    registry: &KeyRegistry,
+    log: &dyn ProofLog,
+) -> Result<(String, ProofOutcome), ProtocolError> {
+    let ours = VersionInfo::current().with_feature(COMMITTED_CHALLENGE);
+    let (version, offer) = SchnorrProtocol::accept_hello_offer(transport, &ours).await?;
+    let Some((identity, expected_key)) = registry.resolve(offer.identity.as_deref()) else {
+        let unknown = ProtocolError::UnknownIdentity(offer.identity);
+        let _ = transport.send(Message::error(&unknown.to_string())).await;
+        return Err(unknown);
+    };
+    debug!(identity, scheme = %expected_key.scheme(), "Prover identified");
+    let rounds = match expected_key {
+        RegisteredKey::Ristretto(key) => {
+            let policy = ChallengePolicy::negotiated(&ours, &offer);
+            SchnorrProtocol::verify_interactive_with_policy(transport, key, &Params::default(), log, policy).await?
+        }
+        RegisteredKey::Ed25519(key) => verify_ed25519_session(transport, key).await?,
+    };
+    Ok((identity.to_string(), ProofOutcome { version, rounds }))
+}
+
+// the whole session for an Ed25519 prover: the context, then one signed nonce
+async fn verify_ed25519_session<T: Transport>(transport: &mut T, key: &Ed25519PublicKey) -> Result<Vec<bool>, ProtocolError> {
+    let context = crate::protocol::send_context(transport).await?;
+    Ok(vec![SchnorrProtocol::verify_ed25519_round(transport, &context, key).await?])
+}
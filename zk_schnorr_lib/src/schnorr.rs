@@ -0,0 +1,421 @@
+//! Core Schnorr proof types and verification
+//!
+//! The interactive protocol produces a transcript `(R, c, s)`. Anyone holding
+//! the transcript and the public key `X` can re-check the equation `s*G = R + c*X`.
+//! `SchnorrProof` is the non-interactive variant with a hash-derived challenge.
+//! Both work over the Ristretto basepoint by default or any other generator.
+
+use alloc::vec::Vec;
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT; // the standard generator point G
+#[cfg(feature = "std")]
+use base64::prelude::{Engine, BASE64_STANDARD};
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul}; // multi-scalar multiplication and identity check
+#[cfg(feature = "std")]
+use rand_core::OsRng;
+use rand_core::{CryptoRngCore, RngCore};
+use sha2::{Digest, Sha512};
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::error::ProofDecodeError;
+use crate::{ct_scalar_eq, proof_eq_ct, PointDecodeError, ScalarDecodeError};
+use crate::secret::SecretScalar;
+use crate::session::SessionId;
+#[cfg(feature = "std")]
+use crate::signature::{self, Signature};
+use crate::transcript::{transcript_challenge, TranscriptBuilder};
+
+/// The domain [`KeyPair::from_seed`] hashes its seed under
+pub const KEYPAIR_SEED_DOMAIN: &[u8] = b"zk-schnorr-tls/keypair/v1";
+
+/// A secret scalar `x` together with its public key `X = x*G`
+#[derive(Clone)]
+pub struct KeyPair {
+    secret: SecretScalar,
+    public: RistrettoPoint,
+}
+
+impl KeyPair {
+    /// Build a keypair around an existing secret scalar
+    pub fn from_secret(secret: Scalar) -> Self {
+        let secret = SecretScalar::new(secret);
+        Self { public: secret.mul_base(), secret }
+    }
+
+    /// Derive a keypair from a seed with [`scalar_from_seed`] under [`KEYPAIR_SEED_DOMAIN`]
+    pub fn from_seed(seed: &[u8]) -> Self {
+        Self::from_secret(scalar_from_seed(KEYPAIR_SEED_DOMAIN, seed))
+    }
+
+    /// Generate a fresh random keypair
+    pub fn generate(rng: &mut impl CryptoRngCore) -> Self {
+        Self::from_secret(Scalar::random(rng))
+    }
+
+    /// The secret scalar x - never send this anywhere
+    pub fn secret(&self) -> &SecretScalar {
+        &self.secret
+    }
+
+    /// The public key X = x*G
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(self.public)
+    }
+
+    /// Sign `msg` with a fresh nonce from the OS RNG; check it with `verify_signature`
+    #[cfg(feature = "std")]
+    pub fn sign(&self, msg: &[u8]) -> Signature {
+        signature::sign(self.secret.expose_secret(), msg, &mut OsRng)
+    }
+}
+
+// only the public half is printed so the secret can't end up in logs
+impl core::fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("public", &crate::point_to_hex(&self.public))
+            .finish_non_exhaustive()
+    }
+}
+
+/// A public key `X = x*G`
+///
+/// Prints and parses as compressed hex, and can be used as a `HashMap` key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey(pub RistrettoPoint);
+
+/// Secrets below this are ones tests and examples use (`Scalar::from(5u64)` and
+/// the like), so the keys they give are known to anyone who has read the code
+pub const WEAK_SECRET_BOUND: u64 = 256;
+
+impl PublicKey {
+    /// Whether this is `k*G` for some `k` below [`WEAK_SECRET_BOUND`], the identity included
+    ///
+    /// Variable time, which is fine: a public key isn't secret.
+    pub fn is_weak(&self) -> bool {
+        let mut multiple = RistrettoPoint::default();
+        for _ in 0..WEAK_SECRET_BOUND {
+            if multiple == self.0 {
+                return true;
+            }
+            multiple += RISTRETTO_BASEPOINT_POINT;
+        }
+        false
+    }
+}
+
+impl core::ops::Deref for PublicKey {
+    type Target = RistrettoPoint;
+
+    fn deref(&self) -> &RistrettoPoint {
+        &self.0
+    }
+}
+
+impl From<RistrettoPoint> for PublicKey {
+    fn from(point: RistrettoPoint) -> Self {
+        Self(point)
+    }
+}
+
+impl core::fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&crate::point_to_hex(&self.0))
+    }
+}
+
+impl core::str::FromStr for PublicKey {
+    type Err = core::fmt::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        crate::point_from_hex(s).map(Self).map_err(|_| core::fmt::Error)
+    }
+}
+
+// hash the canonical compressed encoding, which is what `Eq` compares
+impl core::hash::Hash for PublicKey {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.compress().as_bytes().hash(state);
+    }
+}
+
+impl serde::Serialize for PublicKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serde_hex::point::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for PublicKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_hex::point::deserialize(deserializer).map(Self)
+    }
+}
+
+/// One completed run of the Schnorr identification protocol
+#[derive(Debug, Clone, Copy)]
+pub struct Proof {
+    /// The prover's commitment R = k*G
+    pub commitment: RistrettoPoint,
+    /// The verifier's challenge c
+    pub challenge: Scalar,
+    /// The prover's response s = k + c*x
+    pub response: Scalar,
+}
+
+impl Proof {
+    pub fn new(commitment: RistrettoPoint, challenge: Scalar, response: Scalar) -> Self {
+        Self { commitment, challenge, response }
+    }
+
+    /// Check the verification equation `s*G = R + c*X` for this proof
+    pub fn verify(&self, public_key: &PublicKey) -> bool {
+        self.verify_with_generator(public_key, &RISTRETTO_BASEPOINT_POINT)
+    }
+
+    /// Check `s*H = R + c*X` for a proof made with a custom generator `H`
+    pub fn verify_with_generator(&self, public_key: &PublicKey, generator: &RistrettoPoint) -> bool {
+        let left_side = generator * self.response; // s*H
+        let right_side = self.commitment + (public_key.0 * self.challenge); // R + c*X
+        bool::from(left_side.ct_eq(&right_side)) // the final check is constant-time too
+    }
+}
+
+// the response is secret-derived, so every field is compared in full whatever the first difference
+impl ConstantTimeEq for Proof {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.commitment.compress().as_bytes().ct_eq(other.commitment.compress().as_bytes())
+            & ct_scalar_eq(&self.challenge, &other.challenge)
+            & ct_scalar_eq(&self.response, &other.response)
+    }
+}
+
+impl PartialEq for Proof {
+    fn eq(&self, other: &Self) -> bool {
+        proof_eq_ct(self, other)
+    }
+}
+
+impl Eq for Proof {}
+
+/// Non-interactive Schnorr proof of knowledge of a discrete log (Fiat-Shamir)
+///
+/// The verifier's random challenge is replaced by one squeezed from a merlin
+/// transcript over the generator, `X` and `R` (see [`crate::transcript`]), so the proof `(R, s)` can be checked
+/// by anyone without interaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchnorrProof {
+    /// Commitment R = k*G
+    pub commitment: RistrettoPoint,
+    /// Response s = k + c*x
+    pub response: Scalar,
+}
+
+impl SchnorrProof {
+    /// Prove knowledge of `secret` for the public key `secret * G` (the basepoint)
+    pub fn prove(secret: &Scalar, rng: &mut impl RngCore) -> Self {
+        Self::prove_with_generator(secret, &RISTRETTO_BASEPOINT_POINT, rng)
+    }
+
+    /// Prove knowledge of `secret` for the public key `secret * generator`
+    pub fn prove_with_generator(secret: &Scalar, generator: &RistrettoPoint, rng: &mut impl RngCore) -> Self {
+        let public_key = generator * secret;
+        let k = random_scalar(rng);
+        let commitment = generator * k;
+        let c = fiat_shamir_challenge(generator, &public_key, &commitment);
+        Self { commitment, response: respond(&k, &c, secret) }
+    }
+
+    /// Verify against a public key over the basepoint
+    pub fn verify(&self, public_key: &PublicKey) -> bool {
+        self.verify_detailed(public_key).valid
+    }
+
+    /// Verify against a public key `X = x * generator`
+    pub fn verify_with_generator(&self, public_key: &PublicKey, generator: &RistrettoPoint) -> bool {
+        self.verify_detailed_with_generator(public_key, generator).valid
+    }
+
+    /// `verify`, also returning the challenge and the commitment it reconstructed
+    pub fn verify_detailed(&self, public_key: &PublicKey) -> VerifyOutcome {
+        self.verify_detailed_with_generator(public_key, &RISTRETTO_BASEPOINT_POINT)
+    }
+
+    /// `verify_with_generator`, also returning the challenge and the commitment it reconstructed
+    pub fn verify_detailed_with_generator(&self, public_key: &PublicKey, generator: &RistrettoPoint) -> VerifyOutcome {
+        let challenge = fiat_shamir_challenge(generator, public_key, &self.commitment);
+        let recomputed_commitment = generator * self.response - public_key.0 * challenge; // R' = s*H - c*X
+        VerifyOutcome { valid: recomputed_commitment == self.commitment, challenge, recomputed_commitment }
+    }
+
+    /// Prove knowledge of `secret` (over the basepoint) for one session only
+    ///
+    /// The session id goes into the challenge, so the proof fails
+    /// `verify_in_session` for any other session. Recording accepted ids in a
+    /// [`SessionCache`](crate::SessionCache) stops the same proof being replayed.
+    pub fn prove_in_session(secret: &Scalar, session: &SessionId, rng: &mut impl RngCore) -> Self {
+        let public_key = RistrettoPoint::mul_base(secret);
+        let k = random_scalar(rng);
+        let commitment = commit(&k);
+        let c = session_challenge(session, &public_key, &commitment);
+        Self { commitment, response: respond(&k, &c, secret) }
+    }
+
+    /// Verify a proof made with `prove_in_session` for `session`
+    pub fn verify_in_session(&self, public_key: &PublicKey, session: &SessionId) -> bool {
+        let c = session_challenge(session, public_key, &self.commitment);
+        Proof::new(self.commitment, c, self.response).verify(public_key)
+    }
+
+    /// Fixed-width encoding: compressed `R` followed by `s`, 32 bytes each
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(self.commitment.compress().as_bytes());
+        bytes[32..].copy_from_slice(self.response.as_bytes());
+        bytes
+    }
+
+    /// Decode `to_bytes` output, rejecting invalid points and non-canonical scalars
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofDecodeError> {
+        if bytes.len() != 64 {
+            return Err(ProofDecodeError::InvalidLength(bytes.len()));
+        }
+        let mut r_bytes = [0u8; 32];
+        r_bytes.copy_from_slice(&bytes[..32]);
+        let commitment = CompressedRistretto(r_bytes).decompress().ok_or(PointDecodeError::InvalidPoint)?;
+
+        let mut s_bytes = [0u8; 32];
+        s_bytes.copy_from_slice(&bytes[32..]);
+        let response = Option::from(Scalar::from_canonical_bytes(s_bytes)).ok_or(ScalarDecodeError::NonCanonical)?;
+        Ok(Self { commitment, response })
+    }
+
+    /// `to_bytes` as standard, padded base64 (88 characters)
+    #[cfg(feature = "std")]
+    pub fn to_base64(&self) -> String {
+        BASE64_STANDARD.encode(self.to_bytes())
+    }
+
+    /// Decode a proof from `to_base64` output
+    #[cfg(feature = "std")]
+    pub fn from_base64(s: &str) -> Result<Self, ProofDecodeError> {
+        Self::from_bytes(&BASE64_STANDARD.decode(s)?)
+    }
+}
+
+/// Check a [`SchnorrProof`] in its [`to_bytes`](SchnorrProof::to_bytes) form against a compressed public key
+///
+/// The same check as `SchnorrProof::from_bytes` followed by `verify`, but on
+/// fixed-size buffers with nothing allocated, so it runs on devices without a
+/// heap. An undecodable key, commitment or response is simply not valid.
+pub fn verify(public_key: &[u8; 32], proof: &[u8; 64]) -> bool {
+    let (r_bytes, s_bytes) = proof.split_at(32);
+    let Some(X) = CompressedRistretto(*public_key).decompress() else { return false };
+    let Some(R) = CompressedRistretto(r_bytes.try_into().expect("32 bytes")).decompress() else { return false };
+    let Some(s) = Option::<Scalar>::from(Scalar::from_canonical_bytes(s_bytes.try_into().expect("32 bytes"))) else { return false };
+    let c = fiat_shamir_challenge(&RISTRETTO_BASEPOINT_POINT, &X, &R);
+    RistrettoPoint::vartime_double_scalar_mul_basepoint(&-c, &X, &s) == R // s*G - c*X, public inputs only
+}
+
+/// The working of one [`SchnorrProof`] check, for audit logs and debugging
+///
+/// A proof is valid when `R' = s*G - c*X` equals its commitment `R`; for one
+/// that fails, comparing the two shows whether the challenge or the response
+/// is off. Every field is public, so the outcome can be stored as a trace
+/// that anyone holding the proof and public key can recompute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct VerifyOutcome {
+    /// Whether `recomputed_commitment` equals the proof's commitment
+    pub valid: bool,
+    /// The Fiat-Shamir challenge c, recomputed from the generator, `X` and `R`
+    #[serde(with = "crate::serde_hex::scalar")]
+    pub challenge: Scalar,
+    /// `R' = s*G - c*X`
+    #[serde(with = "crate::serde_hex::point")]
+    pub recomputed_commitment: RistrettoPoint,
+}
+
+/// Derive an independent generator by hashing a domain string to a curve point
+///
+/// Nobody knows the discrete log of the result with respect to the basepoint,
+/// and both parties get the same point from the same domain string.
+pub fn hash_to_point(domain: &[u8]) -> RistrettoPoint {
+    RistrettoPoint::hash_from_bytes::<Sha512>(domain)
+}
+
+/// Hash `seed` to a scalar under a domain tag, with SHA-512
+///
+/// The hash covers the domain's length, the domain, then the seed, so one
+/// master seed yields unrelated scalars under different domains and no
+/// `(domain, seed)` split of the same bytes collides with another.
+pub fn scalar_from_seed(domain: &[u8], seed: &[u8]) -> Scalar {
+    let hash = Sha512::new().chain_update((domain.len() as u64).to_le_bytes()).chain_update(domain).chain_update(seed);
+    Scalar::from_hash(hash)
+}
+
+// c squeezed from a transcript over (generator, X, R)
+pub(crate) fn fiat_shamir_challenge(generator: &RistrettoPoint, public_key: &RistrettoPoint, commitment: &RistrettoPoint) -> Scalar {
+    transcript_challenge(b"zk-schnorr-tls/schnorr-proof/v2", &[(b"G", generator), (b"X", public_key), (b"R", commitment)])
+}
+
+// c squeezed from a transcript over (session id, G, X, R)
+pub(crate) fn session_challenge(session: &SessionId, public_key: &RistrettoPoint, commitment: &RistrettoPoint) -> Scalar {
+    TranscriptBuilder::new(b"zk-schnorr-tls/schnorr-proof/session/v1")
+        .append_bytes(b"session_id", session.as_bytes())
+        .append_point(b"G", &RISTRETTO_BASEPOINT_POINT)
+        .append_point(b"X", public_key)
+        .append_point(b"R", commitment)
+        .challenge()
+}
+
+/// Verify many `(proof, public key)` pairs at once
+///
+/// Each proof is weighted by a random scalar `a_i` and the whole batch is checked
+/// with a single multi-scalar multiplication:
+/// `(sum a_i*s_i)*G - sum a_i*R_i - sum (a_i*c_i)*X_i == 0`.
+/// The random weights stop an attacker from crafting invalid proofs whose errors
+/// cancel out. Returns `false` for an empty batch.
+pub fn batch_verify(proofs: &[(Proof, PublicKey)], rng: &mut impl RngCore) -> bool {
+    if proofs.is_empty() {
+        return false;
+    }
+
+    let mut generator_weight = Scalar::ZERO; // accumulates sum a_i*s_i
+    let mut scalars = Vec::with_capacity(2 * proofs.len() + 1);
+    let mut points = Vec::with_capacity(2 * proofs.len() + 1);
+
+    for (proof, public_key) in proofs {
+        let a = random_scalar(rng);
+        generator_weight += a * proof.response;
+        scalars.push(-a); // -a_i * R_i
+        points.push(proof.commitment);
+        scalars.push(-(a * proof.challenge)); // -(a_i*c_i) * X_i
+        points.push(public_key.0);
+    }
+    scalars.push(generator_weight);
+    points.push(RISTRETTO_BASEPOINT_POINT);
+
+    // vartime is fine here: every input is public
+    RistrettoPoint::vartime_multiscalar_mul(scalars, points).is_identity()
+}
+
+/// The prover's opening move: the commitment `R = k*G` to nonce `k`
+///
+/// Together with [`respond`] and [`Proof::verify`] this is one whole round of
+/// the interactive protocol with no IO in the way.
+pub fn commit(k: &Scalar) -> RistrettoPoint {
+    RistrettoPoint::mul_base(k)
+}
+
+/// The prover's answer to challenge `c`: `s = k + c*x`
+pub fn respond(k: &Scalar, challenge: &Scalar, secret: &Scalar) -> Scalar {
+    k + challenge * secret
+}
+
+// draw a uniformly random scalar from 64 bytes of rng output
+pub(crate) fn random_scalar(rng: &mut impl RngCore) -> Scalar {
+    let mut wide = [0u8; 64];
+    rng.fill_bytes(&mut wide);
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
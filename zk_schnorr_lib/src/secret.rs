@@ -0,0 +1,48 @@
+//! `SecretScalar`: a scalar that is wiped from memory when dropped
+//!
+//! Used for the long-term secret `x` and the per-round nonce `k`. It has no
+//! `Debug`, `Display` or `Serialize` impl, so it can't end up in logs or on
+//! the wire by accident; reaching the raw `Scalar` takes an explicit
+//! `expose_secret()` call.
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::CryptoRngCore;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// A secret scalar, zeroized on drop
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct SecretScalar(Scalar);
+
+impl SecretScalar {
+    pub fn new(scalar: Scalar) -> Self {
+        Self(scalar)
+    }
+
+    /// Draw a fresh uniformly random secret
+    pub fn random(rng: &mut impl CryptoRngCore) -> Self {
+        Self(Scalar::random(rng))
+    }
+
+    /// The raw scalar, for arithmetic - don't copy it anywhere long-lived
+    pub fn expose_secret(&self) -> &Scalar {
+        &self.0
+    }
+
+    /// `self * G` over the Ristretto basepoint
+    pub fn mul_base(&self) -> RistrettoPoint {
+        RISTRETTO_BASEPOINT_POINT * self.0
+    }
+
+    /// `self * point`
+    pub fn mul_point(&self, point: &RistrettoPoint) -> RistrettoPoint {
+        point * self.0
+    }
+}
+
+impl From<Scalar> for SecretScalar {
+    fn from(scalar: Scalar) -> Self {
+        Self(scalar)
+    }
+}
@@ -0,0 +1,141 @@
+//! `#[serde(with = ...)]` helpers that encode curve values with the hex helpers
+//!
+//! Points use the 32-byte compressed form and scalars their 32-byte canonical
+//! encoding, both as lowercase hex - the same strings that go into `Message`.
+//!
+//! [`SerdePoint`] and [`SerdeScalar`] wrap a value in the same encoding for
+//! places a `with` attribute can't reach, such as map keys or generic fields.
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A `RistrettoPoint` that serializes as compressed hex
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerdePoint(pub RistrettoPoint);
+
+/// A `Scalar` that serializes as hex; only canonical encodings deserialize
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerdeScalar(pub Scalar);
+
+impl From<RistrettoPoint> for SerdePoint {
+    fn from(point: RistrettoPoint) -> Self {
+        Self(point)
+    }
+}
+
+impl From<SerdePoint> for RistrettoPoint {
+    fn from(point: SerdePoint) -> Self {
+        point.0
+    }
+}
+
+impl From<Scalar> for SerdeScalar {
+    fn from(scalar: Scalar) -> Self {
+        Self(scalar)
+    }
+}
+
+impl From<SerdeScalar> for Scalar {
+    fn from(scalar: SerdeScalar) -> Self {
+        scalar.0
+    }
+}
+
+impl Serialize for SerdePoint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        point::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SerdePoint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        point::deserialize(deserializer).map(Self)
+    }
+}
+
+impl Serialize for SerdeScalar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        scalar::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SerdeScalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        scalar::deserialize(deserializer).map(Self)
+    }
+}
+
+/// Serialize a `RistrettoPoint` as compressed hex
+pub mod point {
+    use alloc::string::String;
+
+    use curve25519_dalek::ristretto::RistrettoPoint;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(point: &RistrettoPoint, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&crate::point_to_hex(point))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<RistrettoPoint, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        crate::point_from_hex(&hex).map_err(de::Error::custom)
+    }
+}
+
+/// Serialize a `Scalar` as hex; only canonical encodings deserialize
+pub mod scalar {
+    use alloc::string::String;
+
+    use curve25519_dalek::scalar::Scalar;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(scalar: &Scalar, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&crate::scalar_to_hex(scalar))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Scalar, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        crate::scalar_from_hex_canonical(&hex).map_err(de::Error::custom)
+    }
+}
+
+/// Serialize a `Vec<RistrettoPoint>` as a list of compressed hex strings
+pub mod points {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use curve25519_dalek::ristretto::RistrettoPoint;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(points: &[RistrettoPoint], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(points.iter().map(crate::point_to_hex))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<RistrettoPoint>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|hex| crate::point_from_hex(hex).map_err(de::Error::custom))
+            .collect()
+    }
+}
+
+/// Serialize a `Vec<Scalar>` as a list of hex strings; only canonical encodings deserialize
+pub mod scalars {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use curve25519_dalek::scalar::Scalar;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(scalars: &[Scalar], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(scalars.iter().map(crate::scalar_to_hex))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Scalar>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|hex| crate::scalar_from_hex_canonical(hex).map_err(de::Error::custom))
+            .collect()
+    }
+}
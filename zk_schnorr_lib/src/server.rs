@@ -0,0 +1,697 @@
+//! An embeddable verifier server (`server` feature)
+//!
+//! [`VerifierServer`] is everything the `verifier` binary does, as a library:
+//! accept TCP connections, admit them through an optional
+//! [`ConnectionLimiter`], complete the TLS handshake, verify the prover
+//! against a [`KeyRegistry`], and hand each result to a callback as a
+//! [`VerificationEvent`] so the embedding service can grant access or audit.
+//! With a [`session_log`](VerifierServerBuilder::session_log) every session is
+//! also written to an append-only [`AuditLog`].
+//!
+//! ```no_run
+//! # async fn example(config: rustls::ServerConfig, registry: zk_schnorr_lib::KeyRegistry) -> std::io::Result<()> {
+//! use zk_schnorr_lib::server::VerifierServer;
+//!
+//! let server = VerifierServer::builder()
+//!     .bind("127.0.0.1:4433".parse().unwrap())
+//!     .tls(config)
+//!     .expected_keys(registry)
+//!     .on_result(|event| println!("{:?} accepted: {}", event.identity, event.accepted))
+//!     .build()
+//!     .unwrap();
+//! let handle = server.spawn().await?;
+//! // ...
+//! handle.shutdown().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use rustls::ServerConfig;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio_rustls::TlsAcceptor;
+use tracing::{error, info, info_span, warn, Instrument};
+
+use crate::audit::{NullLog, ProofLog, SessionAuditRecord, SessionOutcome};
+use crate::codec::WireFormat;
+use crate::error::{ProtocolError, ZkError};
+use crate::framing::DEFAULT_MAX_MESSAGE_LEN;
+use crate::limits::ConnectionLimiter;
+use crate::metrics::{Metrics, MetricsSnapshot};
+use crate::proof_store::ProofEntry;
+use crate::registry::KeyRegistry;
+use crate::runner::{run_verifier_registry, ProofOutcome};
+use crate::schnorr::{Proof, PublicKey};
+use crate::session::SessionId;
+use crate::session_log::AuditLog;
+use crate::timeouts::{with_timeout, Timeouts};
+use crate::transport::{StreamTransport, TimeoutTransport, Transport};
+
+/// How long sessions in flight get to finish once shutdown starts, unless configured
+pub const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(30);
+
+/// The result of one prover's session, as handed to the `on_result` callback
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationEvent {
+    pub peer_addr: SocketAddr,
+    /// The registered identity the prover was checked against, if it got that far
+    pub identity: Option<String>,
+    /// At least one round, and every round verified
+    pub accepted: bool,
+    /// From the accepted TCP connection to the end of the session
+    pub duration: Duration,
+}
+
+type ResultCallback = Arc<dyn Fn(&VerificationEvent) + Send + Sync>;
+
+/// Configures a [`VerifierServer`]; `bind`, `tls` and `expected_keys` are required
+pub struct VerifierServerBuilder {
+    bind: Option<SocketAddr>,
+    tls: Option<Arc<ServerConfig>>,
+    registry: KeyRegistry,
+    on_result: Option<ResultCallback>,
+    timeouts: Timeouts,
+    wire: WireFormat,
+    max_message_len: usize,
+    audit_log: Arc<dyn ProofLog>,
+    session_log: Option<AuditLog>,
+    limiter: Option<Arc<ConnectionLimiter>>,
+    shutdown_grace: Duration,
+    #[cfg(feature = "ws")]
+    websocket: bool,
+    #[cfg(feature = "ws")]
+    bind_ws: Option<SocketAddr>,
+}
+
+impl VerifierServerBuilder {
+    /// Listen on `addr` in `run`, `run_until` and `spawn`; port 0 picks a free one, see [`ServerHandle::local_addr`]
+    pub fn bind(mut self, addr: SocketAddr) -> Self {
+        self.bind = Some(addr);
+        self
+    }
+
+    pub fn tls(mut self, config: impl Into<Arc<ServerConfig>>) -> Self {
+        self.tls = Some(config.into());
+        self
+    }
+
+    /// The keys provers may prove, by the identity they claim in their `hello`
+    pub fn expected_keys(mut self, registry: KeyRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Called once per session that got through the handshake, from the session's task
+    pub fn on_result(mut self, callback: impl Fn(&VerificationEvent) + Send + Sync + 'static) -> Self {
+        self.on_result = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Message encoding on the wire; nothing is negotiated, so provers must use the same
+    pub fn wire(mut self, wire: WireFormat) -> Self {
+        self.wire = wire;
+        self
+    }
+
+    /// Refuse length-prefixed messages over `max` bytes, see [`StreamTransport::with_max_frame_len`]
+    pub fn max_message_len(mut self, max: usize) -> Self {
+        self.max_message_len = max;
+        self
+    }
+
+    /// Record every checked proof, see [`crate::audit`]
+    pub fn audit_log(mut self, log: Arc<dyn ProofLog>) -> Self {
+        self.audit_log = log;
+        self
+    }
+
+    /// Append a [`SessionAuditRecord`] to `log` for every session that got through the handshake
+    pub fn session_log(mut self, log: AuditLog) -> Self {
+        self.session_log = Some(log);
+        self
+    }
+
+    /// Refuse connections over the limiter's caps; without one every connection is admitted
+    pub fn limiter(mut self, limiter: ConnectionLimiter) -> Self {
+        self.limiter = Some(Arc::new(limiter));
+        self
+    }
+
+    pub fn shutdown_grace(mut self, grace: Duration) -> Self {
+        self.shutdown_grace = grace;
+        self
+    }
+
+    /// Speak WebSocket (`wss://`) instead of length-prefixed frames, see [`crate::ws`]
+    #[cfg(feature = "ws")]
+    pub fn websocket(mut self, websocket: bool) -> Self {
+        self.websocket = websocket;
+        self
+    }
+
+    /// Also accept WebSocket (`wss://`) connections on `addr`, alongside those on `bind`
+    ///
+    /// Both listeners share the registry, limiter and metrics; see [`ServerHandle::ws_local_addr`].
+    #[cfg(feature = "ws")]
+    pub fn bind_ws(mut self, addr: SocketAddr) -> Self {
+        self.bind_ws = Some(addr);
+        self
+    }
+
+    pub fn build(self) -> Result<VerifierServer, ZkError> {
+        let tls = self.tls.ok_or(ZkError::ServerConfig("no TLS configuration"))?;
+        if self.registry.is_empty() {
+            return Err(ZkError::ServerConfig("no expected keys"));
+        }
+        let state = ServerState {
+            acceptor: TlsAcceptor::from(tls),
+            metrics: Arc::new(Metrics::new()),
+            registry: self.registry,
+            on_result: self.on_result,
+            timeouts: self.timeouts,
+            wire: self.wire,
+            max_message_len: self.max_message_len,
+            audit_log: self.audit_log,
+            session_log: self.session_log,
+            limiter: self.limiter,
+            #[cfg(feature = "ws")]
+            websocket: self.websocket,
+        };
+        Ok(VerifierServer {
+            bind: self.bind,
+            #[cfg(feature = "ws")]
+            bind_ws: self.bind_ws,
+            shutdown_grace: self.shutdown_grace,
+            state: Arc::new(state),
+        })
+    }
+}
+
+/// A configured verifier, ready to `run` in place or `spawn` in the background
+pub struct VerifierServer {
+    bind: Option<SocketAddr>,
+    #[cfg(feature = "ws")]
+    bind_ws: Option<SocketAddr>,
+    shutdown_grace: Duration,
+    state: Arc<ServerState>,
+}
+
+impl VerifierServer {
+    pub fn builder() -> VerifierServerBuilder {
+        VerifierServerBuilder {
+            bind: None,
+            tls: None,
+            registry: KeyRegistry::new(),
+            on_result: None,
+            timeouts: Timeouts::default(),
+            wire: WireFormat::Json,
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+            audit_log: Arc::new(NullLog),
+            session_log: None,
+            limiter: None,
+            shutdown_grace: DEFAULT_SHUTDOWN_GRACE,
+            #[cfg(feature = "ws")]
+            websocket: false,
+            #[cfg(feature = "ws")]
+            bind_ws: None,
+        }
+    }
+
+    /// Serve until ctrl-C or SIGTERM, then drain the sessions in flight
+    pub async fn run(self) -> io::Result<ShutdownSummary> {
+        self.run_until(shutdown_signal()).await
+    }
+
+    /// Serve until `shutdown` resolves, then drain the sessions in flight
+    pub async fn run_until(self, shutdown: impl Future<Output = ()>) -> io::Result<ShutdownSummary> {
+        let listeners = self.listen().await?;
+        self.serve_on(listeners, shutdown).await
+    }
+
+    /// Bind, then serve in a background task until `ServerHandle::shutdown`
+    pub async fn spawn(self) -> io::Result<ServerHandle> {
+        let listeners = self.listen().await?;
+        let local_addr = listeners.main.local_addr()?;
+        #[cfg(feature = "ws")]
+        let ws_local_addr = listeners.ws.as_ref().map(TcpListener::local_addr).transpose()?;
+        let state_metrics = self.metrics();
+        let (stop, stopped) = oneshot::channel::<()>();
+        let shutdown = async {
+            let _ = stopped.await; // a dropped handle stops the server too
+        };
+        let task = tokio::spawn(self.serve_on(listeners, shutdown));
+        Ok(ServerHandle {
+            local_addr,
+            #[cfg(feature = "ws")]
+            ws_local_addr,
+            metrics: state_metrics,
+            stop,
+            task,
+        })
+    }
+
+    /// The server's counters, live; clone the `Arc` before running to read them from elsewhere
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.state.metrics.clone()
+    }
+
+    async fn listen(&self) -> io::Result<Listeners> {
+        let addr = self.bind.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no bind address configured"))?;
+        self.listeners(TcpListener::bind(addr).await?).await
+    }
+
+    // `main`, plus the WebSocket listener if `bind_ws` asked for one
+    async fn listeners(&self, main: TcpListener) -> io::Result<Listeners> {
+        #[cfg(feature = "ws")]
+        let ws = match self.bind_ws {
+            Some(addr) => Some(TcpListener::bind(addr).await?),
+            None => None,
+        };
+        Ok(Listeners {
+            main,
+            #[cfg(feature = "ws")]
+            ws,
+        })
+    }
+
+    /// `run_until` on a listener bound by the caller, e.g. one handed over by
+    /// a service manager; no `bind` address is needed, though a `bind_ws` one
+    /// is still bound here
+    pub async fn run_on(self, listener: TcpListener, shutdown: impl Future<Output = ()>) -> io::Result<ShutdownSummary> {
+        let listeners = self.listeners(listener).await?;
+        self.serve_on(listeners, shutdown).await
+    }
+
+    async fn serve_on(self, listeners: Listeners, shutdown: impl Future<Output = ()>) -> io::Result<ShutdownSummary> {
+        info!(addr = %listeners.main.local_addr()?, "Verifier listening, ready to accept Schnorr protocol connections");
+        #[cfg(feature = "ws")]
+        if let Some(ws) = &listeners.ws {
+            info!(addr = %ws.local_addr()?, "Also accepting WebSocket connections");
+        }
+        let state = self.state;
+        let mut next_session_id: u64 = 0; // numbers sessions so their log lines can be told apart
+        let summary = serve_listeners(listeners, shutdown, self.shutdown_grace, |stream, addr, on_ws| {
+            next_session_id += 1;
+            state.clone().admit(stream, addr, next_session_id, on_ws)
+        })
+        .await?;
+        let metrics = state.metrics.snapshot();
+        info!(
+            completed = summary.completed,
+            aborted = summary.aborted,
+            verified = metrics.proofs_verified,
+            rejected = metrics.total_rejected(),
+            "Verifier stopped"
+        );
+        Ok(summary)
+    }
+}
+
+/// A server running in the background
+pub struct ServerHandle {
+    local_addr: SocketAddr,
+    #[cfg(feature = "ws")]
+    ws_local_addr: Option<SocketAddr>,
+    metrics: Arc<Metrics>,
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<io::Result<ShutdownSummary>>,
+}
+
+impl ServerHandle {
+    /// The address actually bound, with the real port when asked for port 0
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// The address the `bind_ws` listener actually bound, if there is one
+    #[cfg(feature = "ws")]
+    pub fn ws_local_addr(&self) -> Option<SocketAddr> {
+        self.ws_local_addr
+    }
+
+    /// Current counter values
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Stop accepting, let the sessions in flight finish within the grace period, and wait for that
+    pub async fn shutdown(self) -> io::Result<ShutdownSummary> {
+        let _ = self.stop.send(());
+        self.task.await.map_err(io::Error::other)?
+    }
+}
+
+// everything a session task needs, shared by all of them
+struct ServerState {
+    acceptor: TlsAcceptor,
+    metrics: Arc<Metrics>,
+    registry: KeyRegistry,
+    on_result: Option<ResultCallback>,
+    timeouts: Timeouts,
+    wire: WireFormat,
+    max_message_len: usize,
+    audit_log: Arc<dyn ProofLog>,
+    session_log: Option<AuditLog>,
+    limiter: Option<Arc<ConnectionLimiter>>,
+    #[cfg(feature = "ws")]
+    websocket: bool,
+}
+
+pub(crate) type SessionResult = Result<(String, ProofOutcome), ProtocolError>;
+
+impl ServerState {
+    // admission control, then the session to run for this connection; `None` refuses it.
+    // `on_ws` says it came in on the `bind_ws` listener
+    fn admit(self: Arc<Self>, stream: TcpStream, addr: SocketAddr, session_id: u64, on_ws: bool) -> Option<impl Future<Output = ()> + Send + 'static> {
+        let span = info_span!("session", remote_addr = %addr, session_id); // tags every log line of this session with the peer
+        span.in_scope(|| info!("Accepted TCP connection"));
+        self.metrics.connection_accepted();
+
+        let permit = match &self.limiter {
+            Some(limiter) => match limiter.try_admit(addr.ip()) {
+                Ok(permit) => Some(permit),
+                Err(rejection) => {
+                    span.in_scope(|| warn!(%rejection, stats = %limiter.stats(), "Refusing connection"));
+                    return None; // dropping the stream closes it
+                }
+            },
+            None => None,
+        };
+
+        Some(
+            async move {
+                let started = Instant::now();
+                if let Some(Finished { result, handshake, session_id }) = self.session(stream, on_ws).await {
+                    report(&result);
+                    self.metrics.record_session(result.as_ref().map(|(_, outcome)| outcome));
+                    self.metrics.observe_latency(started.elapsed());
+                    if let Some(on_result) = &self.on_result {
+                        on_result(&VerificationEvent {
+                            peer_addr: addr,
+                            identity: result.as_ref().ok().map(|(identity, _)| identity.clone()),
+                            accepted: matches!(&result, Ok((_, outcome)) if outcome.accepted()),
+                            duration: started.elapsed(),
+                        });
+                    }
+                    if let Some(log) = &self.session_log {
+                        let record = session_record(addr, &self.registry, &result, session_id, handshake, started.elapsed());
+                        if let Err(e) = log.record(&record).await {
+                            warn!(error = %e, path = %log.path().display(), "Failed to record session");
+                        }
+                    }
+                }
+                drop(permit); // free the session slot so the stats below count it as closed
+                match &self.limiter {
+                    Some(limiter) => info!(stats = %limiter.stats(), "Connection closed"),
+                    None => info!("Connection closed"),
+                }
+            }
+            .instrument(span),
+        )
+    }
+
+    // the handshake, bounded so a client that never finishes it is dropped, then the proofs;
+    // `None` if the handshake failed, which is logged here
+    #[cfg_attr(not(feature = "ws"), allow(unused_variables))]
+    async fn session(&self, stream: TcpStream, on_ws: bool) -> Option<Finished> {
+        let started = Instant::now();
+        #[cfg(feature = "ws")]
+        if self.websocket || on_ws {
+            return match with_timeout(self.timeouts.handshake, "WebSocket handshake", crate::ws::accept_ws(stream, &self.acceptor)).await {
+                Ok(Ok(transport)) => {
+                    info!("WebSocket handshake complete");
+                    let handshake = started.elapsed();
+                    let mut transport = TimeoutTransport::new(transport, self.timeouts.message);
+                    let (result, session_id) = self.verify(&mut transport).await;
+                    let mut transport = transport.into_inner();
+                    // answers the prover's close frame, or sends ours and waits for the reply
+                    let _ = with_timeout(self.timeouts.message, "WebSocket close", transport.close()).await;
+                    Some(Finished { result, handshake, session_id })
+                }
+                Ok(Err(e)) => {
+                    warn!(error = %e, "WebSocket handshake failed");
+                    self.metrics.tls_handshake_failed();
+                    None
+                }
+                Err(timed_out) => {
+                    warn!("{}, disconnecting", timed_out);
+                    self.metrics.tls_handshake_failed();
+                    None
+                }
+            };
+        }
+        match with_timeout(self.timeouts.handshake, "TLS handshake", self.acceptor.accept(stream)).await {
+            Ok(Ok(tls_stream)) => {
+                info!("TLS handshake complete");
+                let handshake = started.elapsed();
+                let transport = StreamTransport::with_codec(tls_stream, self.wire).with_max_frame_len(self.max_message_len);
+                let (result, session_id) = self.verify(&mut TimeoutTransport::new(transport, self.timeouts.message)).await;
+                Some(Finished { result, handshake, session_id })
+            }
+            Ok(Err(e)) => {
+                warn!(error = %e, "TLS handshake failed");
+                self.metrics.tls_handshake_failed();
+                None
+            }
+            Err(timed_out) => {
+                warn!("{}, disconnecting", timed_out);
+                self.metrics.tls_handshake_failed();
+                None
+            }
+        }
+    }
+
+    // the proofs, and the protocol session they were checked in
+    async fn verify<T: Transport>(&self, transport: &mut T) -> (SessionResult, Option<SessionId>) {
+        let log = SessionTap { log: self.audit_log.as_ref(), session_id: Mutex::new(None) };
+        let session = run_verifier_registry(transport, &self.registry, &log);
+        let result = match with_timeout(self.timeouts.session, "session to complete", session).await {
+            Ok(result) => result,
+            Err(timed_out) => Err(timed_out.into()),
+        };
+        (result, log.session_id.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner()))
+    }
+}
+
+// a session that got through the handshake
+struct Finished {
+    result: SessionResult,
+    handshake: Duration,
+    session_id: Option<SessionId>,
+}
+
+// passes proofs on to the audit log, noting the protocol session they came from
+struct SessionTap<'a> {
+    log: &'a dyn ProofLog,
+    session_id: Mutex<Option<SessionId>>,
+}
+
+impl ProofLog for SessionTap<'_> {
+    fn record(&self, session_id: &SessionId, public_key: &PublicKey, proof: &Proof, verified: bool, timestamp: SystemTime) {
+        *self.session_id.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(*session_id);
+        self.log.record(session_id, public_key, proof, verified, timestamp);
+    }
+
+    fn try_record(&self, entry: ProofEntry) -> Result<(), ZkError> {
+        *self.session_id.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(entry.session_id);
+        self.log.try_record(entry)
+    }
+}
+
+fn session_record(
+    peer_addr: SocketAddr,
+    registry: &KeyRegistry,
+    result: &SessionResult,
+    session_id: Option<SessionId>,
+    handshake: Duration,
+    duration: Duration,
+) -> SessionAuditRecord {
+    let (identity, outcome, failure_reason, rounds, verified) = match result {
+        Ok((identity, proofs)) if proofs.accepted() => (Some(identity.clone()), SessionOutcome::Accepted, None, proofs.rounds.len(), proofs.verified()),
+        Ok((identity, proofs)) => {
+            let reason = match proofs.rounds.len() {
+                0 => "no proofs were sent".to_string(),
+                rounds => format!("{} of {rounds} rounds failed", proofs.failed()),
+            };
+            (Some(identity.clone()), SessionOutcome::Rejected, Some(reason), proofs.rounds.len(), proofs.verified())
+        }
+        Err(e) => {
+            let claimed = match e {
+                ProtocolError::UnknownIdentity(claimed) => claimed.clone(),
+                _ => None,
+            };
+            (claimed, SessionOutcome::Error, Some(e.to_string()), 0, 0)
+        }
+    };
+    SessionAuditRecord {
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default(),
+        peer_addr,
+        public_key: identity.as_deref().and_then(|identity| registry.get(identity)).copied(),
+        identity,
+        session_id,
+        outcome,
+        failure_reason,
+        rounds,
+        verified,
+        handshake_ms: handshake.as_millis() as u64,
+        duration_ms: duration.as_millis() as u64,
+    }
+}
+
+pub(crate) fn report(result: &SessionResult) {
+    match result {
+        Ok((identity, outcome)) => {
+            info!(identity, version = %outcome.version, rounds = outcome.rounds.len(), verified = outcome.verified(), failed = outcome.failed(), "Prover finished");
+        }
+        Err(ProtocolError::Timeout(timed_out)) => warn!("{}, disconnecting", timed_out),
+        Err(ProtocolError::ConnectionClosed) => warn!("Prover hung up in the middle of a round"),
+        Err(ProtocolError::Negotiation(e)) => warn!(error = %e, "Prover speaks an incompatible protocol version"),
+        Err(e @ ProtocolError::UnknownIdentity(_)) => warn!(error = %e, "Refusing unregistered prover"),
+        Err(e) if e.is_malformed() => warn!(error = %e, "Rejected malformed message from prover"),
+        Err(e) => error!(error = %e, "Error in Schnorr protocol"),
+    }
+}
+
+/// How the sessions still running at shutdown ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ShutdownSummary {
+    /// Sessions that finished, successfully or not, before the grace period ran out
+    pub completed: usize,
+    /// Sessions still running when the grace period ran out, and cut off
+    pub aborted: usize,
+}
+
+/// Accept connections until `shutdown` resolves, then drain the sessions in flight
+///
+/// `handle` is given each accepted connection and returns the session to run
+/// for it, or `None` to refuse it (dropping the stream closes it). Sessions
+/// run as tasks; once `shutdown` fires the listener is closed, so new
+/// connections are refused, and the remaining sessions get up to `grace` to
+/// finish before they are aborted.
+pub async fn serve<S, H, F>(listener: TcpListener, shutdown: S, grace: Duration, mut handle: H) -> io::Result<ShutdownSummary>
+where
+    S: Future<Output = ()>,
+    H: FnMut(TcpStream, SocketAddr) -> Option<F>,
+    F: Future<Output = ()> + Send + 'static,
+{
+    let listeners = Listeners {
+        main: listener,
+        #[cfg(feature = "ws")]
+        ws: None,
+    };
+    serve_listeners(listeners, shutdown, grace, |stream, addr, _| handle(stream, addr)).await
+}
+
+// what a server accepts on: the `bind` listener, and with `bind_ws` a WebSocket one
+struct Listeners {
+    main: TcpListener,
+    #[cfg(feature = "ws")]
+    ws: Option<TcpListener>,
+}
+
+impl Listeners {
+    // the next connection on either listener, and whether it came in on the WebSocket one
+    async fn accept(&self) -> io::Result<(TcpStream, SocketAddr, bool)> {
+        #[cfg(feature = "ws")]
+        if let Some(ws) = &self.ws {
+            return tokio::select! {
+                accepted = self.main.accept() => accepted.map(|(stream, addr)| (stream, addr, false)),
+                accepted = ws.accept() => accepted.map(|(stream, addr)| (stream, addr, true)),
+            };
+        }
+        self.main.accept().await.map(|(stream, addr)| (stream, addr, false))
+    }
+}
+
+// `serve`, on every listener, telling `handle` which one each connection came in on
+async fn serve_listeners<S, H, F>(listeners: Listeners, shutdown: S, grace: Duration, mut handle: H) -> io::Result<ShutdownSummary>
+where
+    S: Future<Output = ()>,
+    H: FnMut(TcpStream, SocketAddr, bool) -> Option<F>,
+    F: Future<Output = ()> + Send + 'static,
+{
+    let mut sessions = JoinSet::new();
+    let mut summary = ShutdownSummary::default();
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            biased;
+            () = &mut shutdown => break,
+            Some(_) = sessions.join_next() => summary.completed += 1, // reap as we go so the set doesn't grow
+            accepted = listeners.accept() => {
+                let (stream, addr, on_ws) = accepted?;
+                if let Some(session) = handle(stream, addr, on_ws) {
+                    sessions.spawn(session);
+                }
+            }
+        }
+    }
+    drop(listeners);
+    info!(in_flight = sessions.len(), grace_secs = grace.as_secs_f64(), "Shutting down, no longer accepting connections");
+
+    let drain = async {
+        while sessions.join_next().await.is_some() {
+            summary.completed += 1;
+        }
+    };
+    if tokio::time::timeout(grace, drain).await.is_err() {
+        summary.aborted = sessions.len();
+        warn!(aborted = summary.aborted, "Grace period over, aborting the remaining sessions");
+        sessions.shutdown().await;
+    }
+    Ok(summary)
+}
+
+/// Resolves on ctrl-C, or on SIGTERM where there is one
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("failed to listen for ctrl-C");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        signal(SignalKind::terminate()).expect("failed to listen for SIGTERM").recv().await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => info!("Received ctrl-C"),
+        () = terminate => info!("Received SIGTERM"),
+    }
+}
+
+/// Answer every HTTP request on `listener` with `metrics` in the Prometheus text format
+///
+/// Plain HTTP with no routing: whatever path is asked for gets the metrics,
+/// which is all a Prometheus scraper needs. Runs until the task is dropped.
+pub async fn serve_metrics(listener: TcpListener, metrics: Arc<Metrics>) -> io::Result<()> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let body = metrics.snapshot().to_prometheus();
+        tokio::spawn(async move {
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).await; // the request itself doesn't matter
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(head.as_bytes()).await;
+            let _ = stream.write_all(body.as_bytes()).await;
+            let _ = stream.shutdown().await;
+        });
+    }
+}
@@ -0,0 +1,126 @@
+//! Session identifiers and replay detection
+//!
+//! Every interactive session gets a fresh random [`SessionId`] from the
+//! verifier, and non-interactive proofs can be bound to one with
+//! `SchnorrProof::prove_in_session`. A proof only verifies for the session it
+//! was made in; [`SessionCache`] remembers recently seen ids so the same
+//! session can't be played back a second time.
+
+use alloc::string::String;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::collections::{HashSet, VecDeque};
+
+use rand_core::RngCore;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::DecodeError;
+
+/// 16 random bytes naming one protocol run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SessionId([u8; 16]);
+
+impl SessionId {
+    pub fn new(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn random(rng: &mut impl RngCore) -> Self {
+        let mut bytes = [0u8; 16];
+        rng.fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// Parse exactly 16 hex-encoded bytes
+    pub fn from_hex(s: &str) -> Result<Self, DecodeError> {
+        let bytes = hex::decode(s)?;
+        let got = bytes.len();
+        Ok(Self(bytes.try_into().map_err(|_| DecodeError::Length { expected: 16, got })?))
+    }
+}
+
+impl fmt::Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl Serialize for SessionId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for SessionId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::from_hex(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+/// The most recently seen session ids, evicting the least recently seen
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct SessionCache {
+    capacity: usize,
+    order: VecDeque<SessionId>, // least recently seen at the front
+    seen: HashSet<SessionId>,
+}
+
+#[cfg(feature = "std")]
+impl SessionCache {
+    /// Default number of ids remembered by the verifier
+    pub const DEFAULT_CAPACITY: usize = 10_000;
+
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "a session cache must hold at least one id");
+        Self { capacity, order: VecDeque::with_capacity(capacity), seen: HashSet::with_capacity(capacity) }
+    }
+
+    /// Record `id`, returning `false` if it was already seen (a replay)
+    ///
+    /// A replayed id counts as seen again and moves to the back of the queue.
+    pub fn insert(&mut self, id: SessionId) -> bool {
+        if self.seen.contains(&id) {
+            if let Some(pos) = self.order.iter().position(|seen| *seen == id) {
+                self.order.remove(pos);
+            }
+            self.order.push_back(id);
+            return false;
+        }
+        if self.order.len() == self.capacity
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.seen.remove(&oldest);
+        }
+        self.order.push_back(id);
+        self.seen.insert(id);
+        true
+    }
+
+    pub fn contains(&self, id: &SessionId) -> bool {
+        self.seen.contains(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for SessionCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
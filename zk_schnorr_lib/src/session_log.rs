@@ -0,0 +1,216 @@
+//! An append-only JSONL log of every session (`server` feature)
+//!
+//! [`AuditLog`] writes one [`SessionAuditRecord`] per line. Sessions hand
+//! their record to a channel; a single writer task owns the file, so lines
+//! from concurrent sessions never interleave. How often the file is synced is an [`FsyncPolicy`], and with
+//! `max_bytes` set the file is rotated before it would grow past that size:
+//! `audit.jsonl` becomes `audit.jsonl.1`, the old `.1` becomes `.2`, and so on
+//! up to `keep` old files. The queue holds at most `queue` records: when the
+//! disk falls that far behind, [`AuditLog::record`] waits for room, so a slow
+//! disk slows sessions down instead of losing records or buffering them
+//! without limit.
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, oneshot};
+use tracing::warn;
+
+use crate::audit::SessionAuditRecord;
+
+/// When the session log is synced to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FsyncPolicy {
+    /// Leave it to the OS; a crash can lose the last records
+    Never,
+    /// After every record, at the cost of a sync per session
+    Always,
+    /// Once the queue of records is empty, so a burst of sessions costs one sync
+    #[default]
+    Batched,
+}
+
+impl fmt::Display for FsyncPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FsyncPolicy::Never => "never",
+            FsyncPolicy::Always => "always",
+            FsyncPolicy::Batched => "batched",
+        })
+    }
+}
+
+impl FromStr for FsyncPolicy {
+    type Err = UnknownFsyncPolicy;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "never" => Ok(FsyncPolicy::Never),
+            "always" => Ok(FsyncPolicy::Always),
+            "batched" => Ok(FsyncPolicy::Batched),
+            other => Err(UnknownFsyncPolicy(other.to_string())),
+        }
+    }
+}
+
+/// An fsync policy name that isn't one of `never`, `always` or `batched`
+#[derive(Debug, thiserror::Error)]
+#[error("unknown fsync policy {0:?}, expected never, always or batched")]
+pub struct UnknownFsyncPolicy(pub String);
+
+/// How an [`AuditLog`] writes its file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditLogOptions {
+    pub fsync: FsyncPolicy,
+    /// Rotate before the file would grow past this many bytes; never when `None`
+    pub max_bytes: Option<u64>,
+    /// Rotated files to keep; older ones are deleted
+    pub keep: usize,
+    /// Records waiting for the writer before `record` has to wait too
+    pub queue: usize,
+}
+
+impl Default for AuditLogOptions {
+    fn default() -> Self {
+        Self { fsync: FsyncPolicy::default(), max_bytes: None, keep: 5, queue: 1024 }
+    }
+}
+
+enum Command {
+    Record(String),
+    Flush(oneshot::Sender<io::Result<()>>),
+}
+
+/// Appends a JSON line per session to a file, from a single writer task
+///
+/// Clones share the writer. The task stops once
+/// every clone is dropped and the records already queued are written.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    commands: mpsc::Sender<Command>,
+}
+
+impl AuditLog {
+    /// Open (or create) `path` for appending and start the writer task
+    ///
+    /// Panics if `options.queue` is zero.
+    pub async fn open(path: impl Into<PathBuf>, options: AuditLogOptions) -> io::Result<Self> {
+        let path = path.into();
+        let writer = Writer::open(path.clone(), options).await?;
+        let (commands, queue) = mpsc::channel(options.queue);
+        tokio::spawn(writer.run(queue));
+        Ok(Self { path, commands })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Queue `record` for writing, waiting for room if the queue is full
+    ///
+    /// Returns once the writer has the record, not once it is on disk (see
+    /// [`flush`](Self::flush)). Fails only if the writer task has stopped.
+    pub async fn record(&self, record: &SessionAuditRecord) -> io::Result<()> {
+        let line = serde_json::to_string(record).expect("a SessionAuditRecord always serializes");
+        self.commands.send(Command::Record(line)).await.map_err(|_| writer_stopped())
+    }
+
+    /// Wait until every record queued so far is written, and synced unless the policy is `Never`
+    pub async fn flush(&self) -> io::Result<()> {
+        let (done, written) = oneshot::channel();
+        self.commands.send(Command::Flush(done)).await.map_err(|_| writer_stopped())?;
+        written.await.map_err(|_| writer_stopped())?
+    }
+}
+
+fn writer_stopped() -> io::Error {
+    io::Error::other("session audit log writer has stopped")
+}
+
+// the one owner of the file
+struct Writer {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    options: AuditLogOptions,
+}
+
+impl Writer {
+    async fn open(path: PathBuf, options: AuditLogOptions) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path).await?;
+        let len = file.metadata().await?.len();
+        Ok(Self { path, file, len, options })
+    }
+
+    async fn run(mut self, mut queue: mpsc::Receiver<Command>) {
+        while let Some(command) = queue.recv().await {
+            match command {
+                Command::Record(line) => {
+                    let synced = match self.options.fsync {
+                        FsyncPolicy::Always => true,
+                        FsyncPolicy::Batched => queue.is_empty(),
+                        FsyncPolicy::Never => false,
+                    };
+                    if let Err(e) = self.append(&line, synced).await {
+                        warn!(error = %e, path = %self.path.display(), "Failed to write session audit record");
+                    }
+                }
+                Command::Flush(done) => {
+                    let _ = done.send(self.sync(self.options.fsync != FsyncPolicy::Never).await);
+                }
+            }
+        }
+        let _ = self.sync(self.options.fsync != FsyncPolicy::Never).await; // every sender is gone
+    }
+
+    async fn append(&mut self, line: &str, synced: bool) -> io::Result<()> {
+        let bytes = line.len() as u64 + 1;
+        if self.options.max_bytes.is_some_and(|max| self.len > 0 && self.len + bytes > max) {
+            self.rotate().await?;
+        }
+        self.file.write_all(format!("{line}\n").as_bytes()).await?;
+        self.len += bytes;
+        self.sync(synced).await
+    }
+
+    // finish the pending write, then fsync if asked to
+    async fn sync(&mut self, synced: bool) -> io::Result<()> {
+        self.file.flush().await?;
+        if synced {
+            self.file.sync_data().await?;
+        }
+        Ok(())
+    }
+
+    // shift `path.N` to `path.N+1`, dropping the oldest, move `path` to `path.1` and start afresh
+    async fn rotate(&mut self) -> io::Result<()> {
+        self.sync(true).await?;
+        if self.options.keep == 0 {
+            fs::remove_file(&self.path).await?;
+        } else {
+            let _ = fs::remove_file(rotated_path(&self.path, self.options.keep)).await; // may not exist yet
+            for n in (1..self.options.keep).rev() {
+                match fs::rename(rotated_path(&self.path, n), rotated_path(&self.path, n + 1)).await {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                    _ => {}
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1)).await?;
+        }
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path).await?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+/// `audit.jsonl` -> `audit.jsonl.<n>`, where the `n`th most recent rotated file lives
+pub fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{n}"));
+    PathBuf::from(rotated)
+}
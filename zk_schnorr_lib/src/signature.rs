@@ -0,0 +1,168 @@
+//! Schnorr signatures over Ristretto
+//!
+//! A signature on message `m` under `X = x*G` is `(R, s)` with `R = k*G`,
+//! `s = k + c*x` and `c` drawn from a merlin transcript of the context, the
+//! generator, `R`, `X` and `m`. It verifies when
+//! `s*G = R + c*X`, the same equation as the identification protocol.
+//! On the wire a signature is 64 bytes: compressed `R` followed by `s`, both
+//! canonical, and hex-encoded when serialized with serde.
+//!
+//! [`sign_with_params`] and [`verify_with_params`] do the same over another
+//! generator `H` (see [`crate::params`]); the generator is always part of `c`.
+//! [`schnorr_sign_with_context`] and [`schnorr_verify_with_context`] also bind
+//! an application-chosen public input, so a signature can't be carried over
+//! from one context to another.
+
+use std::fmt;
+use std::str::FromStr;
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use rand_core::RngCore;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::DecodeError;
+use crate::params::Params;
+use crate::schnorr::{random_scalar, KeyPair, PublicKey};
+use crate::transcript::TranscriptBuilder;
+use crate::{PointDecodeError, ScalarDecodeError};
+
+/// A Schnorr signature `(R, s)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    /// Nonce commitment R
+    pub R: RistrettoPoint,
+    /// Response s = k + c*x
+    pub s: Scalar,
+}
+
+impl Signature {
+    /// `R || s`, 32 bytes each
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(self.R.compress().as_bytes());
+        bytes[32..].copy_from_slice(self.s.as_bytes());
+        bytes
+    }
+
+    /// Parse `R || s`, accepting only canonical encodings of both halves
+    pub fn from_bytes(bytes: &[u8; 64]) -> Result<Self, DecodeError> {
+        let mut r_bytes = [0u8; 32];
+        r_bytes.copy_from_slice(&bytes[..32]);
+        let R = CompressedRistretto(r_bytes).decompress().ok_or(PointDecodeError::InvalidPoint)?;
+
+        let mut s_bytes = [0u8; 32];
+        s_bytes.copy_from_slice(&bytes[32..]);
+        let s = Option::from(Scalar::from_canonical_bytes(s_bytes)).ok_or(ScalarDecodeError::NonCanonical)?;
+        Ok(Self { R, s })
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.to_bytes()))
+    }
+}
+
+impl FromStr for Signature {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s)?;
+        let got = bytes.len();
+        let bytes: [u8; 64] = bytes.try_into().map_err(|_| DecodeError::Length { expected: 64, got })?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl Serialize for Signature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Sign `message` with the secret scalar `x`
+pub fn sign(secret: &Scalar, message: &[u8], rng: &mut impl RngCore) -> Signature {
+    sign_with_params(&Params::default(), secret, message, rng)
+}
+
+/// Sign `message` under `X = x*H` for `params`' generator `H`
+pub fn sign_with_params(params: &Params, secret: &Scalar, message: &[u8], rng: &mut impl RngCore) -> Signature {
+    let public = params.public_key(secret);
+    let k = random_scalar(rng);
+    let R = params.generator() * k;
+    let c = challenge_with(params, &R, &public, message);
+    Signature { R, s: k + c * secret }
+}
+
+/// Check `s*G = R + c*X` for the challenge `c` of `R`, `X` and `message`
+pub fn verify(public: &PublicKey, message: &[u8], sig: &Signature) -> bool {
+    verify_with_params(&Params::default(), public, message, sig)
+}
+
+/// Check `s*H = R + c*X` for `params`' generator `H`
+///
+/// A signature made over another generator doesn't verify.
+pub fn verify_with_params(params: &Params, public: &PublicKey, message: &[u8], sig: &Signature) -> bool {
+    let c = challenge_with(params, &sig.R, public, message);
+    params.generator() * sig.s == sig.R + public.0 * c
+}
+
+/// `verify` for a bare public key point, the counterpart of `KeyPair::sign`
+pub fn verify_signature(public: &RistrettoPoint, msg: &[u8], sig: &Signature) -> bool {
+    verify(&PublicKey(*public), msg, sig)
+}
+
+// the challenge over G with no context
+pub(crate) fn challenge(R: &RistrettoPoint, public: &PublicKey, message: &[u8]) -> Scalar {
+    challenge_with(&Params::default(), R, public, message)
+}
+
+/// The signature challenge for `message` under `public`, bound to the public input `context`
+///
+/// `context` is whatever else the signature must be tied to, such as a block
+/// hash, an application name and version or a deadline, so a signature made
+/// for one context is no good in any other. An empty `context` gives the
+/// challenge [`sign`] and [`verify`] use. Context and message are appended
+/// to the transcript under their own labels, each length-prefixed, so no
+/// different split of the same bytes between them gives the same challenge.
+pub fn challenge_from_public_input(R: &RistrettoPoint, public: &PublicKey, message: &[u8], context: &[u8]) -> Scalar {
+    challenge_with_context(&Params::default(), R, public, message, context)
+}
+
+/// Sign `message` bound to the public input `context`; see [`challenge_from_public_input`]
+pub fn schnorr_sign_with_context(keypair: &KeyPair, message: &[u8], context: &[u8], rng: &mut impl RngCore) -> Signature {
+    let secret = keypair.secret().expose_secret();
+    let k = random_scalar(rng);
+    let R = RistrettoPoint::mul_base(&k);
+    let c = challenge_from_public_input(&R, &keypair.public_key(), message, context);
+    Signature { R, s: k + c * secret }
+}
+
+/// Check a [`schnorr_sign_with_context`] signature; it only verifies under the `context` it was made for
+pub fn schnorr_verify_with_context(pk: &PublicKey, message: &[u8], context: &[u8], sig: &Signature) -> bool {
+    let c = challenge_from_public_input(&sig.R, pk, message, context);
+    RistrettoPoint::mul_base(&sig.s) == sig.R + pk.0 * c
+}
+
+fn challenge_with(params: &Params, R: &RistrettoPoint, public: &PublicKey, message: &[u8]) -> Scalar {
+    challenge_with_context(params, R, public, message, b"")
+}
+
+// c from the transcript `context, H, R, X, m`; `H` is appended even when it is
+// `G`, so a challenge always names the generator it was made over
+fn challenge_with_context(params: &Params, R: &RistrettoPoint, public: &PublicKey, message: &[u8], context: &[u8]) -> Scalar {
+    TranscriptBuilder::new(b"zk-schnorr-tls/signature/v2")
+        .append_bytes(b"context", context)
+        .append_point(b"H", params.generator())
+        .append_point(b"R", R)
+        .append_point(b"X", public)
+        .append_bytes(b"message", message)
+        .challenge()
+}
@@ -0,0 +1,71 @@
+//! Deadlines for the network side of the protocol
+//!
+//! Without them a peer that connects and then goes quiet would pin a task
+//! forever, since a read from the socket never returns.
+
+#[cfg(feature = "tls")]
+use std::future::Future;
+use std::time::Duration;
+
+/// How long each stage of a session may take
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Limit for completing the TLS handshake
+    pub handshake: Duration,
+    /// Limit for each individual message to arrive
+    pub message: Duration,
+    /// Limit for the whole commit/challenge/response exchange
+    pub session: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            handshake: Duration::from_secs(10),
+            message: Duration::from_secs(30),
+            session: Duration::from_secs(120),
+        }
+    }
+}
+
+/// Deadlines and limits for running the protocol over a network connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolConfig {
+    /// Limit for the prover's TCP connect
+    pub connect_timeout: Duration,
+    /// Limit for each message of a round to arrive
+    pub step_timeout: Duration,
+    /// Most sessions a verifier runs at once; see `ConnectionLimiter`
+    pub max_concurrent_sessions: usize,
+}
+
+impl Default for ProtocolConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            step_timeout: Timeouts::default().message,
+            max_concurrent_sessions: 64,
+        }
+    }
+}
+
+/// A deadline passed before the awaited step completed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("timed out waiting for {waiting_for}")]
+pub struct TimedOut {
+    /// What we were waiting for, e.g. "response"
+    pub waiting_for: &'static str,
+}
+
+/// Run `future` to completion, or give up with `TimedOut` after `limit`
+///
+/// The future is dropped on timeout, which cancels the pending read or write.
+#[cfg(feature = "tls")]
+pub async fn with_timeout<F>(limit: Duration, waiting_for: &'static str, future: F) -> Result<F::Output, TimedOut>
+where
+    F: Future,
+{
+    tokio::time::timeout(limit, future)
+        .await
+        .map_err(|_| TimedOut { waiting_for })
+}
@@ -0,0 +1,488 @@
+//! TLS certificates and rustls configurations for both binaries
+//!
+//! Self-signed certificates for development, server configs with or without
+//! client authentication, and client configs that trust a self-signed
+//! certificate, a pinned fingerprint or the webpki roots. The `_tls13`
+//! variants refuse to negotiate anything older than TLS 1.3.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use hex::encode as hex_encode;
+use rcgen::{Certificate, CertificateParams, DistinguishedName, SanType};
+use rustls::client::{ServerCertVerified, ServerCertVerifier}; // certificate pinning
+use rustls::{Certificate as RustlsCertificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig, SupportedProtocolVersion};
+use sha2::{Digest, Sha256}; // certificate fingerprints
+use time::{Duration, OffsetDateTime}; // validity period bounds for generated certificates
+use tracing::{debug, info, warn};
+use zeroize::Zeroizing;
+
+use crate::cert_rotation::RotatingCertResolver;
+
+// for the `_tls13` configs: no downgrade to 1.2, whatever the peer offers
+const TLS13_ONLY: &[&SupportedProtocolVersion] = &[&rustls::version::TLS13];
+
+/// Errors that can occur during TLS certificate operations
+#[derive(Debug, thiserror::Error)]
+pub enum TlsError {
+    #[error("Certificate generation failed: {0}")]
+    CertificateGeneration(#[from] rcgen::RcgenError),
+    #[error("TLS configuration failed: {0}")]
+    TlsConfig(#[from] rustls::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid server name: {0}")]
+    InvalidServerName(#[from] rustls::client::InvalidDnsNameError),
+    #[error("No certificate found in the PEM input")]
+    MissingCertificate,
+    #[error("No private key found in the PEM input")]
+    MissingPrivateKey,
+}
+
+/// Generated TLS certificate and private key pair
+pub struct TlsCertificate {
+    pub certificate: Certificate,
+    pub cert_der: Vec<u8>,
+    pub private_key_der: Zeroizing<Vec<u8>>, // wiped when the certificate is dropped
+}
+
+impl TlsCertificate {
+    /// The certificate as a PEM `CERTIFICATE` block
+    pub fn cert_pem(&self) -> String {
+        der_to_pem("CERTIFICATE", &self.cert_der)
+    }
+
+    /// The PKCS#8 private key as a PEM `PRIVATE KEY` block
+    pub fn private_key_pem(&self) -> Zeroizing<String> {
+        Zeroizing::new(der_to_pem("PRIVATE KEY", &self.private_key_der))
+    }
+
+    /// SHA-256 of the certificate, the pin for `create_client_config_pinned`
+    pub fn fingerprint(&self) -> [u8; 32] {
+        cert_fingerprint(&self.cert_der)
+    }
+}
+
+/// SHA-256 of a DER-encoded certificate, as pinned by `create_client_config_pinned`
+pub fn cert_fingerprint(cert_der: &[u8]) -> [u8; 32] {
+    Sha256::digest(cert_der).into()
+}
+
+/// Wrap DER bytes in a PEM block with the given label, 64 base64 characters per line
+pub fn der_to_pem(label: &str, der: &[u8]) -> String {
+    let encoded = BASE64_STANDARD.encode(der);
+    let mut pem = format!("-----BEGIN {label}-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {label}-----\n"));
+    pem
+}
+
+/// The key pair a generated certificate is signed with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CertKeyAlgorithm {
+    /// ECDSA over P-256 with SHA-256, which every TLS client accepts
+    #[default]
+    EcdsaP256,
+    /// Ed25519: smaller and faster, for peers that support it (rustls does)
+    Ed25519,
+}
+
+impl CertKeyAlgorithm {
+    fn rcgen(self) -> &'static rcgen::SignatureAlgorithm {
+        match self {
+            CertKeyAlgorithm::EcdsaP256 => &rcgen::PKCS_ECDSA_P256_SHA256,
+            CertKeyAlgorithm::Ed25519 => &rcgen::PKCS_ED25519,
+        }
+    }
+}
+
+/// Options for generating a self-signed certificate
+///
+/// `Default` gives the development settings used by `generate_self_signed_cert`:
+/// valid for `localhost` and `127.0.0.1`, with rcgen's very long validity window.
+#[derive(Debug, Clone)]
+pub struct CertOptions {
+    /// Subject alternative names the certificate is valid for; any that parse
+    /// as an IP address become IP SANs, the rest DNS names
+    pub subject_alt_names: Vec<String>,
+    /// Common name (CN) of the subject and issuer
+    pub common_name: String,
+    /// Start of the validity period
+    pub not_before: OffsetDateTime,
+    /// End of the validity period
+    pub not_after: OffsetDateTime,
+    pub key_algorithm: CertKeyAlgorithm,
+}
+
+impl Default for CertOptions {
+    fn default() -> Self {
+        Self {
+            subject_alt_names: vec!["localhost".to_string(), "127.0.0.1".to_string()],
+            common_name: "ZK Schnorr TLS Demo".to_string(),
+            not_before: rcgen::date_time_ymd(1975, 1, 1), // same window rcgen uses by default
+            not_after: rcgen::date_time_ymd(4096, 1, 1),
+            key_algorithm: CertKeyAlgorithm::default(),
+        }
+    }
+}
+
+/// Generate a self-signed certificate for development use
+/// 
+/// This creates a certificate valid for 'localhost' and '127.0.0.1'
+/// which is perfect for our local development and testing.
+/// 
+/// # Returns
+/// A `TlsCertificate` containing both the certificate and private key
+/// in DER format, ready to be used with rustls.
+pub fn generate_self_signed_cert() -> Result<TlsCertificate, TlsError> {
+    generate_self_signed_cert_with(CertOptions::default())
+}
+
+/// Generate a self-signed certificate for `subject_alt_names`, valid from now for `validity_days`
+///
+/// For a verifier on an internal hostname: `["verifier.internal", "10.0.0.5"]`
+/// gives a DNS SAN and an IP SAN. The key is ECDSA P-256; see
+/// [`generate_self_signed_cert_with`] for Ed25519 or any other window.
+pub fn generate_self_signed_cert_for(
+    subject_alt_names: &[String],
+    validity_days: u32,
+    common_name: &str,
+) -> Result<TlsCertificate, TlsError> {
+    let now = OffsetDateTime::now_utc();
+    generate_self_signed_cert_with(CertOptions {
+        subject_alt_names: subject_alt_names.to_vec(),
+        common_name: common_name.to_string(),
+        not_before: now,
+        not_after: now + Duration::days(validity_days.into()),
+        key_algorithm: CertKeyAlgorithm::default(),
+    })
+}
+
+/// Generate a self-signed certificate with custom names and validity period
+///
+/// Use this to get a certificate for your real hostname, or a short-lived
+/// (or already expired) one for testing expiry handling.
+pub fn generate_self_signed_cert_with(options: CertOptions) -> Result<TlsCertificate, TlsError> {
+    // Set up certificate parameters
+    let mut params = CertificateParams::default();
+    params.subject_alt_names = options.subject_alt_names.iter().map(|name| san(name)).collect();
+    params.not_before = options.not_before;
+    params.not_after = options.not_after;
+    params.alg = options.key_algorithm.rcgen();
+    
+    // Set certificate details
+    params.distinguished_name = DistinguishedName::new();
+    params.distinguished_name.push(
+        rcgen::DnType::CommonName,
+        options.common_name.as_str()
+    );
+    params.distinguished_name.push(
+        rcgen::DnType::OrganizationName,
+        "Zero Knowledge Demo"
+    );
+    
+    // Generate the certificate
+    let certificate = Certificate::from_params(params)?;
+    
+    // Get DER-encoded certificate and private key
+    let cert_der = certificate.serialize_der()?;
+    let private_key_der = certificate.serialize_private_key_der();
+    
+    info!(
+        names = %options.subject_alt_names.join(", "),
+        not_before = %options.not_before,
+        not_after = %options.not_after,
+        issuer = %options.common_name,
+        key = ?options.key_algorithm,
+        "Generated self-signed TLS certificate"
+    );
+    
+    Ok(TlsCertificate {
+        certificate,
+        cert_der,
+        private_key_der: Zeroizing::new(private_key_der),
+    })
+}
+
+// an IP SAN for anything that parses as an address, so clients connecting by IP accept it
+fn san(name: &str) -> SanType {
+    match name.parse::<IpAddr>() {
+        Ok(ip) => SanType::IpAddress(ip),
+        Err(_) => SanType::DnsName(name.to_string()),
+    }
+}
+
+/// Create a TLS server configuration from a certificate
+/// 
+/// This sets up the server-side TLS configuration that will:
+/// - Use the provided certificate for authentication
+/// - Support modern TLS versions (1.2 and 1.3)
+/// - Use secure cipher suites
+/// - Not require client certificates (server-only authentication)
+///
+/// With a `rotating` resolver, `tls_cert` is installed in it and every
+/// handshake asks the resolver, so a later `RotatingCertResolver::reload`
+/// changes the certificate without building a new config.
+pub fn create_server_config(tls_cert: &TlsCertificate, rotating: Option<Arc<RotatingCertResolver>>) -> Result<ServerConfig, TlsError> {
+    server_config(tls_cert, rotating, rustls::DEFAULT_VERSIONS)
+}
+
+/// `create_server_config`, refusing every protocol version but TLS 1.3
+///
+/// A client that only offers TLS 1.2 gets a `protocol_version` alert and the
+/// handshake fails on both sides.
+pub fn create_server_config_tls13(tls_cert: &TlsCertificate, rotating: Option<Arc<RotatingCertResolver>>) -> Result<ServerConfig, TlsError> {
+    server_config(tls_cert, rotating, TLS13_ONLY)
+}
+
+fn server_config(
+    tls_cert: &TlsCertificate,
+    rotating: Option<Arc<RotatingCertResolver>>,
+    versions: &[&'static SupportedProtocolVersion],
+) -> Result<ServerConfig, TlsError> {
+    let builder = ServerConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(versions)?
+        .with_no_client_auth();
+    let config = match rotating {
+        Some(resolver) => {
+            resolver.replace(vec![tls_cert.cert_der.clone()], &tls_cert.private_key_der)?;
+            builder.with_cert_resolver(resolver)
+        }
+        None => {
+            let cert = RustlsCertificate(tls_cert.cert_der.clone());
+            let private_key = PrivateKey(tls_cert.private_key_der.to_vec());
+            builder.with_single_cert(vec![cert], private_key)?
+        }
+    };
+    
+    debug!(versions = ?versions, "Created TLS server configuration (server-only authentication, no client certs required)");
+    
+    Ok(config)
+}
+
+/// A certificate chain and its private key, read from PEM
+///
+/// For a certificate issued by a CA, or made with openssl, in place of
+/// `generate_self_signed_cert`.
+pub struct PemCertificate {
+    /// DER of each certificate in the chain, leaf first
+    pub chain_der: Vec<Vec<u8>>,
+    pub private_key_der: Zeroizing<Vec<u8>>,
+}
+
+impl PemCertificate {
+    /// Parse `cert_pem` (one or more `CERTIFICATE` blocks) and `key_pem` (PKCS#8, PKCS#1 or SEC1)
+    pub fn from_pem(cert_pem: &[u8], key_pem: &[u8]) -> Result<Self, TlsError> {
+        let chain_der = rustls_pemfile::certs(&mut &cert_pem[..])
+            .map(|cert| cert.map(|der| der.to_vec()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if chain_der.is_empty() {
+            return Err(TlsError::MissingCertificate);
+        }
+        let key = rustls_pemfile::private_key(&mut &key_pem[..])?.ok_or(TlsError::MissingPrivateKey)?;
+        Ok(Self { chain_der, private_key_der: Zeroizing::new(key.secret_der().to_vec()) })
+    }
+
+    /// The leaf certificate, the one a client pins or trusts directly
+    pub fn leaf_der(&self) -> &[u8] {
+        &self.chain_der[0]
+    }
+}
+
+/// Create a TLS server configuration from a certificate chain loaded from PEM
+///
+/// Like `create_server_config`, with no client certificates required.
+pub fn create_server_config_from_pem(pem_cert: &PemCertificate) -> Result<ServerConfig, TlsError> {
+    let chain = pem_cert.chain_der.iter().cloned().map(RustlsCertificate).collect();
+    let private_key = PrivateKey(pem_cert.private_key_der.to_vec());
+
+    let config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(chain, private_key)?;
+
+    debug!(chain = pem_cert.chain_der.len(), "Created TLS server configuration from a PEM certificate");
+
+    Ok(config)
+}
+
+/// Create a TLS server configuration that also requires a client certificate (mutual TLS)
+///
+/// Clients whose certificate doesn't chain to `client_roots` (or that present
+/// none) fail the handshake, before a single protocol message is exchanged.
+pub fn create_server_config_mtls(tls_cert: &TlsCertificate, client_roots: &RootCertStore) -> Result<ServerConfig, TlsError> {
+    let cert = RustlsCertificate(tls_cert.cert_der.clone());
+    let private_key = PrivateKey(tls_cert.private_key_der.to_vec());
+    let client_verifier = rustls::server::AllowAnyAuthenticatedClient::new(client_roots.clone()).boxed();
+
+    let config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_client_cert_verifier(client_verifier)
+        .with_single_cert(vec![cert], private_key)?;
+
+    debug!(client_roots = client_roots.len(), "Created TLS server configuration requiring client certificates");
+
+    Ok(config)
+}
+
+/// Create a TLS client configuration that trusts `server_cert` and presents `client_cert`
+///
+/// The counterpart of `create_server_config_mtls`. The same development-only
+/// caveat as `create_client_config_insecure` applies to trusting a self-signed server.
+pub fn create_client_config_with_identity(server_cert: &TlsCertificate, client_cert: &TlsCertificate) -> Result<ClientConfig, TlsError> {
+    let mut root_store = RootCertStore::empty();
+    root_store.add(&RustlsCertificate(server_cert.cert_der.clone()))?;
+
+    let cert = RustlsCertificate(client_cert.cert_der.clone());
+    let private_key = PrivateKey(client_cert.private_key_der.to_vec());
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_store)
+        .with_client_auth_cert(vec![cert], private_key)?;
+
+    debug!("Created TLS client configuration with a client certificate");
+
+    Ok(config)
+}
+
+/// Create a TLS client configuration that validates the server like a browser would
+///
+/// The server's chain must lead to one of the Mozilla roots bundled by
+/// `webpki-roots`, and its certificate must be valid for `server_name`. Pass
+/// the same name to `TlsConnector::connect`; this only checks it is a valid
+/// DNS name up front, so a typo fails here rather than at the handshake.
+pub fn create_client_config_verified(server_name: &str) -> Result<ClientConfig, TlsError> {
+    rustls::ServerName::try_from(server_name)?;
+
+    let mut root_store = RootCertStore::empty();
+    root_store.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+        rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(anchor.subject, anchor.spki, anchor.name_constraints)
+    }));
+    let roots = root_store.len();
+
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_store)
+        .with_no_client_auth();
+
+    debug!(server_name, roots, "Created TLS client configuration with webpki validation");
+
+    Ok(config)
+}
+
+/// SHA-256 fingerprint of the one server certificate a client will accept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PinnedCert {
+    pub fingerprint: [u8; 32],
+}
+
+impl PinnedCert {
+    /// Pin the DER-encoded certificate `der`
+    pub fn from_der(der: &[u8]) -> Self {
+        Self { fingerprint: cert_fingerprint(der) }
+    }
+}
+
+impl From<[u8; 32]> for PinnedCert {
+    fn from(fingerprint: [u8; 32]) -> Self {
+        Self { fingerprint }
+    }
+}
+
+// Accepts exactly the pinned leaf certificate, whoever signed it and whatever names it lists
+struct PinnedCertVerifier {
+    pinned: PinnedCert,
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &RustlsCertificate,
+        _intermediates: &[RustlsCertificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if PinnedCert::from_der(&end_entity.0) == self.pinned {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::InvalidCertificate(rustls::CertificateError::ApplicationVerificationFailure))
+        }
+    }
+}
+
+/// Create a TLS client configuration that only accepts the leaf certificate whose SHA-256 is `pinned`
+///
+/// `pinned` is a `PinnedCert` or the raw fingerprint from [`cert_fingerprint`].
+/// No CA or hostname check happens: any other certificate fails the
+/// handshake, even one validly signed for the right name, and the pinned one
+/// passes even when self-signed. The handshake signature is still checked,
+/// so the server must hold the certificate's private key.
+pub fn create_client_config_pinned(pinned: impl Into<PinnedCert>) -> Result<ClientConfig, TlsError> {
+    let pinned = pinned.into();
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(PinnedCertVerifier { pinned }))
+        .with_no_client_auth();
+
+    debug!(fingerprint = %hex_encode(pinned.fingerprint), "Created TLS client configuration pinning one certificate");
+
+    Ok(config)
+}
+
+/// Create a TLS client configuration that trusts our self-signed certificate
+/// 
+/// For development, we need to explicitly trust our self-signed certificate
+/// since it won't be signed by a standard Certificate Authority.
+/// 
+/// # Security Note
+/// `server_cert` becomes the only trust anchor, so whoever holds its private
+/// key is trusted for any name it lists. This is ONLY safe for
+/// development/demo purposes on localhost; real deployments should use
+/// `create_client_config_verified`.
+pub fn create_client_config_insecure(server_cert: &TlsCertificate) -> Result<ClientConfig, TlsError> {
+    create_client_config_insecure_from_der(&server_cert.cert_der)
+}
+
+/// `create_client_config_insecure`, offering only TLS 1.3
+///
+/// For a client that must never be talked down to TLS 1.2; a server that
+/// doesn't speak 1.3 fails the handshake. The same development-only caveat
+/// applies.
+pub fn create_client_config_insecure_tls13(server_cert: &TlsCertificate) -> Result<ClientConfig, TlsError> {
+    insecure_client_config(&server_cert.cert_der, TLS13_ONLY)
+}
+
+/// Same as `create_client_config_insecure`, for when only the server's DER
+/// certificate is available (e.g. read from the file the verifier wrote at startup)
+pub fn create_client_config_insecure_from_der(server_cert_der: &[u8]) -> Result<ClientConfig, TlsError> {
+    insecure_client_config(server_cert_der, rustls::DEFAULT_VERSIONS)
+}
+
+fn insecure_client_config(server_cert_der: &[u8], versions: &[&'static SupportedProtocolVersion]) -> Result<ClientConfig, TlsError> {
+    let mut root_store = RootCertStore::empty();
+    
+    // Add our self-signed certificate as a trusted root
+    // This is needed because our cert isn't signed by a standard CA
+    let cert = RustlsCertificate(server_cert_der.to_vec());
+    root_store.add(&cert)?;
+    
+    let config = ClientConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(versions)?
+        .with_root_certificates(root_store)
+        .with_no_client_auth();
+    
+    debug!(versions = ?versions, "Created TLS client configuration trusting a self-signed certificate");
+    warn!("TLS client trusts a self-signed certificate: development only, not for production!");
+    
+    Ok(config)
+}
\ No newline at end of file
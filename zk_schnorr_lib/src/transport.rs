@@ -0,0 +1,160 @@
+//! Pluggable transports for carrying protocol messages
+//!
+//! The Schnorr logic in `SchnorrProtocol` only needs to send and receive
+//! whole `Message`s, so it is written against the `Transport` trait. The
+//! binaries use `TlsTransport`; tests can use the in-memory `ChannelTransport`.
+
+use std::io;
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::mpsc;
+use tokio_util::codec::Framed;
+
+use crate::codec::WireFormat;
+use crate::framing::MessageCodec;
+use crate::timeouts::with_timeout;
+use crate::Message;
+
+/// Something that can exchange whole protocol messages with a peer
+///
+/// `recv` reports a peer that hung up as `io::ErrorKind::UnexpectedEof` and a
+/// missed deadline as `io::ErrorKind::TimedOut`.
+#[allow(async_fn_in_trait)] // implementors are concrete types, so Send-ness is checked where they're spawned
+pub trait Transport {
+    async fn send(&mut self, msg: Message) -> io::Result<()>;
+    async fn recv(&mut self) -> io::Result<Message>;
+
+    /// Encode every later message with `codec`; transports that pass
+    /// `Message`s around whole have nothing to encode and ignore it
+    fn set_codec(&mut self, _codec: WireFormat) {}
+}
+
+/// Length-prefixed messages over any async byte stream, JSON until told otherwise
+///
+/// The stream is read and written through a [`MessageCodec`], so a `recv`
+/// cut short (say by a `TimeoutTransport`) loses nothing: whatever part of a
+/// frame has arrived stays buffered for the next one. A frame whose length
+/// prefix is over `max_frame_len` (by default [`DEFAULT_MAX_MESSAGE_LEN`](crate::DEFAULT_MAX_MESSAGE_LEN)) is
+/// refused before its body is read.
+pub struct StreamTransport<S> {
+    framed: Framed<S, MessageCodec>,
+}
+
+impl<S> StreamTransport<S> {
+    pub fn new(stream: S) -> Self {
+        Self::with_codec(stream, WireFormat::Json)
+    }
+
+    pub fn with_codec(stream: S, codec: WireFormat) -> Self {
+        Self { framed: Framed::new(stream, MessageCodec::new(codec)) }
+    }
+
+    /// Refuse incoming frames longer than `max` bytes; anything over `MAX_FRAME_LEN` is refused regardless
+    pub fn with_max_frame_len(mut self, max: usize) -> Self {
+        let codec = self.framed.codec_mut();
+        *codec = codec.with_max_len(max);
+        self
+    }
+
+    pub fn codec(&self) -> WireFormat {
+        self.framed.codec().format()
+    }
+
+    pub fn max_frame_len(&self) -> usize {
+        self.framed.codec().max_len()
+    }
+
+    /// Give back the underlying stream, dropping anything read past the last message
+    pub fn into_inner(self) -> S {
+        self.framed.into_inner()
+    }
+}
+
+impl<S> Transport for StreamTransport<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    async fn send(&mut self, msg: Message) -> io::Result<()> {
+        self.framed.send(msg).await // flushes
+    }
+
+    async fn recv(&mut self) -> io::Result<Message> {
+        self.framed
+            .next()
+            .await
+            .unwrap_or_else(|| Err(io::Error::new(io::ErrorKind::UnexpectedEof, "peer closed the connection")))
+    }
+
+    fn set_codec(&mut self, codec: WireFormat) {
+        self.framed.codec_mut().set_format(codec);
+    }
+}
+
+/// Messages over a TLS connection, from either the client or the server side
+pub type TlsTransport<IO> = StreamTransport<tokio_rustls::TlsStream<IO>>;
+
+/// In-memory transport backed by a pair of `mpsc` channels
+pub struct ChannelTransport {
+    tx: mpsc::Sender<Message>,
+    rx: mpsc::Receiver<Message>,
+}
+
+impl ChannelTransport {
+    /// Create two connected ends: whatever one sends, the other receives
+    pub fn pair() -> (Self, Self) {
+        let (a_tx, b_rx) = mpsc::channel(16);
+        let (b_tx, a_rx) = mpsc::channel(16);
+        (Self { tx: a_tx, rx: a_rx }, Self { tx: b_tx, rx: b_rx })
+    }
+}
+
+impl Transport for ChannelTransport {
+    async fn send(&mut self, msg: Message) -> io::Result<()> {
+        self.tx
+            .send(msg)
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "peer dropped its channel"))
+    }
+
+    async fn recv(&mut self) -> io::Result<Message> {
+        self.rx
+            .recv()
+            .await
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "peer dropped its channel"))
+    }
+}
+
+/// Wraps another transport and bounds how long each `recv` may take
+pub struct TimeoutTransport<T> {
+    inner: T,
+    limit: Duration,
+}
+
+impl<T> TimeoutTransport<T> {
+    pub fn new(inner: T, limit: Duration) -> Self {
+        Self { inner, limit }
+    }
+
+    /// Give back the wrapped transport
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Transport> Transport for TimeoutTransport<T> {
+    async fn send(&mut self, msg: Message) -> io::Result<()> {
+        self.inner.send(msg).await
+    }
+
+    async fn recv(&mut self) -> io::Result<Message> {
+        with_timeout(self.limit, "message", self.inner.recv())
+            .await
+            .map_err(|timed_out| io::Error::new(io::ErrorKind::TimedOut, timed_out))?
+    }
+
+    fn set_codec(&mut self, codec: WireFormat) {
+        self.inner.set_codec(codec);
+    }
+}
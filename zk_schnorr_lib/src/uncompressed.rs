@@ -0,0 +1,150 @@
+//! The 64-byte uncompressed point form: affine Edwards coordinates `x || y`
+//!
+//! A Ristretto point is a class of Edwards points, so "its" coordinates are
+//! those of the representative that decoding the 32-byte compressed form
+//! yields (RFC 9496, section 4.3.1). Each coordinate is 32 bytes little-endian.
+//! Only that representative is accepted back, which keeps the form canonical.
+//!
+//! dalek keeps its field arithmetic private, so the two conversions here
+//! redo the RFC formulas with big integers. Points are public values, so the
+//! variable-time arithmetic is fine.
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use num_bigint::BigUint;
+
+// arithmetic modulo p = 2^255 - 19
+struct Field {
+    p: BigUint,
+    d: BigUint,
+    sqrt_m1: BigUint,
+    invsqrt_a_minus_d: BigUint,
+}
+
+impl Field {
+    fn new() -> Self {
+        let p = (BigUint::from(1u8) << 255u32) - 19u32;
+        let mut f = Field { d: BigUint::default(), sqrt_m1: BigUint::default(), invsqrt_a_minus_d: BigUint::default(), p };
+        f.sqrt_m1 = BigUint::from(2u8).modpow(&((&f.p - 1u32) >> 2u32), &f.p);
+        f.d = f.mul(&f.neg(&121665u32.into()), &f.inv(&121666u32.into()));
+        // a - d with a = -1
+        let a_minus_d = f.sub(&f.neg(&1u32.into()), &f.d);
+        f.invsqrt_a_minus_d = f.sqrt_ratio_m1(&1u32.into(), &a_minus_d).1;
+        f
+    }
+
+    fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a + b) % &self.p
+    }
+
+    fn sub(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a + &self.p - (b % &self.p)) % &self.p
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a * b) % &self.p
+    }
+
+    fn neg(&self, a: &BigUint) -> BigUint {
+        self.sub(&BigUint::default(), a)
+    }
+
+    fn inv(&self, a: &BigUint) -> BigUint {
+        a.modpow(&(&self.p - 2u32), &self.p)
+    }
+
+    fn is_negative(&self, a: &BigUint) -> bool {
+        a.bit(0)
+    }
+
+    fn abs(&self, a: &BigUint) -> BigUint {
+        if self.is_negative(a) { self.neg(a) } else { a.clone() }
+    }
+
+    // (u/v is square, the non-negative sqrt(u/v) or sqrt(i*u/v))
+    fn sqrt_ratio_m1(&self, u: &BigUint, v: &BigUint) -> (bool, BigUint) {
+        let v3 = self.mul(&self.mul(v, v), v);
+        let v7 = self.mul(&self.mul(&v3, &v3), v);
+        let exp = (&self.p - 5u32) >> 3u32;
+        let mut r = self.mul(&self.mul(u, &v3), &self.mul(u, &v7).modpow(&exp, &self.p));
+        let check = self.mul(v, &self.mul(&r, &r));
+        let correct_sign = check == *u;
+        let flipped_sign = check == self.neg(u);
+        let flipped_sign_i = check == self.mul(&self.neg(u), &self.sqrt_m1);
+        if flipped_sign || flipped_sign_i {
+            r = self.mul(&r, &self.sqrt_m1);
+        }
+        (correct_sign || flipped_sign, self.abs(&r))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Option<BigUint> {
+        let n = BigUint::from_bytes_le(bytes);
+        (n < self.p).then_some(n)
+    }
+
+    fn encode(&self, n: &BigUint) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        let bytes = n.to_bytes_le();
+        out[..bytes.len()].copy_from_slice(&bytes);
+        out
+    }
+}
+
+/// The affine coordinates `x || y` of the point's canonical representative
+pub(crate) fn to_uncompressed(point: &RistrettoPoint) -> [u8; 64] {
+    let f = Field::new();
+    let s = f.decode(point.compress().as_bytes()).expect("compressed points are canonical");
+
+    // RFC 9496, 4.3.1 with a = -1
+    let ss = f.mul(&s, &s);
+    let u1 = f.sub(&1u32.into(), &ss);
+    let u2 = f.add(&1u32.into(), &ss);
+    let u2_sqr = f.mul(&u2, &u2);
+    let v = f.sub(&f.neg(&f.mul(&f.d, &f.mul(&u1, &u1))), &u2_sqr);
+    let (_, invsqrt) = f.sqrt_ratio_m1(&1u32.into(), &f.mul(&v, &u2_sqr));
+    let den_x = f.mul(&invsqrt, &u2);
+    let den_y = f.mul(&f.mul(&invsqrt, &den_x), &v);
+    let x = f.abs(&f.mul(&f.mul(&2u32.into(), &s), &den_x));
+    let y = f.mul(&u1, &den_y);
+
+    let mut out = [0u8; 64];
+    out[..32].copy_from_slice(&f.encode(&x));
+    out[32..].copy_from_slice(&f.encode(&y));
+    out
+}
+
+/// Parse `x || y`, accepting only what `to_uncompressed` produces
+pub(crate) fn from_uncompressed(bytes: &[u8; 64]) -> Option<RistrettoPoint> {
+    let f = Field::new();
+    let x = f.decode(&bytes[..32])?;
+    let y = f.decode(&bytes[32..])?;
+
+    // on the curve: -x^2 + y^2 = 1 + d*x^2*y^2
+    let xx = f.mul(&x, &x);
+    let yy = f.mul(&y, &y);
+    if f.sub(&yy, &xx) != f.add(&1u32.into(), &f.mul(&f.d, &f.mul(&xx, &yy))) {
+        return None;
+    }
+
+    // RFC 9496, 4.3.2 with Z = 1 and T = x*y
+    let t = f.mul(&x, &y);
+    let u1 = f.mul(&f.add(&1u32.into(), &y), &f.sub(&1u32.into(), &y));
+    let u2 = t.clone();
+    let (_, invsqrt) = f.sqrt_ratio_m1(&1u32.into(), &f.mul(&u1, &f.mul(&u2, &u2)));
+    let den1 = f.mul(&invsqrt, &u1);
+    let den2 = f.mul(&invsqrt, &u2);
+    let z_inv = f.mul(&f.mul(&den1, &den2), &t);
+    let rotate = f.is_negative(&f.mul(&t, &z_inv));
+    let (x_r, mut y_r, den_inv) = if rotate {
+        (f.mul(&y, &f.sqrt_m1), f.mul(&x, &f.sqrt_m1), f.mul(&den1, &f.invsqrt_a_minus_d))
+    } else {
+        (x.clone(), y.clone(), den2)
+    };
+    if f.is_negative(&f.mul(&x_r, &z_inv)) {
+        y_r = f.neg(&y_r);
+    }
+    let s = f.abs(&f.mul(&den_inv, &f.sub(&1u32.into(), &y_r)));
+
+    // the class must decode, and to exactly the representative we were given
+    let point = CompressedRistretto(f.encode(&s)).decompress()?;
+    (to_uncompressed(&point) == *bytes).then_some(point)
+}
@@ -0,0 +1,167 @@
+//! Protocol versioning and the opening `hello` exchange
+//!
+//! The prover opens the connection with a `hello` message carrying its
+//! [`VersionInfo`]. The verifier answers with a `hello` of its own naming the
+//! selected version, or with an `error` whose payload is a JSON
+//! [`VersionRejection`] listing what it supports. All of this happens before
+//! any key material is touched.
+//!
+//! Compatibility rule: peers with the same major version interoperate at the
+//! lower of their two minor versions; different majors never do. Both must
+//! also prove over the same generator (see [`crate::params`]), which the
+//! `hello` names unless it is the default.
+
+use std::fmt;
+use std::str::FromStr;
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+use serde::{Deserialize, Serialize};
+
+use crate::params::Params;
+use crate::serde_hex::SerdePoint;
+
+/// A `major.minor` protocol version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Version {
+    pub major: u16,
+    pub minor: u16,
+}
+
+/// The version this build of the library speaks
+pub const PROTOCOL_VERSION: Version = Version { major: 1, minor: 0 };
+
+impl Version {
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for Version {
+    type Err = NegotiationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || NegotiationError::InvalidVersion(s.to_string());
+        let (major, minor) = s.split_once('.').ok_or_else(invalid)?;
+        Ok(Self { major: major.parse().map_err(|_| invalid())?, minor: minor.parse().map_err(|_| invalid())? })
+    }
+}
+
+/// What one side announces in its `hello`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub version: Version,
+    /// Optional protocol extensions, e.g. "or-proof"
+    pub supported_features: Vec<String>,
+    /// Who the prover claims to be, for a verifier that knows several keys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+    /// The generator proofs are made over; absent for the Ristretto basepoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator: Option<SerdePoint>,
+}
+
+impl VersionInfo {
+    /// `PROTOCOL_VERSION` with every extension this build implements
+    pub fn current() -> Self {
+        let features = ["multi-round", "or-proof", "dleq"];
+        Self {
+            version: PROTOCOL_VERSION,
+            supported_features: features.iter().map(|f| f.to_string()).collect(),
+            identity: None,
+            generator: None,
+        }
+    }
+
+    /// The same announcement, naming the key the prover is about to prove
+    pub fn with_identity(self, identity: impl Into<String>) -> Self {
+        Self { identity: Some(identity.into()), ..self }
+    }
+
+    /// The same announcement, also listing `feature`, e.g. [`COMMITTED_CHALLENGE`](crate::COMMITTED_CHALLENGE)
+    pub fn with_feature(mut self, feature: impl Into<String>) -> Self {
+        let feature = feature.into();
+        if !self.supports(&feature) {
+            self.supported_features.push(feature);
+        }
+        self
+    }
+
+    pub fn supports(&self, feature: &str) -> bool {
+        self.supported_features.iter().any(|f| f == feature)
+    }
+
+    /// The same announcement, proving over `params`' generator
+    pub fn with_params(self, params: &Params) -> Self {
+        Self { generator: params.bound_generator().copied().map(SerdePoint), ..self }
+    }
+
+    /// The parameters this side proves over
+    pub fn params(&self) -> Params {
+        self.generator.map_or_else(Params::default, |SerdePoint(generator)| Params::with_generator(generator))
+    }
+}
+
+/// Payload of the `error` a verifier sends when it can't speak the prover's version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionRejection {
+    pub reason: String,
+    pub supported: Vec<Version>,
+    /// The verifier's generator, when a different one was the reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator: Option<SerdePoint>,
+}
+
+/// Why two peers could not agree on a version
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum NegotiationError {
+    #[error("protocol version {peer} is incompatible, supported: {}", list(supported))]
+    Incompatible { peer: Version, supported: Vec<Version> },
+    #[error("peer selected version {selected}, which we did not offer")]
+    Downgrade { selected: Version },
+    #[error("invalid version {0:?}, expected major.minor")]
+    InvalidVersion(String),
+    #[error("verifier proves over generator {}, prover over {}", hex::encode(verifier.as_bytes()), hex::encode(prover.as_bytes()))]
+    GeneratorMismatch { verifier: CompressedRistretto, prover: CompressedRistretto },
+}
+
+fn list(versions: &[Version]) -> String {
+    versions.iter().map(Version::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// Pick the version a client and server will speak
+///
+/// Same major: the lower of the two minors, so the newer side downgrades.
+/// Different majors: `NegotiationError::Incompatible`, listing the server's version.
+/// Different generators: `NegotiationError::GeneratorMismatch`.
+pub fn negotiate(client: &VersionInfo, server: &VersionInfo) -> Result<Version, NegotiationError> {
+    if client.version.major != server.version.major {
+        return Err(NegotiationError::Incompatible { peer: client.version, supported: vec![server.version] });
+    }
+    check_generator(client, server)?;
+    Ok(Version::new(client.version.major, client.version.minor.min(server.version.minor)))
+}
+
+/// Check a server's selection against what the client offered
+///
+/// It must keep our major and may only lower our minor.
+pub fn check_selected(offered: &VersionInfo, selected: Version) -> Result<Version, NegotiationError> {
+    if selected.major != offered.version.major || selected.minor > offered.version.minor {
+        return Err(NegotiationError::Downgrade { selected });
+    }
+    Ok(selected)
+}
+
+/// Check that a client and server prove over the same generator
+pub fn check_generator(client: &VersionInfo, server: &VersionInfo) -> Result<(), NegotiationError> {
+    let (prover, verifier) = (client.params(), server.params());
+    if prover != verifier {
+        return Err(NegotiationError::GeneratorMismatch { verifier: verifier.generator().compress(), prover: prover.generator().compress() });
+    }
+    Ok(())
+}
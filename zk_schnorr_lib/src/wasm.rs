@@ -0,0 +1,89 @@
+//! Keypairs and signatures for JavaScript (`wasm` feature)
+//!
+//! The `#[wasm_bindgen]` functions take and return hex strings, the same
+//! encodings `Message` carries, so a browser prover can produce keys and
+//! signatures a native verifier checks. Each is a thin shim over a plain Rust
+//! function ([`generate_keypair`], [`schnorr_sign`], [`schnorr_verify`]) that
+//! native code and tests can call directly.
+//!
+//! On `wasm32` the nonce comes from `OsRng` as everywhere else: the crate
+//! enables `getrandom`'s `js` backend for that target, which reads
+//! `crypto.getRandomValues`. The crate types are left alone, so build the
+//! module with `cargo rustc --crate-type cdylib` for `wasm32-unknown-unknown`
+//! and run `wasm-bindgen` over it; `wasm/test.js` has the exact commands and
+//! exercises the result from Node.
+
+use rand_core::OsRng;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::error::DecodeError;
+use crate::schnorr::KeyPair;
+use crate::signature::{self, Signature};
+use crate::{point_from_hex, point_to_hex, public_key_from_hex, scalar_from_hex_canonical, scalar_to_hex};
+
+/// A keypair as JavaScript sees it: `{ publicKey, secretScalar }`, both hex
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HexKeyPair {
+    pub public_key: String,
+    pub secret_scalar: String,
+}
+
+/// A signature as JavaScript sees it: `{ R, s }`, both hex
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HexSignature {
+    pub R: String,
+    pub s: String,
+}
+
+/// The keypair `KeyPair::from_seed` derives from the hex-encoded `seed_hex`
+pub fn generate_keypair(seed_hex: &str) -> Result<HexKeyPair, DecodeError> {
+    let keypair = KeyPair::from_seed(&hex::decode(seed_hex)?);
+    Ok(HexKeyPair {
+        public_key: keypair.public_key().to_string(),
+        secret_scalar: scalar_to_hex(keypair.secret().expose_secret()),
+    })
+}
+
+/// Sign the hex-encoded message with the canonical hex secret scalar, with a fresh nonce
+pub fn schnorr_sign(secret_hex: &str, message_hex: &str) -> Result<HexSignature, DecodeError> {
+    let secret = scalar_from_hex_canonical(secret_hex)?;
+    let message = hex::decode(message_hex)?;
+    let Signature { R, s } = signature::sign(&secret, &message, &mut OsRng);
+    Ok(HexSignature { R: point_to_hex(&R), s: scalar_to_hex(&s) })
+}
+
+/// Check a signature made by [`schnorr_sign`]; any input that doesn't decode is `false`
+pub fn schnorr_verify(pub_key_hex: &str, message_hex: &str, R_hex: &str, s_hex: &str) -> bool {
+    let decoded = (|| -> Result<_, DecodeError> {
+        let public = public_key_from_hex(pub_key_hex)?;
+        let sig = Signature { R: point_from_hex(R_hex)?, s: scalar_from_hex_canonical(s_hex)? };
+        Ok((public, hex::decode(message_hex)?, sig))
+    })();
+    decoded.is_ok_and(|(public, message, sig)| signature::verify(&public, &message, &sig))
+}
+
+/// `generate_keypair` for JavaScript; throws on a seed that isn't hex
+#[wasm_bindgen]
+pub fn wasm_generate_keypair(seed_hex: &str) -> Result<JsValue, JsError> {
+    to_js(&generate_keypair(seed_hex)?)
+}
+
+/// `schnorr_sign` for JavaScript; throws on a secret or message that doesn't decode
+#[wasm_bindgen]
+pub fn wasm_schnorr_sign(secret_hex: &str, message_hex: &str) -> Result<JsValue, JsError> {
+    to_js(&schnorr_sign(secret_hex, message_hex)?)
+}
+
+/// `schnorr_verify` for JavaScript
+#[wasm_bindgen]
+pub fn wasm_schnorr_verify(pub_key_hex: &str, message_hex: &str, R_hex: &str, s_hex: &str) -> bool {
+    schnorr_verify(pub_key_hex, message_hex, R_hex, s_hex)
+}
+
+// a plain JS object with the same fields as the JSON, through JSON.parse
+fn to_js(value: &impl Serialize) -> Result<JsValue, JsError> {
+    let json = serde_json::to_string(value)?;
+    js_sys::JSON::parse(&json).map_err(|_| JsError::new("JSON.parse rejected our own output"))
+}
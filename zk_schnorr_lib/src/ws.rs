@@ -0,0 +1,143 @@
+//! WebSocket transport for browser clients (`ws` feature)
+//!
+//! Browsers can't open a raw TLS socket, but they can speak WebSocket. Each
+//! [`Message`] travels as one text frame holding its JSON form, so a page can
+//! take part with nothing more than `JSON.stringify` and `JSON.parse`. The
+//! frame already delimits the message, so there is no length prefix and no
+//! choice of codec.
+//!
+//! Messages and frames over [`MAX_FRAME_LEN`] are refused, as on the
+//! length-prefixed transports; an oversized message gets a close frame with
+//! code 1009 before the error is returned. A close frame from the peer reads
+//! as EOF once our reply has gone out, and [`WsTransport::close`] starts the
+//! close handshake from our side.
+
+use std::io;
+use std::sync::Arc;
+
+use futures_util::{SinkExt, StreamExt};
+use rustls::ClientConfig;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::{CloseFrame, WebSocketConfig};
+use tokio_tungstenite::tungstenite::{self, Message as Frame};
+use tokio_tungstenite::{Connector, MaybeTlsStream, WebSocketStream};
+use tracing::debug;
+
+use crate::error::ZkError;
+use crate::framing::MAX_FRAME_LEN;
+use crate::transport::Transport;
+use crate::Message;
+
+/// Whole messages over a WebSocket, one JSON text frame each
+pub struct WsTransport<S = MaybeTlsStream<TcpStream>> {
+    inner: WebSocketStream<S>,
+}
+
+impl<S> WsTransport<S> {
+    /// Wrap a WebSocket whose handshake is already done
+    pub fn new(inner: WebSocketStream<S>) -> Self {
+        Self { inner }
+    }
+
+    /// Give back the underlying WebSocket
+    pub fn into_inner(self) -> WebSocketStream<S> {
+        self.inner
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> WsTransport<S> {
+    /// Send a close frame and wait for the peer's, ending the WebSocket cleanly
+    ///
+    /// Anything the peer still sends before its close frame is discarded. A
+    /// connection the peer already closed is not an error.
+    pub async fn close(&mut self) -> io::Result<()> {
+        match self.inner.close(None).await {
+            Ok(()) => {}
+            Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => return Ok(()),
+            Err(e) => return Err(ws_to_io(e)),
+        }
+        while let Some(frame) = self.inner.next().await {
+            match frame {
+                Ok(Frame::Close(_)) => break, // handshake done; how the peer then drops TCP doesn't matter
+                Ok(_) => continue,
+                Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => break,
+                Err(e) => return Err(ws_to_io(e)),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Transport for WsTransport<S> {
+    async fn send(&mut self, msg: Message) -> io::Result<()> {
+        let json = serde_json::to_string(&msg)?;
+        self.inner.send(Frame::Text(json)).await.map_err(ws_to_io)
+    }
+
+    async fn recv(&mut self) -> io::Result<Message> {
+        loop {
+            let frame = match self.inner.next().await {
+                Some(Err(e @ tungstenite::Error::Capacity(_))) => {
+                    let too_big = CloseFrame { code: CloseCode::Size, reason: "message too big".into() };
+                    let _ = self.inner.close(Some(too_big)).await; // best effort, the error below is what matters
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+                }
+                Some(frame) => frame.map_err(ws_to_io)?,
+                None => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "WebSocket closed")),
+            };
+            match frame {
+                Frame::Text(json) => return Ok(serde_json::from_str(&json)?),
+                Frame::Close(_) => {
+                    let _ = self.inner.flush().await; // tungstenite queued our reply to the close; send it before hanging up
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "peer closed the WebSocket"));
+                }
+                Frame::Ping(_) | Frame::Pong(_) => continue, // tungstenite answers pings itself
+                Frame::Binary(_) | Frame::Frame(_) => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "expected a text frame"));
+                }
+            }
+        }
+    }
+}
+
+// A closed connection reads as EOF, like every other transport
+fn ws_to_io(e: tungstenite::Error) -> io::Error {
+    match e {
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+            io::Error::new(io::ErrorKind::UnexpectedEof, e)
+        }
+        tungstenite::Error::Io(e) => e,
+        e => io::Error::other(e),
+    }
+}
+
+/// Size limits for both ends: no message, and no frame of one, over [`MAX_FRAME_LEN`]
+pub fn ws_config() -> WebSocketConfig {
+    WebSocketConfig {
+        max_message_size: Some(MAX_FRAME_LEN),
+        max_frame_size: Some(MAX_FRAME_LEN),
+        ..WebSocketConfig::default()
+    }
+}
+
+/// Open a WebSocket to a verifier at `url`, e.g. `wss://localhost:4434`
+///
+/// `wss://` URLs are secured with `tls_config`; it is ignored for plain `ws://`.
+pub async fn connect_ws(url: &str, tls_config: Arc<ClientConfig>) -> Result<WsTransport, ZkError> {
+    let connector = Connector::Rustls(tls_config);
+    let (inner, _response) = tokio_tungstenite::connect_async_tls_with_config(url, Some(ws_config()), false, Some(connector)).await?;
+    debug!(url, "WebSocket connected");
+    Ok(WsTransport::new(inner))
+}
+
+/// Complete the TLS and then the WebSocket handshake on an accepted TCP connection
+pub async fn accept_ws(stream: TcpStream, tls_acceptor: &TlsAcceptor) -> Result<WsTransport<TlsStream<TcpStream>>, ZkError> {
+    let tls_stream = tls_acceptor.accept(stream).await?;
+    let inner = tokio_tungstenite::accept_async_with_config(tls_stream, Some(ws_config())).await?;
+    debug!("WebSocket handshake complete");
+    Ok(WsTransport::new(inner))
+}
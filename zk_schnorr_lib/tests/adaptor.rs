@@ -0,0 +1,53 @@
+#![cfg(feature = "std")]
+#![allow(non_snake_case)] // T follows the usual maths notation
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT as G;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::adaptor::{adapt, extract, pre_sign, pre_verify};
+use zk_schnorr_lib::signature::verify;
+use zk_schnorr_lib::KeyPair;
+
+const MESSAGE: &[u8] = b"swap 1 coin for 1 token";
+
+#[test]
+fn adapted_pre_signature_is_a_valid_signature() {
+    let keypair = KeyPair::generate(&mut OsRng);
+    let t = Scalar::random(&mut OsRng);
+    let T = G * t;
+
+    let pre_sig = pre_sign(&keypair, MESSAGE, &T, &mut OsRng);
+    assert!(pre_verify(&keypair.public_key(), MESSAGE, &T, &pre_sig));
+
+    let sig = adapt(&pre_sig, &t);
+    assert!(verify(&keypair.public_key(), MESSAGE, &sig));
+    assert!(!verify(&keypair.public_key(), b"another message", &sig));
+}
+
+#[test]
+fn extract_recovers_the_adaptor_secret() {
+    let keypair = KeyPair::generate(&mut OsRng);
+    let t = Scalar::random(&mut OsRng);
+    let pre_sig = pre_sign(&keypair, MESSAGE, &(G * t), &mut OsRng);
+
+    let sig = adapt(&pre_sig, &t);
+    assert_eq!(extract(&pre_sig, &sig), t);
+}
+
+#[test]
+fn pre_signature_alone_does_not_verify() {
+    let keypair = KeyPair::generate(&mut OsRng);
+    let t = Scalar::random(&mut OsRng);
+    let pre_sig = pre_sign(&keypair, MESSAGE, &(G * t), &mut OsRng);
+
+    assert!(!verify(&keypair.public_key(), MESSAGE, &adapt(&pre_sig, &Scalar::ZERO)));
+    assert!(!verify(&keypair.public_key(), MESSAGE, &adapt(&pre_sig, &(t + Scalar::ONE))));
+}
+
+#[test]
+fn pre_verify_rejects_wrong_adaptor_point() {
+    let keypair = KeyPair::generate(&mut OsRng);
+    let T = G * Scalar::random(&mut OsRng);
+    let pre_sig = pre_sign(&keypair, MESSAGE, &T, &mut OsRng);
+    assert!(!pre_verify(&keypair.public_key(), MESSAGE, &(T + G), &pre_sig));
+}
@@ -0,0 +1,135 @@
+#![cfg(feature = "std")]
+#![allow(non_snake_case)] // A, B, G, H follow the usual maths notation
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT as G;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::and_proof::{prove, verify};
+use zk_schnorr_lib::{AndProof, AndProofError, DecodeError, MultiAndProof, hash_to_point};
+
+struct Setup {
+    x: Scalar,
+    y: Scalar,
+    H: curve25519_dalek::ristretto::RistrettoPoint,
+}
+
+fn setup() -> Setup {
+    Setup {
+        x: Scalar::random(&mut OsRng),
+        y: Scalar::random(&mut OsRng),
+        H: hash_to_point(b"and-proof test generator H"),
+    }
+}
+
+fn transcript() -> Transcript {
+    Transcript::new(b"and-proof-test")
+}
+
+#[test]
+fn honest_proof_verifies() {
+    let Setup { x, y, H } = setup();
+    let proof = prove(&x, &y, &G, &H, &mut transcript(), &mut OsRng);
+    assert!(verify(&(G * x), &(H * y), &G, &H, &proof, &mut transcript()));
+}
+
+#[test]
+fn tampered_s1_fails() {
+    let Setup { x, y, H } = setup();
+    let mut proof = prove(&x, &y, &G, &H, &mut transcript(), &mut OsRng);
+    proof.s1 += Scalar::ONE;
+    assert!(!verify(&(G * x), &(H * y), &G, &H, &proof, &mut transcript()));
+}
+
+#[test]
+fn tampered_s2_fails() {
+    let Setup { x, y, H } = setup();
+    let mut proof = prove(&x, &y, &G, &H, &mut transcript(), &mut OsRng);
+    proof.s2 += Scalar::ONE;
+    assert!(!verify(&(G * x), &(H * y), &G, &H, &proof, &mut transcript()));
+}
+
+#[test]
+fn transcript_context_is_bound() {
+    let Setup { x, y, H } = setup();
+    let mut prover_transcript = transcript();
+    prover_transcript.append_message(b"session", b"one");
+    let proof = prove(&x, &y, &G, &H, &mut prover_transcript, &mut OsRng);
+
+    let mut other_transcript = transcript();
+    other_transcript.append_message(b"session", b"two");
+    assert!(!verify(&(G * x), &(H * y), &G, &H, &proof, &mut other_transcript));
+}
+
+#[test]
+fn serde_round_trip() {
+    let Setup { x, y, H } = setup();
+    let proof = prove(&x, &y, &G, &H, &mut transcript(), &mut OsRng);
+    let json = serde_json::to_string(&proof).unwrap();
+    let decoded: AndProof = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, proof);
+    assert!(verify(&(G * x), &(H * y), &G, &H, &decoded, &mut transcript()));
+}
+
+fn statements(n: usize) -> (Vec<Scalar>, Vec<curve25519_dalek::ristretto::RistrettoPoint>) {
+    let secrets: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut OsRng)).collect();
+    let publics = secrets.iter().map(|x| G * x).collect();
+    (secrets, publics)
+}
+
+#[test]
+fn multi_proof_verifies_for_every_size() {
+    for n in [1, 2, 5] {
+        let (secrets, publics) = statements(n);
+        let proof = MultiAndProof::prove(&secrets, &publics, &mut OsRng).unwrap();
+        assert_eq!(proof.pairs.len(), n);
+        assert!(proof.verify(&publics));
+    }
+}
+
+#[test]
+fn multi_proof_fails_if_any_secret_is_wrong() {
+    let (mut secrets, publics) = statements(3);
+    secrets[1] += Scalar::ONE;
+    let proof = MultiAndProof::prove(&secrets, &publics, &mut OsRng).unwrap();
+    assert!(!proof.verify(&publics));
+}
+
+#[test]
+fn multi_proof_is_bound_to_the_keys_and_their_order() {
+    let (secrets, mut publics) = statements(3);
+    let proof = MultiAndProof::prove(&secrets, &publics, &mut OsRng).unwrap();
+    assert!(!proof.verify(&publics[..2]));
+    publics.swap(0, 2);
+    assert!(!proof.verify(&publics));
+}
+
+#[test]
+fn multi_proof_rejects_mismatched_or_empty_input() {
+    let (secrets, publics) = statements(3);
+    assert_eq!(
+        MultiAndProof::prove(&secrets[..2], &publics, &mut OsRng),
+        Err(AndProofError::LengthMismatch { secrets: 2, publics: 3 })
+    );
+    assert_eq!(MultiAndProof::prove(&[], &[], &mut OsRng), Err(AndProofError::Empty));
+}
+
+#[test]
+fn multi_proof_bytes_round_trip() {
+    let (secrets, publics) = statements(4);
+    let proof = MultiAndProof::prove(&secrets, &publics, &mut OsRng).unwrap();
+    let bytes = proof.to_bytes();
+    assert_eq!(bytes.len(), 4 + 4 * 64);
+    assert_eq!(&bytes[..4], &4u32.to_be_bytes());
+    let decoded = MultiAndProof::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded, proof);
+    assert!(decoded.verify(&publics));
+}
+
+#[test]
+fn multi_proof_with_wrong_length_prefix_is_rejected() {
+    let (secrets, publics) = statements(2);
+    let mut bytes = MultiAndProof::prove(&secrets, &publics, &mut OsRng).unwrap().to_bytes();
+    bytes[3] = 3;
+    assert!(matches!(MultiAndProof::from_bytes(&bytes), Err(DecodeError::Length { expected: 196, got: 132 })));
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "tls")]
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::{
+    commit, respond, scalar_from_hex, ChallengeContext, ChannelTransport, KeyPair, Message, ProtocolError, PublicKey,
+    SchnorrProtocol, Transport, MAX_AND_STATEMENTS,
+};
+
+fn keypairs(n: usize) -> (Vec<KeyPair>, Vec<PublicKey>) {
+    let keypairs: Vec<KeyPair> = (0..n).map(|_| KeyPair::generate(&mut OsRng)).collect();
+    let pubkeys = keypairs.iter().map(KeyPair::public_key).collect();
+    (keypairs, pubkeys)
+}
+
+#[tokio::test]
+async fn and_round_proves_every_key() {
+    let (keypairs, pubkeys) = keypairs(3);
+    let context = ChallengeContext::random(&mut OsRng);
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let (proved, verified) = tokio::join!(
+        SchnorrProtocol::prove_and_round(&mut prover_end, &context, &keypairs),
+        SchnorrProtocol::verify_and_round(&mut verifier_end, &context, &pubkeys),
+    );
+    verified.unwrap();
+    let proofs = proved.unwrap();
+    assert_eq!(proofs.len(), 3);
+    assert!(proofs.iter().zip(&pubkeys).all(|(proof, key)| proof.verify(key)));
+    assert!(proofs.iter().all(|proof| proof.challenge == proofs[0].challenge), "one challenge for all");
+}
+
+#[tokio::test]
+async fn one_corrupted_response_rejects_the_whole_proof() {
+    let (keypairs, pubkeys) = keypairs(4);
+    let context = ChallengeContext::random(&mut OsRng);
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let cheating_prover = async {
+        let nonces: Vec<Scalar> = keypairs.iter().map(|_| Scalar::random(&mut OsRng)).collect();
+        let commitments: Vec<RistrettoPoint> = nonces.iter().map(commit).collect();
+        prover_end.send(Message::commit_batch(&commitments)).await.unwrap();
+        let c = scalar_from_hex(&prover_end.recv().await.unwrap().payload).unwrap();
+        let mut responses: Vec<Scalar> = nonces
+            .iter()
+            .zip(&keypairs)
+            .map(|(k, keypair)| respond(k, &c, keypair.secret().expose_secret()))
+            .collect();
+        responses[2] += Scalar::ONE;
+        prover_end.send(Message::response_batch(&responses)).await.unwrap();
+        prover_end.recv().await.unwrap()
+    };
+    let (verdict, verified) = tokio::join!(cheating_prover, SchnorrProtocol::verify_and_round(&mut verifier_end, &context, &pubkeys));
+    assert!(matches!(verified, Err(ProtocolError::StatementFailed { index: 2 })));
+    assert_eq!((verdict.kind.as_str(), verdict.payload.as_str()), ("result", "rejected"));
+}
+
+#[tokio::test]
+async fn too_many_commitments_are_refused_undecoded() {
+    let (_, pubkeys) = keypairs(2);
+    let context = ChallengeContext::random(&mut OsRng);
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let flooding_prover = async {
+        let commitments = vec![RistrettoPoint::default(); MAX_AND_STATEMENTS + 1];
+        prover_end.send(Message::commit_batch(&commitments)).await.unwrap();
+        prover_end.recv().await.unwrap()
+    };
+    let (reply, verified) = tokio::join!(flooding_prover, SchnorrProtocol::verify_and_round(&mut verifier_end, &context, &pubkeys));
+    assert!(matches!(verified, Err(ProtocolError::TooManyStatements { got, max: MAX_AND_STATEMENTS }) if got == MAX_AND_STATEMENTS + 1));
+    assert_eq!(reply.kind, "error");
+}
+
+#[tokio::test]
+async fn commitment_count_must_match_the_keys() {
+    let (keypairs, pubkeys) = keypairs(3);
+    let context = ChallengeContext::random(&mut OsRng);
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let (proved, verified) = tokio::join!(
+        SchnorrProtocol::prove_and_round(&mut prover_end, &context, &keypairs[..2]),
+        SchnorrProtocol::verify_and_round(&mut verifier_end, &context, &pubkeys),
+    );
+    assert!(matches!(verified, Err(ProtocolError::StatementCountMismatch { expected: 3, got: 2 })));
+    assert!(matches!(proved, Err(ProtocolError::Rejected { .. })));
+}
+
+#[tokio::test]
+async fn prover_refuses_more_than_the_cap() {
+    let (keypairs, _) = keypairs(MAX_AND_STATEMENTS + 1);
+    let context = ChallengeContext::random(&mut OsRng);
+    let (mut prover_end, _verifier_end) = ChannelTransport::pair();
+
+    let proved = SchnorrProtocol::prove_and_round(&mut prover_end, &context, &keypairs).await;
+    assert!(matches!(proved, Err(ProtocolError::TooManyStatements { .. })));
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "tls")]
+
+use std::time::{Duration, SystemTime};
+
+use curve25519_dalek::scalar::Scalar;
+use tempfile::TempDir;
+use zk_schnorr_lib::{
+    run_prover, run_verifier_logged, AuditRecord, JsonFileLog, KeyPair, Proof, ProofLog, SessionId, StreamTransport,
+};
+
+#[tokio::test]
+async fn full_proof_is_recorded_once() {
+    let dir = TempDir::new().unwrap();
+    let log = JsonFileLog::new(dir.path().join("audit.jsonl"));
+    let keypair = KeyPair::from_seed(b"audit");
+    let expected = keypair.public_key();
+    let (prover_end, verifier_end) = tokio::io::duplex(4096);
+    let (mut prover, mut verifier) = (StreamTransport::new(prover_end), StreamTransport::new(verifier_end));
+
+    let prove = async move { run_prover(&mut prover, &keypair).await };
+    let (proved, verified) = tokio::join!(prove, run_verifier_logged(&mut verifier, &expected, &log));
+    proved.unwrap();
+    assert!(verified.unwrap().accepted());
+
+    let contents = std::fs::read_to_string(&log.path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].contains("\"verified\":true"));
+    let record: AuditRecord = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(record.public_key, expected.to_string());
+    assert!(!dir.path().join("audit.jsonl.tmp").exists());
+}
+
+#[test]
+fn records_are_appended_in_order() {
+    let dir = TempDir::new().unwrap();
+    let log = JsonFileLog::new(dir.path().join("audit.jsonl"));
+    let keypair = KeyPair::from_seed(b"audit");
+    let proof = Proof::new(keypair.public_key().0, Scalar::ONE, Scalar::ONE);
+    let at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+    log.record(&SessionId::new([1; 16]), &keypair.public_key(), &proof, false, at);
+    log.record(&SessionId::new([2; 16]), &keypair.public_key(), &proof, true, at);
+
+    let records: Vec<AuditRecord> =
+        std::fs::read_to_string(&log.path).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].session_id, "01".repeat(16));
+    assert_eq!((records[0].verified, records[1].verified), (false, true));
+    assert_eq!(records[1].timestamp, 1_700_000_000);
+}
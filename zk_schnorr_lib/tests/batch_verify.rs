@@ -0,0 +1,44 @@
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::scalar::Scalar;
+use proptest::prelude::*;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::{Proof, PublicKey, batch_verify};
+
+// build an honest (proof, public key) pair from raw secret, nonce and challenge bytes
+fn honest_proof(x: [u8; 32], k: [u8; 32], c: [u8; 32]) -> (Proof, PublicKey) {
+    let x = Scalar::from_bytes_mod_order(x);
+    let k = Scalar::from_bytes_mod_order(k);
+    let c = Scalar::from_bytes_mod_order(c);
+    let proof = Proof::new(RISTRETTO_BASEPOINT_POINT * k, c, k + c * x);
+    (proof, PublicKey(RISTRETTO_BASEPOINT_POINT * x))
+}
+
+fn batch_strategy() -> impl Strategy<Value = Vec<(Proof, PublicKey)>> {
+    prop::collection::vec(
+        (any::<[u8; 32]>(), any::<[u8; 32]>(), any::<[u8; 32]>())
+            .prop_map(|(x, k, c)| honest_proof(x, k, c)),
+        1..8,
+    )
+}
+
+#[test]
+fn empty_batch_is_rejected() {
+    assert!(!batch_verify(&[], &mut OsRng));
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn valid_batches_verify(batch in batch_strategy()) {
+        prop_assert!(batch.iter().all(|(proof, key)| proof.verify(key)));
+        prop_assert!(batch_verify(&batch, &mut OsRng));
+    }
+
+    #[test]
+    fn one_bad_proof_fails_the_batch(mut batch in batch_strategy(), index in any::<prop::sample::Index>()) {
+        let bad = index.index(batch.len());
+        batch[bad].0.response += Scalar::ONE; // s no longer matches R + c*X
+        prop_assert!(!batch_verify(&batch, &mut OsRng));
+    }
+}
@@ -0,0 +1,35 @@
+use curve25519_dalek::scalar::Scalar;
+use zk_schnorr_lib::{scalar_from_hex, scalar_from_hex_canonical, scalar_to_hex, ScalarDecodeError};
+
+// the group order l = 2^252 + 27742317777372353535851937790883648493, little-endian
+const GROUP_ORDER_HEX: &str = "edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010";
+
+#[test]
+fn group_order_is_rejected_by_strict_decoder() {
+    assert!(matches!(scalar_from_hex_canonical(GROUP_ORDER_HEX), Err(ScalarDecodeError::NonCanonical)));
+}
+
+#[test]
+fn permissive_decoder_reduces_group_order_to_zero() {
+    assert_eq!(scalar_from_hex(GROUP_ORDER_HEX).unwrap(), Scalar::ZERO);
+}
+
+#[test]
+fn order_plus_one_aliases_one_only_on_permissive_path() {
+    let l_plus_one = "eed3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010";
+    assert_eq!(scalar_from_hex(l_plus_one).unwrap(), Scalar::ONE);
+    assert!(matches!(scalar_from_hex_canonical(l_plus_one), Err(ScalarDecodeError::NonCanonical)));
+}
+
+#[test]
+fn canonical_round_trip() {
+    let s = Scalar::from(123456789u64);
+    assert_eq!(scalar_from_hex_canonical(&scalar_to_hex(&s)).unwrap(), s);
+    assert_eq!(scalar_from_hex_canonical(&scalar_to_hex(&-Scalar::ONE)).unwrap(), -Scalar::ONE);
+}
+
+#[test]
+fn bad_hex_and_length_are_reported() {
+    assert!(matches!(scalar_from_hex_canonical("zz"), Err(ScalarDecodeError::HexDecode(_))));
+    assert!(matches!(scalar_from_hex_canonical("abcd"), Err(ScalarDecodeError::InvalidLength(2))));
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "server")]
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustls::ServerName;
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use zk_schnorr_lib::{
+    create_client_config_insecure, create_server_config, generate_self_signed_cert, run_prover, watch_cert_file, KeyPair,
+    KeyRegistry, RotatingCertResolver, ServerHandle, StreamTransport, TlsCertificate, VerifierServer,
+};
+
+const CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+fn write_pem(dir: &Path, cert: &TlsCertificate) -> (PathBuf, PathBuf) {
+    let (cert_path, key_path) = (dir.join("cert.pem"), dir.join("key.pem"));
+    std::fs::write(&cert_path, cert.cert_pem()).unwrap();
+    std::fs::write(&key_path, cert.private_key_pem().as_bytes()).unwrap();
+    (cert_path, key_path)
+}
+
+// a proof session over TLS, trusting only `trusted`
+async fn prove(handle: &ServerHandle, trusted: &TlsCertificate, keypair: &KeyPair) -> std::io::Result<bool> {
+    let connector = TlsConnector::from(Arc::new(create_client_config_insecure(trusted).unwrap()));
+    let tcp = TcpStream::connect(handle.local_addr()).await?;
+    let tls = connector.connect(ServerName::try_from("localhost").unwrap(), tcp).await?;
+    Ok(run_prover(&mut StreamTransport::new(tls), keypair).await.unwrap().accepted())
+}
+
+// wait, within a second, for `resolver` to serve `cert`
+async fn until_serving(resolver: &RotatingCertResolver, cert: &TlsCertificate) {
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while resolver.current_leaf_der() != cert.cert_der {
+            tokio::time::sleep(CHECK_INTERVAL / 2).await;
+        }
+    })
+    .await
+    .expect("certificate was not reloaded");
+}
+
+#[tokio::test]
+async fn rewritten_certificate_is_served_without_a_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = generate_self_signed_cert().unwrap();
+    let (cert_path, key_path) = write_pem(dir.path(), &first);
+
+    let resolver = Arc::new(RotatingCertResolver::new(&first).unwrap());
+    let watcher = watch_cert_file(Arc::clone(&resolver), cert_path, key_path, CHECK_INTERVAL);
+    let keypair = KeyPair::from_seed(b"alice");
+    let handle = VerifierServer::builder()
+        .bind("127.0.0.1:0".parse().unwrap())
+        .tls(create_server_config(&first, Some(Arc::clone(&resolver))).unwrap())
+        .expected_keys(KeyRegistry::single("alice", keypair.public_key()))
+        .build()
+        .unwrap()
+        .spawn()
+        .await
+        .unwrap();
+
+    assert!(prove(&handle, &first, &keypair).await.unwrap());
+
+    let second = generate_self_signed_cert().unwrap();
+    write_pem(dir.path(), &second);
+    until_serving(&resolver, &second).await;
+
+    assert!(prove(&handle, &second, &keypair).await.unwrap());
+    assert!(prove(&handle, &first, &keypair).await.is_err(), "the old certificate is no longer presented");
+
+    watcher.abort();
+    handle.shutdown().await.unwrap();
+}
+
+#[test]
+fn reload_swaps_the_certificate() {
+    let first = generate_self_signed_cert().unwrap();
+    let second = generate_self_signed_cert().unwrap();
+    let resolver = RotatingCertResolver::new(&first).unwrap();
+    assert_eq!(resolver.current_leaf_der(), first.cert_der);
+
+    resolver.reload(&second).unwrap();
+    assert_eq!(resolver.current_leaf_der(), second.cert_der);
+}
+
+#[tokio::test]
+async fn unreadable_files_keep_the_current_certificate() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = generate_self_signed_cert().unwrap();
+    let (cert_path, key_path) = write_pem(dir.path(), &first);
+    let resolver = Arc::new(RotatingCertResolver::new(&first).unwrap());
+    let watcher = watch_cert_file(Arc::clone(&resolver), cert_path.clone(), key_path, CHECK_INTERVAL);
+
+    std::fs::write(&cert_path, "not a certificate").unwrap();
+    tokio::time::sleep(CHECK_INTERVAL * 4).await;
+    assert_eq!(resolver.current_leaf_der(), first.cert_der);
+
+    let second = generate_self_signed_cert().unwrap();
+    write_pem(dir.path(), &second);
+    until_serving(&resolver, &second).await;
+    watcher.abort();
+}
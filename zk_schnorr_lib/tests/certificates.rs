@@ -0,0 +1,115 @@
+#![cfg(feature = "tls")]
+
+use std::net::IpAddr;
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use rcgen::date_time_ymd;
+use time::{Duration, OffsetDateTime};
+use x509_parser::extensions::GeneralName;
+use x509_parser::oid_registry::OID_SIG_ED25519;
+use x509_parser::prelude::{FromDer, X509Certificate};
+use zk_schnorr_lib::{
+    CertKeyAlgorithm, CertOptions, PemCertificate, TlsError, create_server_config, create_server_config_from_pem,
+    generate_self_signed_cert, generate_self_signed_cert_for, generate_self_signed_cert_with,
+};
+
+fn der_contains(der: &[u8], needle: &str) -> bool {
+    der.windows(needle.len()).any(|w| w == needle.as_bytes())
+}
+
+#[test]
+fn custom_names_end_up_in_the_certificate() {
+    let options = CertOptions {
+        subject_alt_names: vec!["verifier.internal".to_string()],
+        common_name: "Custom Verifier".to_string(),
+        ..CertOptions::default()
+    };
+    let cert = generate_self_signed_cert_with(options).unwrap();
+
+    assert!(der_contains(&cert.cert_der, "verifier.internal"));
+    assert!(der_contains(&cert.cert_der, "Custom Verifier"));
+    assert!(!der_contains(&cert.cert_der, "localhost"));
+    assert!(create_server_config(&cert, None).is_ok());
+}
+
+#[test]
+fn default_options_keep_the_development_names() {
+    let cert = generate_self_signed_cert_with(CertOptions::default()).unwrap();
+    assert!(der_contains(&cert.cert_der, "localhost"));
+    // rcgen encodes IP SANs as raw address bytes rather than text
+    assert!(cert.cert_der.windows(4).any(|w| w == [127, 0, 0, 1]));
+}
+
+#[test]
+fn validity_window_is_configurable() {
+    let options = CertOptions {
+        not_before: date_time_ymd(2020, 1, 1),
+        not_after: date_time_ymd(2020, 1, 2),
+        ..CertOptions::default()
+    };
+    let cert = generate_self_signed_cert_with(options).unwrap();
+    // validity times are encoded as UTCTime YYMMDDHHMMSSZ
+    assert!(der_contains(&cert.cert_der, "200101000000Z"));
+    assert!(der_contains(&cert.cert_der, "200102000000Z"));
+}
+
+#[test]
+fn pem_certificate_round_trips_into_a_server_config() {
+    let cert = generate_self_signed_cert().unwrap();
+    // serialize_pem would sign a fresh certificate, so wrap the DER we already have
+    let cert_pem = format!("-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n", BASE64_STANDARD.encode(&cert.cert_der));
+    let key_pem = cert.certificate.serialize_private_key_pem();
+
+    let pem_cert = PemCertificate::from_pem(cert_pem.as_bytes(), key_pem.as_bytes()).unwrap();
+    assert_eq!(pem_cert.leaf_der(), cert.cert_der.as_slice());
+    assert_eq!(pem_cert.private_key_der.as_slice(), cert.private_key_der.as_slice());
+    assert!(create_server_config_from_pem(&pem_cert).is_ok());
+}
+
+#[test]
+fn pem_without_a_certificate_or_key_is_refused() {
+    let cert = generate_self_signed_cert().unwrap();
+    let cert_pem = cert.certificate.serialize_pem().unwrap();
+    let key_pem = cert.certificate.serialize_private_key_pem();
+
+    assert!(matches!(PemCertificate::from_pem(key_pem.as_bytes(), key_pem.as_bytes()), Err(TlsError::MissingCertificate)));
+    assert!(matches!(PemCertificate::from_pem(cert_pem.as_bytes(), cert_pem.as_bytes()), Err(TlsError::MissingPrivateKey)));
+}
+
+#[test]
+fn requested_sans_and_validity_are_in_the_certificate() {
+    let names = ["verifier.internal", "10.0.0.5", "::1"].map(String::from);
+    let cert = generate_self_signed_cert_for(&names, 30, "Internal Verifier").unwrap();
+    let (_, parsed) = X509Certificate::from_der(&cert.cert_der).unwrap();
+
+    let sans = parsed.subject_alternative_name().unwrap().expect("a SAN extension");
+    let general_names = &sans.value.general_names;
+    assert_eq!(general_names.len(), 3);
+    assert!(matches!(general_names[0], GeneralName::DNSName("verifier.internal")));
+    assert!(matches!(general_names[1], GeneralName::IPAddress(&[10, 0, 0, 5])));
+    let GeneralName::IPAddress(v6) = general_names[2] else { panic!("{:?} is not an IP SAN", general_names[2]) };
+    assert_eq!(<[u8; 16]>::try_from(v6).map(IpAddr::from).unwrap(), "::1".parse::<IpAddr>().unwrap());
+
+    let expected_not_after = OffsetDateTime::now_utc() + Duration::days(30);
+    assert!((parsed.validity().not_after.to_datetime() - expected_not_after).abs() < Duration::minutes(1));
+    assert!(parsed.validity().is_valid());
+    assert_eq!(parsed.subject().iter_common_name().next().unwrap().as_str().unwrap(), "Internal Verifier");
+    assert!(create_server_config(&cert, None).is_ok());
+}
+
+#[test]
+fn the_existing_generator_still_covers_localhost() {
+    let cert = generate_self_signed_cert().unwrap();
+    let (_, parsed) = X509Certificate::from_der(&cert.cert_der).unwrap();
+    let sans = parsed.subject_alternative_name().unwrap().unwrap();
+    assert!(matches!(sans.value.general_names[..], [GeneralName::DNSName("localhost"), GeneralName::IPAddress(&[127, 0, 0, 1])]));
+}
+
+#[test]
+fn ed25519_keys_can_be_chosen() {
+    let options = CertOptions { key_algorithm: CertKeyAlgorithm::Ed25519, ..CertOptions::default() };
+    let cert = generate_self_signed_cert_with(options).unwrap();
+    let (_, parsed) = X509Certificate::from_der(&cert.cert_der).unwrap();
+    assert_eq!(parsed.signature_algorithm.algorithm, OID_SIG_ED25519);
+    assert!(create_server_config(&cert, None).is_ok());
+}
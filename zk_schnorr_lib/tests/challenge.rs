@@ -0,0 +1,44 @@
+#![allow(non_snake_case)] // R follows the usual maths notation
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT as G;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::ChallengeContext;
+
+#[test]
+fn challenge_is_bound_to_session_commitment_and_key() {
+    let (R, X) = (G * Scalar::from(11u64), G * Scalar::from(7u64));
+    let context = ChallengeContext::new([1; 32]);
+    let c = context.challenge(&R, &X);
+
+    assert_eq!(c, context.challenge(&R, &X));
+    assert_ne!(c, ChallengeContext::new([2; 32]).challenge(&R, &X));
+    assert_ne!(c, context.challenge(&(R + G), &X));
+    assert_ne!(c, context.challenge(&R, &(X + G)));
+}
+
+#[test]
+fn challenge_depends_on_the_randomness_as_well_as_the_session_id() {
+    let (R, X) = (G * Scalar::from(11u64), G * Scalar::from(7u64));
+    let mut other_random = [1; 32];
+    other_random[31] = 2;
+    let (context, other) = (ChallengeContext::new([1; 32]), ChallengeContext::new(other_random));
+
+    assert_eq!(context.session_id(), other.session_id());
+    assert_ne!(context.challenge(&R, &X), other.challenge(&R, &X));
+}
+
+#[test]
+fn random_contexts_differ() {
+    assert_ne!(ChallengeContext::random(&mut OsRng), ChallengeContext::random(&mut OsRng));
+}
+
+#[test]
+fn hex_round_trip_requires_exactly_32_bytes() {
+    let context = ChallengeContext::random(&mut OsRng);
+    assert_eq!(context.to_hex().len(), 64);
+    assert_eq!(ChallengeContext::from_hex(&context.to_hex()).unwrap(), context);
+    assert!(ChallengeContext::from_hex(&"00".repeat(31)).is_err());
+    assert!(ChallengeContext::from_hex(&"00".repeat(33)).is_err());
+    assert!(ChallengeContext::from_hex("not hex").is_err());
+}
@@ -0,0 +1,160 @@
+#![cfg(feature = "tls")]
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use zk_schnorr_lib::{
+    BinaryCodec, Codec, CodecError, JsonCodec, KeyPair, Message, ProtocolError, SchnorrProtocol, SessionId,
+    StreamTransport, WireFormat,
+};
+
+fn commit_message() -> Message {
+    Message::commit(&RISTRETTO_BASEPOINT_POINT)
+}
+
+fn sample_messages() -> Vec<Message> {
+    vec![
+        commit_message(),
+        Message::result(true),
+        Message::error("ABCD is upper-case hex, not a point"),
+        Message { kind: "response".to_string(), payload: String::new(), session: None },
+    ]
+}
+
+#[test]
+fn every_supported_format_round_trips() {
+    for &format in WireFormat::supported() {
+        for msg in sample_messages() {
+            let decoded = format.decode(&format.encode(&msg)).unwrap();
+            assert_eq!((decoded.kind, decoded.payload), (msg.kind, msg.payload), "{format}");
+        }
+    }
+}
+
+#[test]
+fn session_tag_round_trips_in_every_format() {
+    let msg = commit_message().in_session(&SessionId::new([7; 16]));
+    for &format in WireFormat::supported() {
+        let decoded = format.decode(&format.encode(&msg)).unwrap();
+        assert_eq!(decoded.session, msg.session, "{format}");
+        assert_eq!(decoded.payload, msg.payload, "{format}");
+    }
+}
+
+#[test]
+fn untagged_messages_encode_as_before() {
+    let msg = commit_message();
+    assert!(!JsonCodec.encode(&msg).windows(7).any(|w| w == b"session"));
+    // kind length, kind, payload: no room left for a session id
+    assert_eq!(BinaryCodec.encode(&msg).len(), 2 + "commit".len() + msg.payload.len());
+    let tagged = msg.in_session(&SessionId::new([7; 16]));
+    assert_eq!(BinaryCodec.encode(&tagged).len(), 2 + "commit".len() + 16 + tagged.payload.len());
+}
+
+#[test]
+fn binary_is_smaller_than_json() {
+    let msg = commit_message();
+    assert!(BinaryCodec.encode(&msg).len() < JsonCodec.encode(&msg).len());
+}
+
+#[test]
+fn truncated_binary_frame_is_rejected() {
+    assert!(matches!(BinaryCodec.decode(&[0, 10, b'c']), Err(CodecError::Binary(_))));
+}
+
+#[test]
+fn json_frame_is_not_binary_and_vice_versa() {
+    let msg = commit_message();
+    assert!(JsonCodec.decode(&BinaryCodec.encode(&msg)).is_err());
+    // the JSON text happens to parse as a binary header, but the kind comes out wrong
+    assert!(BinaryCodec.decode(&JsonCodec.encode(&msg)).map_or(true, |m| m.kind != msg.kind));
+}
+
+#[test]
+fn format_names_parse() {
+    for format in [WireFormat::Json, WireFormat::Cbor, WireFormat::Binary] {
+        assert_eq!(format.to_string().parse::<WireFormat>().unwrap(), format);
+    }
+    assert!("xml".parse::<WireFormat>().is_err());
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn cbor_commit_is_smaller_than_json() {
+    use zk_schnorr_lib::{message_from_cbor, message_to_cbor, CborCodec};
+
+    let msg = commit_message();
+    let json_len = JsonCodec.encode(&msg).len();
+    let cbor = message_to_cbor(&msg);
+    assert!(cbor.len() < json_len);
+    assert_eq!(message_from_cbor(&cbor).unwrap().payload, msg.payload);
+    // raw 32-byte point instead of 64 hex characters
+    assert!(CborCodec.encode(&msg).len() < json_len * 2 / 3);
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn json_and_cbor_do_not_mix() {
+    use zk_schnorr_lib::CborCodec;
+
+    let msg = commit_message();
+    assert!(matches!(CborCodec.decode(&JsonCodec.encode(&msg)), Err(CodecError::Cbor(_))));
+    assert!(matches!(JsonCodec.decode(&CborCodec.encode(&msg)), Err(CodecError::Json(_))));
+}
+
+#[cfg(not(feature = "cbor"))]
+#[test]
+fn cbor_needs_the_feature() {
+    assert!(!WireFormat::Cbor.is_supported());
+    assert!(matches!(WireFormat::Cbor.decode(b""), Err(CodecError::Unsupported(WireFormat::Cbor))));
+}
+
+#[tokio::test]
+async fn negotiated_codec_carries_the_whole_session() {
+    let keypair = KeyPair::from_seed(b"codec-test");
+    let expected = keypair.public_key();
+    let (prover_end, verifier_end) = tokio::io::duplex(1024);
+    let (mut prover, mut verifier) = (StreamTransport::new(prover_end), StreamTransport::new(verifier_end));
+
+    let prove = async {
+        SchnorrProtocol::request_codec(&mut prover, WireFormat::Binary).await.unwrap();
+        SchnorrProtocol::run_prover(&mut prover, &keypair).await.unwrap();
+    };
+    let verify = async {
+        let codec = SchnorrProtocol::accept_codec(&mut verifier, WireFormat::supported()).await.unwrap();
+        assert_eq!(codec, WireFormat::Binary);
+        SchnorrProtocol::run_verifier(&mut verifier, &expected).await.unwrap()
+    };
+    let ((), verified) = tokio::join!(prove, verify);
+    assert!(verified);
+    assert_eq!(prover.codec(), WireFormat::Binary);
+}
+
+#[tokio::test]
+async fn unsupported_codec_is_refused() {
+    let (prover_end, verifier_end) = tokio::io::duplex(1024);
+    let (mut prover, mut verifier) = (StreamTransport::new(prover_end), StreamTransport::new(verifier_end));
+
+    let (requested, accepted) = tokio::join!(
+        SchnorrProtocol::request_codec(&mut prover, WireFormat::Binary),
+        SchnorrProtocol::accept_codec(&mut verifier, &[WireFormat::Json]),
+    );
+    assert!(matches!(accepted, Err(ProtocolError::UnsupportedCodec(name)) if name == "binary"));
+    assert!(matches!(requested, Err(ProtocolError::Rejected { .. })));
+    assert_eq!(prover.codec(), WireFormat::Json);
+}
+
+#[tokio::test]
+async fn mismatched_wire_formats_fail_instead_of_hanging() {
+    let keypair = KeyPair::from_seed(b"codec-test");
+    let expected = keypair.public_key();
+    let (prover_end, verifier_end) = tokio::io::duplex(1024);
+    let mut prover = StreamTransport::with_codec(prover_end, WireFormat::Json);
+    let mut verifier = StreamTransport::with_codec(verifier_end, WireFormat::Binary);
+
+    let prove = async {
+        let result = SchnorrProtocol::run_prover(&mut prover, &keypair).await;
+        drop(prover); // hang up, as the binary does on error
+        result
+    };
+    let (proved, _) = tokio::join!(prove, SchnorrProtocol::verify_interactive(&mut verifier, &expected));
+    assert!(matches!(proved, Err(ProtocolError::Serde(_))));
+}
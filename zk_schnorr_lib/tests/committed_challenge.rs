@@ -0,0 +1,91 @@
+#![cfg(feature = "tls")]
+
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::{
+    run_prover_with, run_verifier_with_params, ChallengeContext, ChallengeOpening, ChallengePolicy, ChannelTransport, DecodeError,
+    KeyPair, Message, NullLog, Params, ProtocolError, ProverOptions, SchnorrProtocol, Transport, VersionInfo, COMMITTED_CHALLENGE,
+};
+
+fn committed() -> ProverOptions {
+    ProverOptions { rounds: 2, committed_challenge: true, ..ProverOptions::default() }
+}
+
+#[test]
+fn an_opening_opens_only_its_own_commitment() {
+    let opening = ChallengeOpening::random(&mut OsRng);
+    assert!(opening.opens(&opening.commitment()));
+    assert!(!ChallengeOpening::random(&mut OsRng).opens(&opening.commitment()));
+    assert!(!ChallengeOpening::new(opening.challenge(), [7; 32]).opens(&opening.commitment())); // same c, another salt
+    assert_eq!(ChallengeOpening::from_hex(&opening.to_hex()).unwrap(), opening);
+}
+
+#[test]
+fn revealed_challenges_must_be_canonical_and_nonzero() {
+    let zero = ChallengeOpening::new(Scalar::ZERO, [1; 32]).to_hex();
+    assert!(matches!(ChallengeOpening::from_hex(&zero), Err(DecodeError::Scalar(_))));
+    let non_canonical = format!("{}{}", "ff".repeat(32), "01".repeat(32));
+    assert!(matches!(ChallengeOpening::from_hex(&non_canonical), Err(DecodeError::Scalar(_))));
+    assert!(matches!(ChallengeOpening::from_hex(&"01".repeat(32)), Err(DecodeError::Length { expected: 64, got: 32 })));
+}
+
+#[test]
+fn both_hellos_must_list_the_feature() {
+    let (plain, committed) = (VersionInfo::current(), VersionInfo::current().with_feature(COMMITTED_CHALLENGE));
+    assert_eq!(ChallengePolicy::negotiated(&committed, &committed), ChallengePolicy::Committed);
+    assert_eq!(ChallengePolicy::negotiated(&committed, &plain), ChallengePolicy::Derived);
+    assert_eq!(ChallengePolicy::negotiated(&plain, &committed), ChallengePolicy::Derived);
+}
+
+#[tokio::test]
+async fn committed_challenges_prove_every_round() {
+    let keypair = KeyPair::from_seed(b"committed");
+    let expected = keypair.public_key();
+    let (params, (mut prover, mut verifier)) = (Params::default(), ChannelTransport::pair());
+
+    let prove = async move { run_prover_with(&mut prover, &keypair, &committed()).await };
+    let (proved, verified) = tokio::join!(prove, run_verifier_with_params(&mut verifier, &expected, &params, &NullLog));
+    assert_eq!(proved.unwrap().rounds, [true, true]);
+    assert_eq!(verified.unwrap().rounds, [true, true]);
+}
+
+#[tokio::test]
+async fn a_verifier_without_the_feature_gets_derived_challenges() {
+    let keypair = KeyPair::from_seed(b"committed");
+    let expected = keypair.public_key();
+    let (mut prover, mut verifier) = ChannelTransport::pair();
+
+    let prove = async move { run_prover_with(&mut prover, &keypair, &committed()).await };
+    let verify = async {
+        SchnorrProtocol::accept_hello(&mut verifier, &VersionInfo::current()).await?;
+        SchnorrProtocol::verify_interactive_with_params(&mut verifier, &expected, &Params::default(), &NullLog).await
+    };
+    let (proved, verified) = tokio::join!(prove, verify);
+    assert_eq!(proved.unwrap().rounds, [true, true]);
+    assert_eq!(verified.unwrap(), [true, true]);
+}
+
+#[tokio::test]
+async fn prover_refuses_a_challenge_that_does_not_open_the_commitment() {
+    let keypair = KeyPair::from_seed(b"committed");
+    let (mut prover, mut verifier) = ChannelTransport::pair();
+
+    let prove = async move { run_prover_with(&mut prover, &keypair, &committed()).await };
+    let cheat = async move {
+        let ours = VersionInfo::current().with_feature(COMMITTED_CHALLENGE);
+        SchnorrProtocol::accept_hello_offer(&mut verifier, &ours).await.unwrap();
+        let context = ChallengeContext::random(&mut OsRng);
+        verifier.send(Message::context(&context).in_session(context.session_id())).await.unwrap();
+
+        let committed = ChallengeOpening::random(&mut OsRng);
+        verifier.send(Message::challenge_commit(&committed.commitment()).in_session(context.session_id())).await.unwrap();
+        assert_eq!(verifier.recv().await.unwrap().kind, "commit");
+        // pick another challenge now that R is known
+        let chosen = ChallengeOpening::random(&mut OsRng);
+        verifier.send(Message::challenge_reveal(&chosen).in_session(context.session_id())).await.unwrap();
+        verifier.recv().await.unwrap()
+    };
+    let (proved, answer) = tokio::join!(prove, cheat);
+    assert!(matches!(proved, Err(ProtocolError::ChallengeCommitmentMismatch)), "{proved:?}");
+    assert_eq!(answer.kind, "error");
+}
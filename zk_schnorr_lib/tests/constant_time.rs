@@ -0,0 +1,45 @@
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use subtle::ConstantTimeEq;
+use zk_schnorr_lib::{Proof, ct_scalar_eq, proof_eq_ct};
+
+#[test]
+fn ct_scalar_eq_matches_equality() {
+    let a = Scalar::from(12345u64);
+    let b = Scalar::from(12346u64);
+    assert!(bool::from(ct_scalar_eq(&a, &a)));
+    assert!(!bool::from(ct_scalar_eq(&a, &b)));
+}
+
+#[test]
+fn proof_equality_covers_every_field() {
+    let base = Proof::new(curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT, Scalar::ONE, Scalar::ONE);
+    assert_eq!(base, base);
+
+    let mut other = base;
+    other.response += Scalar::ONE;
+    assert_ne!(base, other);
+
+    let mut other = base;
+    other.challenge += Scalar::ONE;
+    assert_ne!(base, other);
+}
+
+#[test]
+fn proof_eq_ct_tells_apart_proofs_differing_in_one_field() {
+    let base = Proof::new(RISTRETTO_BASEPOINT_POINT * Scalar::from(7u64), Scalar::from(11u64), Scalar::from(13u64));
+    let copy = base;
+    assert!(proof_eq_ct(&base, &copy));
+    assert!(bool::from(base.ct_eq(&base)));
+
+    let others = [
+        Proof { commitment: base.commitment + RISTRETTO_BASEPOINT_POINT, ..base },
+        Proof { challenge: base.challenge + Scalar::ONE, ..base },
+        Proof { response: base.response + Scalar::ONE, ..base },
+    ];
+    for other in &others {
+        assert!(!proof_eq_ct(&base, other), "{other:?}");
+        assert!(!bool::from(base.ct_eq(other)));
+        assert_ne!(&base, other);
+    }
+}
@@ -0,0 +1,32 @@
+#![cfg(feature = "derive")]
+
+use curve25519_dalek::scalar::Scalar;
+use zk_schnorr_lib::{derive_key, derive_keypair, KeyPair};
+
+#[test]
+fn same_master_and_info_reproduce_the_child() {
+    let master = Scalar::from(42u64);
+    assert_eq!(derive_key(&master, b"role/prover"), derive_key(&master, b"role/prover"));
+}
+
+#[test]
+fn different_info_gives_different_children() {
+    let master = Scalar::from(42u64);
+    let a = derive_key(&master, b"session/1");
+    let b = derive_key(&master, b"session/2");
+    assert_ne!(a, b);
+    assert_ne!(a, master);
+}
+
+#[test]
+fn different_masters_give_different_children() {
+    assert_ne!(derive_key(&Scalar::from(1u64), b"info"), derive_key(&Scalar::from(2u64), b"info"));
+}
+
+#[test]
+fn derive_keypair_matches_derive_key() {
+    let master = KeyPair::from_seed(b"root");
+    let child = derive_keypair(&master, b"verifier-auth");
+    assert_eq!(child.secret().expose_secret(), &derive_key(master.secret().expose_secret(), b"verifier-auth"));
+    assert_ne!(child.public_key(), master.public_key());
+}
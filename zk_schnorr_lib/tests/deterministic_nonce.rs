@@ -0,0 +1,125 @@
+#![cfg(feature = "tls")]
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::scalar::Scalar;
+use tokio::io::DuplexStream;
+use zk_schnorr_lib::protocol::{derive_nonce, deterministic_nonce, NonceLedger};
+use zk_schnorr_lib::{
+    ChallengeContext, ChannelTransport, KeyPair, Message, PublicKey, ProtocolError, SchnorrProtocol, SecretScalar, point_from_hex, prove_interactive,
+    prove_interactive_deterministic, recv_message, send_message,
+};
+
+// a minimal verifier for the session `session` and key `key` that accepts any response
+async fn mock_verifier(stream: DuplexStream, session: u8, key: PublicKey) {
+    let context = ChallengeContext::new([session; 32]);
+    let (mut read_half, mut write_half) = tokio::io::split(stream);
+    send_message(&mut write_half, &Message::context(&context)).await.unwrap();
+    let Ok(commit) = recv_message(&mut read_half).await else {
+        return;
+    };
+    let c = context.challenge(&point_from_hex(&commit.payload).unwrap(), &key);
+    send_message(&mut write_half, &Message::challenge(&c)).await.unwrap();
+    if recv_message(&mut read_half).await.is_ok() {
+        send_message(&mut write_half, &Message::result(true)).await.unwrap();
+    }
+}
+
+async fn run_deterministic(secret: &Scalar, context: &[u8], session: u8) -> Result<zk_schnorr_lib::Proof, ProtocolError> {
+    let (prover_end, verifier_end) = tokio::io::duplex(1024);
+    let verifier = tokio::spawn(mock_verifier(verifier_end, session, public_key(secret)));
+    let result = prove_interactive_deterministic(prover_end, secret, context).await;
+    verifier.await.unwrap();
+    result
+}
+
+fn public_key(secret: &Scalar) -> PublicKey {
+    PublicKey(RISTRETTO_BASEPOINT_POINT * secret)
+}
+
+#[test]
+fn nonce_is_deterministic_and_context_bound() {
+    let x = Scalar::from(7u64);
+    assert_eq!(deterministic_nonce(&x, b"ctx"), deterministic_nonce(&x, b"ctx"));
+    assert_ne!(deterministic_nonce(&x, b"ctx"), deterministic_nonce(&x, b"other"));
+    assert_ne!(deterministic_nonce(&x, b"ctx"), deterministic_nonce(&Scalar::from(8u64), b"ctx"));
+}
+
+#[tokio::test]
+async fn deterministic_proof_verifies() {
+    let x = Scalar::from(1001u64);
+    let proof = run_deterministic(&x, b"deterministic_proof_verifies", 5).await.unwrap();
+    assert!(proof.verify(&public_key(&x)));
+    assert_eq!(proof.commitment, RISTRETTO_BASEPOINT_POINT * deterministic_nonce(&x, b"deterministic_proof_verifies"));
+}
+
+#[tokio::test]
+async fn same_challenge_may_be_answered_again() {
+    let x = Scalar::from(1002u64);
+    // same session, same commitment, hence the same challenge
+    let first = run_deterministic(&x, b"replay", 9).await.unwrap();
+    let second = run_deterministic(&x, b"replay", 9).await.unwrap();
+    assert_eq!(first, second);
+}
+
+#[tokio::test]
+async fn different_challenge_for_reused_nonce_is_refused() {
+    let x = Scalar::from(1003u64);
+    // a second session derives a different challenge for the same commitment
+    run_deterministic(&x, b"reuse", 1).await.unwrap();
+    let err = run_deterministic(&x, b"reuse", 2).await.unwrap_err();
+    assert!(matches!(err, ProtocolError::NonceReuse));
+}
+
+#[test]
+fn nonce_ledger_forgets_the_oldest_commitment_first() {
+    let commitment = |n: u64| RISTRETTO_BASEPOINT_POINT * Scalar::from(n);
+    let mut ledger = NonceLedger::new(2);
+    ledger.record(&commitment(1), &Scalar::from(10u64)).unwrap();
+    ledger.record(&commitment(2), &Scalar::from(20u64)).unwrap();
+    ledger.record(&commitment(2), &Scalar::from(20u64)).unwrap(); // same challenge again
+    assert!(matches!(ledger.record(&commitment(1), &Scalar::from(11u64)), Err(ProtocolError::NonceReuse)));
+
+    ledger.record(&commitment(3), &Scalar::from(30u64)).unwrap();
+    assert_eq!(ledger.len(), 2);
+    // 1 fell out of the window, so it is no longer checked
+    ledger.record(&commitment(1), &Scalar::from(11u64)).unwrap();
+    assert!(matches!(ledger.record(&commitment(3), &Scalar::from(31u64)), Err(ProtocolError::NonceReuse)));
+}
+
+#[tokio::test]
+async fn random_nonce_proof_verifies() {
+    let x = Scalar::from(1004u64);
+    let (prover_end, verifier_end) = tokio::io::duplex(1024);
+    let verifier = tokio::spawn(mock_verifier(verifier_end, 3, public_key(&x)));
+    let proof = prove_interactive(prover_end, &x).await.unwrap();
+    verifier.await.unwrap();
+
+    assert!(proof.verify(&public_key(&x)));
+    // the commitment went over the wire in the usual hex form
+    let commitment = point_from_hex(&Message::commit(&proof.commitment).payload).unwrap();
+    assert_eq!(commitment, proof.commitment);
+}
+
+#[test]
+fn derived_nonce_depends_on_key_and_transcript() {
+    let x = SecretScalar::new(Scalar::from(11u64));
+    assert_eq!(derive_nonce(&x, b"session"), derive_nonce(&x, b"session"));
+    assert_ne!(derive_nonce(&x, b"session"), derive_nonce(&x, b"session2"));
+    assert_ne!(derive_nonce(&x, b"session"), derive_nonce(&SecretScalar::new(Scalar::from(12u64)), b"session"));
+}
+
+#[tokio::test]
+async fn deterministic_rounds_verify_with_distinct_nonces() {
+    let keypair = KeyPair::from_seed(b"deterministic-rounds");
+    let expected = keypair.public_key();
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async move {
+        let context = SchnorrProtocol::open_prover_session(&mut prover_end).await.unwrap();
+        let first = SchnorrProtocol::prove_round_deterministic(&mut prover_end, &context, &keypair, 1).await.unwrap();
+        let second = SchnorrProtocol::prove_round_deterministic(&mut prover_end, &context, &keypair, 2).await.unwrap();
+        assert_ne!(first.commitment, second.commitment);
+    };
+    let (_, results) = tokio::join!(prover, SchnorrProtocol::verify_interactive(&mut verifier_end, &expected));
+    assert_eq!(results.unwrap(), vec![true, true]);
+}
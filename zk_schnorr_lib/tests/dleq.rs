@@ -0,0 +1,56 @@
+#![cfg(feature = "tls")]
+
+#![allow(non_snake_case)] // follow the usual maths notation
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT as G;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::{hash_to_point, ChannelTransport, DleqProof, Message, ProtocolError, Transport};
+
+#[test]
+fn same_secret_verifies() {
+    let H = hash_to_point(b"dleq-test/H");
+    let x = Scalar::random(&mut OsRng);
+    let proof = DleqProof::prove(&x, &G, &H, &mut OsRng);
+    assert!(proof.verify(&(G * x), &(H * x), &G, &H));
+}
+
+#[test]
+fn different_secret_is_rejected() {
+    let H = hash_to_point(b"dleq-test/H");
+    let x = Scalar::random(&mut OsRng);
+    let y = Scalar::random(&mut OsRng);
+    let proof = DleqProof::prove(&x, &G, &H, &mut OsRng);
+    assert!(!proof.verify(&(G * x), &(H * y), &G, &H));
+}
+
+#[test]
+fn proof_is_bound_to_the_generators() {
+    let H = hash_to_point(b"dleq-test/H");
+    let other_H = hash_to_point(b"dleq-test/other");
+    let x = Scalar::random(&mut OsRng);
+    let proof = DleqProof::prove(&x, &G, &H, &mut OsRng);
+    assert!(!proof.verify(&(G * x), &(other_H * x), &G, &other_H));
+    assert!(!proof.verify(&(H * x), &(G * x), &H, &G));
+}
+
+#[tokio::test]
+async fn proof_travels_as_a_dleq_message() {
+    let H = hash_to_point(b"dleq-test/H");
+    let x = Scalar::random(&mut OsRng);
+    let proof = DleqProof::prove(&x, &G, &H, &mut OsRng);
+
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+    prover_end.send(Message::dleq(&proof)).await.unwrap();
+    let received = DleqProof::from_message(&verifier_end.recv().await.unwrap()).unwrap();
+    assert_eq!(received, proof);
+    assert!(received.verify(&(G * x), &(H * x), &G, &H));
+}
+
+#[test]
+fn malformed_dleq_messages_are_rejected() {
+    let garbage = Message { kind: "dleq".to_string(), payload: "{}".to_string(), session: None };
+    assert!(matches!(DleqProof::from_message(&garbage), Err(ProtocolError::Serde(_))));
+    let wrong_kind = Message::error("nope");
+    assert!(matches!(DleqProof::from_message(&wrong_kind), Err(ProtocolError::UnexpectedMessage { expected: "dleq", .. })));
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "std")]
+
+use zk_schnorr_lib::ed25519::challenge_message;
+use zk_schnorr_lib::{verify_ed25519, Ed25519Error, Ed25519PublicKey, Ed25519SigningKey, ZkError};
+
+// RFC 8032 section 7.1, TEST 1 to TEST 3: (secret key, public key, message, signature)
+const VECTORS: [(&str, &str, &str, &str); 3] = [
+    (
+        "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        "",
+        "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+    ),
+    (
+        "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+        "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        "72",
+        "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+    ),
+    (
+        "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+        "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+        "af82",
+        "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+    ),
+];
+
+fn bytes<const N: usize>(hex_str: &str) -> [u8; N] {
+    hex::decode(hex_str).unwrap().try_into().unwrap()
+}
+
+#[test]
+fn rfc8032_vectors_verify() {
+    for (_, public, msg, sig) in VECTORS {
+        assert_eq!(verify_ed25519(&bytes(public), &hex::decode(msg).unwrap(), &bytes(sig)), Ok(()), "vector with public key {public}");
+    }
+}
+
+#[test]
+fn signing_reproduces_rfc8032_vectors() {
+    for (secret, public, msg, sig) in VECTORS {
+        let key = Ed25519SigningKey::from_seed(&bytes(secret));
+        assert_eq!(key.public_key(), public.parse::<Ed25519PublicKey>().unwrap());
+        assert_eq!(hex::encode(key.sign(&hex::decode(msg).unwrap())), sig);
+    }
+}
+
+#[test]
+fn tampered_signatures_fail() {
+    let (_, public, msg, sig) = VECTORS[1];
+    let (public, msg, sig) = (bytes(public), hex::decode(msg).unwrap(), bytes::<64>(sig));
+
+    assert_eq!(verify_ed25519(&public, b"another message", &sig), Err(Ed25519Error::VerificationFailed));
+    let mut flipped = sig;
+    flipped[40] ^= 1;
+    assert_eq!(verify_ed25519(&public, &msg, &flipped), Err(Ed25519Error::VerificationFailed));
+    assert_eq!(verify_ed25519(&bytes(VECTORS[0].1), &msg, &sig), Err(Ed25519Error::VerificationFailed));
+}
+
+#[test]
+fn non_canonical_s_is_malformed() {
+    let (_, public, msg, sig) = VECTORS[0];
+    let mut sig = bytes::<64>(sig);
+    sig[32..].copy_from_slice(&[0xff; 32]); // far above the group order
+    assert_eq!(verify_ed25519(&bytes(public), &hex::decode(msg).unwrap(), &sig), Err(Ed25519Error::MalformedSignature));
+}
+
+#[test]
+fn small_order_keys_are_refused() {
+    // the identity point: s = 0, R = identity "verifies" any message under it
+    let mut identity = [0u8; 32];
+    identity[0] = 1;
+    let mut sig = [0u8; 64];
+    sig[..32].copy_from_slice(&identity);
+    let refused = verify_ed25519(&identity, b"anything", &sig);
+    assert_eq!(refused, Err(Ed25519Error::InvalidPublicKey));
+    assert!(matches!(ZkError::from(refused.unwrap_err()), ZkError::Ed25519(Ed25519Error::InvalidPublicKey)));
+}
+
+#[test]
+fn challenges_are_domain_separated() {
+    let key = Ed25519SigningKey::derive(b"hardware token");
+    let nonce = [7u8; 32];
+    let sig = key.sign(&challenge_message(&nonce));
+    assert_eq!(verify_ed25519(&key.public_key().0, &challenge_message(&nonce), &sig), Ok(()));
+    assert!(verify_ed25519(&key.public_key().0, &nonce, &sig).is_err());
+    assert!(verify_ed25519(&key.public_key().0, &challenge_message(&[8u8; 32]), &sig).is_err());
+}
+
+#[test]
+fn public_keys_are_hex_in_json() {
+    let key = Ed25519SigningKey::derive(b"hardware token").public_key();
+    let json = serde_json::to_string(&key).unwrap();
+    assert_eq!(json, format!("\"{key}\""));
+    assert_eq!(serde_json::from_str::<Ed25519PublicKey>(&json).unwrap(), key);
+    assert!("abcd".parse::<Ed25519PublicKey>().is_err());
+}
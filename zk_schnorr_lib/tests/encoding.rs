@@ -0,0 +1,96 @@
+#![cfg(feature = "std")]
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use proptest::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use zk_schnorr_lib::{
+    point_from_b64, point_from_bytes, point_from_hex, point_to_b64, point_to_bytes, point_to_hex, scalar_from_b64,
+    scalar_from_bytes, scalar_from_hex_canonical, scalar_to_b64, scalar_to_bytes, scalar_to_hex, B64DecodeError, Message,
+    PayloadDecodeError, PayloadEncoding, ScalarDecodeError,
+};
+
+fn point() -> impl Strategy<Value = RistrettoPoint> {
+    any::<u64>().prop_map(|seed| RistrettoPoint::random(&mut StdRng::seed_from_u64(seed)))
+}
+
+fn scalar() -> impl Strategy<Value = Scalar> {
+    any::<[u8; 64]>().prop_map(|wide| Scalar::from_bytes_mod_order_wide(&wide))
+}
+
+// the bytes under a base64url string, to compare with the hex ones
+fn unb64(s: &str) -> Vec<u8> {
+    use base64::prelude::{Engine, BASE64_URL_SAFE_NO_PAD};
+    BASE64_URL_SAFE_NO_PAD.decode(s).unwrap()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn point_encodings_carry_the_same_bytes(p in point()) {
+        let bytes = point_to_bytes(&p);
+        prop_assert_eq!(hex::decode(point_to_hex(&p)).unwrap(), bytes.to_vec());
+        prop_assert_eq!(unb64(&point_to_b64(&p)), bytes.to_vec());
+        prop_assert_eq!(point_from_bytes(bytes).unwrap(), p);
+        prop_assert_eq!(point_from_b64(&point_to_b64(&p)).unwrap(), point_from_hex(&point_to_hex(&p)).unwrap());
+    }
+
+    #[test]
+    fn scalar_encodings_carry_the_same_bytes(s in scalar()) {
+        let bytes = scalar_to_bytes(&s);
+        prop_assert_eq!(hex::decode(scalar_to_hex(&s)).unwrap(), bytes.to_vec());
+        prop_assert_eq!(unb64(&scalar_to_b64(&s)), bytes.to_vec());
+        prop_assert_eq!(scalar_from_bytes(bytes).unwrap(), s);
+        prop_assert_eq!(scalar_from_b64(&scalar_to_b64(&s)).unwrap(), scalar_from_hex_canonical(&scalar_to_hex(&s)).unwrap());
+    }
+
+    #[test]
+    fn messages_decode_in_either_encoding(p in point(), s in scalar()) {
+        for encoding in [PayloadEncoding::Hex, PayloadEncoding::Base64Url] {
+            let commit = Message::commit_with(&p, encoding);
+            prop_assert_eq!(commit.payload_point(encoding).unwrap(), p);
+            prop_assert_eq!(commit.payload_point_detect().unwrap(), p);
+            let response = Message::response_with(&s, encoding);
+            prop_assert_eq!(response.payload_scalar(encoding).unwrap(), s);
+            prop_assert_eq!(response.payload_scalar_detect().unwrap(), s);
+        }
+    }
+}
+
+#[test]
+fn hex_constructors_are_unchanged() {
+    let p = RistrettoPoint::mul_base(&Scalar::from(9u64));
+    assert_eq!(Message::commit_with(&p, PayloadEncoding::Hex).payload, Message::commit(&p).payload);
+    assert_eq!(Message::challenge_with(&Scalar::ONE, PayloadEncoding::default()).payload, Message::challenge(&Scalar::ONE).payload);
+}
+
+#[test]
+fn decoding_is_strict_unless_detection_is_asked_for() {
+    let s = Scalar::from(7u64);
+    let b64 = Message::response_with(&s, PayloadEncoding::Base64Url);
+    assert!(b64.payload_scalar(PayloadEncoding::Hex).is_err());
+    assert!(Message::response(&s).payload_scalar(PayloadEncoding::Base64Url).is_err());
+    let neither = Message { kind: "response".to_string(), payload: "abc".to_string(), session: None };
+    assert!(matches!(neither.payload_scalar_detect(), Err(PayloadDecodeError::UnknownEncoding)));
+}
+
+#[test]
+fn b64_errors() {
+    assert!(matches!(point_from_b64("AAAA"), Err(B64DecodeError::InvalidLength(3))));
+    assert!(matches!(point_from_b64("not base64!"), Err(B64DecodeError::Base64(_))));
+    let all_ones = format!("{}8", "_".repeat(42)); // 0xff * 32, above the group order and not a point
+    assert!(matches!(scalar_from_b64(&all_ones), Err(B64DecodeError::NonCanonical)));
+    assert!(matches!(point_from_b64(&all_ones), Err(B64DecodeError::InvalidPoint)));
+    let padded = format!("{}=", scalar_to_b64(&Scalar::ONE));
+    assert!(scalar_from_b64(&padded).is_err());
+    assert!(matches!(scalar_from_bytes([0xff; 32]), Err(ScalarDecodeError::NonCanonical)));
+}
+
+#[test]
+fn encoding_names() {
+    assert_eq!("base64url".parse::<PayloadEncoding>().unwrap(), PayloadEncoding::Base64Url);
+    assert_eq!(PayloadEncoding::Hex.to_string().parse::<PayloadEncoding>().unwrap(), PayloadEncoding::Hex);
+    assert!("base32".parse::<PayloadEncoding>().is_err());
+}
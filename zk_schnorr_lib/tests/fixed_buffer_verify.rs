@@ -0,0 +1,40 @@
+// runs with and without std: everything here is in the no_std core
+use rand::rngs::OsRng;
+use zk_schnorr_lib::{verify, KeyPair, SchnorrProof};
+
+fn proved(seed: &[u8]) -> ([u8; 32], [u8; 64]) {
+    let keypair = KeyPair::from_seed(seed);
+    let proof = SchnorrProof::prove(keypair.secret().expose_secret(), &mut OsRng);
+    (keypair.public_key().compress().to_bytes(), proof.to_bytes())
+}
+
+#[test]
+fn accepts_what_schnorr_proof_accepts() {
+    let (public_key, proof) = proved(b"firmware");
+    assert!(verify(&public_key, &proof));
+    let decoded = SchnorrProof::from_bytes(&proof).unwrap();
+    assert!(decoded.verify(&KeyPair::from_seed(b"firmware").public_key()));
+}
+
+#[test]
+fn rejects_another_key_and_tampered_proofs() {
+    let (public_key, proof) = proved(b"firmware");
+    let (other_key, _) = proved(b"someone else");
+    assert!(!verify(&other_key, &proof));
+
+    let mut tampered = proof;
+    tampered[40] ^= 1; // response
+    assert!(!verify(&public_key, &tampered));
+    let mut tampered = proof;
+    tampered[0] ^= 1; // commitment
+    assert!(!verify(&public_key, &tampered));
+}
+
+#[test]
+fn undecodable_inputs_are_not_valid() {
+    let (public_key, proof) = proved(b"firmware");
+    let mut non_canonical = proof;
+    non_canonical[32..].copy_from_slice(&[0xff; 32]);
+    assert!(!verify(&public_key, &non_canonical));
+    assert!(!verify(&[0xff; 32], &proof)); // not a point
+}
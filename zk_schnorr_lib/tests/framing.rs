@@ -0,0 +1,220 @@
+#![cfg(feature = "tls")]
+
+use bytes::Bytes;
+use curve25519_dalek::scalar::Scalar;
+use futures_util::{SinkExt, StreamExt};
+use rand::rngs::OsRng;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio_util::codec::{FramedRead, FramedWrite};
+use zk_schnorr_lib::{
+    ChallengeContext, DEFAULT_MAX_FRAME_LEN, DEFAULT_MAX_MESSAGE_LEN, FrameError, JsonCodec, KeyPair, MAX_AND_STATEMENTS,
+    MAX_FRAME_LEN, Message, MessageCodec, ProtocolError, PublicKey, SchnorrProtocol, SessionId, StreamTransport, Transport,
+    WireFormat, ZkError, read_message, recv_message, recv_message_limited, send_message, write_message,
+};
+
+#[tokio::test]
+async fn round_trip_over_duplex() {
+    let (mut client, server) = tokio::io::duplex(1024);
+    let mut reader = BufReader::new(server);
+
+    let sent = Message::challenge(&Scalar::from(42u64));
+    write_message(&mut client, &sent).await.unwrap();
+    let received = read_message(&mut reader, DEFAULT_MAX_FRAME_LEN).await.unwrap();
+
+    assert_eq!(received.kind, sent.kind);
+    assert_eq!(received.payload, sent.payload);
+}
+
+#[tokio::test]
+async fn oversized_frame_is_rejected_without_reading_it_all() {
+    // an endless stream with no newline: if the reader tried to buffer the
+    // whole line this test would never finish
+    let endless = tokio::io::repeat(b'a');
+    let mut reader = BufReader::new(endless);
+
+    let err = read_message(&mut reader, DEFAULT_MAX_FRAME_LEN).await.unwrap_err();
+    assert!(matches!(err, FrameError::TooLarge { max } if max == DEFAULT_MAX_FRAME_LEN));
+}
+
+#[tokio::test]
+async fn frame_at_the_limit_is_accepted() {
+    let line = serde_json::to_string(&Message::challenge(&Scalar::ONE)).unwrap();
+    let input = format!("{line}\n");
+    let mut reader = BufReader::new(input.as_bytes());
+
+    assert!(read_message(&mut reader, line.len()).await.is_ok());
+}
+
+#[tokio::test]
+async fn embedded_nul_is_rejected() {
+    let mut reader = BufReader::new(&b"{\"kind\":\"commit\0\",\"payload\":\"\"}\n"[..]);
+    let err = read_message(&mut reader, DEFAULT_MAX_FRAME_LEN).await.unwrap_err();
+    assert!(matches!(err, FrameError::EmbeddedNul));
+}
+
+#[tokio::test]
+async fn invalid_utf8_is_rejected() {
+    let mut reader = BufReader::new(&b"\xff\xfe\n"[..]);
+    let err = read_message(&mut reader, DEFAULT_MAX_FRAME_LEN).await.unwrap_err();
+    assert!(matches!(err, FrameError::InvalidUtf8(_)));
+}
+
+#[tokio::test]
+async fn clean_eof_reports_connection_closed() {
+    let mut reader = BufReader::new(&b""[..]);
+    let err = read_message(&mut reader, DEFAULT_MAX_FRAME_LEN).await.unwrap_err();
+    assert!(matches!(err, FrameError::ConnectionClosed));
+}
+
+#[tokio::test]
+async fn consecutive_frames_are_split_correctly() {
+    let (mut client, server) = tokio::io::duplex(4096);
+    let mut reader = BufReader::new(server);
+    for i in 0..10u64 {
+        write_message(&mut client, &Message::challenge(&Scalar::from(i))).await.unwrap();
+    }
+    drop(client);
+
+    for i in 0..10u64 {
+        let msg = read_message(&mut reader, DEFAULT_MAX_FRAME_LEN).await.unwrap();
+        assert_eq!(msg.payload, zk_schnorr_lib::scalar_to_hex(&Scalar::from(i)));
+    }
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).await.unwrap();
+    assert!(rest.is_empty());
+}
+
+#[tokio::test]
+async fn thousand_length_prefixed_messages_back_to_back() {
+    let (mut client, mut server) = tokio::io::duplex(64 * 1024);
+
+    let writer = tokio::spawn(async move {
+        for i in 0..1000u64 {
+            send_message(&mut client, &Message::challenge(&Scalar::from(i))).await.unwrap();
+        }
+    });
+
+    for i in 0..1000u64 {
+        let msg = recv_message(&mut server).await.unwrap();
+        assert_eq!(msg.kind, "challenge");
+        assert_eq!(msg.payload, zk_schnorr_lib::scalar_to_hex(&Scalar::from(i)));
+    }
+    writer.await.unwrap();
+}
+
+#[tokio::test]
+async fn length_prefix_over_the_cap_is_invalid_data() {
+    let prefix = ((MAX_FRAME_LEN + 1) as u32).to_be_bytes();
+    let mut reader = &prefix[..]; // no body follows: it must not be allocated or awaited
+    let err = recv_message(&mut reader).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[tokio::test]
+async fn payload_with_newline_survives_length_prefixed_framing() {
+    let (mut client, mut server) = tokio::io::duplex(1024);
+    let msg = Message { kind: "commit".to_string(), payload: "line one\nline two".to_string(), session: None };
+    send_message(&mut client, &msg).await.unwrap();
+    let received = recv_message(&mut server).await.unwrap();
+    assert_eq!(received.payload, msg.payload);
+}
+
+#[tokio::test]
+async fn length_prefix_over_a_chosen_limit_is_too_large() {
+    let prefix = 1025u32.to_be_bytes();
+    let mut reader = &prefix[..]; // the body is never read
+    let err = recv_message_limited(&mut reader, &JsonCodec, 1024).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    let inner = err.into_inner().unwrap().downcast::<FrameError>().unwrap();
+    assert!(matches!(*inner, FrameError::TooLarge { max: 1024 }));
+}
+
+#[tokio::test]
+async fn verifier_refuses_an_oversized_message_before_reading_it() {
+    let expected = KeyPair::from_seed(b"framing-test").public_key();
+    let (prover_end, verifier_end) = tokio::io::duplex(64 * 1024);
+    let mut verifier_end = StreamTransport::new(verifier_end).with_max_frame_len(256);
+    let mut prover_end = StreamTransport::new(prover_end);
+
+    let prover = async {
+        prover_end.recv().await.unwrap(); // the context
+        let mut stream = prover_end.into_inner();
+        stream.write_all(&u32::MAX.to_be_bytes()).await.unwrap(); // announce 4 GiB, send nothing
+        StreamTransport::new(stream).recv().await.unwrap()
+    };
+    let (reply, verified) = tokio::join!(prover, SchnorrProtocol::verify_interactive(&mut verifier_end, &expected));
+    assert!(matches!(verified, Err(ProtocolError::MessageTooLarge { max: 256 })));
+    assert_eq!(reply.kind, "error");
+}
+
+#[tokio::test]
+async fn default_limit_fits_the_largest_and_proof() {
+    let keypairs: Vec<KeyPair> = (0..MAX_AND_STATEMENTS).map(|_| KeyPair::generate(&mut OsRng)).collect();
+    let pubkeys: Vec<PublicKey> = keypairs.iter().map(KeyPair::public_key).collect();
+    let context = ChallengeContext::random(&mut OsRng);
+    let (prover_end, verifier_end) = tokio::io::duplex(64 * 1024);
+    let (mut prover_end, mut verifier_end) = (StreamTransport::new(prover_end), StreamTransport::new(verifier_end));
+    assert_eq!(verifier_end.max_frame_len(), DEFAULT_MAX_MESSAGE_LEN);
+
+    let (proved, verified) = tokio::join!(
+        SchnorrProtocol::prove_and_round(&mut prover_end, &context, &keypairs),
+        SchnorrProtocol::verify_and_round(&mut verifier_end, &context, &pubkeys),
+    );
+    verified.unwrap();
+    assert_eq!(proved.unwrap().len(), MAX_AND_STATEMENTS);
+}
+
+#[test]
+fn message_converts_to_json_bytes_and_back() {
+    let msg = Message { session: Some(SessionId::new([7; 16])), ..Message::challenge(&Scalar::from(9u64)) };
+    let bytes = Bytes::from(&msg);
+    assert_eq!(bytes, serde_json::to_vec(&msg).unwrap());
+    let back = Message::try_from(bytes).unwrap();
+    assert_eq!((back.kind, back.payload, back.session), (msg.kind, msg.payload, msg.session));
+    assert!(matches!(Message::try_from(Bytes::from_static(b"{\"kind\":")), Err(ZkError::Json(_))));
+}
+
+#[tokio::test]
+async fn hundred_messages_through_framed_read_and_write() {
+    for format in [WireFormat::Json, WireFormat::Binary] {
+        let (client, server) = tokio::io::duplex(256); // smaller than the batch, so frames arrive in pieces
+        let sent: Vec<Message> = (0..100u64).map(|i| Message::challenge(&Scalar::from(i))).collect();
+        let outgoing = sent.clone();
+        let writer = tokio::spawn(async move {
+            let mut framed = FramedWrite::new(client, MessageCodec::new(format));
+            for msg in outgoing {
+                framed.send(msg).await.unwrap();
+            }
+        });
+
+        let received: Vec<Message> = FramedRead::new(server, MessageCodec::new(format)).map(Result::unwrap).collect().await;
+        writer.await.unwrap();
+        assert_eq!(received.len(), 100, "{format}");
+        for (got, want) in received.iter().zip(&sent) {
+            assert_eq!((&got.kind, &got.payload), (&want.kind, &want.payload), "{format}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn message_codec_frames_match_send_message() {
+    let (mut client, server) = tokio::io::duplex(1024);
+    let msg = Message::challenge(&Scalar::from(3u64));
+    send_message(&mut client, &msg).await.unwrap();
+    drop(client);
+    let mut framed = FramedRead::new(server, MessageCodec::default());
+    assert_eq!(framed.next().await.unwrap().unwrap().payload, msg.payload);
+    assert!(framed.next().await.is_none()); // a clean close between frames
+}
+
+#[tokio::test]
+async fn message_codec_refuses_oversized_and_truncated_frames() {
+    let prefix = 1025u32.to_be_bytes();
+    let err = FramedRead::new(&prefix[..], MessageCodec::default().with_max_len(1024)).next().await.unwrap().unwrap_err();
+    let inner = err.into_inner().unwrap().downcast::<FrameError>().unwrap();
+    assert!(matches!(*inner, FrameError::TooLarge { max: 1024 }));
+
+    let truncated = [0, 0, 0, 10, b'{'];
+    let err = FramedRead::new(&truncated[..], MessageCodec::default()).next().await.unwrap().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
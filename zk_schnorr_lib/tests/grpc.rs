@@ -0,0 +1,107 @@
+#![cfg(feature = "grpc")]
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::{Channel, Endpoint, Server};
+use tracing_test::traced_test;
+use zk_schnorr_lib::grpc::{client_tls_config, server_tls_config, SchnorrMessage, SchnorrVerifierClient};
+use zk_schnorr_lib::{
+    generate_self_signed_cert, run_prover_with, GrpcTransport, KeyPair, KeyRegistry, Message, Metrics, ProverOptions,
+    SchnorrService, SessionId, TimeoutTransport,
+};
+
+// a TLS gRPC verifier for `key` on a free port, with its certificate's DER and its metrics
+async fn start(key: &KeyPair) -> (SocketAddr, Vec<u8>, Arc<Metrics>) {
+    let tls_cert = generate_self_signed_cert().unwrap();
+    let service = SchnorrService::new(KeyRegistry::single("demo-prover", key.public_key()));
+    let metrics = service.metrics();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = Server::builder().tls_config(server_tls_config(&tls_cert)).unwrap().add_service(service.into_server());
+    tokio::spawn(server.serve_with_incoming(TcpListenerStream::new(listener)));
+    (addr, tls_cert.cert_der.clone(), metrics)
+}
+
+async fn client(addr: SocketAddr, cert_der: &[u8]) -> SchnorrVerifierClient<Channel> {
+    let channel = Endpoint::from_shared(format!("https://{addr}"))
+        .unwrap()
+        .tls_config(client_tls_config(cert_der, "localhost"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    SchnorrVerifierClient::new(channel)
+}
+
+// the server finishes its side after the prover has hung up
+async fn wait_for_session(metrics: &Metrics) {
+    for _ in 0..100 {
+        if metrics.snapshot().latency_count > 0 {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("the server never finished the session");
+}
+
+#[tokio::test]
+#[traced_test]
+async fn proof_over_grpc_is_verified() {
+    let keypair = KeyPair::from_seed(b"demo-prover-secret");
+    let (addr, cert_der, metrics) = start(&keypair).await;
+
+    let mut client = client(addr, &cert_der).await;
+    let mut transport = TimeoutTransport::new(GrpcTransport::connect(&mut client).await.unwrap(), Duration::from_secs(5));
+    let options = ProverOptions { rounds: 2, ..ProverOptions::default() };
+    let outcome = run_prover_with(&mut transport, &keypair, &options).await.unwrap();
+    assert_eq!(outcome.rounds, vec![true, true]);
+    drop(transport); // ends the request stream, and with it the session
+
+    wait_for_session(&metrics).await;
+    assert_eq!(metrics.snapshot().proofs_verified, 2);
+    assert!(logs_contain("Prover finished"));
+    assert!(logs_contain("verified=2"));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn wrong_key_is_rejected_over_grpc() {
+    let (addr, cert_der, metrics) = start(&KeyPair::from_seed(b"demo-prover-secret")).await;
+
+    let mut client = client(addr, &cert_der).await;
+    let mut transport = GrpcTransport::connect(&mut client).await.unwrap();
+    let impostor = KeyPair::from_seed(b"someone-else");
+    assert!(run_prover_with(&mut transport, &impostor, &ProverOptions::default()).await.is_err());
+    drop(transport);
+
+    wait_for_session(&metrics).await;
+    assert_eq!(metrics.snapshot().proofs_verified, 0);
+    assert!(metrics.snapshot().total_rejected() > 0);
+}
+
+#[test]
+fn messages_keep_their_session_tag() {
+    let id = SessionId::new([7; 16]);
+    let msg = Message::result(true).in_session(&id);
+    let grpc = SchnorrMessage::from(msg.clone());
+    assert_eq!(grpc.session, [7; 16]);
+    let back = Message::try_from(grpc).unwrap();
+    assert_eq!((back.kind, back.payload, back.session), (msg.kind, msg.payload, Some(id)));
+
+    let untagged = SchnorrMessage::from(Message::result(false));
+    assert!(untagged.session.is_empty());
+    assert!(Message::try_from(untagged).unwrap().session.is_none());
+}
+
+#[test]
+fn malformed_messages_are_invalid_data() {
+    let bad_utf8 = SchnorrMessage { kind: "commit".to_string(), payload: vec![0xff, 0xfe], session: Vec::new() };
+    assert_eq!(Message::try_from(bad_utf8).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+
+    let short_tag = SchnorrMessage { kind: "commit".to_string(), payload: Vec::new(), session: vec![1; 8] };
+    assert_eq!(Message::try_from(short_tag).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,52 @@
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use zk_schnorr_lib::{
+    point_from_hex, point_from_hex_nonidentity, point_to_hex, public_key_from_hex, scalar_from_hex_canonical,
+    scalar_from_hex_nonzero, scalar_to_hex, KeyPair, PointDecodeError, PublicKey, ScalarDecodeError, WEAK_SECRET_BOUND,
+};
+
+// the canonical compressed encoding of the identity point
+const IDENTITY_HEX: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[test]
+fn identity_is_rejected_by_strict_decoder() {
+    assert!(matches!(point_from_hex_nonidentity(IDENTITY_HEX), Err(PointDecodeError::IdentityPoint)));
+    assert!(matches!(public_key_from_hex(IDENTITY_HEX), Err(PointDecodeError::IdentityPoint)));
+}
+
+#[test]
+fn permissive_decoder_accepts_identity() {
+    assert_eq!(point_from_hex(IDENTITY_HEX).unwrap(), RistrettoPoint::default());
+    assert_eq!(point_to_hex(&RistrettoPoint::default()), IDENTITY_HEX);
+}
+
+#[test]
+fn strict_decoder_passes_other_points_through() {
+    let point = KeyPair::from_seed(b"identity-test").public_key().0;
+    assert_eq!(point_from_hex_nonidentity(&point_to_hex(&point)).unwrap(), point);
+    assert!(matches!(point_from_hex_nonidentity(&"ff".repeat(32)), Err(PointDecodeError::InvalidPoint)));
+}
+
+#[test]
+fn zero_is_rejected_only_on_the_nonzero_path() {
+    let zero = scalar_to_hex(&Scalar::ZERO);
+    assert_eq!(scalar_from_hex_canonical(&zero).unwrap(), Scalar::ZERO);
+    assert!(matches!(scalar_from_hex_nonzero(&zero), Err(ScalarDecodeError::Zero)));
+    assert_eq!(scalar_from_hex_nonzero(&scalar_to_hex(&Scalar::ONE)).unwrap(), Scalar::ONE);
+}
+
+#[test]
+fn small_multiples_of_the_basepoint_are_weak_keys() {
+    for k in [1u64, 5, 42, WEAK_SECRET_BOUND - 1] {
+        let key = PublicKey(RistrettoPoint::mul_base(&Scalar::from(k)));
+        assert!(key.is_weak(), "{k}*G");
+        assert!(matches!(public_key_from_hex(&key.to_string()), Err(PointDecodeError::WeakKey)));
+    }
+    assert!(!PublicKey(RistrettoPoint::mul_base(&Scalar::from(WEAK_SECRET_BOUND))).is_weak());
+    assert!(PublicKey(RISTRETTO_BASEPOINT_POINT).is_weak());
+
+    let key = KeyPair::from_seed(b"identity-test").public_key();
+    assert!(!key.is_weak());
+    assert_eq!(public_key_from_hex(&key.to_string()).unwrap(), key);
+}
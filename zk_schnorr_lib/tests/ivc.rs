@@ -0,0 +1,66 @@
+#![cfg(feature = "std")]
+
+use rand::rngs::OsRng;
+use zk_schnorr_lib::{ivc_prove, ivc_verify_chain, IVCStep, KeyPair};
+
+fn chain(keypair: &KeyPair, len: usize) -> Vec<IVCStep> {
+    let mut steps: Vec<IVCStep> = Vec::new();
+    for n in 0..len {
+        let state = format!("state {n}");
+        let step = ivc_prove(steps.last(), state.as_bytes(), keypair, &mut OsRng);
+        steps.push(step);
+    }
+    steps
+}
+
+#[test]
+fn chain_of_five_steps_verifies() {
+    let keypair = KeyPair::generate(&mut OsRng);
+    let steps = chain(&keypair, 5);
+    assert!(ivc_verify_chain(&steps, &keypair.public_key()));
+    assert!(ivc_verify_chain(&steps[..1], &keypair.public_key()));
+}
+
+#[test]
+fn chain_checks_against_the_genesis_key() {
+    let steps = chain(&KeyPair::generate(&mut OsRng), 5);
+    assert!(!ivc_verify_chain(&steps, &KeyPair::generate(&mut OsRng).public_key()));
+}
+
+#[test]
+fn empty_chain_is_rejected() {
+    assert!(!ivc_verify_chain(&[], &KeyPair::generate(&mut OsRng).public_key()));
+}
+
+#[test]
+fn forged_step_breaks_the_chain_from_there_on() {
+    let keypair = KeyPair::generate(&mut OsRng);
+    let forger = KeyPair::generate(&mut OsRng);
+    let honest = chain(&keypair, 5);
+
+    for forged_at in 1..4 {
+        let mut steps = honest.clone();
+        steps[forged_at] = ivc_prove(Some(&steps[forged_at - 1]), b"forged state", &forger, &mut OsRng);
+        for end in 1..=steps.len() {
+            assert_eq!(ivc_verify_chain(&steps[..end], &keypair.public_key()), end <= forged_at, "forged {forged_at}, up to {end}");
+        }
+    }
+}
+
+#[test]
+fn replacing_a_step_breaks_the_next_even_with_the_right_key() {
+    let keypair = KeyPair::generate(&mut OsRng);
+    let mut steps = chain(&keypair, 5);
+    // a valid step, but for another state than the one step 3 was made after
+    steps[2] = ivc_prove(Some(&steps[1]), b"rewritten state", &keypair, &mut OsRng);
+    assert!(ivc_verify_chain(&steps[..3], &keypair.public_key()));
+    assert!(!ivc_verify_chain(&steps[..4], &keypair.public_key()));
+}
+
+#[test]
+fn steps_cannot_be_reordered() {
+    let keypair = KeyPair::generate(&mut OsRng);
+    let mut steps = chain(&keypair, 5);
+    steps.swap(1, 2);
+    assert!(!ivc_verify_chain(&steps, &keypair.public_key()));
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "std")]
+
+use rand::rngs::OsRng;
+use zk_schnorr_lib::signature::verify;
+use zk_schnorr_lib::{KeyPair, SchnorrKeyring, ZkError};
+
+#[test]
+fn keys_are_added_looked_up_and_removed() {
+    let keyring = SchnorrKeyring::new();
+    let alice = KeyPair::from_seed(b"alice");
+    keyring.add_keypair("alice", alice.clone()).unwrap();
+    keyring.add_keypair("bob", KeyPair::from_seed(b"bob")).unwrap();
+    assert_eq!(keyring.get_public_key("alice"), Some(alice.public_key()));
+    assert_eq!(keyring.names(), ["alice", "bob"]);
+
+    let removed = keyring.remove_keypair("alice").unwrap();
+    assert_eq!(removed.public_key(), alice.public_key());
+    assert_eq!(keyring.get_public_key("alice"), None);
+    assert!(keyring.remove_keypair("alice").is_none());
+    assert!(matches!(keyring.sign("alice", b"gone", &mut OsRng), Err(ZkError::UnknownKey(name)) if name == "alice"));
+}
+
+#[test]
+fn duplicate_names_are_refused() {
+    let keyring = SchnorrKeyring::new();
+    let first = KeyPair::from_seed(b"first");
+    keyring.add_keypair("alice", first.clone()).unwrap();
+
+    let duplicate = keyring.add_keypair("alice", KeyPair::from_seed(b"second"));
+    assert!(matches!(duplicate, Err(ZkError::DuplicateKey(name)) if name == "alice"));
+    assert_eq!(keyring.get_public_key("alice"), Some(first.public_key()), "the first key stays");
+}
+
+#[test]
+fn debug_shows_names_only() {
+    let keyring = SchnorrKeyring::new();
+    let keypair = KeyPair::from_seed(b"alice");
+    keyring.add_keypair("alice", keypair.clone()).unwrap();
+
+    let debug = format!("{keyring:?}");
+    assert_eq!(debug, r#"SchnorrKeyring { names: ["alice"] }"#);
+    assert!(!debug.contains(&hex::encode(keypair.secret().expose_secret().as_bytes())));
+}
+
+#[tokio::test]
+async fn names_sign_concurrently() {
+    let keyring = SchnorrKeyring::new();
+    let names: Vec<String> = (0..4).map(|i| format!("user-{i}")).collect();
+    for name in &names {
+        keyring.add_keypair(name, KeyPair::from_seed(name.as_bytes())).unwrap();
+    }
+
+    let signers: Vec<_> = (0..16)
+        .map(|i| {
+            let (keyring, name) = (keyring.clone(), names[i % names.len()].clone());
+            tokio::spawn(async move {
+                let message = format!("message {i}");
+                let sig = keyring.sign(&name, message.as_bytes(), &mut OsRng).unwrap();
+                (name, message, sig)
+            })
+        })
+        .collect();
+
+    for signer in signers {
+        let (name, message, sig) = signer.await.unwrap();
+        let public_key = keyring.get_public_key(&name).unwrap();
+        assert!(verify(&public_key, message.as_bytes(), &sig), "{name}: {message}");
+        assert!(!verify(&public_key, b"another message", &sig));
+    }
+}
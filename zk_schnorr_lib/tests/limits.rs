@@ -0,0 +1,52 @@
+#![cfg(feature = "tls")]
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::{Duration, Instant};
+
+use zk_schnorr_lib::{ConnectionLimiter, LimiterStats, Rejection};
+
+const ALICE: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+const BOB: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+#[test]
+fn concurrent_sessions_are_capped() {
+    let limiter = ConnectionLimiter::new(2, 100);
+    let first = limiter.try_admit(ALICE).unwrap();
+    let _second = limiter.try_admit(BOB).unwrap();
+    assert_eq!(limiter.try_admit(ALICE).unwrap_err(), Rejection::TooManyConnections);
+
+    drop(first); // finishing a session frees its slot
+    assert!(limiter.try_admit(ALICE).is_ok());
+}
+
+#[test]
+fn rate_limit_is_per_ip_and_refills() {
+    let limiter = ConnectionLimiter::new(100, 3);
+    let start = Instant::now();
+
+    for _ in 0..3 {
+        limiter.try_admit_at(ALICE, start).unwrap();
+    }
+    assert_eq!(limiter.try_admit_at(ALICE, start).unwrap_err(), Rejection::RateLimited);
+    assert!(limiter.try_admit_at(BOB, start).is_ok(), "other peers have their own bucket");
+
+    // 3 per minute = one token every 20 seconds
+    assert!(limiter.try_admit_at(ALICE, start + Duration::from_secs(10)).is_err());
+    assert!(limiter.try_admit_at(ALICE, start + Duration::from_secs(21)).is_ok());
+}
+
+#[test]
+fn counters_track_outcomes() {
+    let limiter = ConnectionLimiter::new(1, 2);
+    let start = Instant::now();
+    let _held = limiter.try_admit_at(ALICE, start).unwrap();
+    let _ = limiter.try_admit_at(ALICE, start); // no slot left
+    let _ = limiter.try_admit_at(ALICE, start); // bucket empty
+
+    let stats = limiter.stats();
+    assert_eq!(stats, LimiterStats { accepted: 1, rejected_too_many: 1, rejected_rate_limited: 1 });
+    assert_eq!(
+        stats.to_string(),
+        "accepted: 1, rejected: 2 (too many connections: 1, rate limited: 1)"
+    );
+}
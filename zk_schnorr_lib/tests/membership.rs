@@ -0,0 +1,60 @@
+#![cfg(feature = "std")]
+
+use rand::rngs::OsRng;
+use zk_schnorr_lib::{prove_membership, ring_verify, verify_membership, KeyPair, MembershipProof, PublicKey, ZkError};
+
+fn set(n: usize) -> (Vec<KeyPair>, Vec<PublicKey>) {
+    let keypairs: Vec<KeyPair> = (0..n).map(|_| KeyPair::generate(&mut OsRng)).collect();
+    let publics = keypairs.iter().map(KeyPair::public_key).collect();
+    (keypairs, publics)
+}
+
+#[test]
+fn every_member_proves_membership() {
+    let (keypairs, publics) = set(4);
+    for (index, keypair) in keypairs.iter().enumerate() {
+        let proof = prove_membership(keypair, &publics, b"members only", &mut OsRng).unwrap();
+        assert!(verify_membership(&publics, b"members only", &proof), "member {index}");
+        assert!(!verify_membership(&publics, b"another message", &proof));
+    }
+}
+
+#[test]
+fn a_key_outside_the_set_cannot_prove() {
+    let (_, publics) = set(3);
+    let outsider = KeyPair::generate(&mut OsRng);
+    assert!(matches!(prove_membership(&outsider, &publics, b"members only", &mut OsRng), Err(ZkError::KeyNotInSet)));
+    assert!(matches!(prove_membership(&outsider, &[], b"members only", &mut OsRng), Err(ZkError::KeyNotInSet)));
+}
+
+#[test]
+fn proof_only_verifies_against_the_set_it_was_made_for() {
+    let (keypairs, publics) = set(3);
+    let proof = prove_membership(&keypairs[0], &publics, b"members only", &mut OsRng).unwrap();
+
+    let mut added = publics.clone();
+    added.push(KeyPair::generate(&mut OsRng).public_key());
+    assert!(!verify_membership(&added, b"members only", &proof));
+
+    let removed = &publics[..2];
+    assert!(!verify_membership(removed, b"members only", &proof));
+
+    let reordered = [publics[1], publics[0], publics[2]];
+    assert!(!verify_membership(&reordered, b"members only", &proof));
+}
+
+#[test]
+fn proof_survives_a_json_round_trip() {
+    let (keypairs, publics) = set(3);
+    let proof = prove_membership(&keypairs[2], &publics, b"members only", &mut OsRng).unwrap();
+    let back: MembershipProof = serde_json::from_str(&serde_json::to_string(&proof).unwrap()).unwrap();
+    assert_eq!(back, proof);
+    assert!(verify_membership(&publics, b"members only", &back));
+}
+
+#[test]
+fn membership_proof_is_not_a_ring_signature_over_the_same_message() {
+    let (keypairs, publics) = set(2);
+    let proof = prove_membership(&keypairs[1], &publics, b"members only", &mut OsRng).unwrap();
+    assert!(!ring_verify(b"members only", &publics, proof.ring_signature()));
+}
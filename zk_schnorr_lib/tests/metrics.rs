@@ -0,0 +1,68 @@
+#![cfg(feature = "tls")]
+
+use std::time::Duration;
+
+use zk_schnorr_lib::{DecodeError, Metrics, PointDecodeError, ProofOutcome, ProtocolError, RejectReason, TimedOut, Version};
+
+#[test]
+fn errors_are_classified_by_where_the_proof_went_wrong() {
+    let bad_point = ProtocolError::Decode(DecodeError::Point(PointDecodeError::InvalidPoint));
+    assert_eq!(RejectReason::of(&bad_point), RejectReason::BadCommit);
+    let early_response = ProtocolError::UnexpectedMessage { expected: "commit", got: "response".into() };
+    assert_eq!(RejectReason::of(&early_response), RejectReason::BadCommit);
+    assert_eq!(RejectReason::of(&ProtocolError::VerificationFailed), RejectReason::BadResponse);
+    assert_eq!(RejectReason::of(&ProtocolError::Rejected { reason: "no".into() }), RejectReason::WrongKey);
+    assert_eq!(RejectReason::of(&ProtocolError::UnknownIdentity(None)), RejectReason::WrongKey);
+    assert_eq!(RejectReason::of(&ProtocolError::Timeout(TimedOut { waiting_for: "response" })), RejectReason::Timeout);
+    assert_eq!(RejectReason::of(&ProtocolError::ConnectionClosed), RejectReason::Other);
+}
+
+#[test]
+fn sessions_add_up() {
+    let metrics = Metrics::new();
+    metrics.connection_accepted();
+    metrics.connection_accepted();
+    metrics.tls_handshake_failed();
+    let outcome = ProofOutcome { version: Version::new(1, 0), rounds: vec![true, false, true] };
+    metrics.record_session(Ok(&outcome));
+    metrics.record_session(Err(&ProtocolError::ConnectionClosed));
+
+    let snapshot = metrics.snapshot();
+    assert_eq!((snapshot.connections_accepted, snapshot.tls_handshake_failures), (2, 1));
+    assert_eq!(snapshot.proofs_verified, 2);
+    assert_eq!(snapshot.rejected(RejectReason::BadResponse), 1);
+    assert_eq!(snapshot.rejected(RejectReason::Other), 1);
+    assert_eq!(snapshot.total_rejected(), 2);
+}
+
+#[test]
+fn latency_buckets_are_cumulative() {
+    let metrics = Metrics::new();
+    metrics.observe_latency(Duration::from_millis(3));
+    metrics.observe_latency(Duration::from_millis(80));
+    metrics.observe_latency(Duration::from_secs(60)); // past the last bucket, only in +Inf
+
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.latency_buckets[0], 1); // <= 5ms
+    assert_eq!(snapshot.latency_buckets[4], 2); // <= 100ms
+    assert_eq!(*snapshot.latency_buckets.last().unwrap(), 2); // <= 10s
+    assert_eq!(snapshot.latency_count, 3);
+    assert_eq!(snapshot.latency_sum, Duration::from_millis(60_083));
+}
+
+#[test]
+fn prometheus_text() {
+    let metrics = Metrics::new();
+    metrics.connection_accepted();
+    metrics.proof_rejected(RejectReason::Timeout);
+    metrics.observe_latency(Duration::from_millis(20));
+
+    let text = metrics.snapshot().to_prometheus();
+    assert!(text.contains("# TYPE zk_connections_accepted_total counter\nzk_connections_accepted_total 1\n"));
+    assert!(text.contains("zk_proofs_rejected_total{reason=\"timeout\"} 1\n"));
+    assert!(text.contains("zk_proofs_rejected_total{reason=\"wrong_key\"} 0\n"));
+    assert!(text.contains("zk_verification_latency_seconds_bucket{le=\"0.01\"} 0\n"));
+    assert!(text.contains("zk_verification_latency_seconds_bucket{le=\"0.025\"} 1\n"));
+    assert!(text.contains("zk_verification_latency_seconds_bucket{le=\"+Inf\"} 1\n"));
+    assert!(text.ends_with("zk_verification_latency_seconds_count 1\n"));
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "tls")]
+
+use std::sync::Arc;
+
+use rustls::{Certificate, RootCertStore, ServerName};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use zk_schnorr_lib::{
+    TlsCertificate, TlsError, create_client_config_insecure, create_client_config_verified, create_client_config_with_identity,
+    create_server_config, create_server_config_mtls, generate_self_signed_cert,
+};
+
+fn roots_for(cert: &TlsCertificate) -> RootCertStore {
+    let mut roots = RootCertStore::empty();
+    roots.add(&Certificate(cert.cert_der.clone())).unwrap();
+    roots
+}
+
+// run a handshake plus one byte each way; Ok only if both sides got through
+async fn handshake(server: rustls::ServerConfig, client: rustls::ClientConfig) -> Result<(), std::io::Error> {
+    let (client_io, server_io) = tokio::io::duplex(16 * 1024);
+    let acceptor = TlsAcceptor::from(Arc::new(server));
+    let connector = TlsConnector::from(Arc::new(client));
+
+    let server_side = async {
+        let mut stream = acceptor.accept(server_io).await?;
+        let mut byte = [0u8; 1];
+        stream.read_exact(&mut byte).await?;
+        Ok::<_, std::io::Error>(())
+    };
+    let client_side = async {
+        let mut stream = connector.connect(ServerName::try_from("localhost").unwrap(), client_io).await?;
+        stream.write_all(b"x").await?;
+        stream.flush().await?;
+        Ok::<_, std::io::Error>(stream)
+    };
+    let (server_result, client_result) = tokio::join!(server_side, client_side);
+    client_result?;
+    server_result
+}
+
+#[tokio::test]
+async fn known_client_certificate_is_accepted() {
+    let server_cert = generate_self_signed_cert().unwrap();
+    let client_cert = generate_self_signed_cert().unwrap();
+    let server = create_server_config_mtls(&server_cert, &roots_for(&client_cert)).unwrap();
+    let client = create_client_config_with_identity(&server_cert, &client_cert).unwrap();
+    handshake(server, client).await.unwrap();
+}
+
+#[tokio::test]
+async fn unknown_client_certificate_is_rejected() {
+    let server_cert = generate_self_signed_cert().unwrap();
+    let trusted = generate_self_signed_cert().unwrap();
+    let stranger = generate_self_signed_cert().unwrap();
+    let server = create_server_config_mtls(&server_cert, &roots_for(&trusted)).unwrap();
+    let client = create_client_config_with_identity(&server_cert, &stranger).unwrap();
+    assert!(handshake(server, client).await.is_err());
+}
+
+#[tokio::test]
+async fn client_without_certificate_is_rejected() {
+    let server_cert = generate_self_signed_cert().unwrap();
+    let client_cert = generate_self_signed_cert().unwrap();
+    let server = create_server_config_mtls(&server_cert, &roots_for(&client_cert)).unwrap();
+    let client = create_client_config_insecure(&server_cert).unwrap();
+    assert!(handshake(server, client).await.is_err());
+}
+
+#[tokio::test]
+async fn verified_client_refuses_a_self_signed_server() {
+    let server_cert = generate_self_signed_cert().unwrap();
+    let insecure = create_client_config_insecure(&server_cert).unwrap();
+    assert!(handshake(create_server_config(&server_cert, None).unwrap(), insecure).await.is_ok());
+
+    let verified = create_client_config_verified("localhost").unwrap();
+    assert!(handshake(create_server_config(&server_cert, None).unwrap(), verified).await.is_err());
+}
+
+#[test]
+fn verified_client_rejects_an_invalid_server_name() {
+    assert!(matches!(create_client_config_verified("not a hostname"), Err(TlsError::InvalidServerName(_))));
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "tls")]
+
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use zk_schnorr_lib::{
+    ChallengeContext, ChannelTransport, KeyPair, Message, MultiplexedProver, MultiplexedVerifier, ProofRequest, ProofResult,
+    ProtocolError, SchnorrProtocol, Transport,
+};
+
+#[tokio::test]
+async fn ten_sub_proofs_over_one_connection() {
+    let keypair = KeyPair::from_seed(b"multiplex-test");
+    let (prover, verifier) = (MultiplexedProver::new(&keypair), MultiplexedVerifier::new(keypair.public_key()));
+    let ids: Vec<u64> = (100..110).collect();
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let (proved, verified) = tokio::join!(prover.run(&mut prover_end, &ids), verifier.run(&mut verifier_end));
+    let expected: Vec<ProofResult> = ids.iter().map(|&proof_id| ProofResult { proof_id, verified: true }).collect();
+    assert_eq!(proved.unwrap(), expected);
+    assert_eq!(verified.unwrap(), expected);
+}
+
+// a sub-proof whose response doesn't satisfy s*G = R + c*X; returns the verdict
+async fn bogus_sub_proof(transport: &mut ChannelTransport, proof_id: u64) -> ProofResult {
+    transport.send(Message::proof_request(&ProofRequest { proof_id })).await.unwrap();
+    transport.send(Message::commit(&RistrettoPoint::mul_base(&Scalar::from(5u64)))).await.unwrap();
+    transport.recv().await.unwrap(); // the challenge
+    transport.send(Message::response(&Scalar::ONE)).await.unwrap();
+    serde_json::from_str(&transport.recv().await.unwrap().payload).unwrap()
+}
+
+#[tokio::test]
+async fn failed_sub_proof_keeps_its_id_and_the_session() {
+    let keypair = KeyPair::from_seed(b"multiplex-test");
+    let verifier = MultiplexedVerifier::new(keypair.public_key());
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async move {
+        prover_end.recv().await.unwrap(); // the context
+        let verdict = bogus_sub_proof(&mut prover_end, 7).await;
+        prover_end.send(Message::end_session()).await.unwrap();
+        verdict
+    };
+    let (verdict, verified) = tokio::join!(prover, verifier.run(&mut verifier_end));
+    assert_eq!(verdict, ProofResult { proof_id: 7, verified: false });
+    assert_eq!(verified.unwrap(), vec![verdict]);
+}
+
+#[tokio::test]
+async fn commitment_reused_across_sub_proofs_is_refused() {
+    let keypair = KeyPair::from_seed(b"multiplex-test");
+    let verifier = MultiplexedVerifier::new(keypair.public_key());
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async move {
+        prover_end.recv().await.unwrap(); // the context
+        bogus_sub_proof(&mut prover_end, 1).await;
+        prover_end.send(Message::proof_request(&ProofRequest { proof_id: 2 })).await.unwrap();
+        prover_end.send(Message::commit(&RistrettoPoint::mul_base(&Scalar::from(5u64)))).await.unwrap();
+        prover_end.recv().await.unwrap()
+    };
+    let (reply, verified) = tokio::join!(prover, verifier.run(&mut verifier_end));
+    assert!(matches!(verified, Err(ProtocolError::NonceReuse)));
+    assert_eq!(reply.kind, "error");
+}
+
+#[tokio::test]
+async fn hanging_up_without_end_session_is_a_closed_connection() {
+    let keypair = KeyPair::from_seed(b"multiplex-test");
+    let verifier = MultiplexedVerifier::new(keypair.public_key());
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async move {
+        prover_end.recv().await.unwrap(); // the context
+        // dropping prover_end closes the connection
+    };
+    let (_, verified) = tokio::join!(prover, verifier.run(&mut verifier_end));
+    assert!(matches!(verified, Err(ProtocolError::ConnectionClosed)));
+}
+
+#[tokio::test]
+async fn plain_commit_is_unexpected() {
+    let keypair = KeyPair::from_seed(b"multiplex-test");
+    let verifier = MultiplexedVerifier::new(keypair.public_key());
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async {
+        prover_end.recv().await.unwrap(); // the context
+        prover_end.send(Message::commit(&keypair.public_key())).await.unwrap();
+        prover_end.recv().await.unwrap()
+    };
+    let (reply, verified) = tokio::join!(prover, verifier.run(&mut verifier_end));
+    assert!(matches!(verified, Err(ProtocolError::UnexpectedMessage { expected: "proof-request", .. })));
+    assert_eq!(reply.kind, "error");
+}
+
+#[test]
+fn challenge_is_bound_to_the_proof_id() {
+    let key = KeyPair::from_seed(b"multiplex-test").public_key();
+    let context = ChallengeContext::random(&mut rand::rngs::OsRng);
+    let commitment = RistrettoPoint::mul_base(&Scalar::from(9u64));
+    assert_ne!(context.challenge_for_proof(1, &commitment, &key), context.challenge_for_proof(2, &commitment, &key));
+}
+
+#[tokio::test]
+async fn verify_interactive_serves_a_multiplexing_prover() {
+    let keypair = KeyPair::from_seed(b"multiplex-test");
+    let expected = keypair.public_key();
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async move {
+        let results = MultiplexedProver::new(&keypair).run(&mut prover_end, &[1, 2]).await.unwrap();
+        assert!(results.iter().all(|result| result.verified));
+    };
+    let (_, results) = tokio::join!(prover, SchnorrProtocol::verify_interactive(&mut verifier_end, &expected));
+    assert_eq!(results.unwrap(), vec![true, true]);
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "std")]
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::musig::{combine_commitments, combine_responses};
+use zk_schnorr_lib::musig2::aggregate_key;
+use zk_schnorr_lib::{aggregate_keys, ChallengeContext, Cosigner, KeyPair, Proof, PublicKey};
+
+// one round by every cosigner, with the challenge a verifier would send for the aggregate key
+fn cosign(keypairs: &[KeyPair]) -> (PublicKey, Proof) {
+    let keys: Vec<RistrettoPoint> = keypairs.iter().map(|keypair| keypair.public_key().0).collect();
+    let aggregate = aggregate_keys(&keys);
+    let cosigners: Vec<Cosigner> = keypairs.iter().map(|keypair| Cosigner::new(keypair, &keys)).collect();
+
+    let (nonces, commitments): (Vec<_>, Vec<_>) = cosigners.iter().map(|cosigner| cosigner.commit(&mut OsRng)).unzip();
+    let commitment = combine_commitments(&commitments);
+    let c = ChallengeContext::random(&mut OsRng).challenge(&commitment, &aggregate);
+    let shares: Vec<Scalar> = cosigners.iter().zip(nonces).map(|(cosigner, nonce)| cosigner.respond(nonce, &c)).collect();
+    (PublicKey(aggregate), combine_responses(commitment, c, &shares))
+}
+
+#[test]
+fn three_cosigners_prove_for_the_aggregate_key() {
+    let keypairs: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate(&mut OsRng)).collect();
+    let (aggregate, proof) = cosign(&keypairs);
+    assert!(proof.verify(&aggregate));
+    assert!(keypairs.iter().all(|keypair| !proof.verify(&keypair.public_key())));
+}
+
+#[test]
+fn aggregate_matches_musig2() {
+    let keys: Vec<PublicKey> = (0..3).map(|_| KeyPair::generate(&mut OsRng).public_key()).collect();
+    let points: Vec<RistrettoPoint> = keys.iter().map(|key| key.0).collect();
+    assert_eq!(aggregate_keys(&points), aggregate_key(&keys).0);
+}
+
+#[test]
+fn key_order_changes_the_aggregate() {
+    let a = KeyPair::generate(&mut OsRng).public_key().0;
+    let b = KeyPair::generate(&mut OsRng).public_key().0;
+    assert_ne!(aggregate_keys(&[a, b]), aggregate_keys(&[b, a]));
+}
+
+#[test]
+fn altered_share_breaks_the_proof() {
+    let keypairs: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate(&mut OsRng)).collect();
+    let (aggregate, proof) = cosign(&keypairs);
+    let altered = Proof::new(proof.commitment, proof.challenge, proof.response + Scalar::ONE);
+    assert!(!altered.verify(&aggregate));
+}
+
+#[test]
+fn rogue_key_only_works_without_coefficients() {
+    let honest = KeyPair::generate(&mut OsRng).public_key().0;
+    // the attacker knows x' and announces X_rogue = x'*G - X_honest
+    let attacker = KeyPair::generate(&mut OsRng);
+    let rogue = attacker.public_key().0 - honest;
+
+    // alone, it proves for the plain sum X_honest + X_rogue = x'*G
+    let k = Scalar::random(&mut OsRng);
+    let commitment = RistrettoPoint::mul_base(&k);
+    let plain_sum = PublicKey(honest + rogue);
+    let c = ChallengeContext::random(&mut OsRng).challenge(&commitment, &plain_sum);
+    let forged = Proof::new(commitment, c, k + c * attacker.secret().expose_secret());
+    assert!(forged.verify(&plain_sum));
+
+    let aggregate = PublicKey(aggregate_keys(&[honest, rogue]));
+    assert_ne!(aggregate, plain_sum);
+    assert!(!forged.verify(&aggregate));
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "std")]
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::musig2::{aggregate, aggregate_key};
+use zk_schnorr_lib::signature::verify;
+use zk_schnorr_lib::{KeyPair, MuSig2Error, MuSig2Session, PublicKey, Signature};
+
+struct Signers {
+    publics: Vec<PublicKey>,
+    sessions: Vec<MuSig2Session>,
+}
+
+fn signers(n: usize) -> Signers {
+    let keypairs: Vec<KeyPair> = (0..n).map(|_| KeyPair::generate(&mut OsRng)).collect();
+    let publics: Vec<PublicKey> = keypairs.iter().map(KeyPair::public_key).collect();
+    let sessions = keypairs.iter().map(|keypair| MuSig2Session::new(keypair, &publics).unwrap()).collect();
+    Signers { publics, sessions }
+}
+
+// both rounds for every signer: the nonce commitments and the partial signatures
+fn sign(signers: &mut Signers, message: &[u8]) -> (Vec<(RistrettoPoint, RistrettoPoint)>, Vec<Scalar>) {
+    let nonces: Vec<_> = signers.sessions.iter_mut().map(|session| session.commit(&mut OsRng)).collect();
+    let partials = signers.sessions.iter_mut().map(|session| session.partial_sign(&nonces, message).unwrap()).collect();
+    (nonces, partials)
+}
+
+fn sign_and_aggregate(n: usize, message: &[u8]) -> (PublicKey, Signature) {
+    let mut signers = signers(n);
+    let (nonces, partials) = sign(&mut signers, message);
+    let key = aggregate_key(&signers.publics);
+    assert!(signers.sessions.iter().all(|session| session.aggregate_key() == key));
+    (key, aggregate(&partials, &nonces, &key, message))
+}
+
+#[test]
+fn two_of_two_verifies_under_the_aggregate_key() {
+    let (key, sig) = sign_and_aggregate(2, b"pay bob 5");
+    assert!(verify(&key, b"pay bob 5", &sig));
+    assert!(!verify(&key, b"pay bob 50", &sig));
+}
+
+#[test]
+fn three_of_three_verifies_under_the_aggregate_key() {
+    let (key, sig) = sign_and_aggregate(3, b"release");
+    assert!(verify(&key, b"release", &sig));
+}
+
+#[test]
+fn missing_partial_signature_breaks_the_aggregate() {
+    let mut signers = signers(3);
+    let (nonces, partials) = sign(&mut signers, b"m");
+    let key = aggregate_key(&signers.publics);
+    for missing in 0..3 {
+        let mut partial = partials.clone();
+        partial.remove(missing);
+        assert!(!verify(&key, b"m", &aggregate(&partial, &nonces, &key, b"m")), "without signer {missing}");
+    }
+}
+
+#[test]
+fn no_single_key_or_plain_sum_verifies() {
+    let mut signers = signers(2);
+    let (nonces, partials) = sign(&mut signers, b"m");
+    let key = aggregate_key(&signers.publics);
+    let sig = aggregate(&partials, &nonces, &key, b"m");
+    let plain_sum = PublicKey(signers.publics[0].0 + signers.publics[1].0);
+    assert!(!verify(&plain_sum, b"m", &sig));
+    assert!(!verify(&signers.publics[0], b"m", &sig));
+}
+
+#[test]
+fn nonces_are_used_once() {
+    let mut signers = signers(2);
+    sign(&mut signers, b"first");
+    let nonces = vec![(RistrettoPoint::default(), RistrettoPoint::default()); 2];
+    assert_eq!(signers.sessions[0].partial_sign(&nonces, b"second"), Err(MuSig2Error::NoNonces));
+}
+
+#[test]
+fn malformed_nonce_lists_are_refused() {
+    let mut signers = signers(2);
+    let ours = signers.sessions[0].commit(&mut OsRng);
+    assert_eq!(
+        signers.sessions[0].partial_sign(&[ours], b"m"),
+        Err(MuSig2Error::NonceCountMismatch { nonces: 1, signers: 2 })
+    );
+    let theirs = signers.sessions[1].commit(&mut OsRng);
+    assert_eq!(signers.sessions[0].partial_sign(&[theirs, theirs], b"m"), Err(MuSig2Error::MissingOwnNonces));
+}
+
+#[test]
+fn own_nonces_must_sit_at_our_key_position() {
+    let mut signers = signers(2);
+    let ours = signers.sessions[0].commit(&mut OsRng);
+    let theirs = signers.sessions[1].commit(&mut OsRng);
+    assert_eq!(signers.sessions[0].partial_sign(&[theirs, ours], b"m"), Err(MuSig2Error::MissingOwnNonces));
+}
+
+#[test]
+fn a_key_outside_the_list_cannot_start_a_session() {
+    let publics: Vec<PublicKey> = (0..2).map(|_| KeyPair::generate(&mut OsRng).public_key()).collect();
+    let outsider = KeyPair::generate(&mut OsRng);
+    assert!(matches!(MuSig2Session::new(&outsider, &publics), Err(MuSig2Error::NotASigner)));
+}
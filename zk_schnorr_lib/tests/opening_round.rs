@@ -0,0 +1,89 @@
+#![cfg(feature = "tls")]
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::pedersen::{commit, generator_h};
+use zk_schnorr_lib::{
+    point_from_hex, scalar_from_hex, ChallengeContext, ChannelTransport, Message, OpeningProver, ProtocolError, SchnorrProtocol,
+    Transport,
+};
+
+#[tokio::test]
+async fn opening_round_proves_the_commitment() {
+    let h = generator_h();
+    let (v, r) = (Scalar::from(1234u64), Scalar::random(&mut OsRng));
+    let commitment = commit(&v, &r, &h);
+    let context = ChallengeContext::random(&mut OsRng);
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let (proved, verified) = tokio::join!(
+        SchnorrProtocol::prove_opening_round(&mut prover_end, &context, &commitment, &v, &r, &h),
+        SchnorrProtocol::verify_opening_round(&mut verifier_end, &context, &commitment, &h),
+    );
+    assert!(verified.unwrap());
+    let proof = proved.unwrap();
+    let c = context.challenge_for(&[proof.commitment], &[commitment.0, h]);
+    assert!(proof.verify_with_challenge(&commitment, &h, &c));
+}
+
+#[tokio::test]
+async fn wrong_opening_is_rejected() {
+    let h = generator_h();
+    let r = Scalar::random(&mut OsRng);
+    let commitment = commit(&Scalar::from(7u64), &r, &h);
+    let wrong_value = Scalar::from(8u64);
+    let context = ChallengeContext::random(&mut OsRng);
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let (proved, verified) = tokio::join!(
+        SchnorrProtocol::prove_opening_round(&mut prover_end, &context, &commitment, &wrong_value, &r, &h),
+        SchnorrProtocol::verify_opening_round(&mut verifier_end, &context, &commitment, &h),
+    );
+    assert!(!verified.unwrap());
+    assert!(matches!(proved, Err(ProtocolError::VerificationFailed)));
+}
+
+#[tokio::test]
+async fn response_for_a_fresh_commitment_is_rejected() {
+    let h = generator_h();
+    let (v, r) = (Scalar::from(3u64), Scalar::random(&mut OsRng));
+    let commitment = commit(&v, &r, &h);
+    let context = ChallengeContext::random(&mut OsRng);
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let cheating_prover = async {
+        let challenged = OpeningProver::commit(&v, &r, &h, &mut OsRng);
+        prover_end.send(Message::opening_commit(&challenged.commitment())).await.unwrap();
+        let c = scalar_from_hex(&prover_end.recv().await.unwrap().payload).unwrap();
+        // answer with a proof over another R, which doesn't match what was challenged
+        let swapped = OpeningProver::commit(&v, &r, &h, &mut OsRng).respond(&c);
+        prover_end.send(Message::opening_response(&swapped)).await.unwrap();
+        prover_end.recv().await.unwrap()
+    };
+    let (verdict, verified) =
+        tokio::join!(cheating_prover, SchnorrProtocol::verify_opening_round(&mut verifier_end, &context, &commitment, &h));
+    assert!(!verified.unwrap());
+    assert_eq!((verdict.kind.as_str(), verdict.payload.as_str()), ("result", "rejected"));
+}
+
+#[tokio::test]
+async fn prover_refuses_a_challenge_not_bound_to_the_commitment() {
+    let h = generator_h();
+    let (v, r) = (Scalar::from(3u64), Scalar::random(&mut OsRng));
+    let commitment = commit(&v, &r, &h);
+    let context = ChallengeContext::random(&mut OsRng);
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let lying_verifier = async {
+        let commit_msg = verifier_end.recv().await.unwrap();
+        let nonce_commitment: RistrettoPoint = point_from_hex(&commit_msg.payload).unwrap();
+        let other = commit(&Scalar::from(4u64), &r, &h);
+        verifier_end.send(Message::challenge(&context.challenge_for(&[nonce_commitment], &[other.0, h]))).await.unwrap();
+        verifier_end.recv().await.unwrap()
+    };
+    let (proved, reply) =
+        tokio::join!(SchnorrProtocol::prove_opening_round(&mut prover_end, &context, &commitment, &v, &r, &h), lying_verifier);
+    assert!(matches!(proved, Err(ProtocolError::InvalidChallenge)));
+    assert_eq!(reply.kind, "error");
+}
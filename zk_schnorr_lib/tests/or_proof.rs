@@ -0,0 +1,163 @@
+#![cfg(feature = "tls")]
+
+#![allow(non_snake_case)] // A, B, G, H follow the usual maths notation
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT as G;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use sha2::Sha512;
+use zk_schnorr_lib::or_proof::{prove_first, prove_second, verify};
+use zk_schnorr_lib::{ChallengeContext, ChannelTransport, OrProof, OrProver, ProtocolError, SchnorrProtocol};
+
+fn second_generator() -> RistrettoPoint {
+    RistrettoPoint::hash_from_bytes::<Sha512>(b"or-proof test generator H")
+}
+
+#[test]
+fn first_branch_verifies() {
+    let H = second_generator();
+    let x = Scalar::random(&mut OsRng);
+    let A = G * x;
+    let B = H * Scalar::random(&mut OsRng); // secret for B is unknown to the prover
+
+    let proof = prove_first(&x, &A, &B, &G, &H, &mut OsRng);
+    assert!(verify(&A, &B, &G, &H, &proof));
+}
+
+#[test]
+fn second_branch_verifies() {
+    let H = second_generator();
+    let y = Scalar::random(&mut OsRng);
+    let A = G * Scalar::random(&mut OsRng);
+    let B = H * y;
+
+    let proof = prove_second(&y, &A, &B, &G, &H, &mut OsRng);
+    assert!(verify(&A, &B, &G, &H, &proof));
+}
+
+#[test]
+fn proof_does_not_transfer_to_other_statements() {
+    let H = second_generator();
+    let x = Scalar::random(&mut OsRng);
+    let A = G * x;
+    let B = H * Scalar::random(&mut OsRng);
+    let proof = prove_first(&x, &A, &B, &G, &H, &mut OsRng);
+
+    let other_B = H * Scalar::random(&mut OsRng);
+    assert!(!verify(&A, &other_B, &G, &H, &proof));
+}
+
+#[test]
+fn both_branches_simulated_fails() {
+    // without either secret, the best a cheater can do is simulate both branches
+    let H = second_generator();
+    let A = G * Scalar::random(&mut OsRng);
+    let B = H * Scalar::random(&mut OsRng);
+
+    let (c1, s1) = (Scalar::random(&mut OsRng), Scalar::random(&mut OsRng));
+    let (c2, s2) = (Scalar::random(&mut OsRng), Scalar::random(&mut OsRng));
+    let forged = OrProof { R1: G * s1 - A * c1, R2: H * s2 - B * c2, c1, c2, s1, s2 };
+
+    assert!(!verify(&A, &B, &G, &H, &forged));
+}
+
+#[test]
+fn tampered_response_fails() {
+    let H = second_generator();
+    let x = Scalar::random(&mut OsRng);
+    let A = G * x;
+    let B = H * Scalar::random(&mut OsRng);
+
+    let mut proof = prove_first(&x, &A, &B, &G, &H, &mut OsRng);
+    proof.s2 += Scalar::ONE;
+    assert!(!verify(&A, &B, &G, &H, &proof));
+}
+
+#[test]
+fn basepoint_proof_for_either_index_verifies() {
+    let (x1, x2) = (Scalar::random(&mut OsRng), Scalar::random(&mut OsRng));
+    let (X1, X2) = (G * x1, G * x2);
+
+    assert!(OrProof::prove(0, &x1, &X1, &X2).verify(&X1, &X2));
+    assert!(OrProof::prove(1, &x2, &X1, &X2).verify(&X1, &X2));
+    // claiming the wrong index doesn't produce a valid proof
+    assert!(!OrProof::prove(1, &x1, &X1, &X2).verify(&X1, &X2));
+}
+
+#[test]
+#[should_panic(expected = "known_index must be 0 or 1")]
+fn out_of_range_index_panics() {
+    let x = Scalar::random(&mut OsRng);
+    OrProof::prove(2, &x, &(G * x), &G);
+}
+
+#[test]
+fn serde_round_trip() {
+    let x = Scalar::random(&mut OsRng);
+    let (X1, X2) = (G * Scalar::random(&mut OsRng), G * x);
+    let proof = OrProof::prove(1, &x, &X1, &X2);
+
+    let json = serde_json::to_string(&proof).unwrap();
+    assert!(json.contains(&zk_schnorr_lib::point_to_hex(&proof.R1)));
+    let decoded: OrProof = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, proof);
+    assert!(decoded.verify(&X1, &X2));
+}
+
+#[test]
+fn interactive_split_verifies_for_either_index() {
+    let x = Scalar::random(&mut OsRng);
+    let other = G * Scalar::random(&mut OsRng);
+    for (which, pubkeys) in [(0, [G * x, other]), (1, [other, G * x])] {
+        let prover = OrProver::commit(&x, which, &pubkeys, &mut OsRng);
+        let c = Scalar::random(&mut OsRng); // chosen after the commitment
+        let proof = prover.respond(&c);
+        assert!(proof.verify_with_challenge(&pubkeys, &c));
+        assert!(!proof.verify_with_challenge(&pubkeys, &(c + Scalar::ONE)));
+    }
+}
+
+#[test]
+fn challenge_split_that_does_not_add_up_is_rejected() {
+    let x = Scalar::random(&mut OsRng);
+    let pubkeys = [G * x, G * Scalar::random(&mut OsRng)];
+    let c = Scalar::random(&mut OsRng);
+    let mut proof = OrProver::commit(&x, 0, &pubkeys, &mut OsRng).respond(&c);
+    // shift both branches consistently so only c1 + c2 != c is wrong
+    proof.c1 += Scalar::ONE;
+    proof.s1 += x;
+    assert!(G * proof.s1 == proof.R1 + pubkeys[0] * proof.c1);
+    assert!(!proof.verify_with_challenge(&pubkeys, &c));
+}
+
+#[tokio::test]
+async fn or_round_over_a_transport() {
+    let x = Scalar::random(&mut OsRng);
+    let pubkeys = [G * Scalar::random(&mut OsRng), G * x];
+    let context = ChallengeContext::random(&mut OsRng);
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let (proved, verified) = tokio::join!(
+        SchnorrProtocol::prove_or_round(&mut prover_end, &context, &x, 1, &pubkeys),
+        SchnorrProtocol::verify_or_round(&mut verifier_end, &context, &pubkeys),
+    );
+    assert!(verified.unwrap());
+    let proof = proved.unwrap();
+    assert!(proof.verify_with_challenge(&pubkeys, &context.challenge_for(&[proof.R1, proof.R2], &pubkeys)));
+}
+
+#[tokio::test]
+async fn or_round_without_either_secret_fails() {
+    let x = Scalar::random(&mut OsRng);
+    let pubkeys = [G * Scalar::random(&mut OsRng), G * Scalar::random(&mut OsRng)];
+    let context = ChallengeContext::random(&mut OsRng);
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let (proved, verified) = tokio::join!(
+        SchnorrProtocol::prove_or_round(&mut prover_end, &context, &x, 0, &pubkeys),
+        SchnorrProtocol::verify_or_round(&mut verifier_end, &context, &pubkeys),
+    );
+    assert!(!verified.unwrap());
+    assert!(matches!(proved, Err(ProtocolError::VerificationFailed)));
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "std")]
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use rand::rngs::OsRng;
+use zk_schnorr_lib::signature::{sign, sign_with_params, verify, verify_with_params};
+use zk_schnorr_lib::version::check_generator;
+use zk_schnorr_lib::{hash_to_point, DleqProof, KeyPair, NegotiationError, Params, Proof, VersionInfo};
+
+#[test]
+fn default_is_the_basepoint() {
+    let params = Params::default();
+    assert_eq!(*params.generator(), RISTRETTO_BASEPOINT_POINT);
+    assert!(params.is_default());
+
+    let keypair = KeyPair::from_seed(b"params");
+    assert_eq!(params.public_key_of(&keypair), keypair.public_key());
+}
+
+#[test]
+fn domain_tags_give_independent_generators() {
+    let h = Params::from_domain_tag(b"zk-schnorr-tls/test/H");
+    assert_eq!(h, Params::from_domain_tag(b"zk-schnorr-tls/test/H"));
+    assert_eq!(*h.generator(), hash_to_point(b"zk-schnorr-tls/test/H"));
+    assert_ne!(h, Params::from_domain_tag(b"zk-schnorr-tls/test/J"));
+    assert!(!h.is_default());
+    assert_eq!(Params::with_generator(RISTRETTO_BASEPOINT_POINT), Params::default());
+}
+
+#[test]
+fn proof_over_a_custom_generator_needs_that_generator() {
+    let params = Params::from_domain_tag(b"zk-schnorr-tls/test/H");
+    let keypair = KeyPair::from_seed(b"params");
+    let x = keypair.secret().expose_secret();
+    let public = params.public_key_of(&keypair);
+
+    let (k, c) = (Scalar::from(11u64), Scalar::from(3u64));
+    let proof = Proof::new(params.generator() * k, c, k + c * x);
+    assert!(proof.verify_with_generator(&public, params.generator()));
+    assert!(!proof.verify(&public));
+}
+
+#[test]
+fn signatures_are_bound_to_their_generator() {
+    let params = Params::from_domain_tag(b"zk-schnorr-tls/test/H");
+    let keypair = KeyPair::from_seed(b"params");
+    let x = keypair.secret().expose_secret();
+    let public = params.public_key(x);
+
+    let sig = sign_with_params(&params, x, b"message", &mut OsRng);
+    assert!(verify_with_params(&params, &public, b"message", &sig));
+    assert!(!verify_with_params(&params, &public, b"another message", &sig));
+    assert!(!verify(&public, b"message", &sig));
+    assert!(!verify_with_params(&Params::from_domain_tag(b"zk-schnorr-tls/test/J"), &public, b"message", &sig));
+}
+
+#[test]
+fn default_params_sign_as_before() {
+    let keypair = KeyPair::from_seed(b"params");
+    let x = keypair.secret().expose_secret();
+    let sig = sign(x, b"message", &mut OsRng);
+    assert!(verify_with_params(&Params::default(), &keypair.public_key(), b"message", &sig));
+    let sig = sign_with_params(&Params::default(), x, b"message", &mut OsRng);
+    assert!(verify(&keypair.public_key(), b"message", &sig));
+}
+
+#[test]
+fn dleq_over_params() {
+    let params = Params::from_domain_tag(b"zk-schnorr-tls/test/H");
+    let other = hash_to_point(b"zk-schnorr-tls/test/J");
+    let x = Scalar::from(42u64);
+    let (x1, x2) = (params.generator() * x, other * x);
+
+    let proof = DleqProof::prove_with_params(&x, &params, &other, &mut OsRng);
+    assert!(proof.verify_with_params(&x1, &x2, &params, &other));
+    assert!(!proof.verify_with_params(&x1, &x2, &Params::default(), &other));
+}
+
+#[test]
+fn hello_names_only_a_custom_generator() {
+    let json = serde_json::to_value(VersionInfo::current().with_params(&Params::default())).unwrap();
+    assert!(json.get("generator").is_none());
+
+    let params = Params::from_domain_tag(b"zk-schnorr-tls/test/H");
+    let hello = VersionInfo::current().with_params(&params);
+    let parsed: VersionInfo = serde_json::from_str(&serde_json::to_string(&hello).unwrap()).unwrap();
+    assert_eq!(parsed.params(), params);
+    assert_eq!(VersionInfo::current().params(), Params::default());
+}
+
+#[test]
+fn different_generators_do_not_negotiate() {
+    let h: RistrettoPoint = hash_to_point(b"zk-schnorr-tls/test/H");
+    let prover = VersionInfo::current().with_params(&Params::with_generator(h));
+    let verifier = VersionInfo::current();
+    assert_eq!(
+        check_generator(&prover, &verifier),
+        Err(NegotiationError::GeneratorMismatch { verifier: RISTRETTO_BASEPOINT_POINT.compress(), prover: h.compress() })
+    );
+    assert_eq!(check_generator(&prover, &prover.clone()), Ok(()));
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "std")]
+
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::pedersen::{commit, generator_h};
+use zk_schnorr_lib::{Commitment, OpeningProof, OpeningProver};
+
+#[test]
+fn generator_h_is_deterministic() {
+    assert_eq!(generator_h(), generator_h());
+    assert_ne!(generator_h(), curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT);
+}
+
+#[test]
+fn opening_proof_verifies() {
+    let h = generator_h();
+    let (v, r) = (Scalar::from(42u64), Scalar::random(&mut OsRng));
+    let c = commit(&v, &r, &h);
+    let proof = c.prove_opening(&v, &r, &h, &mut OsRng);
+    assert!(proof.verify(&c, &h));
+}
+
+#[test]
+fn proof_for_other_commitment_fails() {
+    let h = generator_h();
+    let r = Scalar::random(&mut OsRng);
+    let c = commit(&Scalar::from(1u64), &r, &h);
+    let other = commit(&Scalar::from(2u64), &r, &h);
+    let proof = c.prove_opening(&Scalar::from(1u64), &r, &h, &mut OsRng);
+    assert!(!proof.verify(&other, &h));
+}
+
+#[test]
+fn wrong_opening_fails() {
+    let h = generator_h();
+    let r = Scalar::random(&mut OsRng);
+    let c = commit(&Scalar::from(7u64), &r, &h);
+    let proof = c.prove_opening(&Scalar::from(8u64), &r, &h, &mut OsRng);
+    assert!(!proof.verify(&c, &h));
+}
+
+#[test]
+fn commitment_and_proof_serialize_as_hex() {
+    let h = generator_h();
+    let (v, r) = (Scalar::from(5u64), Scalar::random(&mut OsRng));
+    let c = commit(&v, &r, &h);
+    let proof = c.prove_opening(&v, &r, &h, &mut OsRng);
+
+    let json = serde_json::to_string(&c).unwrap();
+    assert_eq!(json, format!("\"{}\"", zk_schnorr_lib::point_to_hex(&c.0)));
+    let decoded: Commitment = serde_json::from_str(&json).unwrap();
+    let decoded_proof: OpeningProof = serde_json::from_str(&serde_json::to_string(&proof).unwrap()).unwrap();
+    assert!(decoded_proof.verify(&decoded, &h));
+}
+
+#[test]
+fn commitments_add_homomorphically() {
+    let h = generator_h();
+    let (r1, r2) = (Scalar::random(&mut OsRng), Scalar::random(&mut OsRng));
+    let (v1, v2) = (Scalar::from(30u64), Scalar::from(12u64));
+    let sum = commit(&v1, &r1, &h) + commit(&v2, &r2, &h);
+    assert_eq!(sum, commit(&(v1 + v2), &(r1 + r2), &h));
+
+    // the summed openings prove the summed commitment
+    let proof = sum.prove_opening(&(v1 + v2), &(r1 + r2), &h, &mut OsRng);
+    assert!(proof.verify(&sum, &h));
+}
+
+#[test]
+fn interactive_prover_answers_any_challenge() {
+    let h = generator_h();
+    let (v, r) = (Scalar::from(9u64), Scalar::random(&mut OsRng));
+    let c = commit(&v, &r, &h);
+    let prover = OpeningProver::commit(&v, &r, &h, &mut OsRng);
+    let challenge = Scalar::random(&mut OsRng);
+    let proof = prover.respond(&challenge);
+    assert!(proof.verify_with_challenge(&c, &h, &challenge));
+    assert!(!proof.verify_with_challenge(&c, &h, &(challenge + Scalar::ONE)));
+    assert!(!proof.verify(&c, &h), "not a Fiat-Shamir challenge");
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "tls")]
+
+use std::sync::Arc;
+
+use rustls::{CertificateError, ServerName};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use zk_schnorr_lib::{cert_fingerprint, create_client_config_pinned, create_server_config, generate_self_signed_cert, PinnedCert};
+
+// run a handshake plus one byte each way, returning the client's error if it gave up
+async fn handshake(server: rustls::ServerConfig, client: rustls::ClientConfig) -> Result<(), std::io::Error> {
+    let (client_io, server_io) = tokio::io::duplex(16 * 1024);
+    let acceptor = TlsAcceptor::from(Arc::new(server));
+    let connector = TlsConnector::from(Arc::new(client));
+
+    let server_side = async {
+        let mut stream = acceptor.accept(server_io).await?;
+        let mut byte = [0u8; 1];
+        stream.read_exact(&mut byte).await?;
+        Ok::<_, std::io::Error>(())
+    };
+    let client_side = async {
+        let mut stream = connector.connect(ServerName::try_from("localhost").unwrap(), client_io).await?;
+        stream.write_all(b"x").await?;
+        stream.flush().await?;
+        Ok::<_, std::io::Error>(stream)
+    };
+    let (server_result, client_result) = tokio::join!(server_side, client_side);
+    client_result?;
+    server_result
+}
+
+#[test]
+fn fingerprint_is_sha256_of_the_der() {
+    let cert = generate_self_signed_cert().unwrap();
+    let expected: [u8; 32] = Sha256::digest(&cert.cert_der).into();
+    assert_eq!(PinnedCert::from_der(&cert.cert_der).fingerprint, expected);
+    assert_eq!(cert_fingerprint(&cert.cert_der), expected);
+    assert_eq!(cert.fingerprint(), expected);
+}
+
+#[tokio::test]
+async fn raw_fingerprint_pins_the_certificate() {
+    let cert = generate_self_signed_cert().unwrap();
+    let client = create_client_config_pinned(cert_fingerprint(&cert.cert_der)).unwrap();
+    handshake(create_server_config(&cert, None).unwrap(), client).await.unwrap();
+
+    let other = generate_self_signed_cert().unwrap();
+    let client = create_client_config_pinned(cert.fingerprint()).unwrap();
+    assert!(handshake(create_server_config(&other, None).unwrap(), client).await.is_err());
+}
+
+#[tokio::test]
+async fn pinned_certificate_is_accepted() {
+    let cert = generate_self_signed_cert().unwrap();
+    let client = create_client_config_pinned(PinnedCert::from_der(&cert.cert_der)).unwrap();
+    handshake(create_server_config(&cert, None).unwrap(), client).await.unwrap();
+}
+
+#[tokio::test]
+async fn any_other_certificate_is_rejected() {
+    let pinned = generate_self_signed_cert().unwrap();
+    let presented = generate_self_signed_cert().unwrap();
+    let client = create_client_config_pinned(PinnedCert::from_der(&pinned.cert_der)).unwrap();
+
+    let err = handshake(create_server_config(&presented, None).unwrap(), client).await.unwrap_err();
+    let tls_error = err.get_ref().and_then(|e| e.downcast_ref::<rustls::Error>());
+    assert_eq!(
+        tls_error,
+        Some(&rustls::Error::InvalidCertificate(CertificateError::ApplicationVerificationFailure))
+    );
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "tls")]
+
+use std::time::{Duration, SystemTime};
+
+use curve25519_dalek::scalar::Scalar;
+use zk_schnorr_lib::{
+    run_prover, run_verifier_logged, InMemoryProofStore, KeyPair, Proof, ProofEntry, ProofStoreBackend, ProtocolError,
+    PublicKey, SessionId, StreamTransport, ZkError,
+};
+
+fn entry(public_key: PublicKey, session: u8, verified: bool) -> ProofEntry {
+    ProofEntry {
+        session_id: SessionId::new([session; 16]),
+        public_key,
+        proof: Proof::new(public_key.0, Scalar::from(u64::from(session)), Scalar::ONE),
+        verified,
+        timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + u64::from(session)),
+    }
+}
+
+#[test]
+fn entries_are_found_by_key_and_by_session() {
+    let store = InMemoryProofStore::new();
+    let keys: Vec<PublicKey> = (0..5).map(|i| KeyPair::from_seed(format!("prover {i}").as_bytes()).public_key()).collect();
+    for session in 0..50u8 {
+        store.store(entry(keys[usize::from(session) % 5], session, session % 3 != 0)).unwrap();
+    }
+    assert_eq!(store.len(), 50);
+
+    for (i, key) in keys.iter().enumerate() {
+        let found = store.lookup_by_public_key(key).unwrap();
+        assert_eq!(found.len(), 10, "key {i}");
+        assert!(found.iter().all(|e| e.public_key == *key));
+        assert!(found.windows(2).all(|pair| pair[0].timestamp < pair[1].timestamp), "oldest first");
+    }
+    assert!(store.lookup_by_public_key(&KeyPair::from_seed(b"stranger").public_key()).unwrap().is_empty());
+
+    let found = store.lookup_by_session(&SessionId::new([17; 16])).unwrap().unwrap();
+    assert_eq!(found, entry(keys[2], 17, true));
+    assert_eq!(store.lookup_by_session(&SessionId::new([99; 16])).unwrap(), None);
+}
+
+#[test]
+fn session_lookup_returns_the_latest_round() {
+    let store = InMemoryProofStore::new();
+    let key = KeyPair::from_seed(b"rounds").public_key();
+    let (first, second) = (entry(key, 1, false), ProofEntry { verified: true, ..entry(key, 1, false) });
+    store.store(first).unwrap();
+    store.store(second).unwrap();
+
+    assert_eq!(store.lookup_by_session(&first.session_id).unwrap(), Some(second));
+    assert_eq!(store.lookup_by_public_key(&key).unwrap(), vec![first, second]);
+}
+
+#[tokio::test]
+async fn verifier_stores_every_checked_proof() {
+    let store = InMemoryProofStore::new();
+    let keypair = KeyPair::from_seed(b"stored");
+    let expected = keypair.public_key();
+    let (prover_end, verifier_end) = tokio::io::duplex(4096);
+    let (mut prover, mut verifier) = (StreamTransport::new(prover_end), StreamTransport::new(verifier_end));
+
+    let prove = async move { run_prover(&mut prover, &keypair).await };
+    let (proved, verified) = tokio::join!(prove, run_verifier_logged(&mut verifier, &expected, &store));
+    proved.unwrap();
+    assert!(verified.unwrap().accepted());
+
+    let stored = store.lookup_by_public_key(&expected).unwrap();
+    assert_eq!(stored.len(), 1);
+    assert!(stored[0].verified && stored[0].proof.verify(&expected));
+    assert_eq!(store.lookup_by_session(&stored[0].session_id).unwrap(), Some(stored[0]));
+}
+
+// refuses everything, as a database that is down would
+struct BrokenStore;
+
+impl ProofStoreBackend for BrokenStore {
+    fn store(&self, _: ProofEntry) -> Result<(), ZkError> {
+        Err(ZkError::Io(std::io::Error::other("database unavailable")))
+    }
+
+    fn lookup_by_public_key(&self, _: &PublicKey) -> Result<Vec<ProofEntry>, ZkError> {
+        Ok(Vec::new())
+    }
+
+    fn lookup_by_session(&self, _: &SessionId) -> Result<Option<ProofEntry>, ZkError> {
+        Ok(None)
+    }
+}
+
+#[tokio::test]
+async fn failing_store_ends_the_session() {
+    let keypair = KeyPair::from_seed(b"stored");
+    let expected = keypair.public_key();
+    let (prover_end, verifier_end) = tokio::io::duplex(4096);
+    let (mut prover, mut verifier) = (StreamTransport::new(prover_end), StreamTransport::new(verifier_end));
+
+    let verify = async move { run_verifier_logged(&mut verifier, &expected, &BrokenStore).await };
+    let (_, verified) = tokio::join!(run_prover(&mut prover, &keypair), verify);
+    assert!(matches!(verified, Err(ProtocolError::Store(e)) if matches!(*e, ZkError::Io(_))));
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "std")]
+
+use std::time::SystemTime;
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::{
+    commit, hash_to_point, read_transcript, verify_transcript, ChallengeSource, KeyPair, Proof, ProofEntry, ProofLog, ProtocolError,
+    SchnorrProof, SessionId, Transcript, TranscriptDir, TranscriptValidity,
+};
+
+#[test]
+fn fiat_shamir_transcripts_rederive_the_challenge() {
+    let keypair = KeyPair::from_seed(b"transcript");
+    let generator = hash_to_point(b"transcript generator");
+    let public_key = (generator * keypair.secret().expose_secret()).into();
+    let proof = SchnorrProof::prove_with_generator(keypair.secret().expose_secret(), &generator, &mut OsRng);
+    let transcript = Transcript::fiat_shamir(&proof, &public_key, &generator);
+    assert!(transcript.verified);
+    assert_eq!(verify_transcript(&transcript).unwrap(), TranscriptValidity::NonInteractive);
+
+    // a challenge that still satisfies the equation but wasn't derived from (G, X, R)
+    let c = transcript.challenge + Scalar::ONE;
+    let forged = Transcript { challenge: c, response: transcript.response + keypair.secret().expose_secret(), ..transcript };
+    assert!(forged.proof().verify_with_generator(&public_key, &generator));
+    assert!(matches!(verify_transcript(&forged), Err(ProtocolError::InvalidChallenge)));
+
+    let wrong_response = Transcript { response: transcript.response + Scalar::ONE, ..transcript };
+    assert!(matches!(verify_transcript(&wrong_response), Err(ProtocolError::VerificationFailed)));
+}
+
+#[test]
+fn session_bound_proofs_are_checked_against_their_session() {
+    let keypair = KeyPair::from_seed(b"transcript");
+    let session = SessionId::new([7; 16]);
+    let proof = SchnorrProof::prove_in_session(keypair.secret().expose_secret(), &session, &mut OsRng);
+    let transcript = Transcript::fiat_shamir_in_session(&proof, &keypair.public_key(), &session);
+    assert_eq!(verify_transcript(&transcript).unwrap(), TranscriptValidity::NonInteractive);
+
+    let moved = Transcript { session_id: Some(SessionId::new([8; 16])), ..transcript };
+    assert!(matches!(verify_transcript(&moved), Err(ProtocolError::InvalidChallenge)));
+}
+
+#[test]
+fn interactive_transcripts_only_show_the_equation_holds() {
+    let keypair = KeyPair::from_seed(b"transcript");
+    let public_key = keypair.public_key();
+    // anyone can simulate an accepting interactive transcript by picking c and s first
+    let (c, s) = (Scalar::from(42u64), Scalar::from(1234u64));
+    let commitment = commit(&s) - public_key.0 * c;
+    let entry = ProofEntry { session_id: SessionId::new([1; 16]), public_key, proof: Proof::new(commitment, c, s), verified: true, timestamp: SystemTime::now() };
+    let transcript = Transcript::interactive(&entry, &RISTRETTO_BASEPOINT_POINT);
+    assert_eq!(transcript.source, ChallengeSource::Interactive);
+    assert_eq!(verify_transcript(&transcript).unwrap(), TranscriptValidity::EquationHolds);
+
+    let tampered = Transcript { challenge: c + Scalar::ONE, ..transcript };
+    assert!(matches!(verify_transcript(&tampered), Err(ProtocolError::VerificationFailed)));
+}
+
+#[test]
+fn transcript_dir_writes_one_file_per_round() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = TranscriptDir::create(dir.path().join("transcripts")).unwrap();
+    let keypair = KeyPair::from_seed(b"transcript");
+    let session = SessionId::new([3; 16]);
+    let (c, s) = (Scalar::from(5u64), Scalar::from(9u64));
+    let proof = Proof::new(commit(&s) - keypair.public_key().0 * c, c, s);
+    for _ in 0..2 {
+        log.record(&session, &keypair.public_key(), &proof, true, SystemTime::now());
+    }
+
+    for round in 1..=2 {
+        let transcript = read_transcript(&log.dir().join(format!("{}-{round}.json", session.to_hex()))).unwrap();
+        assert_eq!((transcript.session_id, transcript.public_key), (Some(session), keypair.public_key()));
+        assert_eq!(transcript.proof().challenge, c);
+        assert_eq!(verify_transcript(&transcript).unwrap(), TranscriptValidity::EquationHolds);
+    }
+    assert!(!log.dir().join(format!("{}-3.json", session.to_hex())).exists());
+}
@@ -0,0 +1,440 @@
+#![cfg(feature = "tls")]
+
+use tracing_test::traced_test;
+use zk_schnorr_lib::{
+    ChallengeContext, ChannelTransport, DecodeError, KeyPair, Message, PointDecodeError, ProtocolError, ScalarDecodeError,
+    SchnorrProtocol, SessionId, Transport,
+};
+
+#[tokio::test]
+async fn prover_and_verifier_over_channels() {
+    let keypair = KeyPair::from_seed(b"channel-test");
+    let expected = keypair.public_key();
+
+    let verified = tokio::spawn(async move {
+        let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+        let (proved, verified) = tokio::join!(
+            SchnorrProtocol::run_prover(&mut prover_end, &keypair),
+            SchnorrProtocol::run_verifier(&mut verifier_end, &expected),
+        );
+        proved.unwrap();
+        verified.unwrap()
+    })
+    .await
+    .unwrap();
+
+    assert!(verified);
+}
+
+#[tokio::test]
+async fn wrong_secret_is_rejected() {
+    let prover_key = KeyPair::from_seed(b"not-the-right-one");
+    let expected = KeyPair::from_seed(b"channel-test").public_key();
+
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+    let (proved, verified) = tokio::join!(
+        SchnorrProtocol::run_prover(&mut prover_end, &prover_key),
+        SchnorrProtocol::run_verifier(&mut verifier_end, &expected),
+    );
+    // challenges are bound to the expected key, so the prover refuses to answer and says so
+    assert!(matches!(proved, Err(ProtocolError::InvalidChallenge)));
+    assert!(matches!(verified, Err(ProtocolError::Rejected { .. })));
+}
+
+#[tokio::test]
+async fn verifier_reports_a_closed_connection() {
+    let expected = KeyPair::from_seed(b"channel-test").public_key();
+    let (prover_end, mut verifier_end) = ChannelTransport::pair();
+    drop(prover_end);
+
+    let err = SchnorrProtocol::run_verifier(&mut verifier_end, &expected).await.unwrap_err();
+    assert!(matches!(err, ProtocolError::ConnectionClosed));
+}
+
+#[tokio::test]
+async fn verifier_rejects_non_canonical_response() {
+    let expected = KeyPair::from_seed(b"channel-test").public_key();
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let cheating_prover = async {
+        prover_end.recv().await.unwrap(); // the context
+        prover_end.send(Message::commit(&expected)).await.unwrap();
+        prover_end.recv().await.unwrap(); // the challenge
+        // the group order encodes zero, but not canonically
+        let payload = "edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010".to_string();
+        prover_end.send(Message { kind: "response".to_string(), payload, session: None }).await.unwrap();
+        prover_end.recv().await.unwrap()
+    };
+    let (reply, verified) = tokio::join!(cheating_prover, SchnorrProtocol::run_verifier(&mut verifier_end, &expected));
+    assert!(matches!(verified, Err(ProtocolError::Decode(DecodeError::Scalar(ScalarDecodeError::NonCanonical)))));
+    assert_eq!(reply.kind, "error");
+}
+
+#[tokio::test]
+async fn several_rounds_over_one_connection() {
+    let keypair = KeyPair::from_seed(b"channel-test");
+    let expected = keypair.public_key();
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async move {
+        SchnorrProtocol::run_prover_rounds(&mut prover_end, &keypair, 3).await.unwrap();
+        // dropping prover_end closes the session
+    };
+    let (_, results) = tokio::join!(prover, SchnorrProtocol::verify_interactive(&mut verifier_end, &expected));
+    assert_eq!(results.unwrap(), vec![true, true, true]);
+}
+
+// a round whose response doesn't satisfy s*G = R + c*X; returns the verdict
+async fn bogus_round(transport: &mut ChannelTransport) -> String {
+    let commitment = curve25519_dalek::RistrettoPoint::mul_base(&curve25519_dalek::Scalar::from(5u64));
+    transport.send(Message::commit(&commitment)).await.unwrap();
+    transport.recv().await.unwrap(); // the challenge
+    transport.send(Message::response(&curve25519_dalek::Scalar::ONE)).await.unwrap();
+    transport.recv().await.unwrap().payload
+}
+
+#[tokio::test]
+async fn failed_round_does_not_end_the_session() {
+    let good = KeyPair::from_seed(b"channel-test");
+    let expected = good.public_key();
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async move {
+        let context = SchnorrProtocol::open_prover_session(&mut prover_end).await.unwrap();
+        SchnorrProtocol::prove_round(&mut prover_end, &context, &good).await.unwrap();
+        assert_eq!(bogus_round(&mut prover_end).await, "rejected");
+        SchnorrProtocol::prove_round(&mut prover_end, &context, &good).await.unwrap();
+    };
+    let (_, results) = tokio::join!(prover, SchnorrProtocol::verify_interactive(&mut verifier_end, &expected));
+    assert_eq!(results.unwrap(), vec![true, false, true]);
+}
+
+#[tokio::test]
+async fn repeated_commitment_is_refused_before_a_second_challenge() {
+    let expected = KeyPair::from_seed(b"channel-test").public_key();
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async {
+        prover_end.recv().await.unwrap(); // the context
+        assert_eq!(bogus_round(&mut prover_end).await, "rejected");
+        // the same R again: a second challenge for it would let s1 - s2 reveal the secret
+        let commitment = curve25519_dalek::RistrettoPoint::mul_base(&curve25519_dalek::Scalar::from(5u64));
+        prover_end.send(Message::commit(&commitment)).await.unwrap();
+        prover_end.recv().await.unwrap()
+    };
+    let (reply, verified) = tokio::join!(prover, SchnorrProtocol::verify_interactive(&mut verifier_end, &expected));
+    assert!(matches!(verified, Err(ProtocolError::NonceReuse)));
+    assert_eq!(reply.kind, "error", "no challenge is sent");
+    assert!(reply.payload.contains("reveal the secret"));
+}
+
+#[tokio::test]
+async fn immediate_close_is_an_empty_session() {
+    let expected = KeyPair::from_seed(b"channel-test").public_key();
+    let (prover_end, mut verifier_end) = ChannelTransport::pair();
+    drop(prover_end);
+    assert!(SchnorrProtocol::verify_interactive(&mut verifier_end, &expected).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn invalid_commitment_is_a_decode_error() {
+    let expected = KeyPair::from_seed(b"channel-test").public_key();
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async {
+        prover_end.recv().await.unwrap(); // the context
+        let payload = "ff".repeat(32); // right length, not a point
+        prover_end.send(Message { kind: "commit".to_string(), payload, session: None }).await.unwrap();
+        prover_end.recv().await.unwrap()
+    };
+    let (reply, verified) = tokio::join!(prover, SchnorrProtocol::verify_interactive(&mut verifier_end, &expected));
+    assert!(matches!(verified, Err(ProtocolError::Decode(DecodeError::Point(_)))));
+    assert_eq!(reply.kind, "error");
+}
+
+#[tokio::test]
+async fn identity_commitment_is_refused() {
+    let expected = KeyPair::from_seed(b"channel-test").public_key();
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async {
+        prover_end.recv().await.unwrap(); // the context
+        prover_end.send(Message::commit(&curve25519_dalek::RistrettoPoint::default())).await.unwrap();
+        prover_end.recv().await.unwrap()
+    };
+    let (reply, verified) = tokio::join!(prover, SchnorrProtocol::verify_interactive(&mut verifier_end, &expected));
+    assert!(matches!(verified, Err(ProtocolError::Decode(DecodeError::Point(PointDecodeError::IdentityPoint)))));
+    assert_eq!(reply.kind, "error", "no challenge is sent");
+}
+
+#[tokio::test]
+async fn zero_response_is_refused() {
+    let expected = KeyPair::from_seed(b"channel-test").public_key();
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async {
+        prover_end.recv().await.unwrap(); // the context
+        let commitment = curve25519_dalek::RistrettoPoint::mul_base(&curve25519_dalek::Scalar::from(5u64));
+        prover_end.send(Message::commit(&commitment)).await.unwrap();
+        prover_end.recv().await.unwrap(); // the challenge
+        prover_end.send(Message::response(&curve25519_dalek::Scalar::ZERO)).await.unwrap();
+        prover_end.recv().await.unwrap()
+    };
+    let (reply, verified) = tokio::join!(prover, SchnorrProtocol::verify_interactive(&mut verifier_end, &expected));
+    assert!(matches!(verified, Err(ProtocolError::Decode(DecodeError::Scalar(ScalarDecodeError::Zero)))));
+    assert_eq!(reply.kind, "error");
+}
+
+#[tokio::test]
+async fn out_of_order_message_is_rejected() {
+    let expected = KeyPair::from_seed(b"channel-test").public_key();
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async {
+        prover_end.recv().await.unwrap(); // the context
+        prover_end.send(Message::response(&curve25519_dalek::Scalar::ONE)).await.unwrap();
+        prover_end.recv().await.unwrap()
+    };
+    let (reply, verified) = tokio::join!(prover, SchnorrProtocol::verify_interactive(&mut verifier_end, &expected));
+    assert!(matches!(verified, Err(ProtocolError::UnexpectedMessage { expected: "commit", .. })));
+    assert_eq!(reply.kind, "error");
+}
+
+#[tokio::test]
+async fn verifier_tags_its_messages_with_the_session() {
+    let keypair = KeyPair::from_seed(b"channel-test");
+    let expected = keypair.public_key();
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async move {
+        let context_msg = prover_end.recv().await.unwrap();
+        let context = ChallengeContext::from_hex(&context_msg.payload).unwrap();
+        assert_eq!(context_msg.session, Some(*context.session_id()));
+        prover_end.send(Message::commit(&expected)).await.unwrap();
+        assert_eq!(prover_end.recv().await.unwrap().session, Some(*context.session_id()));
+        // dropping prover_end ends the session before the response
+    };
+    let _ = tokio::join!(prover, SchnorrProtocol::run_verifier(&mut verifier_end, &expected));
+}
+
+#[tokio::test]
+async fn commitment_from_another_session_is_rejected() {
+    let expected = KeyPair::from_seed(b"channel-test").public_key();
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async {
+        prover_end.recv().await.unwrap(); // the context
+        let stale = SessionId::new([9; 16]);
+        prover_end.send(Message::commit(&expected).in_session(&stale)).await.unwrap();
+        prover_end.recv().await.unwrap()
+    };
+    let (reply, verified) = tokio::join!(prover, SchnorrProtocol::verify_interactive(&mut verifier_end, &expected));
+    assert!(matches!(verified, Err(ProtocolError::SessionMismatch { got: Some(got), .. }) if got == SessionId::new([9; 16])));
+    assert_eq!(reply.kind, "error");
+}
+
+#[tokio::test]
+async fn response_must_carry_its_commitments_session() {
+    let expected = KeyPair::from_seed(b"channel-test").public_key();
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let prover = async {
+        let context = ChallengeContext::from_hex(&prover_end.recv().await.unwrap().payload).unwrap();
+        prover_end.send(Message::commit(&expected).in_session(context.session_id())).await.unwrap();
+        prover_end.recv().await.unwrap(); // the challenge
+        // untagged, though the commitment was tagged
+        prover_end.send(Message::response(&curve25519_dalek::Scalar::ONE)).await.unwrap();
+        prover_end.recv().await.unwrap()
+    };
+    let (reply, verified) = tokio::join!(prover, SchnorrProtocol::run_verifier(&mut verifier_end, &expected));
+    assert!(matches!(verified, Err(ProtocolError::SessionMismatch { got: None, .. })));
+    assert_eq!(reply.kind, "error");
+}
+
+#[tokio::test]
+async fn prover_refuses_a_challenge_from_another_session() {
+    let keypair = KeyPair::from_seed(b"channel-test");
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let verifier = async {
+        verifier_end.send(Message::context(&ChallengeContext::new([1; 32]))).await.unwrap();
+        verifier_end.recv().await.unwrap(); // the commitment
+        let challenge = Message::challenge(&curve25519_dalek::Scalar::ONE).in_session(&SessionId::new([9; 16]));
+        verifier_end.send(challenge).await.unwrap();
+        verifier_end.recv().await.unwrap()
+    };
+    let (proved, reply) = tokio::join!(SchnorrProtocol::run_prover(&mut prover_end, &keypair), verifier);
+    assert!(matches!(proved, Err(ProtocolError::SessionMismatch { .. })));
+    assert_eq!(reply.kind, "error");
+}
+
+#[tokio::test]
+async fn prover_reports_verifier_rejection() {
+    let keypair = KeyPair::from_seed(b"channel-test");
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let verifier = async {
+        verifier_end.send(Message::context(&ChallengeContext::new([1; 32]))).await.unwrap();
+        verifier_end.recv().await.unwrap(); // the commitment
+        verifier_end.send(Message::error("go away")).await.unwrap();
+    };
+    let (proved, _) = tokio::join!(SchnorrProtocol::run_prover(&mut prover_end, &keypair), verifier);
+    assert!(matches!(proved, Err(ProtocolError::Rejected { reason }) if reason == "go away"));
+}
+
+#[tokio::test]
+async fn prover_reports_a_closed_connection() {
+    let keypair = KeyPair::from_seed(b"channel-test");
+    let (mut prover_end, verifier_end) = ChannelTransport::pair();
+    let mut verifier_end = Some(verifier_end);
+
+    let verifier = async {
+        let end = verifier_end.as_mut().unwrap();
+        end.send(Message::context(&ChallengeContext::new([1; 32]))).await.unwrap();
+        end.recv().await.unwrap(); // the commitment
+        verifier_end.take(); // hang up instead of sending a challenge
+    };
+    let (proved, _) = tokio::join!(SchnorrProtocol::run_prover(&mut prover_end, &keypair), verifier);
+    assert!(matches!(proved, Err(ProtocolError::ConnectionClosed)));
+}
+
+#[tokio::test]
+async fn prover_refuses_a_challenge_not_bound_to_the_session() {
+    let keypair = KeyPair::from_seed(b"channel-test");
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let verifier = async {
+        verifier_end.send(Message::context(&ChallengeContext::new([1; 32]))).await.unwrap();
+        verifier_end.recv().await.unwrap(); // the commitment
+        // a plain random challenge, ignoring the context
+        verifier_end.send(Message::challenge(&curve25519_dalek::Scalar::from(7u64))).await.unwrap();
+    };
+    let (proved, _) = tokio::join!(SchnorrProtocol::run_prover(&mut prover_end, &keypair), verifier);
+    assert!(matches!(proved, Err(ProtocolError::InvalidChallenge)));
+}
+
+#[tokio::test]
+async fn prover_refuses_a_zero_challenge() {
+    let keypair = KeyPair::from_seed(b"channel-test");
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let verifier = async {
+        verifier_end.send(Message::context(&ChallengeContext::new([1; 32]))).await.unwrap();
+        verifier_end.recv().await.unwrap(); // the commitment
+        verifier_end.send(Message::challenge(&curve25519_dalek::Scalar::ZERO)).await.unwrap();
+        verifier_end.recv().await.unwrap() // no response, s = k would just be the nonce
+    };
+    let (proved, reply) = tokio::join!(SchnorrProtocol::run_prover(&mut prover_end, &keypair), verifier);
+    assert!(matches!(proved, Err(ProtocolError::InvalidChallenge)));
+    assert_eq!(reply.kind, "error");
+}
+
+#[tokio::test]
+async fn short_challenge_is_a_decode_error() {
+    let keypair = KeyPair::from_seed(b"channel-test");
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let verifier = async {
+        verifier_end.send(Message::context(&ChallengeContext::new([1; 32]))).await.unwrap();
+        verifier_end.recv().await.unwrap(); // the commitment
+        let payload = "07".repeat(16);
+        verifier_end.send(Message { kind: "challenge".to_string(), payload, session: None }).await.unwrap();
+    };
+    let (proved, _) = tokio::join!(SchnorrProtocol::run_prover(&mut prover_end, &keypair), verifier);
+    assert!(matches!(proved, Err(ProtocolError::Decode(DecodeError::Scalar(ScalarDecodeError::InvalidLength(16))))));
+}
+
+#[tokio::test]
+async fn malformed_context_is_a_decode_error() {
+    let keypair = KeyPair::from_seed(b"channel-test");
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+
+    let verifier = async {
+        let short = Message { kind: "context".to_string(), payload: "abcd".to_string(), session: None };
+        verifier_end.send(short).await.unwrap();
+    };
+    let (proved, _) = tokio::join!(SchnorrProtocol::run_prover(&mut prover_end, &keypair), verifier);
+    assert!(matches!(proved, Err(ProtocolError::Decode(DecodeError::Context))));
+}
+
+#[tokio::test]
+async fn invalid_json_is_a_serde_error() {
+    use tokio::io::AsyncWriteExt;
+    use zk_schnorr_lib::{recv_message, StreamTransport};
+
+    let expected = KeyPair::from_seed(b"channel-test").public_key();
+    let (mut prover_end, verifier_end) = tokio::io::duplex(1024);
+    let mut verifier = StreamTransport::new(verifier_end);
+
+    let prover = async {
+        recv_message(&mut prover_end).await.unwrap(); // the context
+        let body = b"{not json";
+        prover_end.write_all(&(body.len() as u32).to_be_bytes()).await.unwrap();
+        prover_end.write_all(body).await.unwrap();
+        recv_message(&mut prover_end).await.unwrap()
+    };
+    let (reply, verified) = tokio::join!(prover, SchnorrProtocol::verify_interactive(&mut verifier, &expected));
+    let err = verified.unwrap_err();
+    assert!(matches!(err, ProtocolError::Serde(_)));
+    assert!(err.is_malformed());
+    assert_eq!(reply.kind, "error");
+}
+
+#[tokio::test]
+#[traced_test]
+async fn successful_proof_logs_each_step() {
+    let keypair = KeyPair::from_seed(b"channel-test");
+    let expected = keypair.public_key();
+
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+    let (proved, verified) = tokio::join!(
+        SchnorrProtocol::run_prover(&mut prover_end, &keypair),
+        SchnorrProtocol::run_verifier(&mut verifier_end, &expected),
+    );
+    proved.unwrap();
+    assert!(verified.unwrap());
+
+    assert!(logs_contain("Received commitment"));
+    assert!(logs_contain("Sent challenge"));
+    assert!(logs_contain("Schnorr verification complete"));
+    assert!(logs_contain("verified=true"));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn failed_proof_is_logged_as_unverified() {
+    let expected = KeyPair::from_seed(b"channel-test").public_key();
+
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+    let prover = async move {
+        SchnorrProtocol::open_prover_session(&mut prover_end).await.unwrap();
+        bogus_round(&mut prover_end).await
+    };
+    let (verdict, verified) = tokio::join!(prover, SchnorrProtocol::run_verifier(&mut verifier_end, &expected));
+    assert_eq!(verdict, "rejected");
+    assert!(!verified.unwrap());
+    assert!(logs_contain("verified=false"));
+    assert!(!logs_contain("verified=true"));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn both_ends_log_the_session_id() {
+    let keypair = KeyPair::from_seed(b"channel-test");
+    let expected = keypair.public_key();
+
+    let (mut prover_end, mut verifier_end) = ChannelTransport::pair();
+    let prover = async move {
+        let context = SchnorrProtocol::open_prover_session(&mut prover_end).await.unwrap();
+        SchnorrProtocol::prove_round(&mut prover_end, &context, &keypair).await.unwrap();
+        context
+    };
+    let (context, _) = tokio::join!(prover, SchnorrProtocol::verify_interactive(&mut verifier_end, &expected));
+
+    let field = format!("session_id={}", context.session_id());
+    logs_assert(|lines: &[&str]| {
+        let sent = lines.iter().any(|line| line.contains("Sent challenge context") && line.contains(&field));
+        let received = lines.iter().any(|line| line.contains("Received challenge context") && line.contains(&field));
+        if sent && received { Ok(()) } else { Err(format!("no matching context events for {field}")) }
+    });
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "std")]
+
+use std::collections::HashMap;
+
+use zk_schnorr_lib::{KeyPair, PublicKey};
+
+#[test]
+fn display_parse_round_trip() {
+    let key = KeyPair::from_seed(b"public-key-test").public_key();
+    let parsed = key.to_string().parse::<PublicKey>().unwrap();
+    assert_eq!(parsed, key);
+    assert_eq!(key.to_string(), zk_schnorr_lib::point_to_hex(&key));
+}
+
+#[test]
+fn invalid_hex_is_rejected() {
+    assert!("not hex".parse::<PublicKey>().is_err());
+    assert!("abcd".parse::<PublicKey>().is_err()); // too short
+    assert!("ff".repeat(32).parse::<PublicKey>().is_err()); // not a valid encoding
+}
+
+#[test]
+fn serde_uses_hex() {
+    let key = KeyPair::from_seed(b"serde").public_key();
+    let json = serde_json::to_string(&key).unwrap();
+    assert_eq!(json, format!("\"{key}\""));
+    assert_eq!(serde_json::from_str::<PublicKey>(&json).unwrap(), key);
+}
+
+#[test]
+fn usable_as_map_key() {
+    let alice = KeyPair::from_seed(b"alice").public_key();
+    let bob = KeyPair::from_seed(b"bob").public_key();
+    let mut names = HashMap::new();
+    names.insert(alice, "alice");
+    names.insert(bob, "bob");
+    assert_eq!(names[&KeyPair::from_seed(b"alice").public_key()], "alice");
+    assert_eq!(names.len(), 2);
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "std")]
+
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::range_proof::{prove, verify};
+use zk_schnorr_lib::{PedersenParams, RangeProof, ZkError};
+
+fn prove_and_verify(value: u64, n_bits: u8) -> bool {
+    let params = PedersenParams::default();
+    let blinding = Scalar::random(&mut OsRng);
+    let commitment = params.commit(&Scalar::from(value), &blinding);
+    let proof = prove(value, &blinding, &params, n_bits, &mut OsRng).unwrap();
+    verify(&commitment.0, &proof, &params, n_bits)
+}
+
+#[test]
+fn zero_is_in_range() {
+    assert!(prove_and_verify(0, 8));
+    assert!(prove_and_verify(0, 1));
+}
+
+#[test]
+fn largest_value_is_in_range() {
+    assert!(prove_and_verify(255, 8));
+    assert!(prove_and_verify((1 << 32) - 1, 32));
+    assert!(prove_and_verify(u64::MAX, 64));
+}
+
+#[test]
+fn values_past_the_range_are_refused() {
+    let params = PedersenParams::default();
+    let blinding = Scalar::random(&mut OsRng);
+    assert!(matches!(
+        prove(256, &blinding, &params, 8, &mut OsRng),
+        Err(ZkError::ValueOutOfRange { value: 256, n_bits: 8 })
+    ));
+    assert!(matches!(prove(0, &blinding, &params, 65, &mut OsRng), Err(ZkError::InvalidRangeBits(65))));
+    assert!(matches!(prove(0, &blinding, &params, 0, &mut OsRng), Err(ZkError::InvalidRangeBits(0))));
+}
+
+#[test]
+fn tampered_proof_fails() {
+    let params = PedersenParams::default();
+    let blinding = Scalar::random(&mut OsRng);
+    let commitment = params.commit(&Scalar::from(42u64), &blinding).0;
+    let proof = prove(42, &blinding, &params, 8, &mut OsRng).unwrap();
+    assert!(verify(&commitment, &proof, &params, 8));
+
+    let mut tampered = proof.clone();
+    tampered.bits[3].s1 += Scalar::ONE;
+    assert!(!verify(&commitment, &tampered, &params, 8));
+
+    let mut swapped = proof.clone();
+    swapped.bits.swap(0, 1);
+    assert!(!verify(&commitment, &swapped, &params, 8));
+
+    assert!(!verify(&(commitment + params.G), &proof, &params, 8)); // a commitment to 43
+    assert!(!verify(&commitment, &proof, &params, 7));
+}
+
+#[test]
+fn a_bit_commitment_to_two_fails() {
+    // commit to 2 in position 0 by hand: the sum still matches a commitment to 2, but no bit proof can cover it
+    let params = PedersenParams::default();
+    let blinding = Scalar::random(&mut OsRng);
+    let honest = prove(1, &blinding, &params, 2, &mut OsRng).unwrap();
+    let mut forged = honest.clone();
+    forged.bits[0].commitment += params.G;
+    let commitment = params.commit(&Scalar::from(2u64), &blinding).0;
+    assert!(!verify(&commitment, &forged, &params, 2));
+}
+
+#[test]
+fn serde_round_trip() {
+    let params = PedersenParams::default();
+    let blinding = Scalar::random(&mut OsRng);
+    let commitment = params.commit(&Scalar::from(9u64), &blinding).0;
+    let proof = prove(9, &blinding, &params, 4, &mut OsRng).unwrap();
+    let decoded: RangeProof = serde_json::from_str(&serde_json::to_string(&proof).unwrap()).unwrap();
+    assert_eq!(decoded, proof);
+    assert!(verify(&commitment, &decoded, &params, 4));
+}
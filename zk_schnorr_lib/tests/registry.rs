@@ -0,0 +1,76 @@
+#![cfg(feature = "std")]
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use zk_schnorr_lib::{Ed25519SigningKey, KeyPair, KeyRegistry, PublicKey, RegisteredKey, Scheme};
+
+#[test]
+fn single_key_serves_named_and_unnamed_provers() {
+    let key = KeyPair::from_seed(b"alice").public_key();
+    let registry = KeyRegistry::single("alice", key);
+    let key = RegisteredKey::from(key);
+    assert_eq!(registry.resolve(Some("alice")), Some(("alice", &key)));
+    assert_eq!(registry.resolve(None), Some(("alice", &key)));
+    assert_eq!(registry.resolve(Some("bob")), None);
+}
+
+#[test]
+fn several_keys_need_a_name() {
+    let alice = KeyPair::from_seed(b"alice").public_key();
+    let bob = KeyPair::from_seed(b"bob").public_key();
+    let mut registry = KeyRegistry::new();
+    assert!(registry.is_empty());
+    assert_eq!(registry.resolve(None), None);
+
+    registry.insert("alice", alice);
+    assert_eq!(registry.insert("bob", alice), None);
+    assert_eq!(registry.insert("bob", bob), Some(alice.into()));
+    assert_eq!(registry.len(), 2);
+    assert_eq!(registry.get("bob"), Some(&bob.into()));
+    assert_eq!(registry.resolve(Some("alice")), Some(("alice", &alice.into())));
+    assert_eq!(registry.resolve(None), None);
+}
+
+#[test]
+fn every_key_carries_its_scheme() {
+    let token = Ed25519SigningKey::derive(b"token").public_key();
+    let mut registry = KeyRegistry::single("alice", KeyPair::from_seed(b"alice").public_key());
+    registry.insert("token", token);
+    assert_eq!(registry.get("alice").map(RegisteredKey::scheme), Some(Scheme::Ristretto));
+    assert_eq!(registry.get("token"), Some(&RegisteredKey::Ed25519(token)));
+    assert_eq!("ed25519".parse::<Scheme>().unwrap(), Scheme::Ed25519);
+    assert_eq!(Scheme::default().to_string(), "ristretto");
+    assert!("rsa".parse::<Scheme>().is_err());
+}
+
+#[test]
+fn registry_round_trips_through_json_with_a_scheme_per_key() {
+    let alice = KeyPair::from_seed(b"alice").public_key();
+    let token = Ed25519SigningKey::derive(b"token").public_key();
+    let mut registry = KeyRegistry::new();
+    registry.insert("alice", alice);
+    registry.insert("token", token);
+
+    let json = serde_json::to_value(&registry).unwrap();
+    assert_eq!(json["token"], serde_json::json!({ "scheme": "ed25519", "key": token.to_string() }));
+    assert_eq!(json["alice"]["scheme"], "ristretto");
+    let parsed: KeyRegistry = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.get("alice"), Some(&alice.into()));
+    assert_eq!(parsed.get("token"), Some(&token.into()));
+}
+
+#[test]
+fn keys_without_a_scheme_are_ristretto() {
+    let alice = KeyPair::from_seed(b"alice").public_key();
+    let parsed: KeyRegistry = serde_json::from_str(&format!(r#"{{"alice": {{"key": "{alice}"}}}}"#)).unwrap();
+    assert_eq!(parsed.get("alice"), Some(&RegisteredKey::Ristretto(alice)));
+    assert!(serde_json::from_str::<KeyRegistry>(r#"{"alice": {"scheme": "ed25519", "key": "00"}}"#).is_err());
+}
+
+#[test]
+fn identity_and_weak_keys_are_refused() {
+    let weak = PublicKey(RistrettoPoint::mul_base(&Scalar::from(5u64)));
+    for key in ["00".repeat(32), weak.to_string()] {
+        assert!(serde_json::from_str::<KeyRegistry>(&format!(r#"{{"alice": {{"key": "{key}"}}}}"#)).is_err(), "{key}");
+    }
+}
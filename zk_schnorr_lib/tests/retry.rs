@@ -0,0 +1,90 @@
+#![cfg(feature = "tls")]
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rustls::ServerName;
+use tokio::net::TcpListener;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use zk_schnorr_lib::{
+    connect_with_retry, create_client_config_insecure, create_server_config, generate_self_signed_cert, ConnectError, ConnectStage,
+    RetryPolicy, TlsCertificate,
+};
+
+// a local address nothing is listening on, for now
+async fn free_addr() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+fn connector_trusting(cert: &TlsCertificate) -> TlsConnector {
+    TlsConnector::from(Arc::new(create_client_config_insecure(cert).unwrap()))
+}
+
+fn localhost() -> ServerName {
+    ServerName::try_from("localhost").unwrap()
+}
+
+fn policy(retries: u32, backoff_ms: u64) -> RetryPolicy {
+    RetryPolicy { retries, backoff: Duration::from_millis(backoff_ms), ..RetryPolicy::default() }
+}
+
+// start accepting TLS on `addr` after `delay`, for one handshake
+fn serve_later(addr: String, cert: &TlsCertificate, delay: Duration) -> tokio::task::JoinHandle<std::io::Result<()>> {
+    let acceptor = TlsAcceptor::from(Arc::new(create_server_config(cert, None).unwrap()));
+    tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+        let listener = TcpListener::bind(&addr).await.unwrap();
+        let (tcp, _) = listener.accept().await.unwrap();
+        acceptor.accept(tcp).await.map(drop)
+    })
+}
+
+#[tokio::test]
+async fn waits_for_a_verifier_that_starts_late() {
+    let (addr, cert) = (free_addr().await, generate_self_signed_cert().unwrap());
+    let server = serve_later(addr.clone(), &cert, Duration::from_millis(150));
+
+    let stream = connect_with_retry(&addr, &connector_trusting(&cert), localhost(), &policy(8, 20)).await.unwrap();
+    assert_eq!(stream.get_ref().0.peer_addr().unwrap().to_string(), addr);
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn gives_up_with_a_summary_of_the_attempts() {
+    let (addr, cert) = (free_addr().await, generate_self_signed_cert().unwrap());
+    let started = Instant::now();
+    let err = connect_with_retry(&addr, &connector_trusting(&cert), localhost(), &policy(2, 40)).await.unwrap_err();
+
+    assert!(started.elapsed() >= Duration::from_millis(20 + 40), "at least half of 40ms, then of 80ms");
+    let ConnectError::Exhausted { attempts, refused, stage, .. } = &err else { panic!("{err}") };
+    assert_eq!((*attempts, *refused, *stage), (3, 3, ConnectStage::Tcp));
+    let message = err.to_string();
+    assert!(message.contains(&addr) && message.contains("after 3 attempts (3 refused)"), "{message}");
+}
+
+#[tokio::test]
+async fn bad_certificate_is_not_retried() {
+    let addr = free_addr().await;
+    let (served, trusted) = (generate_self_signed_cert().unwrap(), generate_self_signed_cert().unwrap());
+    let server = serve_later(addr.clone(), &served, Duration::ZERO);
+    tokio::time::sleep(Duration::from_millis(50)).await; // listening by now
+
+    let started = Instant::now();
+    let err = connect_with_retry(&addr, &connector_trusting(&trusted), localhost(), &policy(5, 1_000)).await.unwrap_err();
+    assert!(matches!(err, ConnectError::Handshake { attempt: 1, .. }), "{err}");
+    assert!(started.elapsed() < Duration::from_millis(500), "no backoff before giving up");
+    assert!(server.await.unwrap().is_err(), "the client aborted the handshake");
+}
+
+#[test]
+fn backoff_doubles_with_jitter_up_to_the_cap() {
+    let policy = RetryPolicy { retries: 10, backoff: Duration::from_millis(100), max_backoff: Duration::from_millis(500) };
+    for (retry, full) in [(1, 100), (2, 200), (3, 400), (4, 500), (10, 500)] {
+        let full = Duration::from_millis(full);
+        for _ in 0..20 {
+            let delay = policy.delay(retry);
+            assert!(delay >= full / 2 && delay <= full, "retry {retry}: {delay:?}");
+        }
+    }
+}
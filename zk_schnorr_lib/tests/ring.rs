@@ -0,0 +1,79 @@
+#![cfg(feature = "std")]
+
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::{ring_sign, ring_verify, KeyPair, Proof, PublicKey, RingError, RingSignature, ZkError};
+
+fn ring(n: usize) -> (Vec<KeyPair>, Vec<PublicKey>) {
+    let keypairs: Vec<KeyPair> = (0..n).map(|_| KeyPair::generate(&mut OsRng)).collect();
+    let publics = keypairs.iter().map(KeyPair::public_key).collect();
+    (keypairs, publics)
+}
+
+fn sign_as(keypairs: &[KeyPair], publics: &[PublicKey], index: usize, message: &[u8]) -> RingSignature {
+    ring_sign(message, publics, index, keypairs[index].secret().expose_secret(), &mut OsRng).unwrap()
+}
+
+#[test]
+fn ring_of_one_is_an_ordinary_schnorr_signature() {
+    let (keypairs, publics) = ring(1);
+    let sig = sign_as(&keypairs, &publics, 0, b"hello");
+    assert!(ring_verify(b"hello", &publics, &sig));
+
+    let proof = Proof::new(sig.R_list[0], sig.c_list[0], sig.s_list[0]);
+    assert!(proof.verify(&publics[0]));
+}
+
+#[test]
+fn every_signer_in_a_ring_of_five_verifies() {
+    let (keypairs, publics) = ring(5);
+    for index in 0..5 {
+        let sig = sign_as(&keypairs, &publics, index, b"anonymous");
+        assert!(ring_verify(b"anonymous", &publics, &sig), "signer {index}");
+    }
+}
+
+#[test]
+fn tampering_with_any_s_fails() {
+    let (keypairs, publics) = ring(5);
+    let sig = sign_as(&keypairs, &publics, 2, b"m");
+    for i in 0..5 {
+        let mut tampered = sig.clone();
+        tampered.s_list[i] += Scalar::ONE;
+        assert!(!ring_verify(b"m", &publics, &tampered), "s_{i}");
+    }
+}
+
+#[test]
+fn other_message_or_ring_fails() {
+    let (keypairs, publics) = ring(3);
+    let sig = sign_as(&keypairs, &publics, 1, b"m");
+    assert!(!ring_verify(b"other", &publics, &sig));
+
+    let mut reordered = publics.clone();
+    reordered.swap(0, 2);
+    assert!(!ring_verify(b"m", &reordered, &sig));
+    assert!(!ring_verify(b"m", &publics[..2], &sig));
+}
+
+#[test]
+fn bad_signer_is_an_error() {
+    let (keypairs, publics) = ring(3);
+    let secret = keypairs[0].secret().expose_secret();
+    assert!(matches!(
+        ring_sign(b"m", &publics, 3, secret, &mut OsRng),
+        Err(ZkError::Ring(RingError::SignerOutOfRange { index: 3, size: 3 }))
+    ));
+    assert!(matches!(ring_sign(b"m", &publics, 1, secret, &mut OsRng), Err(ZkError::Ring(RingError::WrongSignerKey(1)))));
+    assert!(matches!(ring_sign(b"m", &[], 0, secret, &mut OsRng), Err(ZkError::Ring(RingError::EmptyRing))));
+}
+
+#[test]
+fn serde_round_trip() {
+    let (keypairs, publics) = ring(4);
+    let sig = sign_as(&keypairs, &publics, 3, b"m");
+    let json = serde_json::to_string(&sig).unwrap();
+    let decoded: RingSignature = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, sig);
+    assert!(ring_verify(b"m", &publics, &decoded));
+}
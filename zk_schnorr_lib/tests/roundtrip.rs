@@ -0,0 +1,78 @@
+#![cfg(feature = "std")]
+#![allow(non_snake_case)] // follow the usual maths notation
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use proptest::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use zk_schnorr_lib::{point_from_hex, point_to_hex, scalar_from_hex, scalar_to_hex, Message, Proof, PublicKey};
+
+// a scalar drawn uniformly from the canonical values
+#[derive(Debug, Clone, Copy)]
+struct CanonicalScalar(Scalar);
+
+impl Arbitrary for CanonicalScalar {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<[u8; 64]>().prop_map(|wide| CanonicalScalar(Scalar::from_bytes_mod_order_wide(&wide))).boxed()
+    }
+}
+
+fn point() -> impl Strategy<Value = RistrettoPoint> {
+    any::<u64>().prop_map(|seed| RistrettoPoint::random(&mut StdRng::seed_from_u64(seed)))
+}
+
+// the message as the peer sees it after JSON on the wire
+fn over_the_wire(msg: &Message) -> Message {
+    serde_json::from_str(&serde_json::to_string(msg).unwrap()).unwrap()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn canonical_scalar_bytes_round_trip(bytes in any::<[u8; 32]>()) {
+        if let Some(s) = Option::<Scalar>::from(Scalar::from_canonical_bytes(bytes)) {
+            prop_assert_eq!(scalar_from_hex(&scalar_to_hex(&s)).unwrap(), s);
+        }
+    }
+
+    #[test]
+    fn arbitrary_scalars_round_trip(s in any::<CanonicalScalar>()) {
+        prop_assert_eq!(scalar_from_hex(&scalar_to_hex(&s.0)).unwrap(), s.0);
+    }
+
+    #[test]
+    fn points_round_trip(p in point()) {
+        prop_assert_eq!(point_from_hex(&point_to_hex(&p)).unwrap(), p);
+    }
+
+    #[test]
+    fn messages_round_trip(kind in "[a-z]{1,12}", payload in "[0-9a-f]{0,128}") {
+        let msg = Message { kind, payload, session: None };
+        let back = over_the_wire(&msg);
+        prop_assert_eq!(back.kind, msg.kind);
+        prop_assert_eq!(back.payload, msg.payload);
+    }
+
+    #[test]
+    fn three_messages_give_a_verifying_proof(x in any::<CanonicalScalar>(), k in any::<CanonicalScalar>(), c in any::<CanonicalScalar>(), other in any::<CanonicalScalar>()) {
+        let (x, k, c) = (x.0, k.0, c.0);
+        let commit = over_the_wire(&Message::commit(&(RISTRETTO_BASEPOINT_POINT * k)));
+        let challenge = over_the_wire(&Message::challenge(&c));
+        let response = over_the_wire(&Message::response(&(k + c * x)));
+
+        let proof = Proof::new(
+            point_from_hex(&commit.payload).unwrap(),
+            scalar_from_hex(&challenge.payload).unwrap(),
+            scalar_from_hex(&response.payload).unwrap(),
+        );
+        prop_assert!(proof.verify(&PublicKey(RISTRETTO_BASEPOINT_POINT * x)));
+        prop_assume!(other.0 != x);
+        prop_assert!(!proof.verify(&PublicKey(RISTRETTO_BASEPOINT_POINT * other.0)));
+    }
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "tls")]
+
+use zk_schnorr_lib::{
+    run_prover, run_prover_ed25519, run_prover_with, run_verifier, run_verifier_registry, run_verifier_with_params, ChannelTransport,
+    Ed25519SigningKey, KeyPair, KeyRegistry, NegotiationError, NullLog, Params, ProofOutcome, ProtocolError, ProverOptions,
+    StreamTransport, PROTOCOL_VERSION,
+};
+
+fn duplex() -> (StreamTransport<tokio::io::DuplexStream>, StreamTransport<tokio::io::DuplexStream>) {
+    let (prover_end, verifier_end) = tokio::io::duplex(4096);
+    (StreamTransport::new(prover_end), StreamTransport::new(verifier_end))
+}
+
+#[tokio::test]
+async fn single_round_over_a_duplex_pipe() {
+    let keypair = KeyPair::from_seed(b"runner");
+    let expected = keypair.public_key();
+    let (mut prover, mut verifier) = duplex();
+
+    let prove = async move { run_prover(&mut prover, &keypair).await }; // dropping the end closes the session
+    let (proved, verified) = tokio::join!(prove, run_verifier(&mut verifier, &expected));
+
+    let outcome = ProofOutcome { version: PROTOCOL_VERSION, rounds: vec![true] };
+    assert_eq!(proved.unwrap(), outcome);
+    assert_eq!(verified.unwrap(), outcome);
+}
+
+#[tokio::test]
+async fn both_ends_agree_on_every_round() {
+    let keypair = KeyPair::from_seed(b"runner");
+    let expected = keypair.public_key();
+    let (mut prover, mut verifier) = duplex();
+
+    let options = ProverOptions { rounds: 3, deterministic_nonce: true, ..ProverOptions::default() };
+    let prove = async move { run_prover_with(&mut prover, &keypair, &options).await };
+    let (proved, verified) = tokio::join!(prove, run_verifier(&mut verifier, &expected));
+
+    let (proved, verified) = (proved.unwrap(), verified.unwrap());
+    assert_eq!(proved, verified);
+    assert!(verified.accepted());
+    assert_eq!((verified.verified(), verified.failed()), (3, 0));
+}
+
+#[tokio::test]
+async fn prover_with_the_wrong_key_is_not_accepted() {
+    let expected = KeyPair::from_seed(b"runner").public_key();
+    let (mut prover, mut verifier) = duplex();
+
+    let prove = async move { run_prover(&mut prover, &KeyPair::from_seed(b"someone else")).await };
+    let (proved, verified) = tokio::join!(prove, run_verifier(&mut verifier, &expected));
+    assert!(matches!(proved, Err(ProtocolError::InvalidChallenge)));
+    assert!(matches!(verified, Err(ProtocolError::Rejected { .. })));
+}
+
+#[test]
+fn outcome_without_rounds_is_not_accepted() {
+    let outcome = ProofOutcome { version: PROTOCOL_VERSION, rounds: vec![] };
+    assert!(!outcome.accepted());
+    let mixed = ProofOutcome { version: PROTOCOL_VERSION, rounds: vec![true, false, true] };
+    assert!(!mixed.accepted());
+    assert_eq!((mixed.verified(), mixed.failed()), (2, 1));
+}
+
+#[tokio::test]
+async fn session_over_a_custom_generator() {
+    let keypair = KeyPair::from_seed(b"runner");
+    let params = Params::from_domain_tag(b"zk-schnorr-tls/test/H");
+    let expected = params.public_key_of(&keypair);
+    let (mut prover, mut verifier) = duplex();
+
+    let options = ProverOptions { rounds: 2, params, ..ProverOptions::default() };
+    let prove = async move { run_prover_with(&mut prover, &keypair, &options).await };
+    let (proved, verified) = tokio::join!(prove, run_verifier_with_params(&mut verifier, &expected, &params, &NullLog));
+    assert!(proved.unwrap().accepted());
+    assert_eq!(verified.unwrap().rounds, vec![true, true]);
+}
+
+#[tokio::test]
+async fn verifier_refuses_a_prover_on_another_generator() {
+    let keypair = KeyPair::from_seed(b"runner");
+    let h = Params::from_domain_tag(b"zk-schnorr-tls/test/H");
+    let expected = keypair.public_key();
+    let (mut prover, mut verifier) = duplex();
+
+    let options = ProverOptions { params: h, ..ProverOptions::default() };
+    let prove = async move { run_prover_with(&mut prover, &keypair, &options).await };
+    let (proved, verified) = tokio::join!(prove, run_verifier(&mut verifier, &expected));
+    let mismatch = NegotiationError::GeneratorMismatch { verifier: Params::default().generator().compress(), prover: h.generator().compress() };
+    assert!(matches!(proved, Err(ProtocolError::Negotiation(e)) if e == mismatch));
+    assert!(matches!(verified, Err(ProtocolError::Negotiation(e)) if e == mismatch));
+}
+
+#[tokio::test]
+async fn prover_refuses_a_verifier_on_another_generator() {
+    let keypair = KeyPair::from_seed(b"runner");
+    let h = Params::from_domain_tag(b"zk-schnorr-tls/test/H");
+    let expected = h.public_key_of(&keypair);
+    let (mut prover, mut verifier) = duplex();
+
+    let prove = async move { run_prover(&mut prover, &keypair).await };
+    let (proved, verified) = tokio::join!(prove, run_verifier_with_params(&mut verifier, &expected, &h, &NullLog));
+    let mismatch = NegotiationError::GeneratorMismatch { verifier: h.generator().compress(), prover: Params::default().generator().compress() };
+    assert!(matches!(proved, Err(ProtocolError::Negotiation(e)) if e == mismatch));
+    assert!(verified.is_err());
+}
+
+#[tokio::test]
+async fn ed25519_prover_signs_the_nonce_over_channels() {
+    let key = Ed25519SigningKey::derive(b"hardware token");
+    let registry = KeyRegistry::single("token", key.public_key());
+    let (mut prover, mut verifier) = ChannelTransport::pair();
+
+    let prove = async move { run_prover_ed25519(&mut prover, &key, Some("token")).await };
+    let (proved, verified) = tokio::join!(prove, run_verifier_registry(&mut verifier, &registry, &NullLog));
+
+    let outcome = ProofOutcome { version: PROTOCOL_VERSION, rounds: vec![true] };
+    assert_eq!(proved.unwrap(), outcome);
+    assert_eq!(verified.unwrap(), ("token".to_string(), outcome));
+}
+
+#[tokio::test]
+async fn ed25519_signature_from_another_key_is_rejected() {
+    let registry = KeyRegistry::single("token", Ed25519SigningKey::derive(b"hardware token").public_key());
+    let impostor = Ed25519SigningKey::derive(b"someone else");
+    let (mut prover, mut verifier) = ChannelTransport::pair();
+
+    let prove = async move { run_prover_ed25519(&mut prover, &impostor, None).await };
+    let (proved, verified) = tokio::join!(prove, run_verifier_registry(&mut verifier, &registry, &NullLog));
+
+    let outcome = ProofOutcome { version: PROTOCOL_VERSION, rounds: vec![false] };
+    assert_eq!(proved.unwrap(), outcome);
+    assert_eq!(verified.unwrap().1, outcome);
+}
+
+#[tokio::test]
+async fn schnorr_prover_cannot_pass_for_an_ed25519_identity() {
+    let registry = KeyRegistry::single("token", Ed25519SigningKey::derive(b"hardware token").public_key());
+    let keypair = KeyPair::from_seed(b"runner");
+    let (mut prover, mut verifier) = duplex();
+
+    let prove = async move { run_prover(&mut prover, &keypair).await };
+    let (proved, verified) = tokio::join!(prove, run_verifier_registry(&mut verifier, &registry, &NullLog));
+    assert!(proved.is_err());
+    assert!(verified.is_err());
+}
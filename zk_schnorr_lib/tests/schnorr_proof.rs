@@ -0,0 +1,112 @@
+#![cfg(feature = "std")]
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::{commit, respond, KeyPair, Proof, ProofDecodeError, PublicKey, SchnorrProof, VerifyOutcome, hash_to_point};
+
+#[test]
+fn basepoint_proof_round_trip() {
+    let x = Scalar::random(&mut OsRng);
+    let public_key = PublicKey(RISTRETTO_BASEPOINT_POINT * x);
+    let proof = SchnorrProof::prove(&x, &mut OsRng);
+    assert!(proof.verify(&public_key));
+    assert!(!proof.verify(&PublicKey(*public_key + RISTRETTO_BASEPOINT_POINT)));
+}
+
+#[test]
+fn custom_generator_proof_round_trip() {
+    let h = hash_to_point(b"my-app/generator");
+    let x = Scalar::random(&mut OsRng);
+    let public_key = PublicKey(h * x);
+
+    let proof = SchnorrProof::prove_with_generator(&x, &h, &mut OsRng);
+    assert!(proof.verify_with_generator(&public_key, &h));
+    // the same proof is meaningless relative to the basepoint
+    assert!(!proof.verify(&public_key));
+}
+
+#[test]
+fn hash_to_point_is_deterministic_and_domain_separated() {
+    assert_eq!(hash_to_point(b"a"), hash_to_point(b"a"));
+    assert_ne!(hash_to_point(b"a"), hash_to_point(b"b"));
+    assert_ne!(hash_to_point(b"a"), RISTRETTO_BASEPOINT_POINT);
+}
+
+#[test]
+fn tampered_response_fails() {
+    let x = Scalar::random(&mut OsRng);
+    let mut proof = SchnorrProof::prove(&x, &mut OsRng);
+    proof.response += Scalar::ONE;
+    assert!(!proof.verify(&PublicKey(RISTRETTO_BASEPOINT_POINT * x)));
+}
+
+#[test]
+fn interactive_transcript_with_custom_generator() {
+    let h = hash_to_point(b"interactive");
+    let (x, k, c) = (Scalar::from(3u64), Scalar::from(5u64), Scalar::from(7u64));
+    let proof = Proof::new(h * k, c, k + c * x);
+    assert!(proof.verify_with_generator(&PublicKey(h * x), &h));
+    assert!(!proof.verify(&PublicKey(h * x)));
+}
+
+#[test]
+fn blob_round_trips() {
+    let x = Scalar::random(&mut OsRng);
+    let proof = SchnorrProof::prove(&x, &mut OsRng);
+    assert_eq!(SchnorrProof::from_bytes(&proof.to_bytes()).unwrap(), proof);
+
+    let encoded = proof.to_base64();
+    assert_eq!(encoded.len(), 88);
+    let decoded = SchnorrProof::from_base64(&encoded).unwrap();
+    assert!(decoded.verify(&PublicKey(RISTRETTO_BASEPOINT_POINT * x)));
+}
+
+#[test]
+fn invalid_blobs_are_rejected() {
+    let mut bytes = SchnorrProof::prove(&Scalar::ONE, &mut OsRng).to_bytes();
+    assert!(matches!(SchnorrProof::from_bytes(&bytes[..63]), Err(ProofDecodeError::InvalidLength(63))));
+
+    let mut bad_point = bytes;
+    bad_point[..32].fill(0xff);
+    assert!(matches!(SchnorrProof::from_bytes(&bad_point), Err(ProofDecodeError::Point(_))));
+
+    bytes[32..].fill(0xff); // way above the group order
+    assert!(matches!(SchnorrProof::from_bytes(&bytes), Err(ProofDecodeError::Scalar(_))));
+    assert!(matches!(SchnorrProof::from_base64("not base64!"), Err(ProofDecodeError::Base64(_))));
+}
+
+#[test]
+fn commit_and_respond_make_a_valid_proof() {
+    let keypair = KeyPair::from_seed(b"commit-respond");
+    let k = Scalar::from(7u64);
+    let c = Scalar::from(11u64);
+    let proof = Proof::new(commit(&k), c, respond(&k, &c, keypair.secret().expose_secret()));
+    assert!(proof.verify(&keypair.public_key()));
+    assert!(!proof.verify(&KeyPair::from_seed(b"someone else").public_key()));
+}
+
+#[test]
+fn detailed_verification_reconstructs_the_commitment() {
+    let keypair = KeyPair::from_seed(b"audit");
+    let proof = SchnorrProof::prove(keypair.secret().expose_secret(), &mut OsRng);
+    let outcome = proof.verify_detailed(&keypair.public_key());
+    assert!(outcome.valid);
+    assert_eq!(outcome.recomputed_commitment, proof.commitment);
+    assert_eq!(RISTRETTO_BASEPOINT_POINT * proof.response, proof.commitment + *keypair.public_key() * outcome.challenge);
+
+    let json = serde_json::to_string(&outcome).unwrap();
+    assert_eq!(serde_json::from_str::<VerifyOutcome>(&json).unwrap(), outcome);
+}
+
+#[test]
+fn failed_detailed_verification_shows_the_mismatch() {
+    let keypair = KeyPair::from_seed(b"audit");
+    let mut proof = SchnorrProof::prove(keypair.secret().expose_secret(), &mut OsRng);
+    proof.response += Scalar::ONE;
+    let outcome = proof.verify_detailed(&keypair.public_key());
+    assert!(!outcome.valid);
+    assert!(!proof.verify(&keypair.public_key()));
+    // one more G in s shows up as one more G in R'
+    assert_eq!(outcome.recomputed_commitment, proof.commitment + RISTRETTO_BASEPOINT_POINT);
+}
@@ -0,0 +1,78 @@
+use std::marker::PhantomData;
+
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+use zk_schnorr_lib::{KeyPair, SecretScalar};
+
+// Inherent methods win over trait methods when their bounds hold, so
+// `Probe::<T>::is_serialize` is `true` exactly when `T: Serialize`.
+struct Probe<T>(PhantomData<T>);
+
+trait Fallback {
+    fn is_serialize(&self) -> bool {
+        false
+    }
+    fn is_debug(&self) -> bool {
+        false
+    }
+}
+impl<T> Fallback for Probe<T> {}
+
+impl<T: serde::Serialize> Probe<T> {
+    #[allow(dead_code)]
+    fn is_serialize(&self) -> bool {
+        true
+    }
+}
+
+impl<T: std::fmt::Debug> Probe<T> {
+    #[allow(dead_code)]
+    fn is_debug(&self) -> bool {
+        true
+    }
+}
+
+fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+
+#[test]
+fn probe_detects_serialize_and_debug() {
+    // sanity check for the probe itself
+    assert!(Probe::<String>(PhantomData).is_serialize());
+    assert!(Probe::<Scalar>(PhantomData).is_debug());
+}
+
+#[test]
+fn secret_scalar_cannot_be_serialized_or_printed() {
+    assert!(!Probe::<SecretScalar>(PhantomData).is_serialize());
+    assert!(!Probe::<SecretScalar>(PhantomData).is_debug());
+    assert!(!Probe::<KeyPair>(PhantomData).is_serialize());
+}
+
+#[test]
+fn secret_scalar_zeroizes() {
+    assert_zeroize_on_drop::<SecretScalar>();
+    let mut secret = SecretScalar::random(&mut OsRng);
+    assert_ne!(*secret.expose_secret(), Scalar::ZERO);
+    secret.zeroize();
+    assert_eq!(*secret.expose_secret(), Scalar::ZERO);
+}
+
+#[test]
+fn accessors_match_plain_arithmetic() {
+    let x = Scalar::random(&mut OsRng);
+    let secret = SecretScalar::new(x);
+    let basepoint = curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+    assert_eq!(secret.mul_base(), basepoint * x);
+    let keypair = KeyPair::from_secret(x);
+    assert_eq!(*keypair.public_key(), secret.mul_base());
+    assert_eq!(keypair.secret().expose_secret(), &x);
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn certificate_private_key_is_zeroizing() {
+    let cert = zk_schnorr_lib::generate_self_signed_cert().unwrap();
+    let _: &zeroize::Zeroizing<Vec<u8>> = &cert.private_key_der;
+    assert!(!cert.private_key_der.is_empty());
+}
@@ -0,0 +1,29 @@
+use curve25519_dalek::scalar::Scalar;
+use sha2::Sha512;
+use zk_schnorr_lib::{scalar_from_seed, KeyPair, KEYPAIR_SEED_DOMAIN};
+
+#[test]
+fn same_domain_and_seed_reproduce_the_scalar() {
+    assert_eq!(scalar_from_seed(b"app/signing", b"master"), scalar_from_seed(b"app/signing", b"master"));
+}
+
+#[test]
+fn domains_keep_scalars_from_one_seed_apart() {
+    let signing = scalar_from_seed(b"app/signing", b"master");
+    assert_ne!(signing, scalar_from_seed(b"app/auth", b"master"));
+    assert_ne!(signing, scalar_from_seed(b"", b"master"));
+    assert_ne!(scalar_from_seed(b"", b"master"), Scalar::hash_from_bytes::<Sha512>(b"master"));
+}
+
+#[test]
+fn moving_bytes_between_domain_and_seed_changes_the_scalar() {
+    assert_ne!(scalar_from_seed(b"ab", b"c"), scalar_from_seed(b"a", b"bc"));
+    assert_ne!(scalar_from_seed(b"abc", b""), scalar_from_seed(b"", b"abc"));
+}
+
+#[test]
+fn keypair_from_seed_uses_the_keypair_domain() {
+    let keypair = KeyPair::from_seed(b"alice");
+    assert_eq!(keypair.secret().expose_secret(), &scalar_from_seed(KEYPAIR_SEED_DOMAIN, b"alice"));
+    assert_ne!(keypair.secret().expose_secret(), &Scalar::hash_from_bytes::<Sha512>(b"alice"));
+}
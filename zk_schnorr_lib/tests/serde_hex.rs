@@ -0,0 +1,73 @@
+#![cfg(feature = "std")]
+
+use std::collections::BTreeMap;
+
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use serde::{Deserialize, Serialize};
+use zk_schnorr_lib::{point_to_hex, scalar_to_hex, SerdePoint, SerdeScalar};
+
+// a user struct annotated with the field-level helpers
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Annotated {
+    #[serde(with = "zk_schnorr_lib::serde_hex::point")]
+    point: RistrettoPoint,
+    #[serde(with = "zk_schnorr_lib::serde_hex::scalar")]
+    scalar: Scalar,
+}
+
+// the same, with the newtypes instead of attributes
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Wrapped {
+    point: SerdePoint,
+    scalar: SerdeScalar,
+}
+
+fn sample() -> (RistrettoPoint, Scalar) {
+    let scalar = Scalar::from(0xdead_beef_u64);
+    (RistrettoPoint::mul_base(&scalar), scalar)
+}
+
+#[test]
+fn newtypes_round_trip_through_compressed_hex() {
+    let (point, scalar) = sample();
+    let json = serde_json::to_string(&SerdePoint(point)).unwrap();
+    assert_eq!(json, format!("\"{}\"", point_to_hex(&point)));
+    assert_eq!(serde_json::from_str::<SerdePoint>(&json).unwrap(), SerdePoint(point));
+
+    let json = serde_json::to_string(&SerdeScalar(scalar)).unwrap();
+    assert_eq!(json, format!("\"{}\"", scalar_to_hex(&scalar)));
+    assert_eq!(serde_json::from_str::<SerdeScalar>(&json).unwrap(), SerdeScalar(scalar));
+}
+
+#[test]
+fn newtypes_and_with_attributes_encode_the_same() {
+    let (point, scalar) = sample();
+    let annotated = serde_json::to_string(&Annotated { point, scalar }).unwrap();
+    let wrapped = serde_json::to_string(&Wrapped { point: point.into(), scalar: scalar.into() }).unwrap();
+    assert_eq!(annotated, wrapped);
+    assert_eq!(serde_json::from_str::<Annotated>(&wrapped).unwrap(), Annotated { point, scalar });
+}
+
+#[test]
+fn newtypes_work_where_with_cannot() {
+    let (point, scalar) = sample();
+    let map = BTreeMap::from([(scalar_to_hex(&scalar), vec![SerdePoint(point), SerdePoint(point + point)])]);
+    let json = serde_json::to_string(&map).unwrap();
+    assert_eq!(serde_json::from_str::<BTreeMap<String, Vec<SerdePoint>>>(&json).unwrap(), map);
+}
+
+#[test]
+fn invalid_hex_is_rejected() {
+    for bad in ["\"zz\"", "\"abcd\"", "\"\"", "42"] {
+        assert!(serde_json::from_str::<SerdePoint>(bad).is_err(), "{bad} parsed as a point");
+        assert!(serde_json::from_str::<SerdeScalar>(bad).is_err(), "{bad} parsed as a scalar");
+    }
+}
+
+#[test]
+fn non_canonical_encodings_are_rejected() {
+    // 32 bytes of 0xff is neither a valid compressed point nor a reduced scalar
+    let all_ff = format!("\"{}\"", "ff".repeat(32));
+    assert!(serde_json::from_str::<SerdePoint>(&all_ff).is_err());
+    assert!(serde_json::from_str::<SerdeScalar>(&all_ff).is_err());
+}
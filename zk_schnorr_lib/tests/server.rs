@@ -0,0 +1,154 @@
+#![cfg(feature = "server")]
+
+use std::sync::Arc;
+
+use rustls::ServerName;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot};
+use tokio_rustls::TlsConnector;
+use zk_schnorr_lib::{
+    create_client_config_insecure, create_server_config, generate_self_signed_cert, run_prover, run_prover_as, KeyPair,
+    KeyRegistry, ProverOptions, ServerHandle, StreamTransport, VerificationEvent, VerifierServer,
+};
+
+// a server on a free port holding `registry`, plus the events it reports and a connector that trusts it
+async fn start(registry: KeyRegistry) -> (ServerHandle, mpsc::UnboundedReceiver<VerificationEvent>, TlsConnector) {
+    let cert = generate_self_signed_cert().unwrap();
+    let (events_tx, events) = mpsc::unbounded_channel();
+    let server = VerifierServer::builder()
+        .bind("127.0.0.1:0".parse().unwrap())
+        .tls(create_server_config(&cert, None).unwrap())
+        .expected_keys(registry)
+        .on_result(move |event| {
+            let _ = events_tx.send(event.clone());
+        })
+        .build()
+        .unwrap();
+    let handle = server.spawn().await.unwrap();
+    let connector = TlsConnector::from(Arc::new(create_client_config_insecure(&cert).unwrap()));
+    (handle, events, connector)
+}
+
+async fn connect(handle: &ServerHandle, connector: &TlsConnector) -> StreamTransport<tokio_rustls::client::TlsStream<TcpStream>> {
+    let tcp = TcpStream::connect(handle.local_addr()).await.unwrap();
+    let tls = connector.connect(ServerName::try_from("localhost").unwrap(), tcp).await.unwrap();
+    StreamTransport::new(tls)
+}
+
+#[tokio::test]
+async fn unnamed_prover_is_checked_against_the_only_key() {
+    let keypair = KeyPair::from_seed(b"alice");
+    let (handle, mut events, connector) = start(KeyRegistry::single("alice", keypair.public_key())).await;
+
+    let mut transport = connect(&handle, &connector).await;
+    assert!(run_prover(&mut transport, &keypair).await.unwrap().accepted());
+    drop(transport);
+
+    let event = events.recv().await.unwrap();
+    assert!(event.accepted);
+    assert_eq!(event.identity.as_deref(), Some("alice"));
+    assert_eq!(handle.shutdown().await.unwrap().aborted, 0);
+}
+
+#[tokio::test]
+async fn named_provers_are_checked_against_their_own_keys() {
+    let (alice, bob) = (KeyPair::from_seed(b"alice"), KeyPair::from_seed(b"bob"));
+    let mut registry = KeyRegistry::new();
+    registry.insert("alice", alice.public_key());
+    registry.insert("bob", bob.public_key());
+    let (handle, mut events, connector) = start(registry).await;
+
+    let mut transport = connect(&handle, &connector).await;
+    run_prover_as(&mut transport, &bob, "bob", &ProverOptions::default()).await.unwrap();
+    drop(transport);
+    let event = events.recv().await.unwrap();
+    assert_eq!((event.identity.as_deref(), event.accepted), (Some("bob"), true));
+
+    // the challenge is bound to bob's key, so alice's secret can't answer it
+    let mut transport = connect(&handle, &connector).await;
+    assert!(run_prover_as(&mut transport, &alice, "bob", &ProverOptions::default()).await.is_err());
+    assert!(!events.recv().await.unwrap().accepted);
+
+    handle.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn unknown_identity_is_refused() {
+    let keypair = KeyPair::from_seed(b"alice");
+    let (handle, mut events, connector) = start(KeyRegistry::single("alice", keypair.public_key())).await;
+
+    let mut transport = connect(&handle, &connector).await;
+    assert!(run_prover_as(&mut transport, &keypair, "mallory", &ProverOptions::default()).await.is_err());
+
+    let event = events.recv().await.unwrap();
+    assert!(!event.accepted);
+    assert_eq!(event.identity, None);
+    handle.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn runs_on_a_listener_bound_by_the_caller_until_told_to_stop() {
+    let keypair = KeyPair::from_seed(b"alice");
+    let cert = generate_self_signed_cert().unwrap();
+    let server = VerifierServer::builder()
+        .tls(create_server_config(&cert, None).unwrap())
+        .expected_keys(KeyRegistry::single("alice", keypair.public_key()))
+        .build()
+        .unwrap();
+    assert_eq!(server.run_until(std::future::pending()).await.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+
+    let server = VerifierServer::builder()
+        .tls(create_server_config(&cert, None).unwrap())
+        .expected_keys(KeyRegistry::single("alice", keypair.public_key()))
+        .build()
+        .unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stop, stopped) = oneshot::channel::<()>();
+    let running = tokio::spawn(server.run_on(listener, async {
+        let _ = stopped.await;
+    }));
+
+    let connector = TlsConnector::from(Arc::new(create_client_config_insecure(&cert).unwrap()));
+    let tls = connector.connect(ServerName::try_from("localhost").unwrap(), TcpStream::connect(addr).await.unwrap()).await.unwrap();
+    let mut transport = StreamTransport::new(tls);
+    assert!(run_prover(&mut transport, &keypair).await.unwrap().accepted());
+    drop(transport);
+
+    stop.send(()).unwrap();
+    assert_eq!(running.await.unwrap().unwrap().aborted, 0);
+    assert!(TcpStream::connect(addr).await.is_err()); // the listener went with the server
+}
+
+#[tokio::test]
+async fn metrics_count_connections_handshake_failures_and_proofs() {
+    let keypair = KeyPair::from_seed(b"alice");
+    let cert = generate_self_signed_cert().unwrap();
+    let server = VerifierServer::builder()
+        .bind("127.0.0.1:0".parse().unwrap())
+        .tls(create_server_config(&cert, None).unwrap())
+        .expected_keys(KeyRegistry::single("alice", keypair.public_key()))
+        .build()
+        .unwrap();
+    let metrics = server.metrics();
+    let handle = server.spawn().await.unwrap();
+    let connector = TlsConnector::from(Arc::new(create_client_config_insecure(&cert).unwrap()));
+
+    let mut transport = connect(&handle, &connector).await;
+    run_prover(&mut transport, &keypair).await.unwrap();
+    drop(transport);
+
+    // not TLS at all
+    let mut plain = TcpStream::connect(handle.local_addr()).await.unwrap();
+    plain.write_all(b"GET / HTTP/1.0\r\n\r\n").await.unwrap();
+    drop(plain);
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await; // let it be accepted
+
+    handle.shutdown().await.unwrap(); // waits for both sessions to finish
+    let metrics = metrics.snapshot();
+    assert_eq!(metrics.connections_accepted, 2);
+    assert_eq!(metrics.tls_handshake_failures, 1);
+    assert_eq!((metrics.proofs_verified, metrics.total_rejected()), (1, 0));
+    assert_eq!(metrics.latency_count, 1);
+}
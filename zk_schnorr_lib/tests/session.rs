@@ -0,0 +1,65 @@
+#![cfg(feature = "std")]
+
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::{DecodeError, KeyPair, SchnorrProof, SessionCache, SessionId};
+
+#[test]
+fn session_id_hex_round_trip_requires_exactly_16_bytes() {
+    let id = SessionId::random(&mut OsRng);
+    assert_eq!(id.to_hex().len(), 32);
+    assert_eq!(SessionId::from_hex(&id.to_hex()).unwrap(), id);
+    assert_eq!(id.to_string(), id.to_hex());
+    assert!(matches!(SessionId::from_hex(&"00".repeat(15)), Err(DecodeError::Length { expected: 16, got: 15 })));
+    assert!(matches!(SessionId::from_hex("zz"), Err(DecodeError::Hex(_))));
+}
+
+#[test]
+fn cache_reports_replays() {
+    let mut cache = SessionCache::default();
+    let id = SessionId::new([1; 16]);
+    assert!(cache.insert(id));
+    assert!(!cache.insert(id));
+    assert!(cache.insert(SessionId::new([2; 16])));
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn cache_evicts_the_least_recently_seen_id() {
+    let mut cache = SessionCache::new(2);
+    let (a, b, c) = (SessionId::new([1; 16]), SessionId::new([2; 16]), SessionId::new([3; 16]));
+    cache.insert(a);
+    cache.insert(b);
+    cache.insert(a); // a replay still counts as a sighting, so b is now the oldest
+    cache.insert(c);
+
+    assert!(cache.contains(&a));
+    assert!(!cache.contains(&b));
+    assert!(cache.contains(&c));
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn session_bound_proof_only_verifies_in_its_session() {
+    let keypair = KeyPair::generate(&mut OsRng);
+    let (session, other) = (SessionId::random(&mut OsRng), SessionId::random(&mut OsRng));
+    let proof = SchnorrProof::prove_in_session(keypair.secret().expose_secret(), &session, &mut OsRng);
+
+    assert!(proof.verify_in_session(&keypair.public_key(), &session));
+    assert!(!proof.verify_in_session(&keypair.public_key(), &other));
+    assert!(!proof.verify(&keypair.public_key())); // nor as an unbound proof
+    assert!(!proof.verify_in_session(&KeyPair::generate(&mut OsRng).public_key(), &session));
+}
+
+#[test]
+fn replayed_proof_is_caught_by_the_cache() {
+    let x = Scalar::from(42u64);
+    let public_key = KeyPair::from_secret(x).public_key();
+    let session = SessionId::random(&mut OsRng);
+    let proof = SchnorrProof::prove_in_session(&x, &session, &mut OsRng);
+    let mut seen = SessionCache::default();
+
+    let accept = |seen: &mut SessionCache| proof.verify_in_session(&public_key, &session) && seen.insert(session);
+    assert!(accept(&mut seen));
+    assert!(!accept(&mut seen));
+}
@@ -0,0 +1,180 @@
+#![cfg(feature = "server")]
+
+use std::path::Path;
+use std::sync::Arc;
+
+use rustls::ServerName;
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use zk_schnorr_lib::session_log::rotated_path;
+use zk_schnorr_lib::{
+    create_client_config_insecure, create_server_config, generate_self_signed_cert, run_prover_as, run_prover_ed25519,
+    AuditLog, AuditLogOptions, Ed25519SigningKey, FsyncPolicy, KeyPair, KeyRegistry, ProverOptions, RegisteredKey,
+    SessionAuditRecord, SessionOutcome, StreamTransport, VerifierServer,
+};
+
+fn read_records(path: &Path) -> Vec<SessionAuditRecord> {
+    let contents = std::fs::read_to_string(path).unwrap();
+    contents.lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+}
+
+fn record(n: usize) -> SessionAuditRecord {
+    SessionAuditRecord {
+        timestamp: 1_700_000_000 + n as u64,
+        peer_addr: "127.0.0.1:50000".parse().unwrap(),
+        identity: Some(format!("prover-{n}")),
+        public_key: None,
+        session_id: None,
+        outcome: SessionOutcome::Accepted,
+        failure_reason: None,
+        rounds: 1,
+        verified: 1,
+        handshake_ms: 3,
+        duration_ms: 12,
+    }
+}
+
+#[tokio::test]
+async fn accepted_rejected_and_refused_sessions_are_all_recorded() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sessions.jsonl");
+    let log = AuditLog::open(&path, AuditLogOptions { fsync: FsyncPolicy::Always, ..AuditLogOptions::default() }).await.unwrap();
+
+    let alice = KeyPair::from_seed(b"alice");
+    let token = Ed25519SigningKey::derive(b"hardware token");
+    let mut registry = KeyRegistry::new();
+    registry.insert("alice", alice.public_key());
+    registry.insert("token", token.public_key());
+    let cert = generate_self_signed_cert().unwrap();
+    let handle = VerifierServer::builder()
+        .bind("127.0.0.1:0".parse().unwrap())
+        .tls(create_server_config(&cert, None).unwrap())
+        .expected_keys(registry)
+        .session_log(log.clone())
+        .build()
+        .unwrap()
+        .spawn()
+        .await
+        .unwrap();
+    let connector = TlsConnector::from(Arc::new(create_client_config_insecure(&cert).unwrap()));
+    let connect = || async {
+        let tcp = TcpStream::connect(handle.local_addr()).await.unwrap();
+        StreamTransport::new(connector.connect(ServerName::try_from("localhost").unwrap(), tcp).await.unwrap())
+    };
+
+    let options = ProverOptions::default();
+    assert!(run_prover_as(&mut connect().await, &alice, "alice", &options).await.unwrap().accepted());
+    let impostor = Ed25519SigningKey::derive(b"someone else");
+    assert!(!run_prover_ed25519(&mut connect().await, &impostor, Some("token")).await.unwrap().accepted());
+    assert!(run_prover_as(&mut connect().await, &alice, "mallory", &options).await.is_err());
+
+    handle.shutdown().await.unwrap(); // every session has ended, so every record is queued
+    log.flush().await.unwrap();
+    let mut records = read_records(&path);
+    assert_eq!(records.len(), 3);
+    records.sort_by_key(|r| r.identity.clone());
+
+    let [accepted, refused, rejected] = &records[..] else { unreachable!() };
+    assert_eq!((accepted.identity.as_deref(), accepted.outcome), (Some("alice"), SessionOutcome::Accepted));
+    assert_eq!(accepted.public_key, Some(RegisteredKey::Ristretto(alice.public_key())));
+    assert!(accepted.session_id.is_some() && accepted.failure_reason.is_none());
+    assert_eq!((accepted.rounds, accepted.verified), (1, 1));
+    assert!(accepted.duration_ms >= accepted.handshake_ms);
+
+    assert_eq!((rejected.identity.as_deref(), rejected.outcome), (Some("token"), SessionOutcome::Rejected));
+    assert_eq!(rejected.public_key, Some(RegisteredKey::Ed25519(token.public_key())));
+    assert_eq!(rejected.failure_reason.as_deref(), Some("1 of 1 rounds failed"));
+
+    assert_eq!((refused.identity.as_deref(), refused.outcome), (Some("mallory"), SessionOutcome::Error));
+    assert_eq!(refused.public_key, None);
+    assert!(refused.failure_reason.as_deref().unwrap().contains("mallory"));
+    assert_eq!(refused.peer_addr.ip(), accepted.peer_addr.ip());
+}
+
+#[tokio::test]
+async fn concurrent_records_stay_on_their_own_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sessions.jsonl");
+    let log = AuditLog::open(&path, AuditLogOptions::default()).await.unwrap();
+
+    let writers: Vec<_> = (0..100)
+        .map(|n| {
+            let log = log.clone();
+            tokio::spawn(async move { log.record(&record(n)).await.unwrap() })
+        })
+        .collect();
+    for writer in writers {
+        writer.await.unwrap();
+    }
+    log.flush().await.unwrap();
+
+    let mut identities: Vec<_> = read_records(&path).into_iter().map(|r| r.identity.unwrap()).collect();
+    identities.sort();
+    let mut expected: Vec<_> = (0..100).map(|n| format!("prover-{n}")).collect();
+    expected.sort();
+    assert_eq!(identities, expected);
+}
+
+#[tokio::test]
+async fn log_rotates_by_size_and_keeps_the_newest_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sessions.jsonl");
+    let line_len = serde_json::to_string(&record(10)).unwrap().len() as u64 + 1;
+    let options = AuditLogOptions { max_bytes: Some(line_len * 2), keep: 2, ..AuditLogOptions::default() };
+    let log = AuditLog::open(&path, options).await.unwrap();
+
+    for n in 10..17 {
+        log.record(&record(n)).await.unwrap();
+    }
+    log.flush().await.unwrap();
+
+    let identities = |path: &Path| -> Vec<String> { read_records(path).into_iter().map(|r| r.identity.unwrap()).collect() };
+    assert_eq!(identities(&path), ["prover-16"]);
+    assert_eq!(identities(&rotated_path(&path, 1)), ["prover-14", "prover-15"]);
+    assert_eq!(identities(&rotated_path(&path, 2)), ["prover-12", "prover-13"]);
+    assert!(!rotated_path(&path, 3).exists(), "only two rotated files are kept");
+}
+
+#[tokio::test]
+async fn reopening_appends_to_the_existing_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sessions.jsonl");
+    for n in 0..2 {
+        let log = AuditLog::open(&path, AuditLogOptions::default()).await.unwrap();
+        log.record(&record(n)).await.unwrap();
+        log.flush().await.unwrap();
+    }
+    assert_eq!(read_records(&path), vec![record(0), record(1)]);
+}
+
+#[test]
+fn fsync_policies_parse_by_name() {
+    for policy in [FsyncPolicy::Never, FsyncPolicy::Always, FsyncPolicy::Batched] {
+        assert_eq!(policy.to_string().parse::<FsyncPolicy>().unwrap(), policy);
+    }
+    assert!("sometimes".parse::<FsyncPolicy>().is_err());
+}
+
+#[tokio::test]
+async fn a_full_queue_makes_record_wait_instead_of_dropping() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sessions.jsonl");
+    let log = AuditLog::open(&path, AuditLogOptions { queue: 2, ..AuditLogOptions::default() }).await.unwrap();
+
+    // the writer task can't run until this test yields, so the queue only fills
+    log.record(&record(0)).await.unwrap();
+    log.record(&record(1)).await.unwrap();
+    let third = record(2);
+    tokio::select! {
+        biased;
+        _ = log.record(&third) => panic!("a third record should wait for the writer"),
+        _ = std::future::ready(()) => {}
+    }
+
+    for n in 2..5 {
+        log.record(&record(n)).await.unwrap(); // each wait lets the writer catch up
+    }
+    log.flush().await.unwrap();
+    let identities: Vec<_> = read_records(&path).into_iter().map(|r| r.identity.unwrap()).collect();
+    assert_eq!(identities, ["prover-0", "prover-1", "prover-2", "prover-3", "prover-4"]);
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "std")]
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use rand_core::{impls, RngCore};
+use serde::Deserialize;
+use zk_schnorr_lib::signature::{sign, verify};
+use zk_schnorr_lib::{
+    schnorr_sign_with_context, schnorr_verify_with_context, verify_signature, DecodeError, KeyPair, PublicKey, Signature,
+};
+
+// Yields the same byte forever, so the nonce in a test vector is reproducible
+struct FixedRng(u8);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.fill(self.0);
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct Vector {
+    secret: String,
+    nonce_byte: u8,
+    message: String,
+    public: String,
+    signature: String,
+}
+
+fn vectors() -> Vec<Vector> {
+    serde_json::from_str(include_str!("vectors/signatures.json")).unwrap()
+}
+
+#[test]
+fn test_vectors_reproduce_and_verify() {
+    for v in vectors() {
+        let secret = zk_schnorr_lib::scalar_from_hex_canonical(&v.secret).unwrap();
+        let message = hex::decode(&v.message).unwrap();
+        let public: PublicKey = v.public.parse().unwrap();
+        assert_eq!(public.0, RISTRETTO_BASEPOINT_POINT * secret);
+
+        let sig = sign(&secret, &message, &mut FixedRng(v.nonce_byte));
+        assert_eq!(sig.to_string(), v.signature);
+        assert!(verify(&public, &message, &v.signature.parse().unwrap()));
+    }
+}
+
+#[test]
+fn sign_then_verify() {
+    let x = Scalar::from(42u64);
+    let public = PublicKey(RISTRETTO_BASEPOINT_POINT * x);
+    let sig = sign(&x, b"server nonce", &mut OsRng);
+    assert!(verify(&public, b"server nonce", &sig));
+    assert!(!verify(&public, b"another nonce", &sig));
+    assert!(!verify(&PublicKey(RISTRETTO_BASEPOINT_POINT), b"server nonce", &sig));
+}
+
+#[test]
+fn context_bound_signature_fails_in_another_context() {
+    let keypair = KeyPair::from_seed(b"signer");
+    let sig = schnorr_sign_with_context(&keypair, b"transfer 10", b"app-v1", &mut OsRng);
+    assert!(schnorr_verify_with_context(&keypair.public_key(), b"transfer 10", b"app-v1", &sig));
+    assert!(!schnorr_verify_with_context(&keypair.public_key(), b"transfer 10", b"app-v2", &sig));
+    assert!(!schnorr_verify_with_context(&keypair.public_key(), b"transfer 10", b"", &sig));
+    assert!(!verify(&keypair.public_key(), b"transfer 10", &sig), "nor without any context");
+
+    // the same bytes split differently between message and context
+    assert!(!schnorr_verify_with_context(&keypair.public_key(), b"transfer 10a", b"pp-v1", &sig));
+}
+
+#[test]
+fn empty_context_is_the_context_free_signature() {
+    let keypair = KeyPair::from_seed(b"signer");
+    let with_empty = schnorr_sign_with_context(&keypair, b"hello", b"", &mut FixedRng(9));
+    assert_eq!(with_empty, sign(keypair.secret().expose_secret(), b"hello", &mut FixedRng(9)));
+    assert!(verify(&keypair.public_key(), b"hello", &with_empty));
+    assert!(schnorr_verify_with_context(&keypair.public_key(), b"hello", b"", &keypair.sign(b"hello")));
+}
+
+#[test]
+fn keypair_signs_and_verify_signature_checks() {
+    let keypair = KeyPair::from_seed(b"signer");
+    let sig = keypair.sign(b"hello");
+    assert_eq!(sig.to_bytes().len(), 64);
+    assert!(verify_signature(&keypair.public_key(), b"hello", &sig));
+    assert!(!verify_signature(&keypair.public_key(), b"goodbye", &sig));
+    assert!(!verify_signature(&KeyPair::from_seed(b"someone else").public_key(), b"hello", &sig));
+}
+
+#[test]
+fn bytes_round_trip() {
+    let sig = sign(&Scalar::from(3u64), b"m", &mut OsRng);
+    assert_eq!(Signature::from_bytes(&sig.to_bytes()).unwrap(), sig);
+}
+
+#[test]
+fn non_canonical_s_is_rejected() {
+    let mut bytes = sign(&Scalar::from(3u64), b"m", &mut OsRng).to_bytes();
+    bytes[32..].fill(0xff);
+    assert!(matches!(Signature::from_bytes(&bytes), Err(DecodeError::Scalar(_))));
+}
+
+#[test]
+fn serde_uses_hex() {
+    let sig = sign(&Scalar::from(5u64), b"m", &mut OsRng);
+    let json = serde_json::to_string(&sig).unwrap();
+    assert_eq!(json, format!("\"{}\"", hex::encode(sig.to_bytes())));
+    assert_eq!(serde_json::from_str::<Signature>(&json).unwrap(), sig);
+    assert!(matches!("abcd".parse::<Signature>(), Err(DecodeError::Length { expected: 64, got: 2 })));
+}
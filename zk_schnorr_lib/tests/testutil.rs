@@ -0,0 +1,32 @@
+#![cfg(feature = "testutil")]
+
+use curve25519_dalek::scalar::Scalar;
+use zk_schnorr_lib::testutil::{duplex_transports, run_protocol};
+use zk_schnorr_lib::{KeyPair, Message, ProtocolError, SchnorrProtocol, Transport};
+
+#[tokio::test]
+async fn honest_prover_is_accepted() {
+    let keypair = KeyPair::from_seed(b"testutil");
+    assert!(run_protocol(keypair.secret().expose_secret(), &keypair.public_key()).await);
+}
+
+#[tokio::test]
+async fn wrong_secret_is_not_accepted() {
+    let expected = KeyPair::from_seed(b"testutil").public_key();
+    assert!(!run_protocol(&Scalar::from(42u64), &expected).await);
+}
+
+#[tokio::test]
+async fn response_instead_of_commit_is_an_unexpected_message() {
+    let expected = KeyPair::from_seed(b"testutil").public_key();
+    let (mut prover, mut verifier) = duplex_transports();
+
+    let misbehave = async {
+        prover.recv().await.unwrap(); // the context
+        prover.send(Message::response(&Scalar::ONE)).await.unwrap();
+        prover.recv().await.unwrap() // the verifier says why it gave up
+    };
+    let (complaint, verified) = tokio::join!(misbehave, SchnorrProtocol::verify_interactive(&mut verifier, &expected));
+    assert!(matches!(verified, Err(ProtocolError::UnexpectedMessage { expected: "commit", .. })));
+    assert_eq!(complaint.kind, "error");
+}
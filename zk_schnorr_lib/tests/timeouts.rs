@@ -0,0 +1,61 @@
+#![cfg(feature = "tls")]
+
+use std::time::Duration;
+
+use tokio::io::AsyncReadExt;
+use zk_schnorr_lib::{
+    KeyPair, Message, ProtocolConfig, TimedOut, ZkError, recv_message, send_message, verify_stream, with_timeout,
+};
+
+#[tokio::test]
+async fn stalled_client_is_disconnected_after_the_deadline() {
+    let (mut client, server) = tokio::io::duplex(1024); // the client never writes anything
+    let limit = Duration::from_millis(50);
+
+    let verifier = tokio::spawn(async move {
+        let (mut read_half, _write_half) = tokio::io::split(server);
+        let started = tokio::time::Instant::now();
+        let result = with_timeout(limit, "commitment", recv_message(&mut read_half)).await;
+        (result.map(|_| ()), started.elapsed())
+        // both halves drop here, closing the connection
+    });
+
+    let (result, elapsed) = verifier.await.unwrap();
+    assert_eq!(result, Err(TimedOut { waiting_for: "commitment" }));
+    assert!(elapsed >= limit);
+
+    // the stalled client now sees the connection closed
+    let mut buf = [0u8; 1];
+    assert_eq!(client.read(&mut buf).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn timed_out_error_names_what_we_waited_for() {
+    let err = with_timeout(Duration::from_millis(1), "response", std::future::pending::<()>())
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "timed out waiting for response");
+}
+
+#[tokio::test]
+async fn fast_futures_are_not_affected() {
+    let value = with_timeout(Duration::from_secs(1), "nothing", async { 7 }).await;
+    assert_eq!(value, Ok(7));
+}
+
+#[tokio::test]
+async fn client_that_stops_after_the_commitment_times_out() {
+    let expected = KeyPair::from_seed(b"stalled").public_key();
+    let config = ProtocolConfig { step_timeout: Duration::from_millis(50), ..ProtocolConfig::default() };
+    let (mut client, server) = tokio::io::duplex(1024);
+
+    let verifier = tokio::spawn(async move { verify_stream(server, &expected, &config).await });
+
+    recv_message(&mut client).await.unwrap(); // the context
+    send_message(&mut client, &Message::commit(&curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT)).await.unwrap();
+    recv_message(&mut client).await.unwrap(); // the challenge, which we never answer
+
+    let result = tokio::time::timeout(Duration::from_secs(5), verifier).await.expect("verifier hung").unwrap();
+    assert!(matches!(result, Err(ZkError::Timeout(TimedOut { waiting_for: "response" }))));
+    drop(client);
+}
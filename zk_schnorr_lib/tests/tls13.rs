@@ -0,0 +1,84 @@
+#![cfg(feature = "tls")]
+
+use std::sync::Arc;
+
+use rustls::{AlertDescription, Certificate, ClientConfig, RootCertStore, ServerName};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use zk_schnorr_lib::{
+    create_client_config_insecure, create_client_config_insecure_tls13, create_server_config, create_server_config_tls13,
+    generate_self_signed_cert, TlsCertificate,
+};
+
+// a client that, like an old library, can't go past TLS 1.2
+fn tls12_only_client(server_cert: &TlsCertificate) -> ClientConfig {
+    let mut roots = RootCertStore::empty();
+    roots.add(&Certificate(server_cert.cert_der.clone())).unwrap();
+    ClientConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(&[&rustls::version::TLS12])
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth()
+}
+
+// handshake plus one byte; the negotiated version on success, each side's error otherwise
+async fn handshake(
+    server: rustls::ServerConfig,
+    client: ClientConfig,
+) -> Result<rustls::ProtocolVersion, (Option<std::io::Error>, Option<std::io::Error>)> {
+    let (client_io, server_io) = tokio::io::duplex(16 * 1024);
+    let acceptor = TlsAcceptor::from(Arc::new(server));
+    let connector = TlsConnector::from(Arc::new(client));
+
+    let server_side = async {
+        let mut stream = acceptor.accept(server_io).await?;
+        let mut byte = [0u8; 1];
+        stream.read_exact(&mut byte).await?;
+        Ok::<_, std::io::Error>(stream.get_ref().1.protocol_version())
+    };
+    let client_side = async {
+        let mut stream = connector.connect(ServerName::try_from("localhost").unwrap(), client_io).await?;
+        stream.write_all(b"x").await?;
+        stream.flush().await?;
+        Ok::<_, std::io::Error>(stream)
+    };
+    match tokio::join!(server_side, client_side) {
+        (Ok(version), Ok(_)) => Ok(version.expect("negotiated")),
+        (server, client) => Err((server.err(), client.err())),
+    }
+}
+
+fn rustls_error(e: &std::io::Error) -> Option<&rustls::Error> {
+    e.get_ref()?.downcast_ref()
+}
+
+#[tokio::test]
+async fn tls13_configs_talk_to_each_other_and_to_the_defaults() {
+    let cert = generate_self_signed_cert().unwrap();
+    let both = handshake(create_server_config_tls13(&cert, None).unwrap(), create_client_config_insecure_tls13(&cert).unwrap());
+    assert_eq!(both.await.unwrap(), rustls::ProtocolVersion::TLSv1_3);
+
+    let server_only = handshake(create_server_config_tls13(&cert, None).unwrap(), create_client_config_insecure(&cert).unwrap());
+    assert_eq!(server_only.await.unwrap(), rustls::ProtocolVersion::TLSv1_3);
+    let client_only = handshake(create_server_config(&cert, None).unwrap(), create_client_config_insecure_tls13(&cert).unwrap());
+    assert_eq!(client_only.await.unwrap(), rustls::ProtocolVersion::TLSv1_3);
+}
+
+#[tokio::test]
+async fn tls12_only_client_is_refused_with_a_protocol_version_alert() {
+    let cert = generate_self_signed_cert().unwrap();
+    // the default config still lets it in
+    let downgraded = handshake(create_server_config(&cert, None).unwrap(), tls12_only_client(&cert)).await;
+    assert_eq!(downgraded.unwrap(), rustls::ProtocolVersion::TLSv1_2);
+
+    let (server, client) = handshake(create_server_config_tls13(&cert, None).unwrap(), tls12_only_client(&cert)).await.unwrap_err();
+    let server = server.expect("the server fails the handshake");
+    assert!(matches!(rustls_error(&server), Some(rustls::Error::PeerIncompatible(_))), "{server}");
+    let client = client.expect("the client is told why");
+    assert!(
+        matches!(rustls_error(&client), Some(rustls::Error::AlertReceived(AlertDescription::ProtocolVersion))),
+        "{client}"
+    );
+}
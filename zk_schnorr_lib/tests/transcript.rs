@@ -0,0 +1,33 @@
+#![allow(non_snake_case)] // G, X, R follow the usual maths notation
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT as G;
+use curve25519_dalek::scalar::Scalar;
+use zk_schnorr_lib::{transcript_challenge, PublicKey, SchnorrProof};
+
+#[test]
+fn challenge_is_deterministic() {
+    let R = G * Scalar::from(5u64);
+    assert_eq!(transcript_challenge(b"test", &[(b"R", &R)]), transcript_challenge(b"test", &[(b"R", &R)]));
+}
+
+#[test]
+fn domain_labels_and_order_all_change_the_challenge() {
+    let (X, R) = (G * Scalar::from(3u64), G * Scalar::from(5u64));
+    let base = transcript_challenge(b"test", &[(b"X", &X), (b"R", &R)]);
+
+    assert_ne!(base, transcript_challenge(b"other", &[(b"X", &X), (b"R", &R)]));
+    assert_ne!(base, transcript_challenge(b"test", &[(b"Y", &X), (b"R", &R)]));
+    assert_ne!(base, transcript_challenge(b"test", &[(b"R", &R), (b"X", &X)]));
+    assert_ne!(base, transcript_challenge(b"test", &[(b"X", &X)]));
+}
+
+#[test]
+fn schnorr_proof_uses_the_transcript_challenge() {
+    let x = Scalar::from(7u64);
+    let k = Scalar::from(13u64);
+    let (X, R) = (G * x, G * k);
+    let c = transcript_challenge(b"zk-schnorr-tls/schnorr-proof/v2", &[(b"G", &G), (b"X", &X), (b"R", &R)]);
+
+    let proof = SchnorrProof { commitment: R, response: k + c * x };
+    assert!(proof.verify(&PublicKey(X)));
+}
@@ -0,0 +1,64 @@
+use curve25519_dalek::constants::{ED25519_BASEPOINT_POINT, RISTRETTO_BASEPOINT_POINT};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use num_bigint::BigUint;
+use rand::rngs::OsRng;
+use zk_schnorr_lib::{point_from_hex, point_from_hex_uncompressed, point_to_hex, point_to_hex_uncompressed, PointDecodeError};
+
+#[test]
+fn both_forms_round_trip() {
+    let mut points = vec![RistrettoPoint::identity(), RISTRETTO_BASEPOINT_POINT];
+    points.extend((0..32).map(|_| RistrettoPoint::random(&mut OsRng)));
+    for p in points {
+        let long = point_to_hex_uncompressed(&p);
+        assert_eq!(long.len(), 128);
+        assert_eq!(point_from_hex_uncompressed(&long).unwrap(), p);
+        assert_eq!(point_from_hex(&long).unwrap(), p);
+        assert_eq!(point_from_hex(&point_to_hex(&p)).unwrap(), p);
+    }
+}
+
+#[test]
+fn coordinates_are_an_edwards_point_in_the_class() {
+    // the representative differs from the Ed25519 basepoint by 4-torsion at most
+    let bytes = hex::decode(point_to_hex_uncompressed(&RISTRETTO_BASEPOINT_POINT)).unwrap();
+    let mut y = [0u8; 32];
+    y.copy_from_slice(&bytes[32..]);
+    y[31] |= (bytes[0] & 1) << 7; // the sign of x
+    let edwards = CompressedEdwardsY(y).decompress().unwrap();
+    let four = Scalar::from(4u64);
+    assert_eq!(edwards * four, ED25519_BASEPOINT_POINT * four);
+}
+
+#[test]
+fn other_representatives_are_rejected() {
+    // (-x, -y) is the same Ristretto point but not the canonical representative
+    let p = RISTRETTO_BASEPOINT_POINT * Scalar::from(9u64);
+    let bytes = hex::decode(point_to_hex_uncompressed(&p)).unwrap();
+    let p25519 = (BigUint::from(1u8) << 255u32) - 19u32;
+    let neg = |coord: &[u8]| {
+        let mut out = (&p25519 - BigUint::from_bytes_le(coord)).to_bytes_le();
+        out.resize(32, 0);
+        out
+    };
+    let mut other = neg(&bytes[..32]);
+    other.extend(neg(&bytes[32..]));
+    assert!(matches!(point_from_hex_uncompressed(&hex::encode(other)), Err(PointDecodeError::InvalidPoint)));
+}
+
+#[test]
+fn point_off_the_curve_is_rejected() {
+    let mut bytes = hex::decode(point_to_hex_uncompressed(&RISTRETTO_BASEPOINT_POINT)).unwrap();
+    bytes[0] ^= 1;
+    assert!(matches!(point_from_hex(&hex::encode(bytes)), Err(PointDecodeError::InvalidPoint)));
+}
+
+#[test]
+fn length_errors_list_the_accepted_lengths() {
+    let err = point_from_hex(&"00".repeat(40)).unwrap_err();
+    assert!(matches!(err, PointDecodeError::InvalidLength { got: 40, expected: [32, 64] }));
+    let err = point_from_hex_uncompressed(&"00".repeat(32)).unwrap_err();
+    assert!(matches!(err, PointDecodeError::InvalidLength { got: 32, expected: [64] }));
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "tls")]
+
+use zk_schnorr_lib::{
+    negotiate, KeyPair, Message, NegotiationError, ProtocolError, SchnorrProtocol, StreamTransport, Transport, Version,
+    VersionInfo, PROTOCOL_VERSION,
+};
+
+fn info(major: u16, minor: u16) -> VersionInfo {
+    VersionInfo { version: Version::new(major, minor), supported_features: vec!["or-proof".to_string()], identity: None, generator: None }
+}
+
+#[test]
+fn same_version_is_selected() {
+    let current = VersionInfo::current();
+    assert_eq!(negotiate(&current, &current).unwrap(), PROTOCOL_VERSION);
+}
+
+#[test]
+fn newer_minor_downgrades_to_the_older_one() {
+    assert_eq!(negotiate(&info(1, 3), &info(1, 1)).unwrap(), Version::new(1, 1));
+    assert_eq!(negotiate(&info(1, 0), &info(1, 2)).unwrap(), Version::new(1, 0));
+}
+
+#[test]
+fn different_major_is_rejected() {
+    let err = negotiate(&info(2, 0), &info(1, 4)).unwrap_err();
+    assert_eq!(err, NegotiationError::Incompatible { peer: Version::new(2, 0), supported: vec![Version::new(1, 4)] });
+    assert!(negotiate(&info(0, 9), &info(1, 0)).is_err());
+}
+
+#[test]
+fn versions_parse_and_display() {
+    assert_eq!("1.2".parse::<Version>().unwrap(), Version::new(1, 2));
+    assert_eq!(Version::new(3, 10).to_string(), "3.10");
+    assert!(matches!("1".parse::<Version>(), Err(NegotiationError::InvalidVersion(_))));
+    assert!("1.x".parse::<Version>().is_err());
+}
+
+#[tokio::test]
+async fn hello_then_a_proof_over_one_connection() {
+    let keypair = KeyPair::from_seed(b"version-test");
+    let expected = keypair.public_key();
+    let (prover_end, verifier_end) = tokio::io::duplex(1024);
+    let (mut prover, mut verifier) = (StreamTransport::new(prover_end), StreamTransport::new(verifier_end));
+
+    let prove = async {
+        let version = SchnorrProtocol::send_hello(&mut prover, &info(1, 7)).await.unwrap();
+        SchnorrProtocol::run_prover(&mut prover, &keypair).await.unwrap();
+        version
+    };
+    let verify = async {
+        let version = SchnorrProtocol::accept_hello(&mut verifier, &info(1, 2)).await.unwrap();
+        (version, SchnorrProtocol::run_verifier(&mut verifier, &expected).await.unwrap())
+    };
+    let (proved, (accepted, verified)) = tokio::join!(prove, verify);
+    assert_eq!((proved, accepted), (Version::new(1, 2), Version::new(1, 2)));
+    assert!(verified);
+}
+
+#[tokio::test]
+async fn incompatible_prover_is_told_what_the_verifier_supports() {
+    let (prover_end, verifier_end) = tokio::io::duplex(1024);
+    let (mut prover, mut verifier) = (StreamTransport::new(prover_end), StreamTransport::new(verifier_end));
+
+    let (newer, older) = (info(2, 0), info(1, 0));
+    let (sent, accepted) = tokio::join!(
+        SchnorrProtocol::send_hello(&mut prover, &newer),
+        SchnorrProtocol::accept_hello(&mut verifier, &older),
+    );
+    assert!(matches!(accepted, Err(ProtocolError::Negotiation(NegotiationError::Incompatible { .. }))));
+    match sent {
+        Err(ProtocolError::Negotiation(NegotiationError::Incompatible { peer, supported })) => {
+            assert_eq!(peer, Version::new(2, 0));
+            assert_eq!(supported, vec![Version::new(1, 0)]);
+        }
+        other => panic!("expected an incompatible version, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn prover_refuses_a_version_it_did_not_offer() {
+    let (prover_end, verifier_end) = tokio::io::duplex(1024);
+    let (mut prover, mut verifier) = (StreamTransport::new(prover_end), StreamTransport::new(verifier_end));
+
+    let fake_verifier = async {
+        verifier.recv().await.unwrap();
+        verifier.send(Message::hello(&info(1, 9))).await.unwrap(); // claims a minor newer than the prover's
+    };
+    let offered = info(1, 3);
+    let (sent, ()) = tokio::join!(SchnorrProtocol::send_hello(&mut prover, &offered), fake_verifier);
+    assert!(matches!(sent, Err(ProtocolError::Negotiation(NegotiationError::Downgrade { selected })) if selected == Version::new(1, 9)));
+}
+
+#[tokio::test]
+async fn verifier_rejects_a_session_that_skips_the_hello() {
+    let (prover_end, verifier_end) = tokio::io::duplex(1024);
+    let (mut prover, mut verifier) = (StreamTransport::new(prover_end), StreamTransport::new(verifier_end));
+
+    prover.send(Message { kind: "commitment".to_string(), payload: String::new(), session: None }).await.unwrap();
+    let accepted = SchnorrProtocol::accept_hello(&mut verifier, &VersionInfo::current()).await;
+    assert!(matches!(accepted, Err(ProtocolError::UnexpectedMessage { expected: "hello", .. })));
+    assert_eq!(prover.recv().await.unwrap().kind, "error");
+}
+
+#[test]
+fn identity_is_omitted_from_the_wire_unless_set() {
+    let plain = serde_json::to_string(&VersionInfo::current()).unwrap();
+    assert!(!plain.contains("identity"));
+    let old_hello: VersionInfo = serde_json::from_str(&plain).unwrap(); // as sent before identities existed
+    assert_eq!(old_hello.identity, None);
+
+    let named = VersionInfo::current().with_identity("alice");
+    let decoded: VersionInfo = serde_json::from_str(&serde_json::to_string(&named).unwrap()).unwrap();
+    assert_eq!(decoded.identity.as_deref(), Some("alice"));
+}
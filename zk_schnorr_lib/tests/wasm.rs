@@ -0,0 +1,64 @@
+#![cfg(feature = "wasm")]
+
+use zk_schnorr_lib::wasm::{generate_keypair, schnorr_sign, schnorr_verify, HexKeyPair, HexSignature};
+use zk_schnorr_lib::{point_from_hex, scalar_from_hex_canonical, verify_signature, KeyPair, Signature};
+
+const SEED_HEX: &str = "64656d6f2d70726f7665722d736563726574"; // "demo-prover-secret"
+const MESSAGE_HEX: &str = "68656c6c6f"; // "hello"
+
+#[test]
+fn keypair_matches_from_seed() {
+    let keypair = generate_keypair(SEED_HEX).unwrap();
+    let native = KeyPair::from_seed(b"demo-prover-secret");
+    assert_eq!(keypair.public_key, native.public_key().to_string());
+    let secret = scalar_from_hex_canonical(&keypair.secret_scalar).unwrap();
+    assert_eq!(KeyPair::from_secret(secret).public_key(), native.public_key());
+}
+
+#[test]
+fn fields_use_the_javascript_names() {
+    let keypair = HexKeyPair { public_key: "aa".to_string(), secret_scalar: "bb".to_string() };
+    assert_eq!(serde_json::to_string(&keypair).unwrap(), r#"{"publicKey":"aa","secretScalar":"bb"}"#);
+    let sig = HexSignature { R: "cc".to_string(), s: "dd".to_string() };
+    assert_eq!(serde_json::to_string(&sig).unwrap(), r#"{"R":"cc","s":"dd"}"#);
+}
+
+#[test]
+fn signature_verifies_here_and_natively() {
+    let keypair = generate_keypair(SEED_HEX).unwrap();
+    let sig = schnorr_sign(&keypair.secret_scalar, MESSAGE_HEX).unwrap();
+    assert!(schnorr_verify(&keypair.public_key, MESSAGE_HEX, &sig.R, &sig.s));
+
+    let native = Signature { R: point_from_hex(&sig.R).unwrap(), s: scalar_from_hex_canonical(&sig.s).unwrap() };
+    assert!(verify_signature(&point_from_hex(&keypair.public_key).unwrap(), b"hello", &native));
+}
+
+#[test]
+fn native_signature_verifies_through_the_api() {
+    let keypair = KeyPair::from_seed(b"demo-prover-secret");
+    let sig = keypair.sign(b"hello");
+    let (r, s) = (zk_schnorr_lib::point_to_hex(&sig.R), zk_schnorr_lib::scalar_to_hex(&sig.s));
+    assert!(schnorr_verify(&keypair.public_key().to_string(), MESSAGE_HEX, &r, &s));
+}
+
+#[test]
+fn wrong_message_or_key_is_false() {
+    let keypair = generate_keypair(SEED_HEX).unwrap();
+    let other = generate_keypair("00").unwrap();
+    let sig = schnorr_sign(&keypair.secret_scalar, MESSAGE_HEX).unwrap();
+    assert!(!schnorr_verify(&keypair.public_key, "68656c6c6e", &sig.R, &sig.s));
+    assert!(!schnorr_verify(&other.public_key, MESSAGE_HEX, &sig.R, &sig.s));
+}
+
+#[test]
+fn undecodable_input_is_an_error_or_false() {
+    assert!(generate_keypair("not hex").is_err());
+    assert!(schnorr_sign(&"ff".repeat(32), MESSAGE_HEX).is_err()); // not a canonical scalar
+    assert!(schnorr_sign(&"01".repeat(32), "zz").is_err());
+
+    let keypair = generate_keypair(SEED_HEX).unwrap();
+    let sig = schnorr_sign(&keypair.secret_scalar, MESSAGE_HEX).unwrap();
+    assert!(!schnorr_verify("zz", MESSAGE_HEX, &sig.R, &sig.s));
+    assert!(!schnorr_verify(&keypair.public_key, MESSAGE_HEX, &sig.R, &"ff".repeat(32)));
+    assert!(!schnorr_verify(&keypair.public_key, MESSAGE_HEX, "00", &sig.s));
+}
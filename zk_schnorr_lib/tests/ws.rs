@@ -0,0 +1,186 @@
+#![cfg(feature = "ws")]
+
+use std::sync::Arc;
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::Message as Frame;
+use zk_schnorr_lib::{
+    accept_ws, connect_ws, create_client_config_insecure, create_server_config, generate_self_signed_cert,
+    run_prover, run_verifier, ws_config, KeyPair, Message, ProtocolError, Transport, WsTransport, MAX_FRAME_LEN,
+};
+#[cfg(feature = "server")]
+use zk_schnorr_lib::{KeyRegistry, StreamTransport, VerifierServer};
+
+// A listener on a free local port and the ws:// URL that reaches it
+async fn listen() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    (listener, url)
+}
+
+#[tokio::test]
+async fn full_protocol_over_tls_websocket() {
+    let cert = generate_self_signed_cert().unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(create_server_config(&cert, None).unwrap()));
+    let client_config = Arc::new(create_client_config_insecure(&cert).unwrap());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("wss://localhost:{}", listener.local_addr().unwrap().port());
+
+    let keypair = KeyPair::from_seed(b"ws");
+    let expected = keypair.public_key();
+    let verifier = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut transport = accept_ws(stream, &acceptor).await.unwrap();
+        run_verifier(&mut transport, &expected).await
+    });
+
+    let mut transport = connect_ws(&url, client_config).await.unwrap();
+    let proved = run_prover(&mut transport, &keypair).await.unwrap();
+    drop(transport); // hang up so the verifier stops waiting for another round
+
+    let verified = verifier.await.unwrap().unwrap();
+    assert!(proved.accepted());
+    assert_eq!(verified.rounds, vec![true]);
+}
+
+#[tokio::test]
+async fn wrong_key_is_rejected_over_plain_websocket() {
+    let (listener, url) = listen().await;
+    let expected = KeyPair::from_seed(b"ws").public_key();
+    let verifier = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut transport = WsTransport::new(tokio_tungstenite::accept_async(stream).await.unwrap());
+        run_verifier(&mut transport, &expected).await
+    });
+
+    let (socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    let mut transport = WsTransport::new(socket);
+    let proved = run_prover(&mut transport, &KeyPair::from_seed(b"someone else")).await;
+
+    assert!(matches!(proved, Err(ProtocolError::InvalidChallenge)));
+    assert!(matches!(verifier.await.unwrap(), Err(ProtocolError::Rejected { .. })));
+}
+
+#[tokio::test]
+async fn each_message_is_one_json_text_frame() {
+    let (listener, url) = listen().await;
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+        socket.next().await.unwrap().unwrap()
+    });
+
+    let (socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    let mut transport = WsTransport::new(socket);
+    transport.send(Message::error("bye")).await.unwrap();
+
+    let Frame::Text(json) = server.await.unwrap() else { panic!("expected a text frame") };
+    let msg: Message = serde_json::from_str(&json).unwrap();
+    assert_eq!((msg.kind.as_str(), msg.payload.as_str()), ("error", "bye"));
+}
+
+#[tokio::test]
+async fn binary_frame_is_invalid_data() {
+    let (listener, url) = listen().await;
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut transport = WsTransport::new(tokio_tungstenite::accept_async(stream).await.unwrap());
+        transport.recv().await
+    });
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    socket.send(Frame::Binary(b"{}".to_vec())).await.unwrap();
+
+    let err = server.await.unwrap().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[tokio::test]
+async fn closed_socket_reads_as_eof() {
+    let (listener, url) = listen().await;
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut transport = WsTransport::new(tokio_tungstenite::accept_async(stream).await.unwrap());
+        transport.recv().await
+    });
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    socket.close(None).await.unwrap();
+
+    let err = server.await.unwrap().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[tokio::test]
+async fn close_waits_for_the_peers_reply() {
+    let (listener, url) = listen().await;
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut transport = WsTransport::new(tokio_tungstenite::accept_async(stream).await.unwrap());
+        transport.recv().await
+    });
+
+    let (socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    let mut transport = WsTransport::new(socket);
+    transport.close().await.unwrap(); // only returns once the server's close frame is back
+
+    let err = server.await.unwrap().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[tokio::test]
+async fn oversized_message_is_refused_with_close_1009() {
+    let (listener, url) = listen().await;
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let socket = tokio_tungstenite::accept_async_with_config(stream, Some(ws_config())).await.unwrap();
+        WsTransport::new(socket).recv().await
+    });
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    socket.send(Frame::Text("x".repeat(MAX_FRAME_LEN + 1))).await.unwrap();
+
+    let err = server.await.unwrap().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    let Some(Ok(Frame::Close(Some(close)))) = socket.next().await else { panic!("expected a close frame") };
+    assert_eq!(close.code, CloseCode::Size);
+}
+
+#[cfg(feature = "server")]
+#[tokio::test]
+async fn server_accepts_websocket_provers_alongside_tls() {
+    let cert = generate_self_signed_cert().unwrap();
+    let client_config = Arc::new(create_client_config_insecure(&cert).unwrap());
+    let keypair = KeyPair::from_seed(b"browser");
+    let handle = VerifierServer::builder()
+        .bind("127.0.0.1:0".parse().unwrap())
+        .bind_ws("127.0.0.1:0".parse().unwrap())
+        .tls(create_server_config(&cert, None).unwrap())
+        .expected_keys(KeyRegistry::single("browser", keypair.public_key()))
+        .build()
+        .unwrap()
+        .spawn()
+        .await
+        .unwrap();
+
+    // a plain tungstenite client on the WebSocket listener
+    let ws_addr = handle.ws_local_addr().expect("bind_ws was configured");
+    let url = format!("wss://localhost:{}/prove", ws_addr.port());
+    let connector = tokio_tungstenite::Connector::Rustls(client_config.clone());
+    let (socket, _) = tokio_tungstenite::connect_async_tls_with_config(&url, None, false, Some(connector)).await.unwrap();
+    let mut transport = WsTransport::new(socket);
+    assert!(run_prover(&mut transport, &keypair).await.unwrap().accepted());
+    transport.close().await.unwrap();
+
+    // and a raw TLS prover on the main one, same keys
+    let tcp = tokio::net::TcpStream::connect(handle.local_addr()).await.unwrap();
+    let connector = tokio_rustls::TlsConnector::from(client_config);
+    let tls = connector.connect(rustls::ServerName::try_from("localhost").unwrap(), tcp).await.unwrap();
+    assert!(run_prover(&mut StreamTransport::new(tls), &keypair).await.unwrap().accepted());
+
+    let summary = handle.shutdown().await.unwrap();
+    assert_eq!(summary.aborted, 0);
+}
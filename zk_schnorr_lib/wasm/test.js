@@ -0,0 +1,57 @@
+// The wasm API as Node sees it. Build the bindings first, from zk_schnorr_lib/:
+//
+//   cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//   wasm-bindgen --target nodejs --out-dir pkg ../target/wasm32-unknown-unknown/release/zk_schnorr_lib.wasm
+//   node --test wasm/
+//
+// (`wasm-pack build --target nodejs -- --features wasm` also works once `cdylib` is added to the crate types.)
+
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('node:fs');
+const path = require('node:path');
+
+const pkg = path.join(__dirname, '..', 'pkg');
+const zk = require(path.join(pkg, 'zk_schnorr_lib.js'));
+
+const SEED_HEX = Buffer.from('demo-prover-secret').toString('hex');
+const MESSAGE_HEX = Buffer.from('hello').toString('hex');
+const hexOf32Bytes = /^[0-9a-f]{64}$/;
+
+test('keypair is deterministic and hex-encoded', () => {
+  const keypair = zk.wasm_generate_keypair(SEED_HEX);
+  assert.deepStrictEqual(Object.keys(keypair).sort(), ['publicKey', 'secretScalar']);
+  assert.match(keypair.publicKey, hexOf32Bytes);
+  assert.match(keypair.secretScalar, hexOf32Bytes);
+  assert.deepStrictEqual(zk.wasm_generate_keypair(SEED_HEX), keypair);
+});
+
+test('signature verifies, and only for its message and key', () => {
+  const keypair = zk.wasm_generate_keypair(SEED_HEX);
+  const sig = zk.wasm_schnorr_sign(keypair.secretScalar, MESSAGE_HEX);
+  assert.match(sig.R, hexOf32Bytes);
+  assert.match(sig.s, hexOf32Bytes);
+  assert.strictEqual(zk.wasm_schnorr_verify(keypair.publicKey, MESSAGE_HEX, sig.R, sig.s), true);
+
+  const other = zk.wasm_generate_keypair('00');
+  assert.strictEqual(zk.wasm_schnorr_verify(other.publicKey, MESSAGE_HEX, sig.R, sig.s), false);
+  assert.strictEqual(zk.wasm_schnorr_verify(keypair.publicKey, '00', sig.R, sig.s), false);
+});
+
+test('every signature draws a fresh nonce from crypto.getRandomValues', () => {
+  const keypair = zk.wasm_generate_keypair(SEED_HEX);
+  const first = zk.wasm_schnorr_sign(keypair.secretScalar, MESSAGE_HEX);
+  const second = zk.wasm_schnorr_sign(keypair.secretScalar, MESSAGE_HEX);
+  assert.notStrictEqual(first.R, second.R);
+});
+
+test('bad hex throws, or verifies as false', () => {
+  assert.throws(() => zk.wasm_generate_keypair('not hex'));
+  assert.throws(() => zk.wasm_schnorr_sign('ff'.repeat(32), MESSAGE_HEX));
+  assert.strictEqual(zk.wasm_schnorr_verify('zz', MESSAGE_HEX, '00', '00'), false);
+});
+
+test('the module stays under 200 KB', () => {
+  const size = fs.statSync(path.join(pkg, 'zk_schnorr_lib_bg.wasm')).size;
+  assert.ok(size < 200 * 1024, `${size} bytes`);
+});